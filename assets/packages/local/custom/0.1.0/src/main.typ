@@ -0,0 +1,3 @@
+#import "shout.typ": shout
+
+#let name = shout("custom")
@@ -0,0 +1 @@
+#let shout(text) = upper(text) + "!"
@@ -0,0 +1,4 @@
+#import "util.typ": double
+
+#let name = "mypkg"
+#let quad(x) = double(double(x))
@@ -0,0 +1 @@
+#let double(x) = 2 * x
@@ -1,7 +1,11 @@
 use std::fmt::{self, Display, Formatter};
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use typst::geom::RgbaColor;
+use typst_library::layout::Paper;
 
 /// typst creates PDF files from .typ files
 #[derive(Debug, Clone, Parser)]
@@ -15,10 +19,24 @@ pub struct CliArguments {
     #[clap(long = "font-path", env = "TYPST_FONT_PATHS", value_name = "DIR", action = ArgAction::Append)]
     pub font_paths: Vec<PathBuf>,
 
+    /// Skip font files and directories matching a glob pattern when searching
+    /// for fonts, e.g. `**/Noto*` (can be given multiple times)
+    #[clap(long = "exclude-font-path", value_name = "GLOB", action = ArgAction::Append)]
+    pub exclude_font_paths: Vec<String>,
+
+    /// Scan all font files again instead of using the font cache
+    #[clap(long = "no-font-cache")]
+    pub no_font_cache: bool,
+
     /// Configure the root for absolute paths
     #[clap(long = "root", env = "TYPST_ROOT", value_name = "DIR")]
     pub root: Option<PathBuf>,
 
+    /// Configure the directory to search for packages, which are laid out as
+    /// `{namespace}/{name}/{version}`
+    #[clap(long = "package-path", env = "TYPST_PACKAGE_PATH", value_name = "DIR")]
+    pub package_path: Option<PathBuf>,
+
     /// Configure the destination for special generated files
     /// If unset, a results directory will be created on the same directory as the output file(s)
     #[clap(long = "dest", env = "TYPST_DEST", value_name = "DIR")]
@@ -35,6 +53,8 @@ pub struct CliArguments {
 pub enum DiagnosticFormat {
     Human,
     Short,
+    /// One JSON object per line, with 1-based line and column numbers
+    Json,
 }
 
 impl Display for DiagnosticFormat {
@@ -46,6 +66,114 @@ impl Display for DiagnosticFormat {
     }
 }
 
+/// A standard that an exported PDF file can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfStandard {
+    /// PDF/A-2b, for long-term archival
+    #[value(name = "a-2b")]
+    A2b,
+}
+
+/// How to scale pages to exact pixel dimensions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum Scaling {
+    /// Show the whole page, padding it with the background
+    Fit,
+    /// Cover the whole image, cropping the page
+    Fill,
+}
+
+/// A way to arrange the pages of a document onto printed sheets.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum Imposition {
+    /// Two pages per sheet side, ordered for saddle-stitch binding
+    Booklet,
+}
+
+/// A set of 1-based page numbers, consisting of comma-separated single pages
+/// and ranges. Ranges include both ends and may be open towards the end.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PageRanges(Vec<(usize, Option<usize>)>);
+
+impl PageRanges {
+    /// Whether the set contains the given page number.
+    pub fn contains(&self, page: usize) -> bool {
+        self.0
+            .iter()
+            .any(|&(start, end)| start <= page && end.map_or(true, |end| page <= end))
+    }
+}
+
+impl FromStr for PageRanges {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |part: &str| match part.trim().parse::<usize>() {
+            Ok(0) => Err("page numbers start at 1".to_string()),
+            Ok(n) => Ok(n),
+            Err(_) => Err(format!("invalid page number `{}`", part.trim())),
+        };
+
+        let mut ranges = vec![];
+        for part in s.split(',') {
+            let range = match part.split_once('-') {
+                None => {
+                    let page = number(part)?;
+                    (page, Some(page))
+                }
+                Some((start, end)) if end.trim().is_empty() => (number(start)?, None),
+                Some((start, end)) => {
+                    let (start, end) = (number(start)?, number(end)?);
+                    if start > end {
+                        return Err(format!("invalid page range `{}`", part.trim()));
+                    }
+                    (start, Some(end))
+                }
+            };
+            ranges.push(range);
+        }
+
+        Ok(Self(ranges))
+    }
+}
+
+/// The number of columns and rows of pages on a sheet, e.g. `2x2`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Grid {
+    /// The number of pages next to each other.
+    pub columns: NonZeroUsize,
+    /// The number of pages below each other.
+    pub rows: NonZeroUsize,
+}
+
+impl FromStr for Grid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |part: &str| match part.trim().parse::<usize>() {
+            Ok(n) => NonZeroUsize::new(n).ok_or("grid must have at least one cell"),
+            Err(_) => Err("grid must be given as columns and rows, e.g. `2x2`"),
+        };
+
+        let (columns, rows) = s
+            .split_once('x')
+            .ok_or("grid must be given as columns and rows, e.g. `2x2`")?;
+        Ok(Self { columns: number(columns)?, rows: number(rows)? })
+    }
+}
+
+/// Parse a `key=value` pair for `--input`.
+fn parse_input(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or("input must be a key and a value separated by an equals sign")?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("input key must not be empty".into());
+    }
+    Ok((key.into(), value.into()))
+}
+
 /// What to do.
 #[derive(Debug, Clone, Subcommand)]
 #[command()]
@@ -58,8 +186,37 @@ pub enum Command {
     #[command(visible_alias = "w")]
     Watch(CompileCommand),
 
+    /// Watches the input file and serves a live preview over HTTP
+    #[command(visible_alias = "s")]
+    Serve(ServeCommand),
+
+    /// Watches the input file and shows a live preview in a window
+    Preview(CompileCommand),
+
+    /// Checks the input file for errors without exporting or writing anything
+    Check(CompileCommand),
+
     /// List all discovered fonts in system and custom font paths
     Fonts(FontsCommand),
+
+    /// Creates a new project with a main file and a `typst.toml` manifest
+    Init(InitCommand),
+
+    /// Prints a source file with syntax highlighting as HTML or for the
+    /// terminal
+    Highlight(HighlightCommand),
+
+    /// Prints the syntax tree of a source file, with the kind and byte range
+    /// of each node
+    Ast(AstCommand),
+
+    /// Finds the source location of a position in the output using a source
+    /// map, e.g. for inverse search from a PDF viewer
+    Jump(JumpCommand),
+
+    /// Finds the position in the output of a source location using a source
+    /// map, e.g. for forward search from an editor
+    Locate(LocateCommand),
 }
 
 impl Command {
@@ -68,33 +225,187 @@ impl Command {
         match self {
             Command::Compile(cmd) => Some(cmd),
             Command::Watch(cmd) => Some(cmd),
-            Command::Fonts(_) => None,
+            Command::Serve(cmd) => Some(&cmd.compile),
+            Command::Preview(cmd) => Some(cmd),
+            Command::Check(cmd) => Some(cmd),
+            Command::Fonts(_)
+            | Command::Init(_)
+            | Command::Highlight(_)
+            | Command::Ast(_)
+            | Command::Jump(_)
+            | Command::Locate(_) => None,
         }
     }
 
-    /// Returns whether this is a watch command.
+    /// Returns whether this is a watch, serve or preview command.
     pub fn is_watch(&self) -> bool {
-        matches!(self, Command::Watch(_))
+        matches!(self, Command::Watch(_) | Command::Serve(_) | Command::Preview(_))
     }
 }
 
 /// Compiles the input file into a PDF file
 #[derive(Debug, Clone, Parser)]
 pub struct CompileCommand {
-    /// Path to input Typst file
+    /// Path to input Typst file, or `-` to read it from stdin (requires `--root`
+    /// and an explicit output path)
     pub input: PathBuf,
 
-    /// Path to output PDF file or PNG file(s)
+    /// Path to output PDF file, PNG, JPEG or WebP file(s), an animated GIF
+    /// file with all pages, a plain text file, or an HTML, EPUB or DOCX file
+    /// (experimental)
     pub output: Option<PathBuf>,
 
-    /// Opens the output file after compilation using the default PDF viewer
-    #[arg(long = "open")]
+    /// Opens the output file after the first successful compilation using the
+    /// default PDF viewer or the given one
+    #[arg(long = "open", value_name = "VIEWER")]
     pub open: Option<Option<String>>,
 
+    /// Opens the output file again after every successful compilation in watch
+    /// mode, bringing the viewer to the front
+    #[arg(long = "reopen", requires = "open")]
+    pub reopen: bool,
+
     /// The PPI to use if exported as PNG
     #[arg(long = "ppi")]
     pub ppi: Option<f32>,
 
+    /// The width of exported PNG files in pixels, instead of the PPI
+    #[arg(long = "width", value_name = "PX", conflicts_with = "ppi")]
+    pub width: Option<NonZeroU32>,
+
+    /// The height of exported PNG files in pixels, instead of the PPI
+    #[arg(long = "height", value_name = "PX", conflicts_with = "ppi")]
+    pub height: Option<NonZeroU32>,
+
+    /// How to scale pages if both the width and height are given
+    #[arg(long = "scaling", value_name = "MODE", default_value = "fit")]
+    pub scaling: Scaling,
+
+    /// The quality of exported JPEG and WebP files, from 1 to 100
+    #[arg(
+        long = "quality",
+        value_name = "QUALITY",
+        default_value_t = 90,
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub quality: u8,
+
+    /// How long each page is shown in an exported GIF file, in milliseconds
+    #[arg(long = "frame-delay", value_name = "MS", default_value_t = 1000)]
+    pub frame_delay: u32,
+
+    /// The separator between pages in an exported text file
+    #[arg(long = "page-separator", value_name = "SEPARATOR", default_value = "\u{c}")]
+    pub page_separator: String,
+
+    /// Whether to keep soft hyphens in an exported text file. Hyphens
+    /// inserted at line breaks are then written as soft hyphens, too
+    #[arg(long = "soft-hyphens")]
+    pub soft_hyphens: bool,
+
+    /// The background color of exported PNG files as a hex code, e.g.
+    /// `#00000000` for a transparent background
+    #[arg(long = "background", value_name = "COLOR")]
+    pub background: Option<RgbaColor>,
+
+    /// Only export the given pages, e.g. `1-3,7,10-`
+    #[arg(long = "pages", value_name = "PAGES")]
+    pub pages: Option<PageRanges>,
+
+    /// Make a value available to the document through `sys.inputs`, e.g.
+    /// `--input lang=de` (can be given multiple times)
+    #[arg(
+        long = "input",
+        value_name = "KEY=VALUE",
+        action = ArgAction::Append,
+        value_parser = parse_input,
+    )]
+    pub inputs: Vec<(String, String)>,
+
+    /// Allow the document to read the given environment variables, as a
+    /// comma-separated list of names and glob patterns, e.g. `CI,BUILD_*`
+    #[arg(
+        long = "allow-env",
+        value_name = "VARS",
+        value_delimiter = ',',
+        action = ArgAction::Append,
+    )]
+    pub allow_env: Vec<String>,
+
+    /// Allow the document to run external programs
+    #[arg(long = "allow-exec")]
+    pub allow_exec: bool,
+
+    /// Allow the document to fetch remote resources, from all hosts or only
+    /// from a comma-separated list of hosts, e.g. `--allow-net=example.com`
+    #[arg(
+        long = "allow-net",
+        value_name = "HOSTS",
+        value_delimiter = ',',
+        num_args = 0..,
+        require_equals = true,
+    )]
+    pub allow_net: Option<Vec<String>>,
+
+    /// Use the given UNIX timestamp as the current time and the document's
+    /// creation date instead of the system time, for reproducible builds
+    #[arg(
+        long = "creation-timestamp",
+        env = "SOURCE_DATE_EPOCH",
+        value_name = "UNIX_TIMESTAMP"
+    )]
+    pub creation_timestamp: Option<i64>,
+
+    /// Set the document's title, overriding the one from the document
+    #[arg(long = "title")]
+    pub title: Option<String>,
+
+    /// Set the document's authors, overriding the ones from the document (can
+    /// be given multiple times)
+    #[arg(long = "author", action = ArgAction::Append)]
+    pub author: Vec<String>,
+
+    /// Write a JSON manifest of all files written by the document to the given
+    /// path
+    #[arg(long = "write-manifest", value_name = "MANIFEST_JSON")]
+    pub write_manifest: Option<PathBuf>,
+
+    /// Make the exported PDF conform to a standard
+    #[arg(long = "pdf-standard", value_name = "STANDARD")]
+    pub pdf_standard: Option<PdfStandard>,
+
+    /// Arrange the exported pages onto sheets for printing
+    #[arg(long = "imposition", value_name = "MODE")]
+    pub imposition: Option<Imposition>,
+
+    /// Shrink multiple pages onto each sheet, given as columns and rows, e.g.
+    /// `2x2`
+    #[arg(long = "nup", value_name = "GRID", conflicts_with_all = ["imposition", "tile"])]
+    pub nup: Option<Grid>,
+
+    /// Split pages that are larger than the given paper size across multiple
+    /// sheets, e.g. `a4`
+    #[arg(long = "tile", value_name = "PAPER", conflicts_with = "imposition")]
+    pub tile: Option<Paper>,
+
+    /// The number of threads to render PNG pages with (0 for one per CPU)
+    #[arg(long = "jobs", short = 'j', value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Write a JSON source map that records where each piece of text came
+    /// from to the given path. PDF output then also gets a named destination
+    /// `{path}:{line}` for each source line
+    #[arg(long = "source-map", value_name = "MAP_JSON")]
+    pub source_map: Option<PathBuf>,
+
+    /// Fail instead of warning when a requested font family is not available
+    #[arg(long = "fail-on-missing-font")]
+    pub fail_on_missing_font: bool,
+
+    /// Fail the compilation if there are any warnings
+    #[arg(long = "deny-warnings")]
+    pub deny_warnings: bool,
+
     /// In which format to emit diagnostics
     #[clap(
         long,
@@ -103,9 +414,37 @@ pub struct CompileCommand {
     )]
     pub diagnostic_format: DiagnosticFormat,
 
+    /// How long to wait for further file changes before recompiling in watch
+    /// mode, in milliseconds
+    #[arg(long = "watch-delay", value_name = "MS", default_value_t = 100)]
+    pub watch_delay: u64,
+
+    /// Ignore changes to files matching a glob pattern in watch mode. Patterns
+    /// are matched against file names and paths relative to the input file's
+    /// directory
+    #[arg(long = "watch-ignore", value_name = "GLOB", action = ArgAction::Append)]
+    pub watch_ignore: Vec<String>,
+
     /// Produces a flamegraph of the compilation process
     #[arg(long = "flamegraph", value_name = "OUTPUT_SVG")]
     pub flamegraph: Option<Option<PathBuf>>,
+
+    /// Prints how long each phase of the compilation took or, if a path is
+    /// given, writes a Chrome trace of the compilation to it
+    #[arg(long = "timings", value_name = "OUTPUT_JSON")]
+    pub timings: Option<Option<PathBuf>>,
+}
+
+/// Watches the input file and serves a live preview over HTTP
+#[derive(Debug, Clone, Parser)]
+pub struct ServeCommand {
+    /// The compilation settings, the output is written as in watch mode
+    #[command(flatten)]
+    pub compile: CompileCommand,
+
+    /// The address to serve the preview on
+    #[arg(long = "addr", default_value = "127.0.0.1:3000")]
+    pub addr: String,
 }
 
 /// List all discovered fonts in system and custom font paths
@@ -114,4 +453,98 @@ pub struct FontsCommand {
     /// Also list style variants of each font family
     #[arg(long)]
     pub variants: bool,
+
+    /// List every font face with its file, style and coverage as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Creates a new project with a main file and a `typst.toml` manifest
+#[derive(Debug, Clone, Parser)]
+pub struct InitCommand {
+    /// The directory to create the project in
+    #[arg(default_value = ".")]
+    pub directory: PathBuf,
+
+    /// The template to start from
+    #[arg(long = "template", value_name = "TEMPLATE")]
+    pub template: Option<Template>,
+}
+
+/// Prints a source file with syntax highlighting as HTML or for the terminal
+#[derive(Debug, Clone, Parser)]
+pub struct HighlightCommand {
+    /// Path to the Typst file to highlight, or `-` to read it from stdin
+    pub input: PathBuf,
+
+    /// In which format to print the highlighted source
+    #[arg(long = "format", default_value = "ansi")]
+    pub format: HighlightFormat,
+}
+
+/// Which format to print highlighted source code in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum HighlightFormat {
+    /// An HTML `code` element with a CSS class per syntax category
+    Html,
+    /// Text with ANSI escape codes for the terminal
+    Ansi,
+}
+
+/// Prints the syntax tree of a source file
+#[derive(Debug, Clone, Parser)]
+pub struct AstCommand {
+    /// Path to the Typst file to parse, or `-` to read it from stdin
+    pub input: PathBuf,
+
+    /// In which format to print the syntax tree
+    #[arg(long = "format", default_value = "json")]
+    pub format: AstFormat,
+}
+
+/// Which format to print a syntax tree in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum AstFormat {
+    /// A JSON object per node, with `kind`, `start`, `end` and either `text`
+    /// or `children`, as well as `error` for error nodes
+    Json,
+    /// An S-expression per node, one per line
+    Sexpr,
+}
+
+/// Finds the source location of a position in the output using a source map
+#[derive(Debug, Clone, Parser)]
+pub struct JumpCommand {
+    /// The source map written with `--source-map`
+    pub map: PathBuf,
+
+    /// The page, starting at 1
+    pub page: usize,
+
+    /// The distance from the page's left edge, in pt
+    pub x: f64,
+
+    /// The distance from the page's top edge, in pt
+    pub y: f64,
+}
+
+/// Finds the position in the output of a source location using a source map
+#[derive(Debug, Clone, Parser)]
+pub struct LocateCommand {
+    /// The source map written with `--source-map`
+    pub map: PathBuf,
+
+    /// The source location as `file:line` or `file:line:column`, starting at 1
+    pub location: String,
+}
+
+/// A template for a new project.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum Template {
+    /// A report with a title page and numbered headings
+    Report,
+    /// A letter with sender and recipient addresses
+    Letter,
+    /// Slides in landscape format, one per page
+    Slides,
 }
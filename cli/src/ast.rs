@@ -0,0 +1,102 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+
+use ecow::EcoString;
+use serde::Serialize;
+use typst::diag::StrResult;
+use typst::syntax::{LinkedNode, Source, SyntaxKind};
+
+use crate::args::{AstCommand, AstFormat};
+
+/// A syntax node as it is printed by the ast command.
+#[derive(Serialize)]
+struct Node {
+    /// The node's kind, e.g. `Heading`.
+    kind: String,
+    /// The byte range of the node in the source file.
+    start: usize,
+    end: usize,
+    /// The text of a leaf node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<EcoString>,
+    /// The message of an error node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<EcoString>,
+    /// The children of an inner node.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Node>,
+}
+
+impl Node {
+    /// Convert a linked syntax node and its descendants.
+    fn new(node: &LinkedNode) -> Self {
+        let range = node.range();
+        let leaf = node.children().next().is_none();
+        let error = match node.kind() {
+            SyntaxKind::Error => node.errors().into_iter().next(),
+            _ => None,
+        };
+        Self {
+            kind: format!("{:?}", node.kind()),
+            start: range.start,
+            end: range.end,
+            text: leaf.then(|| node.text().clone()),
+            error: error.map(|error| error.message),
+            children: node.children().map(|child| Self::new(&child)).collect(),
+        }
+    }
+
+    /// Write the node as an S-expression, with one node per line.
+    fn write_sexpr(&self, buf: &mut String, depth: usize) {
+        write!(
+            buf,
+            "{:indent$}({} {}..{}",
+            "",
+            self.kind,
+            self.start,
+            self.end,
+            indent = 2 * depth
+        )
+        .unwrap();
+        if let Some(text) = &self.text {
+            write!(buf, " {text:?}").unwrap();
+        }
+        if let Some(error) = &self.error {
+            write!(buf, " (error {error:?})").unwrap();
+        }
+        for child in &self.children {
+            buf.push('\n');
+            child.write_sexpr(buf, depth + 1);
+        }
+        buf.push(')');
+    }
+}
+
+/// Execute an ast command.
+pub fn ast(command: &AstCommand) -> StrResult<()> {
+    let text = if command.input.as_os_str() == "-" {
+        io::read_to_string(io::stdin()).map_err(|_| "failed to read stdin")?
+    } else {
+        fs::read_to_string(&command.input).map_err(|err| {
+            format!("failed to read {} ({err})", command.input.display())
+        })?
+    };
+
+    let source = Source::detached(text);
+    let root = Node::new(&LinkedNode::new(source.root()));
+    match command.format {
+        AstFormat::Json => {
+            let json = serde_json::to_string(&root)
+                .map_err(|_| "failed to serialize syntax tree")?;
+            println!("{json}");
+        }
+        AstFormat::Sexpr => {
+            let mut buf = String::new();
+            root.write_sexpr(&mut buf, 0);
+            println!("{buf}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+
+use typst::diag::{bail, StrResult};
+use typst::util::{hash128, Buffer};
+
+/// Fetches remote resources for a document.
+///
+/// Responses are cached on disk together with their ETag. A cached response
+/// is revalidated with the server and reused if it didn't change or if the
+/// server can't be reached, so that documents still compile offline.
+pub struct Fetcher {
+    /// The hosts that may be accessed. `None` if network access is denied,
+    /// empty if all hosts may be accessed.
+    hosts: Option<Vec<String>>,
+    /// The resources fetched during the current compilation, by URL.
+    fetched: RefCell<HashMap<String, Buffer>>,
+}
+
+impl Fetcher {
+    /// Create a new fetcher that may access the given hosts.
+    pub fn new(hosts: Option<Vec<String>>) -> Self {
+        Self { hosts, fetched: RefCell::default() }
+    }
+
+    /// Forget the resources fetched during the last compilation, so that they
+    /// are revalidated.
+    pub fn reset(&mut self) {
+        self.fetched.get_mut().clear();
+    }
+
+    /// Fetch the resource at the given URL.
+    ///
+    /// Within one compilation, the same URL always yields the same data.
+    pub fn fetch(&self, url: &str) -> StrResult<Buffer> {
+        if let Some(data) = self.fetched.borrow().get(url) {
+            return Ok(data.clone());
+        }
+
+        let host = host(url)?;
+        match &self.hosts {
+            None => bail!("network access is not allowed (allow it with --allow-net)"),
+            Some(hosts) if !hosts.is_empty() && !hosts.iter().any(|h| h == host) => {
+                bail!("network access to {host} is not allowed")
+            }
+            Some(_) => {}
+        }
+
+        let data = download(url)?;
+        self.fetched.borrow_mut().insert(url.into(), data.clone());
+        Ok(data)
+    }
+}
+
+/// Download a resource, going through the on-disk cache.
+#[tracing::instrument(skip_all)]
+fn download(url: &str) -> StrResult<Buffer> {
+    let paths = cache_paths(url);
+    let cached = paths.as_ref().and_then(|(body, _)| fs::read(body).ok());
+    let etag = paths.as_ref().and_then(|(_, etag)| fs::read_to_string(etag).ok());
+
+    let mut request = ureq::get(url);
+    if let (Some(_), Some(etag)) = (&cached, &etag) {
+        request = request.set("If-None-Match", etag);
+    }
+
+    tracing::info!("Fetching {url}");
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(code, _)) => {
+            bail!("failed to fetch {url} (server responded with {code})")
+        }
+        Err(err) => match cached {
+            Some(data) => {
+                tracing::warn!("Using cached version of {url} ({err})");
+                return Ok(data.into());
+            }
+            None => bail!("failed to fetch {url} ({err})"),
+        },
+    };
+
+    if response.status() == 304 {
+        if let Some(data) = cached {
+            return Ok(data.into());
+        }
+    }
+
+    let etag = response.header("ETag").map(ToOwned::to_owned);
+    let mut data = vec![];
+    response
+        .into_reader()
+        .read_to_end(&mut data)
+        .map_err(|err| format!("failed to fetch {url} ({err})"))?;
+
+    // Failing to cache is not an error, the resource will just be downloaded
+    // again next time.
+    if let Some((body, etag_path)) = paths {
+        if let Some(parent) = body.parent() {
+            fs::create_dir_all(parent).ok();
+        }
+        if fs::write(&body, &data).is_ok() {
+            match etag {
+                Some(etag) => fs::write(etag_path, etag).ok(),
+                None => fs::remove_file(etag_path).ok(),
+            };
+        }
+    }
+
+    Ok(data.into())
+}
+
+/// The paths of the cached body and ETag for a URL.
+fn cache_paths(url: &str) -> Option<(PathBuf, PathBuf)> {
+    let dir = dirs::cache_dir()?.join("typst").join("fetch");
+    let key = format!("{:032x}", hash128(url));
+    Some((dir.join(&key), dir.join(format!("{key}.etag"))))
+}
+
+/// Extract the host from an HTTP or HTTPS URL.
+fn host(url: &str) -> StrResult<&str> {
+    let Some(rest) = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))
+    else {
+        bail!("only http and https urls can be fetched");
+    };
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => host,
+    };
+
+    if host.is_empty() {
+        bail!("url has no host");
+    }
+
+    Ok(host)
+}
@@ -0,0 +1,93 @@
+use std::fs;
+use std::io::{self, Write};
+
+use codespan_reporting::term::termcolor::{
+    Color, ColorChoice, ColorSpec, StandardStream, WriteColor,
+};
+use typst::diag::StrResult;
+use typst::ide::{highlight, highlight_html, Tag};
+use typst::syntax::{LinkedNode, Source};
+
+use crate::args::{HighlightCommand, HighlightFormat};
+
+/// Execute a highlight command.
+pub fn highlight_file(command: &HighlightCommand) -> StrResult<()> {
+    let text = if command.input.as_os_str() == "-" {
+        io::read_to_string(io::stdin()).map_err(|_| "failed to read stdin")?
+    } else {
+        fs::read_to_string(&command.input).map_err(|err| {
+            format!("failed to read {} ({err})", command.input.display())
+        })?
+    };
+
+    let source = Source::detached(text);
+    match command.format {
+        HighlightFormat::Html => println!("{}", highlight_html(source.root())),
+        HighlightFormat::Ansi => {
+            let mut w = StandardStream::stdout(ColorChoice::AlwaysAnsi);
+            let node = LinkedNode::new(source.root());
+            highlight_ansi(&mut w, &node, &mut vec![])
+                .and_then(|_| w.reset())
+                .map_err(|_| "failed to write highlighted source")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Highlight one source node, emitting ANSI escape codes.
+///
+/// The stack holds the colors of the enclosing highlighted nodes, which are
+/// restored after a nested node.
+fn highlight_ansi(
+    w: &mut impl WriteColor,
+    node: &LinkedNode,
+    stack: &mut Vec<ColorSpec>,
+) -> io::Result<()> {
+    let tag = highlight(node).filter(|&tag| tag != Tag::Error);
+    if let Some(tag) = tag {
+        stack.push(ansi_color(tag));
+        w.set_color(stack.last().unwrap())?;
+    }
+
+    let text = node.text();
+    if !text.is_empty() {
+        write!(w, "{text}")?;
+    } else {
+        for child in node.children() {
+            highlight_ansi(w, &child, stack)?;
+        }
+    }
+
+    if tag.is_some() {
+        stack.pop();
+        match stack.last() {
+            Some(spec) => w.set_color(spec)?,
+            None => w.reset()?,
+        }
+    }
+
+    Ok(())
+}
+
+/// The terminal color for a highlighting tag.
+fn ansi_color(tag: Tag) -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    match tag {
+        Tag::Comment => spec.set_dimmed(true),
+        Tag::Punctuation | Tag::Error => &mut spec,
+        Tag::Escape | Tag::Number => spec.set_fg(Some(Color::Cyan)),
+        Tag::Strong => spec.set_bold(true),
+        Tag::Emph => spec.set_italic(true),
+        Tag::Link => spec.set_fg(Some(Color::Blue)).set_underline(true),
+        Tag::Raw | Tag::String => spec.set_fg(Some(Color::Green)),
+        Tag::Label | Tag::Ref => spec.set_fg(Some(Color::Cyan)).set_underline(true),
+        Tag::Heading => spec.set_fg(Some(Color::Magenta)).set_bold(true),
+        Tag::ListMarker | Tag::ListTerm => spec.set_fg(Some(Color::Magenta)),
+        Tag::MathDelimiter | Tag::MathOperator => spec.set_fg(Some(Color::Yellow)),
+        Tag::Keyword | Tag::Operator => spec.set_fg(Some(Color::Red)),
+        Tag::Function => spec.set_fg(Some(Color::Blue)),
+        Tag::Interpolated => spec.set_fg(Some(Color::Yellow)),
+    };
+    spec
+}
@@ -1,36 +1,64 @@
 mod args;
+mod ast;
+mod fetch;
+mod highlight;
+mod persist;
+mod preview;
+mod project;
+mod serve;
+mod sourcemap;
 mod trace;
 
+use std::borrow::Cow;
 use std::cell::{Cell, RefCell, RefMut};
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ffi::OsStr;
 use std::fs::{self, File};
-use std::hash::Hash;
 use std::io::{self, IsTerminal, Write};
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::SystemTime;
 
-use chrono::Datelike;
+use chrono::{Datelike, Timelike};
 use clap::Parser;
-use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::diagnostic::{Diagnostic, Label, Severity};
 use codespan_reporting::term::{self, termcolor};
-use comemo::{Prehashed, TrackedMut, Track};
+use comemo::{Prehashed, Track, TrackedMut};
+use ecow::{eco_format, EcoString};
 use elsa::FrozenVec;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::{WebPEncoder, WebPQuality};
+use image::{ColorType, Delay, DynamicImage, RgbaImage};
 use memmap2::Mmap;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use rayon::prelude::*;
 use same_file::{is_same_file, Handle};
+use serde::{Deserialize, Serialize};
 use std::cell::OnceCell;
 use termcolor::{ColorChoice, StandardStream, WriteColor};
-use typst::diag::{bail, FileError, FileResult, SourceError, StrResult};
-use typst::doc::Document;
-use typst::eval::{Datetime, Library};
-use typst::font::{Font, FontBook, FontInfo, FontVariant};
-use typst::geom::Color;
-use typst::syntax::{Source, SourceId};
-use typst::util::{hash128, Access, AccessMode, Buffer, PathExt};
+use typst::diag::{bail, ErrorKind, FileError, FileResult, SourceError, StrResult};
+use typst::doc::{Document, Frame};
+use typst::eval::{Datetime, IntoValue, Library, Tracer};
+use typst::font::{Font, FontBook, FontFlags, FontInfo, FontVariant};
+use typst::geom::{Abs, Color, RgbaColor, Size};
+use typst::syntax::{Source, SourceId, Span};
+use typst::util::{hash128, Access, AccessMode, Buffer, PackageSpec, PathExt, WriteMode};
 use typst::World;
+use typst_library::layout::Paper;
 use walkdir::WalkDir;
 
-use crate::args::{CliArguments, Command, CompileCommand, DiagnosticFormat};
+use crate::args::{
+    CliArguments, Command, CompileCommand, DiagnosticFormat, Grid, Imposition,
+    PageRanges, PdfStandard, Scaling,
+};
+use crate::fetch::Fetcher;
+use crate::preview::Preview;
+use crate::project::Manifest;
+use crate::serve::Server;
+use crate::sourcemap::SourceMap;
 
 type CodespanResult<T> = Result<T, CodespanError>;
 type CodespanError = codespan_reporting::files::Error;
@@ -51,10 +79,19 @@ fn main() -> ExitCode {
     };
 
     let res = match &arguments.command {
-        Command::Compile(_) | Command::Watch(_) => {
-            compile(CompileSettings::with_arguments(arguments))
+        Command::Compile(_)
+        | Command::Watch(_)
+        | Command::Serve(_)
+        | Command::Preview(_)
+        | Command::Check(_) => {
+            CompileSettings::with_arguments(arguments).and_then(compile)
         }
         Command::Fonts(_) => fonts(FontsSettings::with_arguments(arguments)),
+        Command::Init(command) => project::init(command),
+        Command::Highlight(command) => highlight::highlight_file(command),
+        Command::Ast(command) => ast::ast(command),
+        Command::Jump(command) => sourcemap::jump(command),
+        Command::Locate(command) => sourcemap::locate(command),
     };
 
     if let Err(msg) = res {
@@ -91,93 +128,256 @@ fn typst_version() -> &'static str {
 struct CompileSettings {
     /// The path to the input file.
     input: PathBuf,
-    /// The path to the output file.
+    /// Whether to read the input from stdin instead of a file.
+    stdin: bool,
+    /// The path to the output file. Empty if reading from stdin and no output
+    /// was given.
     output: PathBuf,
     /// Whether to watch the input files for changes.
     watch: bool,
+    /// Whether to only check for errors, without exporting or writing.
+    check: bool,
+    /// How long to wait for further changes before recompiling.
+    watch_delay: u64,
+    /// Glob patterns of files whose changes don't trigger recompilation.
+    watch_ignore: Vec<String>,
+    /// The address to serve a live preview on.
+    serve: Option<String>,
+    /// Whether to show a live preview in a window.
+    preview: bool,
     /// The root directory for absolute paths.
     root: Option<PathBuf>,
     /// The destination directory for absolute paths.
     dest: Option<PathBuf>,
+    /// The directory to search for packages.
+    package_path: Option<PathBuf>,
     /// The paths to search for fonts.
     font_paths: Vec<PathBuf>,
+    /// Glob patterns of font files and directories to skip.
+    exclude_font_paths: Vec<String>,
+    /// Whether to use the font cache.
+    font_cache: bool,
     /// The open command to use.
     open: Option<Option<String>>,
+    /// Whether to open the output file after every compilation.
+    reopen: bool,
     /// The PPI to use for PNG export.
     ppi: Option<f32>,
+    /// The pixel width for PNG export, overriding the PPI.
+    width: Option<NonZeroU32>,
+    /// The pixel height for PNG export, overriding the PPI.
+    height: Option<NonZeroU32>,
+    /// How to scale pages to the pixel width and height.
+    scaling: Scaling,
+    /// The quality for JPEG and WebP export.
+    quality: u8,
+    /// How long each page is shown in animated GIF export, in milliseconds.
+    frame_delay: u32,
+    /// The separator between pages in text export.
+    page_separator: String,
+    /// Whether to keep soft hyphens in text export.
+    soft_hyphens: bool,
+    /// The background color for PNG export.
+    background: Option<RgbaColor>,
+    /// The pages to export, all if `None`.
+    pages: Option<PageRanges>,
+    /// The UNIX timestamp to use as the current time, if any.
+    creation_timestamp: Option<i64>,
+    /// The title that overrides the document's title.
+    title: Option<String>,
+    /// The authors that override the document's authors, if not empty.
+    author: Vec<String>,
+    /// The standard the exported PDF should conform to.
+    pdf_standard: Option<PdfStandard>,
+    /// How to arrange the exported pages onto sheets, if at all.
+    imposition: Option<Imposition>,
+    /// How many pages to shrink onto each sheet, if any.
+    nup: Option<Grid>,
+    /// The paper to split large pages across, if any.
+    tile: Option<Paper>,
+    /// Where to write the manifest of written files, if anywhere.
+    write_manifest: Option<PathBuf>,
+    /// Where to write the source map, if anywhere.
+    source_map: Option<PathBuf>,
+    /// The number of threads to use for PNG export, if it should be parallel.
+    jobs: Option<usize>,
     /// In which format to emit diagnostics.
     diagnostic_format: DiagnosticFormat,
+    /// Whether missing font families are errors rather than warnings.
+    fail_on_missing_font: bool,
+    /// Whether all warnings are errors.
+    deny_warnings: bool,
+    /// Inputs that are available to the document through `sys.inputs`.
+    inputs: BTreeMap<String, String>,
+    /// Patterns of environment variables the document may read.
+    allow_env: Vec<String>,
+    /// Whether the document may run external programs.
+    allow_exec: bool,
+    /// The hosts the document may fetch resources from, all if empty and none
+    /// if `None`.
+    allow_net: Option<Vec<String>>,
 }
 
 impl CompileSettings {
-    /// Create a new compile settings from the field values.
-    #[allow(clippy::too_many_arguments)]
-    fn new(
-        input: PathBuf,
-        output: Option<PathBuf>,
-        watch: bool,
-        root: Option<PathBuf>,
-        dest: Option<PathBuf>,
-        font_paths: Vec<PathBuf>,
-        open: Option<Option<String>>,
-        ppi: Option<f32>,
-        diagnostic_format: DiagnosticFormat,
-    ) -> Self {
-        let output = match output {
-            Some(path) => path,
-            None => input.with_extension("pdf"),
+    /// Create a new compile settings from the CLI arguments and a compile command.
+    ///
+    /// Settings that are not given as arguments are taken from the `typst.toml`
+    /// manifest next to the input file or in the root directory, if there is
+    /// one.
+    ///
+    /// # Panics
+    /// Panics if the command is not a compile, watch, serve, preview or check
+    /// command.
+    fn with_arguments(args: CliArguments) -> StrResult<Self> {
+        let watch = args.command.is_watch();
+        let check = matches!(args.command, Command::Check(_));
+        let preview = matches!(args.command, Command::Preview(_));
+        let serve = match &args.command {
+            Command::Serve(command) => Some(command.addr.clone()),
+            _ => None,
         };
-        Self {
+        let CompileCommand {
             input,
             output,
-            watch,
-            root,
-            dest,
-            font_paths,
             open,
-            diagnostic_format,
+            reopen,
             ppi,
-        }
-    }
+            width,
+            height,
+            scaling,
+            quality,
+            frame_delay,
+            page_separator,
+            soft_hyphens,
+            background,
+            pages,
+            inputs,
+            allow_env,
+            allow_exec,
+            allow_net,
+            creation_timestamp,
+            title,
+            author,
+            pdf_standard,
+            imposition,
+            nup,
+            tile,
+            write_manifest,
+            source_map,
+            jobs,
+            diagnostic_format,
+            fail_on_missing_font,
+            deny_warnings,
+            watch_delay,
+            watch_ignore,
+            ..
+        } = match args.command {
+            Command::Compile(command) => command,
+            Command::Watch(command) => command,
+            Command::Serve(command) => command.compile,
+            Command::Preview(command) => command,
+            Command::Check(command) => command,
+            _ => unreachable!(),
+        };
 
-    /// Create a new compile settings from the CLI arguments and a compile command.
-    ///
-    /// # Panics
-    /// Panics if the command is not a compile or watch command.
-    fn with_arguments(args: CliArguments) -> Self {
-        let watch = matches!(args.command, Command::Watch(_));
-        let CompileCommand { input, output, open, ppi, diagnostic_format, .. } =
-            match args.command {
-                Command::Compile(command) => command,
-                Command::Watch(command) => command,
-                _ => unreachable!(),
-            };
+        let manifest = Manifest::find(&input, args.root.as_deref())?;
+        let output = output.or_else(|| manifest.output(&input));
+        let ppi = ppi.or(manifest.ppi);
+        // Inputs from the command line take precedence over the manifest's.
+        let inputs = manifest.inputs.into_iter().chain(inputs).collect();
+        let root = args.root.or(manifest.root);
+        let dest = args.dest.or(manifest.dest);
+        let font_paths = if args.font_paths.is_empty() {
+            manifest.font_paths
+        } else {
+            args.font_paths
+        };
 
-        Self::new(
+        let stdin = input.as_os_str() == "-";
+        let output = match output {
+            Some(path) => path,
+            None if stdin => PathBuf::new(),
+            None => input.with_extension("pdf"),
+        };
+
+        Ok(Self {
             input,
+            stdin,
             output,
             watch,
-            args.root,
-            args.dest,
-            args.font_paths,
+            check,
+            watch_delay,
+            watch_ignore,
+            serve,
+            preview,
+            root,
+            dest,
+            package_path: args.package_path,
+            font_paths,
+            exclude_font_paths: args.exclude_font_paths,
+            font_cache: !args.no_font_cache,
             open,
+            reopen,
             ppi,
+            width,
+            height,
+            scaling,
+            quality,
+            frame_delay,
+            page_separator,
+            soft_hyphens,
+            background,
+            pages,
+            creation_timestamp,
+            title,
+            author,
+            pdf_standard,
+            imposition,
+            nup,
+            tile,
+            write_manifest,
+            source_map,
+            jobs,
             diagnostic_format,
-        )
+            fail_on_missing_font,
+            deny_warnings,
+            inputs,
+            allow_env,
+            allow_exec,
+            allow_net,
+        })
     }
 }
 
 struct FontsSettings {
     /// The font paths
     font_paths: Vec<PathBuf>,
+    /// Glob patterns of font files and directories to skip
+    exclude_font_paths: Vec<String>,
+    /// Whether to use the font cache
+    font_cache: bool,
     /// Whether to include font variants
     variants: bool,
+    /// Whether to list all font faces as JSON
+    json: bool,
 }
 
 impl FontsSettings {
     /// Create font settings from the field values.
-    fn new(font_paths: Vec<PathBuf>, variants: bool) -> Self {
-        Self { font_paths, variants }
+    fn new(
+        font_paths: Vec<PathBuf>,
+        exclude_font_paths: Vec<String>,
+        font_cache: bool,
+        variants: bool,
+        json: bool,
+    ) -> Self {
+        Self {
+            font_paths,
+            exclude_font_paths,
+            font_cache,
+            variants,
+            json,
+        }
     }
 
     /// Create a new font settings from the CLI arguments.
@@ -186,14 +386,32 @@ impl FontsSettings {
     /// Panics if the command is not a fonts command.
     fn with_arguments(args: CliArguments) -> Self {
         match args.command {
-            Command::Fonts(command) => Self::new(args.font_paths, command.variants),
+            Command::Fonts(command) => Self::new(
+                args.font_paths,
+                args.exclude_font_paths,
+                !args.no_font_cache,
+                command.variants,
+                command.json,
+            ),
             _ => unreachable!(),
         }
     }
 }
 
 /// Execute a compilation command.
-fn compile(mut command: CompileSettings) -> StrResult<()> {
+fn compile(command: CompileSettings) -> StrResult<()> {
+    if command.stdin {
+        if command.root.is_none() {
+            bail!("reading from stdin requires a root directory (use --root)");
+        }
+        if command.output.as_os_str().is_empty() && !command.check {
+            bail!("reading from stdin requires an explicit output path");
+        }
+        if command.watch {
+            bail!("cannot watch stdin for changes");
+        }
+    }
+
     // Determine the parent directory of the input file.
     let parent = command
         .input
@@ -217,24 +435,82 @@ fn compile(mut command: CompileSettings) -> StrResult<()> {
     //neither reading nor writing are disabled, by default, though they may be, if need be.
     let mut wp = WriteStorage::default();
 
+    // Parse the patterns of environment variables to allow.
+    let env = command
+        .allow_env
+        .iter()
+        .map(|glob| {
+            glob::Pattern::new(glob).map_err(|err| {
+                format!("invalid environment variable pattern `{glob}`: {err}")
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let permissions = Permissions {
+        env,
+        exec: command.allow_exec,
+        net: command.allow_net.clone(),
+    };
+
+    // A fixed creation timestamp replaces the system time.
+    let now = command
+        .creation_timestamp
+        .map(|timestamp| {
+            chrono::NaiveDateTime::from_timestamp_opt(timestamp, 0)
+                .ok_or("creation timestamp is out of range")
+        })
+        .transpose()?;
+
+    // Search for fonts.
+    let mut searcher =
+        FontSearcher::new(command.font_cache, &command.exclude_font_paths)?;
+    searcher.search(&command.font_paths);
+
     // Create the world that serves sources, fonts and files.
-    let mut world = SystemWorld::new(root, dest, &command.font_paths, &mut wp);
+    let inputs = command
+        .inputs
+        .iter()
+        .map(|(key, value)| (key.as_str().into(), value.as_str().into_value()))
+        .collect();
+    // Packages are searched in the user's data directory by default.
+    let package_path = command
+        .package_path
+        .clone()
+        .or_else(|| dirs::data_dir().map(|dir| dir.join("typst").join("packages")));
+
+    let mut world = SystemWorld::new(
+        root,
+        dest,
+        package_path,
+        typst_library::build_with_inputs(inputs),
+        searcher,
+        permissions,
+        now,
+        &mut wp,
+    );
+
+    // Start the preview server and open the preview window if requested.
+    let server = command.serve.as_deref().map(Server::start).transpose()?;
+    let mut preview = command.preview.then(Preview::open).transpose()?;
 
     // Perform initial compilation.
-    let ok = compile_once(&mut world, &command)?;
-
-    // Open the file if requested, this must be done on the first **successful**
-    // compilation.
-    if ok {
-        if let Some(open) = command.open.take() {
-            open_file(open.as_deref(), &command.output)?;
-        }
-    }
+    let mut opener = Opener::new(command.open.clone(), command.reopen, command.check);
+    let ok = compile_once(&mut world, &command, server.as_ref(), preview.as_mut())?;
+    opener.compiled(ok, &command.output)?;
 
     if !command.watch {
         return Ok(());
     }
 
+    // Parse the patterns of files to ignore.
+    let ignore = command
+        .watch_ignore
+        .iter()
+        .map(|glob| {
+            glob::Pattern::new(glob)
+                .map_err(|err| format!("invalid glob pattern `{glob}`: {err}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
     // Setup file watching.
     let (tx, rx) = std::sync::mpsc::channel();
     let mut watcher = RecommendedWatcher::new(tx, notify::Config::default())
@@ -253,28 +529,52 @@ fn compile(mut command: CompileSettings) -> StrResult<()> {
                 .map_err(|_| "failed to watch root directory")?;
         }
     }
-    // Unwatch the dest directory recursively.
-    if let Ok(dest) = &world.dest {
-        if *dest != parent {
-            let _ = watcher.unwatch(dest); //we discard the result
-        }
-    }
+
+    // Changes to the files the compiler exports and to files matching an
+    // ignore pattern never trigger a recompilation. Files written by the
+    // document are filtered out when the events are handled, since they are
+    // only known once they were written. Everything else in the destination
+    // directory may still be a dependency, e.g. if it is the root directory.
+    let outputs: Vec<PathBuf> = std::iter::once(command.output.clone())
+        .chain(command.source_map.clone())
+        .chain(command.write_manifest.clone())
+        .collect();
+    let ignored = |path: &Path| {
+        outputs
+            .iter()
+            .any(|output| is_same_file(path, output).unwrap_or(false))
+            || persist::is_temporary(path)
+            || ignore.iter().any(|pattern| {
+                path.file_name()
+                    .map_or(false, |name| pattern.matches_path(Path::new(name)))
+                    || path
+                        .strip_prefix(&parent)
+                        .map_or(false, |path| pattern.matches_path(path))
+            })
+    };
 
     // Handle events.
-    let timeout = std::time::Duration::from_millis(100);
+    let timeout = std::time::Duration::from_millis(command.watch_delay);
     loop {
+        // The preview window must keep handling input while waiting for
+        // changes. It stops watching once closed.
+        let first = match &mut preview {
+            Some(preview) => {
+                if !preview.update() {
+                    return Ok(());
+                }
+                rx.recv_timeout(std::time::Duration::from_millis(16)).ok()
+            }
+            None => rx.recv().ok(),
+        };
+
         let mut recompile = false;
-        for event in rx
-            .recv()
+        for event in first
             .into_iter()
             .chain(std::iter::from_fn(|| rx.recv_timeout(timeout).ok()))
         {
             let event = event.map_err(|_| "failed to watch directory")?;
-            if event
-                .paths
-                .iter()
-                .all(|path| is_same_file(path, &command.output).unwrap_or(false))
-            {
+            if event.paths.iter().all(|path| ignored(path)) {
                 continue;
             }
 
@@ -282,16 +582,10 @@ fn compile(mut command: CompileSettings) -> StrResult<()> {
         }
 
         if recompile {
-            let ok = compile_once(&mut world, &command)?;
+            let ok =
+                compile_once(&mut world, &command, server.as_ref(), preview.as_mut())?;
             comemo::evict(30);
-
-            // Ipen the file if requested, this must be done on the first
-            // **successful** compilation
-            if ok {
-                if let Some(open) = command.open.take() {
-                    open_file(open.as_deref(), &command.output)?;
-                }
-            }
+            opener.compiled(ok, &command.output)?;
         }
     }
 }
@@ -300,20 +594,74 @@ fn compile(mut command: CompileSettings) -> StrResult<()> {
 ///
 /// Returns whether it compiled without errors.
 #[tracing::instrument(skip_all)]
-fn compile_once(world: &mut SystemWorld, command: &CompileSettings) -> StrResult<bool> {
+fn compile_once(
+    world: &mut SystemWorld,
+    command: &CompileSettings,
+    server: Option<&Server>,
+    preview: Option<&mut Preview>,
+) -> StrResult<bool> {
     tracing::info!("Starting compilation");
 
-    status(command, Status::Compiling).unwrap();
+    status(command, server, Status::Compiling).unwrap();
 
     world.reset();
-    world.main = world.resolve(&command.input).map_err(|err| err.to_string())?;
+    world.wpaths.clear();
+    world.main = if command.stdin {
+        world.stdin()?
+    } else {
+        world.resolve(&command.input).map_err(|err| err.to_string())?
+    };
+
+    let mut tracer = Tracer::default();
+    let mut result = typst::compile(world, &mut tracer);
 
-    match typst::compile(world) {
+    // Denied warnings fail the compilation.
+    let (denied, warnings): (Vec<_>, Vec<_>) =
+        tracer.warnings().iter().cloned().partition(|warning| {
+            command.deny_warnings
+                || (command.fail_on_missing_font && is_missing_font(warning))
+        });
+    if !denied.is_empty() {
+        let mut errors = result.err().map_or(vec![], |errors| *errors);
+        errors.extend(denied);
+        result = Err(Box::new(errors));
+    }
+
+    match result {
         // Export the PDF / PNG.
-        Ok(document) => {
-            export(&document, command)?;
-            write(world)?;
-            status(command, Status::Success).unwrap();
+        Ok(mut document) => {
+            // Apply metadata overrides from the command line.
+            if let Some(title) = &command.title {
+                document.title = Some(title.as_str().into());
+            }
+            if !command.author.is_empty() {
+                document.author =
+                    command.author.iter().map(|s| s.as_str().into()).collect();
+            }
+            if let Some(now) = world.now {
+                document.date = convert_datetime(now);
+            }
+
+            if !command.check {
+                let map = command.source_map.as_ref().map(|path| {
+                    let root = world.root.as_deref().unwrap_or(Path::new("."));
+                    (path, SourceMap::new(world, root, &document))
+                });
+                export(&document, command, map.as_ref().map(|(_, map)| map))?;
+                if let Some((path, map)) = &map {
+                    map.save(path)?;
+                }
+                write(world, command)?;
+            }
+            if let Some(server) = server {
+                server.publish(&document, command.ppi.unwrap_or(2.0));
+            }
+            if let Some(preview) = preview {
+                preview.publish(&document, command.ppi.unwrap_or(2.0));
+            }
+            status(command, server, Status::Success).unwrap();
+            print_diagnostics(world, vec![], warnings, command.diagnostic_format)
+                .map_err(|_| "failed to print diagnostics")?;
             tracing::info!("Compilation succeeded");
             Ok(true)
         }
@@ -321,8 +669,8 @@ fn compile_once(world: &mut SystemWorld, command: &CompileSettings) -> StrResult
         // Print diagnostics.
         Err(errors) => {
             set_failed();
-            status(command, Status::Error).unwrap();
-            print_diagnostics(world, *errors, command.diagnostic_format)
+            status(command, server, Status::Error).unwrap();
+            print_diagnostics(world, *errors, warnings, command.diagnostic_format)
                 .map_err(|_| "failed to print diagnostics")?;
             tracing::info!("Compilation failed");
             Ok(false)
@@ -330,84 +678,422 @@ fn compile_once(world: &mut SystemWorld, command: &CompileSettings) -> StrResult
     }
 }
 
+/// Convert a chrono datetime into a Typst datetime.
+fn convert_datetime(datetime: chrono::NaiveDateTime) -> Option<Datetime> {
+    Datetime::from_ymd_hms(
+        datetime.year(),
+        datetime.month().try_into().ok()?,
+        datetime.day().try_into().ok()?,
+        datetime.hour().try_into().ok()?,
+        datetime.minute().try_into().ok()?,
+        datetime.second().try_into().ok()?,
+    )
+}
+
+/// Whether a warning reports a requested font family that is not available.
+fn is_missing_font(warning: &SourceError) -> bool {
+    warning.kind == ErrorKind::UnknownFontFamily
+}
+
 /// Export into the target format.
-fn export(document: &Document, command: &CompileSettings) -> StrResult<()> {
-    match command.output.extension() {
-        Some(ext) if ext.eq_ignore_ascii_case("png") => {
+///
+/// If there is a source map, PDF output gets named destinations for its lines.
+#[tracing::instrument(skip_all)]
+fn export(
+    document: &Document,
+    command: &CompileSettings,
+    map: Option<&SourceMap>,
+) -> StrResult<()> {
+    let imposed =
+        command.imposition.is_some() || command.nup.is_some() || command.tile.is_some();
+
+    // HTML, EPUB and DOCX files follow the document's structure instead of its
+    // pages, so pages can't be selected or arranged for them. Text is
+    // extracted from the pages, but arranging them would jumble it.
+    let structured = ["html", "epub", "docx"]
+        .into_iter()
+        .find(|ext| has_extension(&command.output, ext));
+    if let Some(ext) = structured {
+        if command.pages.is_some() {
+            bail!("cannot select pages when exporting to {}", ext.to_uppercase());
+        }
+    }
+    if imposed && (structured.is_some() || has_extension(&command.output, "txt")) {
+        bail!("imposition is only supported when exporting to PDF or images");
+    }
+
+    // Determine which pages to export, keeping their original numbers.
+    let pages: Vec<(usize, &Frame)> = document
+        .pages
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            command.pages.as_ref().map_or(true, |pages| pages.contains(i + 1))
+        })
+        .collect();
+    if pages.is_empty() {
+        bail!("no pages to export");
+    }
+
+    // Arrange the pages onto sheets. The sheets are numbered on their own.
+    let sheets;
+    let pages = if imposed {
+        let frames: Vec<Frame> =
+            pages.into_iter().map(|(_, frame)| frame.clone()).collect();
+        sheets = if let Some(grid) = command.nup {
+            typst::export::nup(&frames, grid.columns, grid.rows)
+        } else if let Some(paper) = command.tile {
+            // Neighbouring sheets overlap by a centimeter for gluing.
+            let sheet = Size::new(paper.width(), paper.height());
+            typst::export::tile(&frames, sheet, Abs::mm(10.0))
+        } else {
+            typst::export::booklet(&frames)
+        };
+        sheets.iter().enumerate().collect()
+    } else {
+        pages
+    };
+
+    // Render pages at the requested resolution.
+    let ppi = command.ppi.unwrap_or(2.0);
+    let fill = command.background.map_or(Color::WHITE, Color::Rgba);
+    let render = |frame: &Frame| match scale_to_pixels(frame, command) {
+        Some((ppi, sized)) => {
+            typst::export::render(sized.as_ref().unwrap_or(frame), ppi, fill)
+        }
+        None => typst::export::render(frame, ppi, fill),
+    };
+
+    // Render pages into images with demultiplied alpha.
+    let rasterize = |frame: &Frame| {
+        let pixmap = render(frame);
+        let data = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let c = pixel.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+        RgbaImage::from_raw(pixmap.width(), pixmap.height(), data).unwrap()
+    };
+
+    // The document with just the exported pages or sheets.
+    let subset = || {
+        if !imposed && pages.len() == document.pages.len() {
+            Cow::Borrowed(document)
+        } else {
+            let pages = pages.iter().map(|&(_, frame)| frame.clone()).collect();
+            Cow::Owned(Document { pages, ..document.clone() })
+        }
+    };
+
+    match command.output.extension().and_then(RasterFormat::from_extension) {
+        Some(RasterFormat::Gif) => {
+            // All pages go into one animated image that shows them in turn.
+            let delay = Delay::from_numer_denom_ms(command.frame_delay, 1);
+            let frames: Vec<_> = pages
+                .iter()
+                .map(|&(_, frame)| {
+                    image::Frame::from_parts(rasterize(frame), 0, 0, delay)
+                })
+                .collect();
+
+            let mut buffer = vec![];
+            let mut encoder = GifEncoder::new(&mut buffer);
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .and_then(|_| encoder.encode_frames(frames))
+                .map_err(|_| "failed to encode GIF file")?;
+            drop(encoder);
+            persist::write(&command.output, &buffer)?;
+        }
+        Some(format) => {
             // Determine whether we have a `{n}` numbering.
             let string = command.output.to_str().unwrap_or_default();
             let numbered = string.contains("{n}");
-            if !numbered && document.pages.len() > 1 {
-                bail!("cannot export multiple PNGs without `{{n}}` in output path");
+            if !numbered && pages.len() > 1 {
+                bail!(
+                    "cannot export multiple {} files without `{{n}}` in output path",
+                    format.name(),
+                );
             }
 
             // Find a number width that accommodates all pages. For instance, the
             // first page should be numbered "001" if there are between 100 and
             // 999 pages.
             let width = 1 + document.pages.len().checked_ilog10().unwrap_or(0) as usize;
-            let ppi = command.ppi.unwrap_or(2.0);
-            let mut storage;
-
-            for (i, frame) in document.pages.iter().enumerate() {
-                let pixmap = typst::export::render(frame, ppi, Color::WHITE);
+            let export = |&(i, frame): &(usize, &Frame)| -> StrResult<()> {
+                let storage;
                 let path = if numbered {
                     storage = string.replace("{n}", &format!("{:0width$}", i + 1));
                     Path::new(&storage)
                 } else {
                     command.output.as_path()
                 };
-                pixmap.save_png(path).map_err(|_| "failed to write PNG file")?;
+                let buffer = match format {
+                    RasterFormat::Png => render(frame)
+                        .encode_png()
+                        .map_err(|_| "failed to encode PNG file")?,
+                    _ => encode_image(format, rasterize(frame), command)?,
+                };
+                persist::write(path, &buffer)
+            };
+
+            match command.jobs {
+                None => pages.iter().try_for_each(export)?,
+                Some(jobs) => {
+                    let pool = rayon::ThreadPoolBuilder::new()
+                        .num_threads(jobs)
+                        .build()
+                        .map_err(|_| "failed to start rendering threads")?;
+                    pool.install(|| pages.par_iter().try_for_each(export))?;
+                }
             }
         }
-        _ => {
-            let buffer = typst::export::pdf(document);
-            fs::write(&command.output, buffer).map_err(|_| "failed to write PDF file")?;
+        None if has_extension(&command.output, "html") => {
+            // The HTML follows the document's structure instead of its pages.
+            let html = typst::export::html(document);
+            persist::write(&command.output, html.as_bytes())?;
+        }
+        None if has_extension(&command.output, "epub") => {
+            let buffer = typst::export::epub(document)?;
+            persist::write(&command.output, &buffer)?;
+        }
+        None if has_extension(&command.output, "docx") => {
+            let buffer = typst::export::docx(document)?;
+            persist::write(&command.output, &buffer)?;
+        }
+        None if has_extension(&command.output, "txt") => {
+            let text = typst::export::text(
+                &subset(),
+                &command.page_separator,
+                command.soft_hyphens,
+            );
+            persist::write(&command.output, text.as_bytes())?;
+        }
+        None => {
+            let standard = command.pdf_standard.map(|standard| match standard {
+                PdfStandard::A2b => typst::export::PdfStandard::A2b,
+            });
+
+            // Destinations on pages that are not exported are dropped, the
+            // others move along with their pages. Imposed sheets don't have
+            // destinations.
+            let destinations: Vec<_> = map
+                .filter(|_| !imposed)
+                .map(|map| map.destinations())
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(name, mut pos)| {
+                    let index =
+                        pages.iter().position(|&(i, _)| i + 1 == pos.page.get())?;
+                    pos.page = NonZeroUsize::new(index + 1)?;
+                    Some((name, pos))
+                })
+                .collect();
+
+            let buffer =
+                typst::export::pdf_with_destinations(&subset(), standard, &destinations)?;
+            persist::write(&command.output, &buffer)?;
         }
     }
     Ok(())
 }
 
+/// Whether a path has the given extension, ignoring case.
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .map_or(false, |other| other.eq_ignore_ascii_case(ext))
+}
+
+/// A raster image format that pages can be exported to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum RasterFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Gif,
+}
+
+impl RasterFormat {
+    /// Determine the format from a file extension.
+    fn from_extension(ext: &OsStr) -> Option<Self> {
+        match ext.to_str()?.to_ascii_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "webp" => Some(Self::Webp),
+            "gif" => Some(Self::Gif),
+            _ => None,
+        }
+    }
+
+    /// The name of the format.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Jpeg => "JPEG",
+            Self::Webp => "WebP",
+            Self::Gif => "GIF",
+        }
+    }
+}
+
+/// Encode a rendered page into a JPEG or WebP file.
+///
+/// JPEG files don't support transparency, so the alpha channel is dropped.
+fn encode_image(
+    format: RasterFormat,
+    image: RgbaImage,
+    command: &CompileSettings,
+) -> StrResult<Vec<u8>> {
+    let (width, height) = image.dimensions();
+    let mut buffer = vec![];
+    let result = match format {
+        RasterFormat::Jpeg => {
+            let rgb = DynamicImage::ImageRgba8(image).into_rgb8();
+            JpegEncoder::new_with_quality(&mut buffer, command.quality).encode(
+                &rgb,
+                width,
+                height,
+                ColorType::Rgb8,
+            )
+        }
+        _ => WebPEncoder::new_with_quality(
+            &mut buffer,
+            WebPQuality::lossy(command.quality),
+        )
+        .encode(&image, width, height, ColorType::Rgba8),
+    };
+
+    result.map_err(|_| eco_format!("failed to encode {} file", format.name()))?;
+    Ok(buffer)
+}
+
+/// Determine the pixels per point that scale a page to the requested pixel
+/// dimensions, if any.
+///
+/// If both a width and a height are given, also returns a frame with the
+/// exact dimensions that holds the centered page.
+fn scale_to_pixels(
+    frame: &Frame,
+    command: &CompileSettings,
+) -> Option<(f32, Option<Frame>)> {
+    let size = frame.size();
+    let sx = command.width.map(|width| width.get() as f64 / size.x.to_pt());
+    let sy = command.height.map(|height| height.get() as f64 / size.y.to_pt());
+    let scale = match (sx, sy) {
+        (None, None) => return None,
+        (Some(scale), None) | (None, Some(scale)) => return Some((scale as f32, None)),
+        (Some(sx), Some(sy)) => match command.scaling {
+            Scaling::Fit => sx.min(sy),
+            Scaling::Fill => sx.max(sy),
+        },
+    };
+
+    let (width, height) = (command.width?.get() as f64, command.height?.get() as f64);
+    let target = Size::new(Abs::pt(width / scale), Abs::pt(height / scale));
+    let mut sized = Frame::new(target);
+    sized.push_frame(((target - size) / 2.0).to_point(), frame.clone());
+    sized.clip();
+    Some((scale as f32, Some(sized)))
+}
+
 /// Apply write calls
 /// These are very limited in where they can write, which is no issue as we excpect to be unable to write everywhere
 #[tracing::instrument(skip_all)]
-fn write(world: &SystemWorld) -> StrResult<()> {
+fn write(world: &mut SystemWorld, command: &CompileSettings) -> StrResult<()> {
     // Find file
     tracing::info!("Writing result files..");
+    world.flushed.clear();
     let hashes = world.hashes.borrow();
-    for (h, s) in world.wpaths.dump() {
+    let mut manifest = vec![];
+
+    // Stage all files first, so that nothing is written if one of them can't
+    // be written.
+    let mut staged = vec![];
+    for (h, data) in world.wpaths.dump() {
         let loc = hashes.iter().find(|(_, v)| match v {
             Err(_) => false,
             Ok(v) => *v == h,
         });
         if let Some((path, _)) = loc {
-            let data = s;
             if data.is_empty() {
                 // Nothing to write
                 continue;
-            } else {
-                // Remember; we aren't interested with order conservation here! what's important is that the data is there.
-                let buffer: Vec<u8> = data.dump();
-                // Generate file name, and write
-                tracing::info!(
-                    "Writing file: {}",
-                    path.to_str().unwrap_or("{invalid_name}")
-                );
-                fs::write(path, buffer).map_err(|_| {
-                    format!(
-                        "failed to write {} file",
-                        path.file_name()
-                            .map_or("..", |s| s.to_str().unwrap_or("{invalid_name}"))
-                    )
-                })?;
             }
+
+            let display = path.to_str().unwrap_or("{invalid_name}");
+            tracing::info!("Writing file: {}", display);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|_| "failed to create destination directory")?;
+            }
+
+            let before = fs::read(path).ok();
+            let original =
+                world.originals.entry(path.clone()).or_insert_with(|| before.clone());
+            let contents = data.contents(path, original.as_deref(), before.as_deref())?;
+            let after = match contents {
+                Some(after) => {
+                    staged.push((
+                        path.clone(),
+                        before.clone(),
+                        persist::stage(path, &after)?,
+                    ));
+                    after
+                }
+                None => {
+                    tracing::info!("Skipped unchanged file: {}", display);
+                    before.clone().unwrap_or_default()
+                }
+            };
+
+            manifest.push(serde_json::json!({
+                "path": path,
+                "size": after.len(),
+                "changed": before.as_ref() != Some(&after),
+                "spans": data.spans.iter().map(|h| format!("{h:032x}")).collect::<Vec<_>>(),
+            }));
+        }
+    }
+
+    // Move the staged files into place. If that fails, the files that were
+    // already replaced are restored.
+    let mut committed = vec![];
+    for (path, before, file) in staged {
+        if let Err(err) = file.commit() {
+            for (path, before) in committed.into_iter().rev() {
+                match before {
+                    Some(data) => persist::write(&path, &data).ok(),
+                    None => fs::remove_file(&path).ok(),
+                };
+            }
+            return Err(err);
         }
+
+        world.flushed.insert(canonicalize_lenient(&path));
+        committed.push((path, before));
+    }
+
+    if let Some(path) = &command.write_manifest {
+        let json = serde_json::json!({ "files": manifest });
+        let buf = serde_json::to_vec_pretty(&json)
+            .map_err(|_| "failed to serialize manifest")?;
+        persist::write(path, &buf)?;
     }
+
     Ok(())
 }
 
 /// Clear the terminal and render the status message.
 #[tracing::instrument(skip_all)]
-fn status(command: &CompileSettings, status: Status) -> io::Result<()> {
+fn status(
+    command: &CompileSettings,
+    server: Option<&Server>,
+    status: Status,
+) -> io::Result<()> {
     if !command.watch {
         return Ok(());
     }
@@ -436,6 +1122,13 @@ fn status(command: &CompileSettings, status: Status) -> io::Result<()> {
     w.reset()?;
     writeln!(w, " {output}")?;
 
+    if let Some(server) = server {
+        w.set_color(&color)?;
+        write!(w, "serving at")?;
+        w.reset()?;
+        writeln!(w, " {}", server.url())?;
+    }
+
     writeln!(w)?;
     writeln!(w, "[{timestamp}] {message}")?;
     writeln!(w)?;
@@ -481,11 +1174,30 @@ impl Status {
 fn print_diagnostics(
     world: &SystemWorld,
     errors: Vec<SourceError>,
+    warnings: Vec<SourceError>,
     diagnostic_format: DiagnosticFormat,
 ) -> Result<(), codespan_reporting::files::Error> {
+    let summary = summarize(world, &errors, &warnings);
+
+    // Group the diagnostics by file, keeping their order within each file.
+    // Diagnostics without a file come first.
+    let mut diagnostics: Vec<_> = errors
+        .into_iter()
+        .map(|error| (Severity::Error, error))
+        .chain(warnings.into_iter().map(|warning| (Severity::Warning, warning)))
+        .collect();
+    diagnostics.sort_by_key(|(_, error)| {
+        let id = error.span.source();
+        (!id.is_detached()).then(|| world.source(id).path())
+    });
+
     let mut w = match diagnostic_format {
         DiagnosticFormat::Human => color_stream(),
         DiagnosticFormat::Short => StandardStream::stderr(ColorChoice::Never),
+        DiagnosticFormat::Json => {
+            return print_json_diagnostics(world, diagnostics.into_iter())
+                .map_err(CodespanError::Io);
+        }
     };
 
     let mut config = term::Config { tab_width: 2, ..Default::default() };
@@ -493,32 +1205,181 @@ fn print_diagnostics(
         config.display_style = term::DisplayStyle::Short;
     }
 
-    for error in errors {
-        // The main diagnostic.
-        let range = error.range(world);
-        let diag = Diagnostic::error()
+    for (severity, error) in diagnostics {
+        // The main diagnostic. Diagnostics that don't belong to a source file
+        // are printed without a label.
+        let mut labels = vec![];
+        if !error.span.is_detached() {
+            labels.push(Label::primary(error.span.source(), error.range(world)));
+        }
+
+        let diag = Diagnostic::new(severity)
             .with_message(error.message)
-            .with_labels(vec![Label::primary(error.span.source(), range)]);
+            .with_labels(labels);
 
         term::emit(&mut w, &config, world, &diag)?;
 
         // Stacktrace-like helper diagnostics.
         for point in error.trace {
             let message = point.v.to_string();
-            let help = Diagnostic::help().with_message(message).with_labels(vec![
-                Label::primary(
-                    point.span.source(),
-                    world.source(point.span.source()).range(point.span),
-                ),
-            ]);
+            let mut labels = vec![];
+            if !point.span.is_detached() {
+                let id = point.span.source();
+                labels.push(Label::primary(id, world.source(id).range(point.span)));
+            }
 
+            let help = Diagnostic::help().with_message(message).with_labels(labels);
             term::emit(&mut w, &config, world, &help)?;
         }
     }
 
+    if let Some(summary) = summary {
+        writeln!(w, "{summary}")?;
+    }
+
     Ok(())
 }
 
+/// Describe how many errors and warnings there are in how many files, if there
+/// is more than one diagnostic.
+fn summarize(
+    world: &SystemWorld,
+    errors: &[SourceError],
+    warnings: &[SourceError],
+) -> Option<String> {
+    if errors.len() + warnings.len() <= 1 {
+        return None;
+    }
+
+    let count = |n: usize, what: &str| match n {
+        1 => format!("1 {what}"),
+        n => format!("{n} {what}s"),
+    };
+
+    let files: HashSet<_> = errors
+        .iter()
+        .chain(warnings)
+        .filter(|diag| !diag.span.is_detached())
+        .map(|diag| world.source(diag.span.source()).path())
+        .collect();
+
+    let mut parts = vec![];
+    if !errors.is_empty() {
+        parts.push(count(errors.len(), "error"));
+    }
+    if !warnings.is_empty() {
+        parts.push(count(warnings.len(), "warning"));
+    }
+
+    Some(format!("{} in {}", parts.join(" and "), count(files.len(), "file")))
+}
+
+/// Print diagnostic messages to the terminal as JSON objects, one per line.
+///
+/// Diagnostics that don't belong to a source file have a `null` span.
+fn print_json_diagnostics(
+    world: &SystemWorld,
+    diagnostics: impl Iterator<Item = (Severity, SourceError)>,
+) -> io::Result<()> {
+    let mut w = io::stderr().lock();
+    for (severity, error) in diagnostics {
+        let trace: Vec<_> = error
+            .trace
+            .iter()
+            .map(|point| {
+                let id = point.span.source();
+                serde_json::json!({
+                    "message": point.v.to_string(),
+                    "span": (!point.span.is_detached()).then(|| {
+                        json_span(world, id, world.source(id).range(point.span))
+                    }),
+                })
+            })
+            .collect();
+
+        let diag = serde_json::json!({
+            "severity": match severity {
+                Severity::Warning => "warning",
+                _ => "error",
+            },
+            "message": error.message.as_str(),
+            "span": (!error.span.is_detached()).then(|| {
+                json_span(world, error.span.source(), error.range(world))
+            }),
+            "trace": trace,
+        });
+
+        writeln!(w, "{diag}")?;
+    }
+
+    w.flush()
+}
+
+/// Describe a byte range in a source file as JSON.
+fn json_span(
+    world: &SystemWorld,
+    id: SourceId,
+    range: Range<usize>,
+) -> serde_json::Value {
+    let source = world.source(id);
+    let position = |byte| {
+        serde_json::json!({
+            "line": source.byte_to_line(byte).map(|line| line + 1),
+            "column": source.byte_to_column(byte).map(|column| column + 1),
+        })
+    };
+
+    serde_json::json!({
+        "path": source.path().display().to_string(),
+        "start": position(range.start),
+        "end": position(range.end),
+        "range": { "start": range.start, "end": range.end },
+    })
+}
+
+/// Decides when to open the output file in a viewer.
+enum Opener {
+    /// The file is not opened (anymore).
+    Never,
+    /// The file is opened after the next successful compilation, with the
+    /// given viewer or the default one.
+    Once(Option<String>),
+    /// The file is opened after every successful compilation, which brings an
+    /// already open viewer to the front.
+    Always(Option<String>),
+}
+
+impl Opener {
+    /// Create an opener from the `--open` and `--reopen` arguments. When only
+    /// checking, there is no file to open.
+    fn new(open: Option<Option<String>>, reopen: bool, check: bool) -> Self {
+        match open {
+            _ if check => Self::Never,
+            None => Self::Never,
+            Some(app) if reopen => Self::Always(app),
+            Some(app) => Self::Once(app),
+        }
+    }
+
+    /// Open the file if a compilation succeeded and it is due.
+    fn compiled(&mut self, ok: bool, path: &Path) -> StrResult<()> {
+        if !ok {
+            return Ok(());
+        }
+
+        match self {
+            Self::Never => {}
+            Self::Once(app) => {
+                open_file(app.as_deref(), path)?;
+                *self = Self::Never;
+            }
+            Self::Always(app) => open_file(app.as_deref(), path)?,
+        }
+
+        Ok(())
+    }
+}
+
 /// Opens the given file using:
 /// - The default file viewer if `open` is `None`.
 /// - The given viewer provided by `open` if it is `Some`.
@@ -534,9 +1395,14 @@ fn open_file(open: Option<&str>, path: &Path) -> StrResult<()> {
 
 /// Execute a font listing command.
 fn fonts(command: FontsSettings) -> StrResult<()> {
-    let mut searcher = FontSearcher::new();
+    let mut searcher =
+        FontSearcher::new(command.font_cache, &command.exclude_font_paths)?;
     searcher.search(&command.font_paths);
 
+    if command.json {
+        return print_fonts_json(&searcher).map_err(|_| "failed to print fonts".into());
+    }
+
     for (name, infos) in searcher.book.families() {
         println!("{name}");
         if command.variants {
@@ -550,10 +1416,41 @@ fn fonts(command: FontsSettings) -> StrResult<()> {
     Ok(())
 }
 
+/// Print all font faces as a JSON array.
+fn print_fonts_json(searcher: &FontSearcher) -> io::Result<()> {
+    let faces: Vec<_> = searcher
+        .fonts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, slot)| Some((slot, searcher.book.info(i)?)))
+        .map(|(slot, info)| {
+            let FontVariant { style, weight, stretch } = info.variant;
+            serde_json::json!({
+                "family": info.family,
+                "path": (!slot.path.as_os_str().is_empty())
+                    .then(|| slot.path.display().to_string()),
+                "index": slot.index,
+                "style": style,
+                "weight": weight.to_number(),
+                "stretch": stretch.to_ratio().get(),
+                "monospace": info.flags.contains(FontFlags::MONOSPACE),
+                "serif": info.flags.contains(FontFlags::SERIF),
+                "coverage": { "codepoints": info.coverage.iter().count() },
+            })
+        })
+        .collect();
+
+    let mut w = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut w, &faces)?;
+    writeln!(w)
+}
+
 /// A world that provides access to the operating system.
 struct SystemWorld<'a> {
     root: FileResult<PathBuf>,
     dest: FileResult<PathBuf>,
+    /// The directory to search for packages.
+    package_path: Option<PathBuf>,
     library: Prehashed<Library>,
     book: Prehashed<FontBook>,
     fonts: Vec<FontSlot>,
@@ -562,7 +1459,39 @@ struct SystemWorld<'a> {
     wpaths: TrackedMut<'a, WriteStorage>,
     sources: FrozenVec<Box<Source>>,
     today: Cell<Option<Datetime>>,
+    /// The results of the external programs run during this compilation, so
+    /// that each program runs only once per set of arguments.
+    outputs: RefCell<HashMap<(EcoString, Vec<EcoString>), StrResult<EcoString>>>,
+    /// The current time in UTC if it is fixed for reproducible builds.
+    now: Option<chrono::NaiveDateTime>,
     main: SourceId,
+    /// Slots whose underlying files changed since the last compilation.
+    dirty: HashSet<PathHash>,
+    /// Whether the file system changed in a way that requires dropping all
+    /// cached slots (files were created, removed or renamed).
+    stale: bool,
+    /// Canonical paths of the files touched by the last flush of written data.
+    /// Events for them are caused by the compilation itself.
+    flushed: HashSet<PathBuf>,
+    /// The contents of the written files from before this session first wrote
+    /// them, `None` for files that didn't exist.
+    originals: HashMap<PathBuf, Option<Vec<u8>>>,
+    /// What the document may do beyond reading and writing files.
+    permissions: Permissions,
+    /// Fetches remote resources.
+    fetcher: Fetcher,
+}
+
+/// Capabilities that are denied to documents unless granted on the command
+/// line.
+struct Permissions {
+    /// Patterns of environment variables the document may read.
+    env: Vec<glob::Pattern>,
+    /// Whether the document may run external programs.
+    exec: bool,
+    /// The hosts the document may fetch resources from, all if empty and none
+    /// if `None`.
+    net: Option<Vec<String>>,
 }
 
 /// Holds details about the location of a font and lazily the font itself.
@@ -572,31 +1501,86 @@ struct FontSlot {
     font: OnceCell<Option<Font>>,
 }
 
-#[derive(Clone,Debug,Default)]
+/// The chunks written to a single file.
+///
+/// Chunks are ordered by their explicit order first. Chunks with the same
+/// order keep the order in which they were written.
+#[derive(Clone, Debug, Default, Hash)]
 struct WriteBuffer {
-    buffer: RefCell<BTreeMap<u128, Vec<u8>>>, 
+    /// How to treat an existing file at the destination.
+    mode: WriteMode,
+    /// The chunks, by order.
+    chunks: BTreeMap<i64, Vec<Vec<u8>>>,
+    /// Whether the only chunk was written without an order. It must stay the
+    /// only one, but can be replaced by another chunk without an order.
+    exclusive: bool,
+    /// Hashes of the spans that wrote the chunks.
+    spans: BTreeSet<u128>,
 }
 
-impl Hash for WriteBuffer {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        let h = self.buffer.borrow();
-        for k in h.iter() { //todo! whooops, no order!!
-            k.hash(state);
+impl WriteBuffer {
+    /// Add a chunk at the given position.
+    fn insert(
+        &mut self,
+        order: Option<i64>,
+        mode: WriteMode,
+        data: Vec<u8>,
+        span: u128,
+    ) -> FileResult<()> {
+        if self.exclusive && order.is_none() {
+            *self = Self::default();
         }
-    }
-}
 
-impl WriteBuffer {
-    fn write(&mut self, at: u128, data: Vec<u8>) -> FileResult<()> {
-        let mut a = self.buffer.borrow_mut();
-        a.insert(at, data);
-        return Ok(());
+        if self.chunks.is_empty() {
+            self.mode = mode;
+        } else if self.exclusive || order.is_none() {
+            return Err(FileError::WrittenTwice);
+        } else if self.mode != mode {
+            return Err(FileError::ConflictingModes);
+        }
+        self.exclusive = order.is_none();
+        self.chunks.entry(order.unwrap_or_default()).or_default().push(data);
+        self.spans.insert(span);
+        Ok(())
     }
+
+    /// The concatenation of all chunks, in order.
     fn dump(&self) -> Vec<u8> {
-        self.buffer.borrow().values().flat_map(|v| v.clone()).collect()
+        self.chunks.values().flatten().flatten().copied().collect()
     }
+
+    /// Whether no chunk was written.
     fn is_empty(&self) -> bool {
-        self.buffer.borrow().is_empty()
+        self.chunks.is_empty()
+    }
+
+    /// The new contents of the file at the given path, honoring the mode.
+    ///
+    /// The mode is applied to the `original` contents of the file, from
+    /// before it was first written in this session, so that recompiling in
+    /// watch mode doesn't append the same data again or fail because the file
+    /// now exists. Returns `None` if the file doesn't need to be touched
+    /// because its `current` contents are already the same. Skipping it keeps
+    /// its modification time, so that tools watching the file aren't triggered
+    /// for nothing.
+    fn contents(
+        &self,
+        path: &Path,
+        original: Option<&[u8]>,
+        current: Option<&[u8]>,
+    ) -> StrResult<Option<Vec<u8>>> {
+        let data = self.dump();
+        let data = match self.mode {
+            WriteMode::Overwrite => data,
+            WriteMode::Append => [original.unwrap_or_default(), &data].concat(),
+            WriteMode::ErrorIfExists => {
+                if original.is_some() {
+                    bail!("failed to write {} (already exists)", path.display());
+                }
+                data
+            }
+        };
+        Ok((current != Some(data.as_slice())).then_some(data))
     }
 }
 
@@ -607,35 +1591,56 @@ struct PathSlot {
     buffer: OnceCell<FileResult<Buffer>>,
 }
 
+/// Collects everything that is written during compilation, per file.
 #[derive(Clone, Debug, Default)]
-struct WriteStorage(RefCell<HashMap<PathHash, WriteBuffer>>);
+struct WriteStorage(RefCell<BTreeMap<PathHash, WriteBuffer>>);
 
 #[comemo::track]
 impl WriteStorage {
-    fn write(&self, path: PathHash, with: (u128, Vec<u8>)) -> FileResult<()> {
-        self.0.borrow_mut().entry(path).or_default().write(with.0, with.1)
+    /// Add a chunk to the file with the given hash.
+    fn write(
+        &self,
+        path: PathHash,
+        order: Option<i64>,
+        mode: WriteMode,
+        data: Vec<u8>,
+        span: u128,
+    ) -> FileResult<()> {
+        self.0
+            .borrow_mut()
+            .entry(path)
+            .or_default()
+            .insert(order, mode, data, span)
+    }
+
+    /// Forget everything that was written.
+    fn clear(&self) {
+        self.0.borrow_mut().clear();
     }
+
+    /// All written files, ordered by their hash.
     fn dump(&self) -> Vec<(PathHash, WriteBuffer)> {
         self.0.borrow().clone().into_iter().collect()
     }
 }
 
-
-
 impl<'a> SystemWorld<'a> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         root: FileResult<PathBuf>,
         dest: FileResult<PathBuf>,
-        font_paths: &[PathBuf],
+        package_path: Option<PathBuf>,
+        library: Library,
+        searcher: FontSearcher,
+        permissions: Permissions,
+        now: Option<chrono::NaiveDateTime>,
         wp: &'a mut WriteStorage,
     ) -> Self {
-        let mut searcher = FontSearcher::new();
-        searcher.search(font_paths);
-
         Self {
             root,
             dest,
-            library: Prehashed::new(typst_library::build()),
+            package_path,
+            library: Prehashed::new(library),
             book: Prehashed::new(searcher.book),
             fonts: searcher.fonts,
             hashes: RefCell::default(),
@@ -643,7 +1648,15 @@ impl<'a> SystemWorld<'a> {
             wpaths: wp.track_mut(),
             sources: FrozenVec::new(),
             today: Cell::new(None),
+            outputs: RefCell::default(),
+            now,
             main: SourceId::detached(),
+            dirty: HashSet::new(),
+            stale: false,
+            flushed: HashSet::new(),
+            originals: HashMap::new(),
+            fetcher: Fetcher::new(permissions.net.clone()),
+            permissions,
         }
     }
 }
@@ -677,14 +1690,7 @@ impl World for SystemWorld<'_> {
             .get_or_init(|| {
                 let path =
                     path.canonicalize().map_err(|f| FileError::from_io(f, path))?;
-                let buf = read(&path)?;
-                let text = if buf.starts_with(b"\xef\xbb\xbf") {
-                    // remove UTF-8 BOM
-                    std::str::from_utf8(&buf[3..])?.to_owned()
-                } else {
-                    // Assume UTF-8
-                    String::from_utf8(buf)?
-                };
+                let text = decode(read(&path)?)?;
                 Ok(self.insert(&path, text))
             })
             .clone()
@@ -694,6 +1700,23 @@ impl World for SystemWorld<'_> {
         &self.sources[id.as_u16() as usize]
     }
 
+    fn package(&self, spec: &PackageSpec) -> StrResult<PathBuf> {
+        let dir = self
+            .package_path
+            .as_ref()
+            .map(|path| {
+                path.join(spec.namespace.as_str())
+                    .join(spec.name.as_str())
+                    .join(spec.version.as_str())
+            })
+            .filter(|dir| dir.is_dir());
+
+        match dir {
+            Some(dir) => project::entrypoint(&dir),
+            None => bail!("package not found ({spec})"),
+        }
+    }
+
     fn book(&self) -> &Prehashed<FontBook> {
         &self.book
     }
@@ -715,15 +1738,26 @@ impl World for SystemWorld<'_> {
             .clone()
     }
 
-    fn write(&self, path: &Path, at: u128, what: Vec<u8>) -> FileResult<()> {
-        self.wpaths.write(self.wslot(path)?, (at, what))
+    fn write(
+        &self,
+        path: &Path,
+        order: Option<i64>,
+        mode: WriteMode,
+        what: Vec<u8>,
+        span: Span,
+    ) -> FileResult<()> {
+        self.wpaths
+            .write(self.wslot(path)?, order, mode, what, hash128(&span))
     }
 
     fn today(&self, offset: Option<i64>) -> Option<Datetime> {
         if self.today.get().is_none() {
-            let datetime = match offset {
-                None => chrono::Local::now().naive_local(),
-                Some(o) => (chrono::Utc::now() + chrono::Duration::hours(o)).naive_utc(),
+            let datetime = match (self.now, offset) {
+                (Some(now), o) => now + chrono::Duration::hours(o.unwrap_or(0)),
+                (None, None) => chrono::Local::now().naive_local(),
+                (None, Some(o)) => {
+                    (chrono::Utc::now() + chrono::Duration::hours(o)).naive_utc()
+                }
             };
 
             self.today.set(Some(Datetime::from_ymd(
@@ -735,6 +1769,54 @@ impl World for SystemWorld<'_> {
 
         self.today.get()
     }
+
+    fn env(&self, name: &str) -> StrResult<Option<EcoString>> {
+        if !self.permissions.env.iter().any(|pattern| pattern.matches(name)) {
+            bail!(
+                "access to environment variable {name} is not allowed \
+                 (allow it with --allow-env)"
+            );
+        }
+        Ok(std::env::var_os(name).map(|value| value.to_string_lossy().into()))
+    }
+
+    fn exec(&self, program: &str, args: &[EcoString]) -> StrResult<EcoString> {
+        if !self.permissions.exec {
+            bail!(
+                "running external programs like {program} is not allowed \
+                 (allow it with --allow-exec)"
+            );
+        }
+
+        self.outputs
+            .borrow_mut()
+            .entry((program.into(), args.to_vec()))
+            .or_insert_with(|| run(program, args))
+            .clone()
+    }
+
+    fn fetch(&self, url: &str) -> StrResult<Buffer> {
+        self.fetcher.fetch(url)
+    }
+}
+
+/// Run an external program with arguments and return its standard output.
+fn run(program: &str, args: &[EcoString]) -> StrResult<EcoString> {
+    tracing::info!("Running {program}");
+    let output = std::process::Command::new(program)
+        .args(args.iter().map(EcoString::as_str))
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run {program} ({err})"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{program} failed with {}: {}", output.status, stderr.trim());
+    }
+
+    String::from_utf8(output.stdout)
+        .map(Into::into)
+        .map_err(|_| eco_format!("output of {program} is not valid utf-8"))
 }
 
 impl SystemWorld<'_> {
@@ -758,6 +1840,8 @@ impl SystemWorld<'_> {
         }))
     }
     fn wslot(&self, path: &Path) -> FileResult<PathHash> {
+        self.check_dest(path)?;
+
         let mut hashes = self.hashes.borrow_mut();
         let hash = match hashes.get(path).cloned() {
             Some(hash) => hash,
@@ -774,6 +1858,29 @@ impl SystemWorld<'_> {
         Ok(hash)
     }
 
+    /// Ensure that a path to be written lies within the destination directory.
+    ///
+    /// Both paths are canonicalized as far as they exist, so that neither `..`
+    /// components nor symlinks can escape the destination.
+    fn check_dest(&self, path: &Path) -> FileResult<()> {
+        let dest = canonicalize_lenient(self.root(AccessMode::W)?);
+        if canonicalize_lenient(path).starts_with(dest) {
+            Ok(())
+        } else {
+            Err(FileError::OutsideDest(path.into()))
+        }
+    }
+
+    /// Read the main source file from stdin.
+    ///
+    /// The source is placed directly in the root directory, so that both
+    /// absolute and relative paths in it are resolved from the root.
+    fn stdin(&self) -> StrResult<SourceId> {
+        let root = self.root.as_ref().map_err(|err| err.to_string())?;
+        let text = io::read_to_string(io::stdin()).map_err(|_| "failed to read stdin")?;
+        Ok(self.insert(&root.join("<stdin>"), text))
+    }
+
     #[tracing::instrument(skip_all)]
     fn insert(&self, path: &Path, text: String) -> SourceId {
         let id = SourceId::from_u16(self.sources.len() as u16);
@@ -782,42 +1889,120 @@ impl SystemWorld<'_> {
         id
     }
 
+    /// Whether the event affects the compilation. Marks the slots of all
+    /// affected files as dirty so that the next [`reset`](Self::reset) only
+    /// invalidates those.
     fn relevant(&mut self, event: &notify::Event) -> bool {
+        // Our own writes must not trigger a recompilation, or we would end up
+        // in a loop. They go through temporary files.
+        if !event.paths.is_empty()
+            && event.paths.iter().all(|path| {
+                persist::is_temporary(path)
+                    || self.flushed.contains(&canonicalize_lenient(path))
+            })
+        {
+            return false;
+        }
+
         match &event.kind {
             notify::EventKind::Any => {}
             notify::EventKind::Access(_) => return false,
-            notify::EventKind::Create(_) => return true,
+            notify::EventKind::Create(_) => {
+                self.stale = true;
+                return true;
+            }
             notify::EventKind::Modify(kind) => match kind {
                 notify::event::ModifyKind::Any => {}
                 notify::event::ModifyKind::Data(_) => {}
                 notify::event::ModifyKind::Metadata(_) => return false,
-                notify::event::ModifyKind::Name(_) => return true,
+                notify::event::ModifyKind::Name(_) => {
+                    self.stale = true;
+                    return true;
+                }
                 notify::event::ModifyKind::Other => return false,
             },
-            notify::EventKind::Remove(_) => {}
+            notify::EventKind::Remove(_) => {
+                let relevant = event.paths.iter().any(|path| self.dependant(path));
+                self.stale |= relevant;
+                return relevant;
+            }
             notify::EventKind::Other => return false,
         }
 
-        event.paths.iter().any(|path| self.dependant(path))
+        let mut relevant = false;
+        for path in &event.paths {
+            if let Some(hash) = self.dependency(path) {
+                self.dirty.insert(hash);
+                relevant = true;
+            }
+        }
+
+        relevant
     }
 
     fn dependant(&self, path: &Path) -> bool {
-        self.hashes.borrow().contains_key(&path.normalize())
-            || PathHash::new(path, AccessMode::R)
-                .map_or(false, |hash| self.paths.borrow().contains_key(&hash))
+        self.dependency(path).is_some()
     }
 
+    /// The hash of the slot the given path was loaded into, if any.
+    fn dependency(&self, path: &Path) -> Option<PathHash> {
+        let hash = match self.hashes.borrow().get(&path.normalize()) {
+            Some(hash) => hash.clone().ok()?,
+            None => PathHash::new(path, AccessMode::R).ok()?,
+        };
+        self.paths.borrow().contains_key(&hash).then_some(hash)
+    }
+
+    /// Prepare the world for the next compilation.
+    ///
+    /// Only the slots of files that changed since the last compilation are
+    /// invalidated. Sources that were already loaded are updated in place, so
+    /// that unchanged files and unchanged parts of changed files can be reused.
     #[tracing::instrument(skip_all)]
     fn reset(&mut self) {
-        self.sources.as_mut().clear();
-        self.hashes.borrow_mut().clear();
-        self.paths.borrow_mut().clear();
         self.today.set(None);
+        self.outputs.get_mut().clear();
+        self.fetcher.reset();
+
+        let dirty = std::mem::take(&mut self.dirty);
+        if std::mem::take(&mut self.stale) {
+            self.clear();
+            return;
+        }
+
+        let sources = self.sources.as_mut();
+        let paths = self.paths.get_mut();
+        for hash in dirty {
+            let Some(slot) = paths.get_mut(&hash) else { continue };
+            slot.buffer.take();
+
+            let Some(Ok(id)) = slot.source.get().cloned() else { continue };
+            let source = &mut sources[id.as_u16() as usize];
+            match read(source.path()).and_then(decode) {
+                Ok(text) if text == source.text() => {}
+                Ok(text) => source.replace(text),
+                Err(_) => {
+                    // The file became unreadable. Its source can't be dropped
+                    // on its own because source ids index into the list of
+                    // sources, so start from scratch to load it anew and
+                    // produce a proper error.
+                    self.clear();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Drop all loaded sources and files.
+    fn clear(&mut self) {
+        self.sources.as_mut().clear();
+        self.hashes.get_mut().clear();
+        self.paths.get_mut().clear();
     }
 }
 
 /// A hash that is the same for all paths pointing to the same entity.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 struct PathHash(u128);
 
 impl PathHash {
@@ -826,11 +2011,11 @@ impl PathHash {
         let handle = match mode {
             Access::Read(_) => Handle::from_path(path).map_err(f)?, //note: opening twice???
             Access::Write(_) => {
-                //Path has been validated, so we can create all misssing directories
-                fs::create_dir_all(path.parent().ok_or(FileError::AccessDenied)?)
-                    .map_err(f)?;
-                let file = File::create(path).map_err(f)?;
-                Handle::from_file(file).map_err(f)?
+                // Files are only created when they are flushed after
+                // compilation, so that the write mode can be honored. Until
+                // then, a written file is identified by its normalized path.
+                path.parent().ok_or(FileError::AccessDenied)?;
+                return Ok(Self(hash128(&path.normalize())));
             }
         };
         let state = hash128(&handle);
@@ -838,6 +2023,35 @@ impl PathHash {
     }
 }
 
+/// Decode the contents of a source file as UTF-8, removing a leading BOM.
+fn decode(buf: Vec<u8>) -> FileResult<String> {
+    Ok(if buf.starts_with(b"\xef\xbb\xbf") {
+        // remove UTF-8 BOM
+        std::str::from_utf8(&buf[3..])?.to_owned()
+    } else {
+        // Assume UTF-8
+        String::from_utf8(buf)?
+    })
+}
+
+/// Canonicalize the longest existing prefix of a path and lexically normalize
+/// the rest, which does not exist (yet).
+fn canonicalize_lenient(path: &Path) -> PathBuf {
+    let path = match std::env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => path.into(),
+    };
+
+    for prefix in path.ancestors() {
+        if let Ok(canon) = prefix.canonicalize() {
+            let rest = path.strip_prefix(prefix).unwrap_or(Path::new(""));
+            return canon.join(rest).normalize();
+        }
+    }
+
+    path.normalize()
+}
+
 /// Read a file.
 #[tracing::instrument(skip_all)]
 fn read(path: &Path) -> FileResult<Vec<u8>> {
@@ -905,12 +2119,32 @@ impl<'a> codespan_reporting::files::Files<'a> for SystemWorld<'_> {
 struct FontSearcher {
     book: FontBook,
     fonts: Vec<FontSlot>,
+    cache: Option<FontCache>,
+    /// Font files and directories to skip.
+    exclude: Vec<glob::Pattern>,
 }
 
 impl FontSearcher {
     /// Create a new, empty system searcher.
-    fn new() -> Self {
-        Self { book: FontBook::new(), fonts: vec![] }
+    ///
+    /// If `cache` is true, font files that did not change since they were
+    /// last indexed are not parsed again. Files and directories matching one
+    /// of the `exclude` glob patterns are skipped.
+    fn new(cache: bool, exclude: &[String]) -> StrResult<Self> {
+        let exclude = exclude
+            .iter()
+            .map(|glob| {
+                glob::Pattern::new(glob)
+                    .map_err(|err| format!("invalid font path pattern `{glob}`: {err}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            book: FontBook::new(),
+            fonts: vec![],
+            cache: cache.then(FontCache::load),
+            exclude,
+        })
     }
 
     /// Search everything that is available.
@@ -923,6 +2157,10 @@ impl FontSearcher {
         for path in font_paths {
             self.search_dir(path)
         }
+
+        if let Some(cache) = &mut self.cache {
+            cache.save();
+        }
     }
 
     /// Add fonts that are embedded in the binary.
@@ -999,10 +2237,12 @@ impl FontSearcher {
 
     /// Search for all fonts in a directory recursively.
     fn search_dir(&mut self, path: impl AsRef<Path>) {
+        let exclude = self.exclude.clone();
         for entry in WalkDir::new(path)
             .follow_links(true)
             .sort_by(|a, b| a.file_name().cmp(b.file_name()))
             .into_iter()
+            .filter_entry(|e| !exclude.iter().any(|p| p.matches_path(e.path())))
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
@@ -1018,17 +2258,111 @@ impl FontSearcher {
     /// Index the fonts in the file at the given path.
     fn search_file(&mut self, path: impl AsRef<Path>) {
         let path = path.as_ref();
-        if let Ok(file) = File::open(path) {
-            if let Ok(mmap) = unsafe { Mmap::map(&file) } {
-                for (i, info) in FontInfo::iter(&mmap).enumerate() {
-                    self.book.push(info);
-                    self.fonts.push(FontSlot {
-                        path: path.into(),
-                        index: i as u32,
-                        font: OnceCell::new(),
-                    });
-                }
+        let infos = match &mut self.cache {
+            Some(cache) => cache.get_or_index(path),
+            None => index(path),
+        };
+
+        for (index, info) in infos {
+            self.book.push(info);
+            self.fonts
+                .push(FontSlot { path: path.into(), index, font: OnceCell::new() });
+        }
+    }
+}
+
+/// Parse the metadata of all fonts in the file at the given path, together
+/// with the index of their face.
+fn index(path: &Path) -> Vec<(u32, FontInfo)> {
+    let Ok(file) = File::open(path) else { return vec![] };
+    let Ok(mmap) = (unsafe { Mmap::map(&file) }) else { return vec![] };
+    FontInfo::iter_indexed(&mmap).collect()
+}
+
+/// Persists the metadata of font files between invocations, so that only
+/// changed files must be parsed again.
+#[derive(Default, Serialize, Deserialize)]
+struct FontCache {
+    /// The version of Typst that wrote the cache.
+    version: String,
+    /// The indexed font files.
+    files: HashMap<PathBuf, CachedFontFile>,
+    /// Whether the cache changed during this invocation.
+    #[serde(skip)]
+    dirty: bool,
+}
+
+/// The metadata of a single font file.
+#[derive(Serialize, Deserialize)]
+struct CachedFontFile {
+    /// When the file was last modified.
+    modified: SystemTime,
+    /// The size of the file in bytes.
+    len: u64,
+    /// The fonts in the file, with the index of their face.
+    infos: Vec<(u32, FontInfo)>,
+}
+
+impl FontCache {
+    /// The path of the cache file.
+    fn path() -> Option<PathBuf> {
+        Some(dirs::cache_dir()?.join("typst").join("fonts.json"))
+    }
+
+    /// Load the cache, or start with an empty one if there is no valid cache.
+    fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|data| serde_json::from_slice::<Self>(&data).ok())
+            .filter(|cache| cache.version == typst_version())
+            .unwrap_or_else(|| Self {
+                version: typst_version().into(),
+                dirty: true,
+                ..Default::default()
+            })
+    }
+
+    /// Return the fonts in the file at the given path, parsing it only if it
+    /// is not in the cache or changed since it was cached.
+    fn get_or_index(&mut self, path: &Path) -> Vec<(u32, FontInfo)> {
+        let Ok(metadata) = fs::metadata(path) else { return vec![] };
+        let Ok(modified) = metadata.modified() else { return index(path) };
+        let len = metadata.len();
+
+        if let Some(file) = self.files.get(path) {
+            if file.modified == modified && file.len == len {
+                return file.infos.clone();
             }
         }
+
+        let infos = index(path);
+        let file = CachedFontFile { modified, len, infos: infos.clone() };
+        self.files.insert(path.into(), file);
+        self.dirty = true;
+        infos
+    }
+
+    /// Write the cache back to disk if it changed, forgetting files that
+    /// don't exist anymore. Failure to do so is not an error as the cache is
+    /// just an optimization.
+    ///
+    /// Files that still exist are kept even if they were not looked up during
+    /// this invocation, as other invocations may search other font paths.
+    fn save(&mut self) {
+        let before = self.files.len();
+        self.files.retain(|path, _| path.exists());
+        if !self.dirty && self.files.len() == before {
+            return;
+        }
+
+        let Some(path) = Self::path() else { return };
+        let Ok(data) = serde_json::to_vec(self) else { return };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).ok();
+        }
+
+        if fs::write(&path, data).is_ok() {
+            self.dirty = false;
+        }
     }
 }
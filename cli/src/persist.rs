@@ -0,0 +1,182 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use typst::diag::StrResult;
+
+/// The extension of temporary files.
+const EXTENSION: &str = "typst-tmp";
+
+/// How often to try to replace a locked file.
+const ATTEMPTS: u32 = 6;
+
+/// How long to wait before the second attempt. The delay doubles with each
+/// further attempt.
+const BACKOFF: Duration = Duration::from_millis(50);
+
+/// Replace the file at the given path with the data, atomically.
+///
+/// Readers of the file see either its old or its new contents, never a
+/// partially written file.
+pub fn write(path: &Path, data: &[u8]) -> StrResult<()> {
+    stage(path, data)?.commit()
+}
+
+/// Write data to a temporary file next to the given path.
+///
+/// The data only replaces the file at the path once the returned file is
+/// committed. If it is dropped instead, the temporary file is removed.
+///
+/// If the path is a symlink, the file it points to is replaced instead of the
+/// link. The new file keeps the permissions of the file it replaces.
+pub fn stage(path: &Path, data: &[u8]) -> StrResult<Staged> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.into());
+    let name = path.file_name().ok_or("cannot write to a directory")?;
+    let temp = path.with_file_name(format!(
+        ".{}.{}.{EXTENSION}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let staged = Staged { temp, path };
+    fs::write(&staged.temp, data).map_err(|err| describe(&staged.path, &err))?;
+    if let Ok(metadata) = fs::metadata(&staged.path) {
+        fs::set_permissions(&staged.temp, metadata.permissions())
+            .map_err(|err| describe(&staged.path, &err))?;
+    }
+
+    Ok(staged)
+}
+
+/// Whether the file at the given path is a temporary file written by
+/// [`stage`].
+pub fn is_temporary(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == EXTENSION)
+}
+
+/// Data that was written to a temporary file and waits to replace its
+/// destination.
+pub struct Staged {
+    temp: PathBuf,
+    path: PathBuf,
+}
+
+impl Staged {
+    /// Move the data to its destination.
+    ///
+    /// Other programs, like PDF viewers on Windows, may lock the destination
+    /// for a short while. Replacing it is then retried a few times, waiting a
+    /// bit longer each time.
+    pub fn commit(self) -> StrResult<()> {
+        let mut attempt = 1;
+        let mut delay = BACKOFF;
+        loop {
+            match fs::rename(&self.temp, &self.path) {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt < ATTEMPTS && is_locked(&err) => {
+                    tracing::info!("Waiting for {} to be unlocked", self.path.display());
+                    thread::sleep(delay);
+                    attempt += 1;
+                    delay *= 2;
+                }
+                Err(err) => return Err(describe(&self.path, &err).into()),
+            }
+        }
+    }
+}
+
+impl Drop for Staged {
+    fn drop(&mut self) {
+        // Does nothing if the file was committed.
+        fs::remove_file(&self.temp).ok();
+    }
+}
+
+/// Whether an error is caused by another process holding the file open.
+///
+/// Only Windows locks open files. The codes are `ERROR_ACCESS_DENIED`,
+/// `ERROR_SHARING_VIOLATION` and `ERROR_LOCK_VIOLATION`.
+fn is_locked(err: &io::Error) -> bool {
+    cfg!(windows) && matches!(err.raw_os_error(), Some(5 | 32 | 33))
+}
+
+/// Describe why writing the file at the given path failed.
+fn describe(path: &Path, err: &io::Error) -> String {
+    let path = path.display();
+    if !is_locked(err) {
+        return format!("failed to write {path} ({err})");
+    }
+
+    let apps = locking_apps(&path.to_string());
+    if apps.is_empty() {
+        format!("failed to write {path} (it is locked by another program)")
+    } else {
+        format!("failed to write {path} (it is open in {})", apps.join(", "))
+    }
+}
+
+/// The names of the applications that hold the file at the given path open.
+#[cfg(windows)]
+fn locking_apps(path: &str) -> Vec<String> {
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+        RM_PROCESS_INFO,
+    };
+
+    let path: Vec<u16> = path.encode_utf16().chain([0]).collect();
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+    let mut session = 0;
+
+    // SAFETY: All buffers outlive the session and their lengths are passed
+    // along correctly. The process infos are plain data, for which all zeros
+    // is a valid value.
+    unsafe {
+        if RmStartSession(&mut session, 0, key.as_mut_ptr()) != 0 {
+            return vec![];
+        }
+
+        let mut infos: Vec<RM_PROCESS_INFO> = vec![std::mem::zeroed(); 16];
+        let mut needed = 0;
+        let mut count = infos.len() as u32;
+        let mut reasons = 0;
+        let found = RmRegisterResources(
+            session,
+            1,
+            &path.as_ptr(),
+            0,
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+        ) == 0
+            && RmGetList(
+                session,
+                &mut needed,
+                &mut count,
+                infos.as_mut_ptr(),
+                &mut reasons,
+            ) == 0;
+        RmEndSession(session);
+
+        if !found {
+            return vec![];
+        }
+
+        infos
+            .iter()
+            .take(count as usize)
+            .map(|info| {
+                let name = &info.strAppName;
+                let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                String::from_utf16_lossy(&name[..len])
+            })
+            .collect()
+    }
+}
+
+/// The names of the applications that hold the file at the given path open.
+#[cfg(not(windows))]
+fn locking_apps(_: &str) -> Vec<String> {
+    vec![]
+}
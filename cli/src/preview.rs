@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use minifb::{Key, KeyRepeat, ScaleMode, Window, WindowOptions};
+use typst::diag::StrResult;
+use typst::doc::Document;
+use typst::geom::Color;
+
+/// The title of the preview window.
+const TITLE: &str = "typst preview";
+
+/// A window that shows a page of the most recently compiled document.
+///
+/// The shown page can be changed with the arrow, page up/down, home and end
+/// keys. Pressing escape closes the window.
+pub struct Preview {
+    window: Window,
+    /// The rendered pages, as their width, height and pixels in `0RGB` format.
+    pages: Vec<(usize, usize, Vec<u32>)>,
+    /// The index of the shown page.
+    page: usize,
+}
+
+impl Preview {
+    /// Open the preview window.
+    pub fn open() -> StrResult<Self> {
+        let options = WindowOptions {
+            resize: true,
+            scale_mode: ScaleMode::AspectRatioStretch,
+            ..WindowOptions::default()
+        };
+
+        // Start with the size of an A4 page.
+        let mut window = Window::new(TITLE, 595, 842, options)
+            .map_err(|err| format!("failed to open preview window ({err})"))?;
+        window.limit_update_rate(Some(Duration::from_millis(16)));
+
+        Ok(Self { window, pages: vec![], page: 0 })
+    }
+
+    /// Show a newly compiled document, staying on the current page if the
+    /// document still has it.
+    #[tracing::instrument(skip_all)]
+    pub fn publish(&mut self, document: &Document, pixel_per_pt: f32) {
+        self.pages = document
+            .pages
+            .iter()
+            .map(|frame| {
+                let pixmap = typst::export::render(frame, pixel_per_pt, Color::WHITE);
+                let pixels = pixmap
+                    .data()
+                    .chunks_exact(4)
+                    .map(|p| u32::from_be_bytes([0, p[0], p[1], p[2]]))
+                    .collect();
+                (pixmap.width() as usize, pixmap.height() as usize, pixels)
+            })
+            .collect();
+
+        self.page = self.page.min(self.pages.len().saturating_sub(1));
+        self.update_title();
+    }
+
+    /// Handle key presses and redraw the window.
+    ///
+    /// Returns `false` once the window was closed.
+    pub fn update(&mut self) -> bool {
+        if !self.window.is_open() || self.window.is_key_down(Key::Escape) {
+            return false;
+        }
+
+        let last = self.pages.len().saturating_sub(1);
+        let page = self.window.get_keys_pressed(KeyRepeat::Yes).into_iter().fold(
+            self.page,
+            |page, key| match key {
+                Key::Right | Key::Down | Key::PageDown | Key::Space => {
+                    (page + 1).min(last)
+                }
+                Key::Left | Key::Up | Key::PageUp | Key::Backspace => {
+                    page.saturating_sub(1)
+                }
+                Key::Home => 0,
+                Key::End => last,
+                _ => page,
+            },
+        );
+
+        if page != self.page {
+            self.page = page;
+            self.update_title();
+        }
+
+        match self.pages.get(self.page) {
+            Some((width, height, pixels)) => {
+                if let Err(err) = self.window.update_with_buffer(pixels, *width, *height)
+                {
+                    tracing::warn!("Failed to draw preview ({err})");
+                }
+            }
+            None => self.window.update(),
+        }
+
+        true
+    }
+
+    /// Show the current page number in the window's title.
+    fn update_title(&mut self) {
+        let title = match self.pages.len() {
+            0 => TITLE.to_string(),
+            n => format!("{TITLE} - page {} of {n}", self.page + 1),
+        };
+        self.window.set_title(&title);
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use typst::diag::{bail, StrResult};
+
+use crate::args::{InitCommand, Template};
+
+/// The file name of a project manifest.
+pub const MANIFEST: &str = "typst.toml";
+
+/// The file name of a project's main file.
+const MAIN: &str = "main.typ";
+
+/// Defaults for compile settings, read from a `typst.toml` file.
+///
+/// Relative paths are resolved relative to the directory of the manifest.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Manifest {
+    /// The root directory for absolute paths.
+    pub root: Option<PathBuf>,
+    /// The destination directory for written files.
+    pub dest: Option<PathBuf>,
+    /// Additional directories to search for fonts.
+    pub font_paths: Vec<PathBuf>,
+    /// The format to export to if no output path is given.
+    pub format: Option<OutputFormat>,
+    /// The path to export to if none is given.
+    pub output: Option<PathBuf>,
+    /// The PPI to use for PNG export.
+    pub ppi: Option<f32>,
+    /// Inputs that are available to the document through `sys.inputs`.
+    pub inputs: BTreeMap<String, String>,
+    /// Information about the project if it is a package.
+    pub package: Option<PackageInfo>,
+}
+
+/// The `[package]` section of a manifest.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PackageInfo {
+    /// The file that is imported when the package is imported, relative to the
+    /// manifest. Defaults to `lib.typ`.
+    pub entrypoint: Option<PathBuf>,
+}
+
+/// A format a document can be exported to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    Pdf,
+    Png,
+    Jpeg,
+    Webp,
+    Gif,
+    Txt,
+    Html,
+    Epub,
+    Docx,
+}
+
+impl Manifest {
+    /// Find the manifest for the given input file.
+    ///
+    /// The manifest is looked for next to the input file first and in the root
+    /// directory second. If there is none, the default manifest is returned.
+    pub fn find(input: &Path, root: Option<&Path>) -> StrResult<Self> {
+        let dir = input.parent().filter(|_| input.as_os_str() != "-");
+        for dir in dir.into_iter().chain(root) {
+            if let Some(manifest) = Self::load(dir)? {
+                return Ok(manifest);
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Load the manifest in the given directory, if there is one.
+    pub fn load(dir: &Path) -> StrResult<Option<Self>> {
+        let path = dir.join(MANIFEST);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(_) => bail!("failed to read {}", path.display()),
+        };
+
+        let mut manifest: Self = toml::from_str(&text).map_err(|err| {
+            format!("failed to parse {}: {}", path.display(), err.message())
+        })?;
+
+        for path in manifest
+            .root
+            .iter_mut()
+            .chain(&mut manifest.dest)
+            .chain(&mut manifest.output)
+            .chain(&mut manifest.font_paths)
+            .chain(manifest.package.iter_mut().flat_map(|info| &mut info.entrypoint))
+        {
+            *path = dir.join(&*path);
+        }
+
+        Ok(Some(manifest))
+    }
+
+    /// The default output path for the given input file.
+    pub fn output(&self, input: &Path) -> Option<PathBuf> {
+        if let Some(output) = &self.output {
+            return Some(output.clone());
+        }
+
+        match self.format? {
+            OutputFormat::Pdf => Some(input.with_extension("pdf")),
+            OutputFormat::Gif => Some(input.with_extension("gif")),
+            OutputFormat::Txt => Some(input.with_extension("txt")),
+            OutputFormat::Html => Some(input.with_extension("html")),
+            OutputFormat::Epub => Some(input.with_extension("epub")),
+            OutputFormat::Docx => Some(input.with_extension("docx")),
+            format => {
+                let stem = input.file_stem()?.to_string_lossy();
+                let ext = match format {
+                    OutputFormat::Jpeg => "jpg",
+                    OutputFormat::Webp => "webp",
+                    _ => "png",
+                };
+                Some(input.with_file_name(format!("{stem}-{{n}}.{ext}")))
+            }
+        }
+    }
+}
+
+/// Find the entrypoint of the package in the given directory.
+pub fn entrypoint(dir: &Path) -> StrResult<PathBuf> {
+    let entrypoint = Manifest::load(dir)?
+        .and_then(|manifest| manifest.package?.entrypoint)
+        .unwrap_or_else(|| dir.join("lib.typ"));
+    if !entrypoint.is_file() {
+        bail!("package entrypoint {} does not exist", entrypoint.display());
+    }
+    Ok(entrypoint)
+}
+
+/// Execute an init command.
+pub fn init(command: &InitCommand) -> StrResult<()> {
+    let dir = &command.directory;
+    let main = dir.join(MAIN);
+    let manifest = dir.join(MANIFEST);
+    for path in [&main, &manifest] {
+        if path.exists() {
+            bail!("{} already exists", path.display());
+        }
+    }
+
+    fs::create_dir_all(dir).map_err(|_| "failed to create project directory")?;
+    fs::write(&main, template(command.template))
+        .map_err(|_| format!("failed to write {}", main.display()))?;
+    fs::write(&manifest, include_str!("../templates/typst.toml"))
+        .map_err(|_| format!("failed to write {}", manifest.display()))?;
+
+    println!("Created a new project in {}.", dir.display());
+    println!("Compile it with `typst compile {}`.", main.display());
+    Ok(())
+}
+
+/// The contents of the main file for the given template.
+fn template(template: Option<Template>) -> &'static str {
+    match template {
+        None => include_str!("../templates/blank.typ"),
+        Some(Template::Report) => include_str!("../templates/report.typ"),
+        Some(Template::Letter) => include_str!("../templates/letter.typ"),
+        Some(Template::Slides) => include_str!("../templates/slides.typ"),
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use typst::diag::StrResult;
+use typst::doc::Document;
+use typst::geom::Color;
+
+/// The page that displays the document and reloads it on changes.
+const INDEX: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>typst</title>
+  <style>
+    body {
+      margin: 0;
+      padding: 16px;
+      display: flex;
+      flex-direction: column;
+      align-items: center;
+      gap: 16px;
+      background: #ddd;
+    }
+    img {
+      max-width: 100%;
+      background: white;
+      box-shadow: 0 2px 8px rgba(0, 0, 0, 0.25);
+    }
+  </style>
+</head>
+<body>
+  <script>
+    const events = new EventSource("/events");
+    events.onmessage = (event) => {
+      const [version, pages] = event.data.split(" ");
+      const images = Array.from({ length: Number(pages) }, (_, i) => {
+        const image = document.createElement("img");
+        image.src = `/page/${i + 1}.png?v=${version}`;
+        return image;
+      });
+      document.body.replaceChildren(...images);
+    };
+  </script>
+</body>
+</html>
+"#;
+
+/// How long an event stream waits for a new document before it checks whether
+/// its client is still connected.
+const HEARTBEAT: Duration = Duration::from_secs(15);
+
+/// Serves the most recently compiled document over HTTP.
+///
+/// The server provides:
+/// - `/`: A page that shows the document and reloads it on changes.
+/// - `/document.pdf`: The document as a PDF file.
+/// - `/page/{n}.png`: The `n`-th page as a PNG image.
+/// - `/events`: A stream of server-sent events that fires whenever a new
+///   document was published. Each event's data contains the document's
+///   version and its number of pages.
+///
+/// The PDF and the pages are only exported once they are requested.
+#[derive(Clone)]
+pub struct Server {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+}
+
+/// The state shared between the compiler and the connection threads.
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+/// The most recently published document.
+#[derive(Default)]
+struct State {
+    /// Increases with each published document.
+    version: u64,
+    /// The document.
+    document: Arc<Document>,
+    /// The resolution to render pages at, in pixels per inch.
+    ppi: f32,
+    /// The exports that were requested so far: The PDF under `None` and each
+    /// page's PNG under its index.
+    exports: HashMap<Option<usize>, Vec<u8>>,
+}
+
+impl Server {
+    /// Start listening on the given address in a background thread.
+    pub fn start(addr: &str) -> StrResult<Self> {
+        let listener = TcpListener::bind(addr)
+            .map_err(|err| format!("failed to listen on {addr} ({err})"))?;
+        let addr = listener.local_addr().map_err(|_| "failed to listen")?;
+        let server = Self { addr, shared: Arc::default() };
+
+        let handle = server.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(Result::ok) {
+                let handle = handle.clone();
+                thread::spawn(move || {
+                    if let Err(err) = handle.respond(&stream) {
+                        tracing::debug!("Failed to respond: {err}");
+                    }
+                });
+            }
+        });
+
+        Ok(server)
+    }
+
+    /// The URL under which the server is reachable.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Publish a newly compiled document and notify all connected clients.
+    #[tracing::instrument(skip_all)]
+    pub fn publish(&self, document: &Document, ppi: f32) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.version += 1;
+        state.document = Arc::new(document.clone());
+        state.ppi = ppi;
+        state.exports.clear();
+        self.shared.changed.notify_all();
+    }
+
+    /// Answer a single request.
+    fn respond(&self, stream: &TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+
+        // Skip the headers, we don't need any of them.
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+            header.clear();
+        }
+
+        let not_found = |stream: &TcpStream| {
+            send(stream, "404 Not Found", "text/plain", b"not found")
+        };
+        match Route::parse(&request) {
+            Route::Index => {
+                send(stream, "200 OK", "text/html; charset=utf-8", INDEX.as_bytes())
+            }
+            Route::Events => self.events(stream),
+            Route::Pdf => match self.export(None) {
+                Some(pdf) => send(stream, "200 OK", "application/pdf", &pdf),
+                None => not_found(stream),
+            },
+            Route::Page(i) => match self.export(Some(i)) {
+                Some(png) => send(stream, "200 OK", "image/png", &png),
+                None => not_found(stream),
+            },
+            Route::NotFound => not_found(stream),
+            Route::NotAllowed => {
+                send(stream, "405 Method Not Allowed", "text/plain", b"")
+            }
+        }
+    }
+
+    /// Export the published document to PDF or one of its pages to PNG.
+    ///
+    /// Exports are kept until the next document is published, so that each is
+    /// only done once. Returns `None` if there is no such page or no document
+    /// was published yet.
+    fn export(&self, page: Option<usize>) -> Option<Vec<u8>> {
+        let (version, document, ppi) = {
+            let state = self.shared.state.lock().unwrap();
+            if let Some(data) = state.exports.get(&page) {
+                return Some(data.clone());
+            }
+            (state.version, state.document.clone(), state.ppi)
+        };
+
+        if version == 0 {
+            return None;
+        }
+
+        // Export without holding the lock, so that other requests and the
+        // compiler aren't blocked in the meantime.
+        let data = match page {
+            Some(i) => {
+                let frame = document.pages.get(i)?;
+                let pixmap = typst::export::render(frame, ppi, Color::WHITE);
+                pixmap.encode_png().ok()?
+            }
+            None => typst::export::pdf(&document),
+        };
+
+        let mut state = self.shared.state.lock().unwrap();
+        if state.version == version {
+            state.exports.insert(page, data.clone());
+        }
+
+        Some(data)
+    }
+
+    /// Stream an event to the client each time a document is published.
+    ///
+    /// Without a new document, a comment is sent every now and then. Once the
+    /// client disconnected, sending fails and the stream ends.
+    fn events(&self, mut stream: &TcpStream) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\n\
+             Content-Type: text/event-stream\r\n\
+             Cache-Control: no-cache\r\n\r\n"
+        )?;
+        stream.flush()?;
+
+        let mut seen = 0;
+        loop {
+            let update = {
+                let state = self.shared.state.lock().unwrap();
+                let (state, _) = self
+                    .shared
+                    .changed
+                    .wait_timeout_while(state, HEARTBEAT, |state| state.version == seen)
+                    .unwrap();
+                (state.version != seen)
+                    .then(|| (state.version, state.document.pages.len()))
+            };
+
+            match update {
+                Some((version, pages)) => {
+                    seen = version;
+                    write!(stream, "data: {version} {pages}\n\n")?;
+                }
+                None => write!(stream, ": heartbeat\n\n")?,
+            }
+            stream.flush()?;
+        }
+    }
+}
+
+/// What a request asks for.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Route {
+    /// The page that shows the document.
+    Index,
+    /// The stream of events.
+    Events,
+    /// The document as a PDF file.
+    Pdf,
+    /// The page with the given zero-based index as a PNG image.
+    Page(usize),
+    /// A path that doesn't exist.
+    NotFound,
+    /// A method other than `GET`.
+    NotAllowed,
+}
+
+impl Route {
+    /// Determine what a request asks for from its request line.
+    fn parse(request: &str) -> Self {
+        let mut parts = request.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let target = parts.next().unwrap_or_default();
+        let path = target.split('?').next().unwrap_or_default();
+        if method != "GET" {
+            return Self::NotAllowed;
+        }
+
+        match path {
+            "/" => Self::Index,
+            "/events" => Self::Events,
+            "/document.pdf" => Self::Pdf,
+            _ => path
+                .strip_prefix("/page/")
+                .and_then(|rest| rest.strip_suffix(".png"))
+                .and_then(|n| n.parse::<usize>().ok())
+                .and_then(|n| n.checked_sub(1))
+                .map_or(Self::NotFound, Self::Page),
+        }
+    }
+}
+
+/// Send a complete response and close the connection.
+fn send(mut stream: &TcpStream, status: &str, mime: &str, body: &[u8]) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: {mime}\r\n\
+         Content-Length: {}\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use typst::doc::Frame;
+    use typst::geom::{Abs, Size};
+
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        assert_eq!(Route::parse("GET / HTTP/1.1\r\n"), Route::Index);
+        assert_eq!(Route::parse("GET /events HTTP/1.1\r\n"), Route::Events);
+        assert_eq!(Route::parse("GET /document.pdf?v=3 HTTP/1.1\r\n"), Route::Pdf);
+        assert_eq!(Route::parse("GET /page/1.png?v=3 HTTP/1.1\r\n"), Route::Page(0));
+        assert_eq!(Route::parse("GET /page/12.png HTTP/1.1\r\n"), Route::Page(11));
+        assert_eq!(Route::parse("GET /page/0.png HTTP/1.1\r\n"), Route::NotFound);
+        assert_eq!(Route::parse("GET /page/one.png HTTP/1.1\r\n"), Route::NotFound);
+        assert_eq!(Route::parse("GET /page/1 HTTP/1.1\r\n"), Route::NotFound);
+        assert_eq!(Route::parse("GET /index.html HTTP/1.1\r\n"), Route::NotFound);
+        assert_eq!(Route::parse("POST / HTTP/1.1\r\n"), Route::NotAllowed);
+        assert_eq!(Route::parse(""), Route::NotAllowed);
+    }
+
+    #[test]
+    fn test_export_lazily() {
+        let server = Server::start("127.0.0.1:0").unwrap();
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(server.addr).unwrap();
+            write!(stream, "GET {path} HTTP/1.1\r\n\r\n").unwrap();
+            let mut response = vec![];
+            stream.read_to_end(&mut response).unwrap();
+            response
+        };
+
+        assert!(get("/page/1.png").starts_with(b"HTTP/1.1 404"));
+
+        let frame = Frame::new(Size::splat(Abs::pt(10.0)));
+        let document = Document { pages: vec![frame], ..Default::default() };
+        server.publish(&document, 72.0);
+        assert!(server.shared.state.lock().unwrap().exports.is_empty());
+
+        let response = get("/page/1.png");
+        assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+        assert!(response.windows(4).any(|w| w == b"\x89PNG"));
+        assert!(get("/page/2.png").starts_with(b"HTTP/1.1 404"));
+
+        let exports = &server.shared.state.lock().unwrap().exports;
+        assert_eq!(exports.keys().collect::<Vec<_>>(), [&Some(0)]);
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+
+use ecow::{eco_format, EcoString};
+use serde::{Deserialize, Serialize};
+use typst::diag::{bail, StrResult};
+use typst::doc::{Document, Frame, FrameItem, Glyph, Position, TextItem};
+use typst::geom::{Abs, Point, Size, Transform};
+use typst::World;
+
+use crate::args::{JumpCommand, LocateCommand};
+use crate::persist;
+
+/// Where each piece of text in a document came from.
+///
+/// Editors use it to jump from a position in the output to the source code
+/// and back. It is written next to the output with `--source-map`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceMap {
+    /// The directory relative to which the paths of the entries are.
+    pub root: PathBuf,
+    /// The runs of text, in the order they appear in the document.
+    pub entries: Vec<Entry>,
+}
+
+/// A run of text that stems from a single piece of source code.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// The page, starting at 1.
+    pub page: usize,
+    /// The distance of the run's left edge from the page's left edge, in pt.
+    pub x: f64,
+    /// The distance of the run's top edge from the page's top edge, in pt.
+    pub y: f64,
+    /// The width of the run, in pt.
+    pub width: f64,
+    /// The height of the run, in pt.
+    pub height: f64,
+    /// The source file, relative to the root if it lies within it.
+    pub path: PathBuf,
+    /// The byte range of the text in the source file.
+    pub start: usize,
+    pub end: usize,
+    /// The line and column of the start of the text, starting at 1.
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceMap {
+    /// Record where the text in the document came from.
+    #[tracing::instrument(skip_all)]
+    pub fn new(world: &dyn World, root: &Path, document: &Document) -> Self {
+        let root = root.canonicalize().unwrap_or_else(|_| root.into());
+        let mut entries = vec![];
+        for (i, frame) in document.pages.iter().enumerate() {
+            collect(world, &root, frame, Transform::identity(), i + 1, &mut entries);
+        }
+        Self { root, entries }
+    }
+
+    /// Read a source map from a file.
+    pub fn load(path: &Path) -> StrResult<Self> {
+        let data = fs::read(path)
+            .map_err(|err| format!("failed to read {} ({err})", path.display()))?;
+        serde_json::from_slice(&data).map_err(|err| {
+            eco_format!("failed to parse source map {} ({err})", path.display())
+        })
+    }
+
+    /// Write the source map to a file.
+    pub fn save(&self, path: &Path) -> StrResult<()> {
+        let data =
+            serde_json::to_vec(self).map_err(|_| "failed to serialize source map")?;
+        persist::write(path, &data)
+    }
+
+    /// Named destinations for the first text of each source line.
+    ///
+    /// They are named `{path}:{line}`, so that editors can ask a PDF viewer to
+    /// show a line by name.
+    pub fn destinations(&self) -> Vec<(EcoString, Position)> {
+        let mut lines = BTreeMap::new();
+        for entry in &self.entries {
+            lines
+                .entry(eco_format!("{}:{}", entry.path.display(), entry.line))
+                .or_insert(entry);
+        }
+
+        lines
+            .into_iter()
+            .filter_map(|(name, entry)| {
+                let page = NonZeroUsize::new(entry.page)?;
+                let point = Point::new(Abs::pt(entry.x), Abs::pt(entry.y));
+                Some((name, Position { page, point }))
+            })
+            .collect()
+    }
+
+    /// Find the text at or, failing that, closest to a point on a page.
+    pub fn jump(&self, page: usize, x: f64, y: f64) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.page == page)
+            .min_by(|a, b| a.distance(x, y).total_cmp(&b.distance(x, y)))
+    }
+
+    /// Find the first text that stems from a source line, preferring the text
+    /// starting closest to the column.
+    ///
+    /// If no text stems from the line, the text of the closest line is used.
+    pub fn locate(&self, path: &Path, line: usize, column: usize) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| self.is_same_path(&entry.path, path))
+            .min_by_key(|entry| {
+                (entry.line.abs_diff(line), entry.column.abs_diff(column))
+            })
+    }
+
+    /// Whether an entry's path refers to the given file.
+    fn is_same_path(&self, entry: &Path, path: &Path) -> bool {
+        let entry = self.root.join(entry);
+        entry == path
+            || path
+                .canonicalize()
+                .map_or(false, |path| path == entry.canonicalize().unwrap_or(entry))
+    }
+}
+
+impl Entry {
+    /// The distance from a point to the run's bounding box, zero if the point
+    /// lies within it.
+    fn distance(&self, x: f64, y: f64) -> f64 {
+        let dx = (self.x - x).max(x - self.x - self.width).max(0.0);
+        let dy = (self.y - y).max(y - self.y - self.height).max(0.0);
+        dx.hypot(dy)
+    }
+}
+
+/// Collect the runs of text in a frame, which is placed on the page with the
+/// given transformation.
+fn collect(
+    world: &dyn World,
+    root: &Path,
+    frame: &Frame,
+    ts: Transform,
+    page: usize,
+    entries: &mut Vec<Entry>,
+) {
+    for (pos, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => {
+                let ts = ts
+                    .pre_concat(Transform::translate(pos.x, pos.y))
+                    .pre_concat(group.transform);
+                collect(world, root, &group.frame, ts, page, entries);
+            }
+            FrameItem::Text(text) => {
+                // Glyphs from the same span form one run.
+                let mut x = pos.x;
+                let mut start = 0;
+                for i in 1..=text.glyphs.len() {
+                    let glyphs = &text.glyphs[start..i];
+                    let next = text.glyphs.get(i);
+                    if next.map_or(false, |glyph| glyph.span.0 == glyphs[0].span.0) {
+                        continue;
+                    }
+
+                    let width = advance(text, glyphs);
+                    let (min, size) = bounds(ts, x, pos.y, width, text.size);
+                    entries.extend(entry(world, root, glyphs, min, size, page));
+                    x += width;
+                    start = i;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Create an entry for glyphs that stem from the same span.
+fn entry(
+    world: &dyn World,
+    root: &Path,
+    glyphs: &[Glyph],
+    pos: Point,
+    size: Size,
+    page: usize,
+) -> Option<Entry> {
+    let (span, _) = glyphs.first()?.span;
+    if span.is_detached() {
+        return None;
+    }
+
+    let source = world.source(span.source());
+    let node = source.find(span)?.range();
+    let (lo, hi) = glyphs.iter().fold((usize::MAX, 0), |(lo, hi), glyph| {
+        let offset = node.start + usize::from(glyph.span.1);
+        (lo.min(offset), hi.max(offset + glyph.range().len()))
+    });
+    let start = lo.min(node.end);
+    let end = hi.min(node.end);
+
+    let path = source.path();
+    Some(Entry {
+        page,
+        x: pos.x.to_pt(),
+        y: pos.y.to_pt(),
+        width: size.x.to_pt(),
+        height: size.y.to_pt(),
+        path: path.strip_prefix(root).unwrap_or(path).into(),
+        start,
+        end,
+        line: source.byte_to_line(start)? + 1,
+        column: source.byte_to_column(start)? + 1,
+    })
+}
+
+/// The bounding box on the page of a run whose baseline starts at `x` and `y`
+/// in a frame placed with the given transformation. Returns its top-left
+/// corner and size.
+fn bounds(ts: Transform, x: Abs, y: Abs, width: Abs, height: Abs) -> (Point, Size) {
+    let corners = [
+        Point::new(x, y - height),
+        Point::new(x + width, y - height),
+        Point::new(x, y),
+        Point::new(x + width, y),
+    ]
+    .map(|point| point.transform(ts));
+
+    let min = corners.into_iter().reduce(Point::min).unwrap_or_default();
+    let max = corners.into_iter().reduce(Point::max).unwrap_or_default();
+
+    (min, (max - min).to_size())
+}
+
+/// The total advance of some glyphs.
+fn advance(text: &TextItem, glyphs: &[Glyph]) -> Abs {
+    glyphs.iter().map(|glyph| glyph.x_advance.at(text.size)).sum()
+}
+
+/// Execute a jump command.
+pub fn jump(command: &JumpCommand) -> StrResult<()> {
+    let map = SourceMap::load(&command.map)?;
+    let Some(entry) = map.jump(command.page, command.x, command.y) else {
+        bail!("there is no text on page {}", command.page);
+    };
+
+    let path = map.root.join(&entry.path);
+    println!("{}:{}:{}", path.display(), entry.line, entry.column);
+    Ok(())
+}
+
+/// Execute a locate command.
+pub fn locate(command: &LocateCommand) -> StrResult<()> {
+    let map = SourceMap::load(&command.map)?;
+    let (path, line, column) = parse_location(&command.location)?;
+    let Some(entry) = map.locate(&path, line, column) else {
+        bail!("no text in the document stems from {}", path.display());
+    };
+
+    println!("{} {} {}", entry.page, entry.x, entry.y);
+    Ok(())
+}
+
+/// Parse a `file:line` or `file:line:column` location.
+fn parse_location(location: &str) -> StrResult<(PathBuf, usize, usize)> {
+    let number = |part: &str| match part.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(eco_format!("invalid location `{location}`")),
+    };
+
+    let (rest, last) = location
+        .rsplit_once(':')
+        .ok_or_else(|| eco_format!("location `{location}` must have a line number"))?;
+    let last = number(last)?;
+    Ok(match rest.rsplit_once(':') {
+        Some((path, line)) if line.parse::<usize>().is_ok() => {
+            (path.into(), number(line)?, last)
+        }
+        _ => (rest.into(), last, 1),
+    })
+}
@@ -1,18 +1,32 @@
+use std::cell::Cell;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Error, ErrorKind, Seek, SeekFrom};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use inferno::flamegraph::Options;
 use tracing::metadata::LevelFilter;
+use tracing::span::Id;
+use tracing::Subscriber;
 use tracing_error::ErrorLayer;
 use tracing_flame::{FlameLayer, FlushGuard};
 use tracing_subscriber::fmt;
+use tracing_subscriber::layer::{Context, Layer};
 use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
 
 use crate::args::CliArguments;
 
-/// Will flush the flamegraph to disk when dropped.
+/// Will flush the flamegraph and the timings to disk when dropped.
 pub struct TracingGuard {
+    flamegraph: Option<FlamegraphGuard>,
+    timings: Option<Timings>,
+}
+
+/// Will produce the flamegraph once tracing is done.
+struct FlamegraphGuard {
     flush_guard: Option<FlushGuard<BufWriter<File>>>,
     temp_file: File,
     output_svg: PathBuf,
@@ -20,6 +34,20 @@ pub struct TracingGuard {
 
 impl TracingGuard {
     pub fn finish(&mut self) -> Result<(), Error> {
+        if let Some(timings) = self.timings.take() {
+            timings.finish()?;
+        }
+
+        if let Some(flamegraph) = &mut self.flamegraph {
+            flamegraph.finish()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FlamegraphGuard {
+    fn finish(&mut self) -> Result<(), Error> {
         if self.flush_guard.is_none() {
             return Ok(());
         }
@@ -55,16 +83,17 @@ impl Drop for TracingGuard {
             if let Err(e) = self.finish() {
                 // Since we are finished, we cannot rely on tracing to log the
                 // error.
-                eprintln!("Failed to flush tracing flamegraph: {e}");
+                eprintln!("Failed to flush tracing output: {e}");
             }
         }
     }
 }
 
 /// Initializes the tracing system and returns a guard that will flush the
-/// flamegraph to disk when dropped.
+/// flamegraph and the timings to disk when dropped.
 pub fn init_tracing(args: &CliArguments) -> Result<Option<TracingGuard>, Error> {
     let flamegraph = args.command.as_compile().and_then(|c| c.flamegraph.as_ref());
+    let timings = args.command.as_compile().and_then(|c| c.timings.as_ref());
 
     if flamegraph.is_some() && args.command.is_watch() {
         return Err(Error::new(
@@ -73,8 +102,16 @@ pub fn init_tracing(args: &CliArguments) -> Result<Option<TracingGuard>, Error>
         ));
     }
 
-    // Short circuit if we don't need to initialize flamegraph or debugging.
-    if flamegraph.is_none() && args.verbosity == 0 {
+    if timings.is_some() && args.command.is_watch() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "cannot use --timings with watch command",
+        ));
+    }
+
+    // Short circuit if we don't need to initialize flamegraph, timings or
+    // debugging.
+    if flamegraph.is_none() && timings.is_none() && args.verbosity == 0 {
         tracing_subscriber::fmt()
             .without_time()
             .with_max_level(level_filter(args))
@@ -89,12 +126,20 @@ pub fn init_tracing(args: &CliArguments) -> Result<Option<TracingGuard>, Error>
     // Error layer for building backtraces
     let error_layer = ErrorLayer::default();
 
+    // Build the layer measuring the compilation phases.
+    let timings = timings.map(|path| Timings::new(path.clone()));
+    let timings_layer = timings.as_ref().map(Timings::layer);
+
     // Build the registry.
-    let registry = tracing_subscriber::registry().with(fmt_layer).with(error_layer);
+    let registry = tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(error_layer)
+        .with(timings_layer);
 
     let Some(path) = flamegraph else {
         registry.init();
-        return Ok(None);
+        return Ok(timings
+            .map(|timings| TracingGuard { flamegraph: None, timings: Some(timings) }));
     };
 
     // Create a temporary file to store the flamegraph data.
@@ -118,9 +163,12 @@ pub fn init_tracing(args: &CliArguments) -> Result<Option<TracingGuard>, Error>
     );
 
     Ok(Some(TracingGuard {
-        flush_guard: Some(flush_guard),
-        temp_file,
-        output_svg: path.clone().unwrap_or_else(|| "flamegraph.svg".into()),
+        flamegraph: Some(FlamegraphGuard {
+            flush_guard: Some(flush_guard),
+            temp_file,
+            output_svg: path.clone().unwrap_or_else(|| "flamegraph.svg".into()),
+        }),
+        timings,
     }))
 }
 
@@ -134,3 +182,238 @@ fn level_filter(args: &CliArguments) -> LevelFilter {
         _ => LevelFilter::TRACE,
     }
 }
+
+/// A phase of the compilation process.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Phase {
+    Parse,
+    Eval,
+    Layout,
+    Export,
+    Write,
+}
+
+impl Phase {
+    /// All phases in the order in which they happen.
+    const ALL: [Self; 5] =
+        [Self::Parse, Self::Eval, Self::Layout, Self::Export, Self::Write];
+
+    /// The phase that a span with the given name measures, if any.
+    fn of(span: &str) -> Option<Self> {
+        Some(match span {
+            "parse" | "reparse" => Self::Parse,
+            "eval" => Self::Eval,
+            "typeset" => Self::Layout,
+            "export" => Self::Export,
+            "write" => Self::Write,
+            _ => return None,
+        })
+    }
+
+    /// The name of the phase.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Parse => "parse",
+            Self::Eval => "eval",
+            Self::Layout => "layout",
+            Self::Export => "export",
+            Self::Write => "write",
+        }
+    }
+}
+
+/// Collects how long the compilation phases take.
+struct Timings {
+    /// Where to write a Chrome trace to. If `None`, a summary of the phases is
+    /// printed instead.
+    output: Option<PathBuf>,
+    /// The measurements, shared with the tracing layer.
+    data: Arc<Mutex<TimingsData>>,
+}
+
+/// The measurements of a [`Timings`].
+struct TimingsData {
+    /// When tracing started.
+    start: Instant,
+    /// The total time spent in each phase, indexed like [`Phase::ALL`].
+    phases: [Duration; 5],
+    /// Every closed span, if a Chrome trace should be written.
+    spans: Option<Vec<SpanRecord>>,
+}
+
+/// A closed span for the Chrome trace.
+struct SpanRecord {
+    name: &'static str,
+    thread: u64,
+    start: Duration,
+    busy: Duration,
+}
+
+/// The time a span spent being entered, stored in its extensions.
+struct SpanTiming {
+    thread: u64,
+    first: Instant,
+    entered: Option<Instant>,
+    busy: Duration,
+    /// Time spent in nested spans of other phases.
+    excluded: Duration,
+}
+
+/// The tracing layer that feeds a [`Timings`].
+struct TimingsLayer {
+    data: Arc<Mutex<TimingsData>>,
+}
+
+impl Timings {
+    /// Start measuring.
+    fn new(output: Option<PathBuf>) -> Self {
+        let data = TimingsData {
+            start: Instant::now(),
+            phases: [Duration::ZERO; 5],
+            spans: output.as_ref().map(|_| vec![]),
+        };
+        Self { output, data: Arc::new(Mutex::new(data)) }
+    }
+
+    /// Create a layer that records into these timings.
+    fn layer(&self) -> TimingsLayer {
+        TimingsLayer { data: self.data.clone() }
+    }
+
+    /// Print the summary or write the Chrome trace.
+    fn finish(self) -> Result<(), Error> {
+        let data = self.data.lock().unwrap();
+        let Some(path) = self.output else {
+            let total: Duration = data.phases.iter().sum();
+            eprintln!("{:<8} {:>12} {:>7}", "phase", "time", "share");
+            for (phase, &time) in Phase::ALL.iter().zip(&data.phases) {
+                let share =
+                    100.0 * time.as_secs_f64() / total.as_secs_f64().max(f64::EPSILON);
+                eprintln!(
+                    "{:<8} {:>12} {:>6.1}%",
+                    phase.name(),
+                    format!("{time:.2?}"),
+                    share
+                );
+            }
+            eprintln!("{:<8} {:>12}", "total", format!("{total:.2?}"));
+            return Ok(());
+        };
+
+        let micros = |duration: Duration| duration.as_secs_f64() * 1e6;
+        let events: Vec<_> = data
+            .spans
+            .iter()
+            .flatten()
+            .map(|span| {
+                serde_json::json!({
+                    "name": span.name,
+                    "ph": "X",
+                    "pid": 1,
+                    "tid": span.thread,
+                    "ts": micros(span.start),
+                    "dur": micros(span.busy),
+                })
+            })
+            .collect();
+
+        let output = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(output, &serde_json::json!({ "traceEvents": events }))
+            .map_err(|e| Error::new(ErrorKind::Other, e))
+    }
+}
+
+impl<S> Layer<S> for TimingsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let now = Instant::now();
+        let mut extensions = span.extensions_mut();
+        match extensions.get_mut::<SpanTiming>() {
+            Some(timing) => timing.entered = Some(now),
+            None => extensions.insert(SpanTiming {
+                thread: thread_number(),
+                first: now,
+                entered: Some(now),
+                busy: Duration::ZERO,
+                excluded: Duration::ZERO,
+            }),
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SpanTiming>() {
+            if let Some(entered) = timing.entered.take() {
+                timing.busy += entered.elapsed();
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let extensions = span.extensions();
+        let Some(timing) = extensions.get::<SpanTiming>() else { return };
+
+        let mut data = self.data.lock().unwrap();
+        if let Some(phase) = Phase::of(span.name()) {
+            // Each phase only counts the time not spent in other phases, e.g.
+            // parsing an imported file is not counted as evaluation. Nested
+            // spans of the same phase are already covered by the outer one.
+            let parent = span
+                .scope()
+                .skip(1)
+                .find_map(|parent| Some((Phase::of(parent.name())?, parent)));
+            let counted = match parent {
+                Some((outer, parent)) if outer == phase => {
+                    let mut extensions = parent.extensions_mut();
+                    if let Some(outer) = extensions.get_mut::<SpanTiming>() {
+                        outer.excluded += timing.excluded;
+                    }
+                    false
+                }
+                Some((_, parent)) => {
+                    let mut extensions = parent.extensions_mut();
+                    if let Some(outer) = extensions.get_mut::<SpanTiming>() {
+                        outer.excluded += timing.busy;
+                    }
+                    true
+                }
+                None => true,
+            };
+
+            if counted {
+                let index = Phase::ALL.iter().position(|&p| p == phase).unwrap();
+                data.phases[index] += timing.busy.saturating_sub(timing.excluded);
+            }
+        }
+
+        let start = timing.first.saturating_duration_since(data.start);
+        if let Some(spans) = &mut data.spans {
+            spans.push(SpanRecord {
+                name: span.name(),
+                thread: timing.thread,
+                start,
+                busy: timing.busy,
+            });
+        }
+    }
+}
+
+/// A small number identifying the current thread in the Chrome trace.
+fn thread_number() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static NUMBER: Cell<u64> = Cell::new(0);
+    }
+
+    NUMBER.with(|number| {
+        if number.get() == 0 {
+            number.set(NEXT.fetch_add(1, Ordering::Relaxed));
+        }
+        number.get()
+    })
+}
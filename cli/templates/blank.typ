@@ -0,0 +1,4 @@
+#set page(paper: "a4")
+#set text(lang: "en")
+
+= Introduction
@@ -0,0 +1,27 @@
+#set page(paper: "a4", margin: (x: 2.5cm, top: 3cm))
+#set text(lang: "en")
+#set par(justify: true)
+
+// The sender's address.
+Jane Doe \
+1 Main Street \
+Springfield
+
+#v(1cm)
+
+// The recipient's address.
+John Doe \
+2 Side Street \
+Shelbyville
+
+#v(1cm)
+#align(right, datetime.today().display("[month repr:long] [day], [year]"))
+
+*Subject*
+
+Dear John,
+
+#lorem(60)
+
+Sincerely, \
+Jane Doe
@@ -0,0 +1,21 @@
+#let title = "Report"
+#let author = "Author"
+
+#set document(title: title, author: author)
+#set page(paper: "a4", numbering: "1")
+#set heading(numbering: "1.1")
+#set text(lang: "en")
+#set par(justify: true)
+
+#page(numbering: none, align(center + horizon)[
+  #text(2em, weight: "bold", title)
+  #v(1em)
+  #text(1.2em, author)
+  #v(1em)
+  #datetime.today().display()
+])
+
+#outline()
+#pagebreak()
+
+= Introduction
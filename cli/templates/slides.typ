@@ -0,0 +1,23 @@
+#set page(paper: "presentation-16-9", margin: 2cm)
+#set text(size: 22pt, lang: "en")
+
+// Every level-one heading starts a new slide.
+#show heading.where(level: 1): it => {
+  pagebreak(weak: true)
+  text(1.4em, it)
+}
+
+#align(center + horizon)[
+  #text(2em, weight: "bold")[Title]
+
+  Presenter
+]
+
+= First slide
+
+- A point
+- Another point
+
+= Second slide
+
+#lorem(20)
@@ -4,13 +4,12 @@ use comemo::Prehashed;
 use md::escape::escape_html;
 use pulldown_cmark as md;
 use typed_arena::Arena;
-use typst::diag::FileResult;
-use typst::eval::Datetime;
+use typst::diag::{FileError, FileResult};
+use typst::eval::{Datetime, Tracer};
 use typst::font::{Font, FontBook};
 use typst::geom::{Point, Size};
-use typst::model::Location;
-use typst::syntax::{Source, SourceId};
-use typst::util::Buffer;
+use typst::syntax::{Source, SourceId, Span};
+use typst::util::{Buffer, WriteMode};
 use typst::World;
 use yaml_front_matter::YamlFrontMatter;
 
@@ -417,7 +416,8 @@ fn code_block(resolver: &dyn Resolver, lang: &str, text: &str) -> Html {
 
     let source = Source::new(SourceId::from_u16(0), Path::new("main.typ"), compile);
     let world = DocWorld(source);
-    let mut frames = match typst::compile(&world) {
+    let mut tracer = Tracer::default();
+    let mut frames = match typst::compile(&world, &mut tracer) {
         Ok(doc) => doc.pages,
         Err(err) => {
             let msg = &err[0].message;
@@ -499,8 +499,15 @@ impl World for DocWorld {
             .into())
     }
 
-    fn write(&self, _: &Path, _: Location, _: Vec<u8>) -> FileResult<()> {
-        todo!()
+    fn write(
+        &self,
+        _: &Path,
+        _: Option<i64>,
+        _: WriteMode,
+        _: Vec<u8>,
+        _: Span,
+    ) -> FileResult<()> {
+        Err(FileError::Disabled)
     }
 
     fn today(&self, _: Option<i64>) -> Option<Datetime> {
@@ -933,6 +933,7 @@ const TYPE_ORDER: &[&str] = &[
     "boolean",
     "integer",
     "float",
+    "decimal",
     "length",
     "angle",
     "ratio",
@@ -940,12 +941,14 @@ const TYPE_ORDER: &[&str] = &[
     "fraction",
     "color",
     "datetime",
+    "duration",
     "string",
     "regex",
     "label",
     "content",
     "array",
     "dictionary",
+    "records",
     "function",
     "arguments",
     "location",
@@ -4,7 +4,8 @@ use std::cmp;
 use std::cmp::Ordering;
 use std::ops::{Div, Rem};
 
-use typst::eval::{Module, Scope};
+use typst::eval::ops;
+use typst::eval::{Decimal, Module, Scope};
 
 use crate::prelude::*;
 
@@ -40,6 +41,13 @@ pub fn module() -> Module {
     scope.define("clamp", clamp_func());
     scope.define("min", min_func());
     scope.define("max", max_func());
+    scope.define("sum", sum_func());
+    scope.define("product", product_func());
+    scope.define("cumsum", cumsum_func());
+    scope.define("mean", mean_func());
+    scope.define("median", median_func());
+    scope.define("quantile", quantile_func());
+    scope.define("variance", variance_func());
     scope.define("even", even_func());
     scope.define("odd", odd_func());
     scope.define("rem", rem_func());
@@ -77,6 +85,7 @@ cast! {
     ToAbs,
     v: i64 => Self(v.abs().into_value()),
     v: f64 => Self(v.abs().into_value()),
+    v: Decimal => Self(Value::Decimal(v.checked_abs().ok_or("value is too large")?)),
     v: Length => Self(Value::Length(v.try_abs()
         .ok_or("cannot take absolute value of this length")?)),
     v: Angle => Self(Value::Angle(v.abs())),
@@ -752,13 +761,15 @@ pub fn fract(
 
 /// Round a number to the nearest integer.
 ///
-/// Optionally, a number of decimal places can be specified.
+/// Optionally, a number of decimal places can be specified. Decimals stay
+/// exact and have halves rounded away from zero.
 ///
 /// ## Example { #example }
 /// ```example
 /// #assert(calc.round(3.14) == 3)
 /// #assert(calc.round(3.5) == 4)
-/// #calc.round(3.1415, digits: 2)
+/// #calc.round(3.1415, digits: 2) \
+/// #calc.round(decimal("2.345"), digits: 2)
 /// ```
 ///
 /// Display: Round
@@ -766,20 +777,35 @@ pub fn fract(
 #[func]
 pub fn round(
     /// The number to round.
-    value: Num,
+    value: ToRound,
     /// The number of decimal places.
     #[named]
     #[default(0)]
     digits: i64,
-) -> Num {
-    match value {
-        Num::Int(n) if digits == 0 => Num::Int(n),
-        _ => {
+) -> StrResult<Value> {
+    Ok(match value {
+        ToRound::Decimal(n) => {
+            Value::Decimal(n.round(digits).ok_or("value is too large")?)
+        }
+        ToRound::Num(Num::Int(n)) if digits == 0 => Value::Int(n),
+        ToRound::Num(value) => {
             let n = value.float();
             let factor = 10.0_f64.powi(digits as i32);
-            Num::Float((n * factor).round() / factor)
+            Value::Float((n * factor).round() / factor)
         }
-    }
+    })
+}
+
+/// A value that can be rounded.
+pub enum ToRound {
+    Num(Num),
+    Decimal(Decimal),
+}
+
+cast! {
+    ToRound,
+    v: Num => Self::Num(v),
+    v: Decimal => Self::Decimal(v),
 }
 
 /// Clamp a number between a minimum and maximum value.
@@ -873,6 +899,224 @@ fn minmax(
     Ok(extremum)
 }
 
+/// Calculate the sum of an array of values.
+///
+/// Integers and floats can be mixed, in which case the sum is a float. Other
+/// values, like lengths, can only be summed with values of the same kind.
+///
+/// ## Example { #example }
+/// ```example
+/// #calc.sum((1, 2, 3.5)) \
+/// #calc.sum((1pt, 2cm))
+/// ```
+///
+/// Display: Sum
+/// Category: calculate
+#[func]
+pub fn sum(
+    /// The values to sum.
+    values: Array,
+    /// What to return if the array is empty.
+    #[named]
+    default: Option<Value>,
+    /// The callsite span.
+    span: Span,
+) -> SourceResult<Value> {
+    values.sum(default, span)
+}
+
+/// Calculate the product of an array of values.
+///
+/// ## Example { #example }
+/// ```example
+/// #calc.product((1, 2, 3, 4)) \
+/// #calc.product((2cm, 0.5, 3))
+/// ```
+///
+/// Display: Product
+/// Category: calculate
+#[func]
+pub fn product(
+    /// The values to multiply.
+    values: Array,
+    /// What to return if the array is empty.
+    #[named]
+    default: Option<Value>,
+    /// The callsite span.
+    span: Span,
+) -> SourceResult<Value> {
+    values.product(default, span)
+}
+
+/// Calculate the running sums of an array of values.
+///
+/// The result has one item for each value: The sum of that value and all
+/// values before it.
+///
+/// ## Example { #example }
+/// ```example
+/// #calc.cumsum((1, 2, 3, 4))
+/// ```
+///
+/// Display: Cumulative Sum
+/// Category: calculate
+#[func]
+pub fn cumsum(
+    /// The values to sum.
+    values: Array,
+) -> StrResult<Array> {
+    let mut sums = Array::new();
+    let mut acc = Value::None;
+    for value in values {
+        acc = ops::add(acc, value)?;
+        sums.push(acc.clone());
+    }
+    Ok(sums)
+}
+
+/// Calculate the arithmetic mean of an array of values.
+///
+/// The mean of integers is a float.
+///
+/// ## Example { #example }
+/// ```example
+/// #calc.mean((1, 2, 3, 4)) \
+/// #calc.mean((1cm, 2cm))
+/// ```
+///
+/// Display: Mean
+/// Category: calculate
+#[func]
+pub fn mean(
+    /// The values to average. Must not be empty.
+    values: Array,
+    /// The callsite span.
+    span: Span,
+) -> SourceResult<Value> {
+    if values.is_empty() {
+        bail!(span, "cannot calculate mean of empty array");
+    }
+
+    let len = values.len() as i64;
+    let sum = values.sum(None, span)?;
+    ops::div(sum, Value::Int(len)).at(span)
+}
+
+/// Calculate the median of an array of values.
+///
+/// For an even number of values, this is the mean of the two middle ones.
+///
+/// ## Example { #example }
+/// ```example
+/// #calc.median((4, 1, 3)) \
+/// #calc.median((4, 1, 3, 2))
+/// ```
+///
+/// Display: Median
+/// Category: calculate
+#[func]
+pub fn median(
+    /// The values whose median to find. Must not be empty.
+    values: Array,
+    /// The callsite span.
+    span: Span,
+) -> SourceResult<Value> {
+    quantile(values, Spanned::new(0.5, span), span)
+}
+
+/// Calculate a quantile of an array of values.
+///
+/// Between two values, the quantile is interpolated linearly.
+///
+/// ## Example { #example }
+/// ```example
+/// #let data = (1, 2, 3, 4, 5, 6, 7, 8)
+/// #calc.quantile(data, 0.25) \
+/// #calc.quantile(data, 0.9)
+/// ```
+///
+/// Display: Quantile
+/// Category: calculate
+#[func]
+pub fn quantile(
+    /// The values whose quantile to find. Must not be empty.
+    values: Array,
+    /// Which quantile to find, between `{0}` and `{1}`.
+    q: Spanned<f64>,
+    /// The callsite span.
+    span: Span,
+) -> SourceResult<Value> {
+    if !(0.0..=1.0).contains(&q.v) {
+        bail!(q.span, "quantile must be between 0 and 1");
+    }
+
+    let sorted = sort_values(values).at(span)?;
+    let Some(last) = sorted.len().checked_sub(1) else {
+        bail!(span, "cannot calculate quantile of empty array");
+    };
+
+    let position = q.v * last as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    if lower == upper || ops::equal(&sorted[lower], &sorted[upper]) {
+        return Ok(sorted[lower].clone());
+    }
+
+    // Interpolate between the two closest values.
+    let (a, b) = (sorted[lower].clone(), sorted[upper].clone());
+    let fract = Value::Float(position - lower as f64);
+    let diff = ops::sub(b, a.clone()).at(span)?;
+    ops::add(a, ops::mul(diff, fract).at(span)?).at(span)
+}
+
+/// Calculate the variance of an array of numbers.
+///
+/// ## Example { #example }
+/// ```example
+/// #calc.variance((2, 4, 4, 4, 5, 5, 7, 9)) \
+/// #calc.variance((1, 2, 3, 4), sample: true)
+/// ```
+///
+/// Display: Variance
+/// Category: calculate
+#[func]
+pub fn variance(
+    /// The numbers whose variance to calculate. Must not be empty.
+    values: Vec<f64>,
+    /// Whether the numbers are a sample of a larger population. If so, the
+    /// sum of the squared deviations is divided by one less than the number of
+    /// values.
+    #[named]
+    #[default(false)]
+    sample: bool,
+    /// The callsite span.
+    span: Span,
+) -> SourceResult<f64> {
+    let count = values.len().saturating_sub(usize::from(sample));
+    if count == 0 {
+        bail!(span, "not enough values to calculate variance");
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let squares: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+    Ok(squares / count as f64)
+}
+
+/// Sort values in ascending order.
+fn sort_values(values: Array) -> StrResult<Vec<Value>> {
+    let mut values: Vec<Value> = values.into_iter().collect();
+    let mut result = Ok(());
+    values.sort_by(|a, b| {
+        ops::compare(a, b).unwrap_or_else(|err| {
+            if result.is_ok() {
+                result = Err(err);
+            }
+            Ordering::Equal
+        })
+    });
+    result.map(|_| values)
+}
+
 /// Determine whether an integer is even.
 ///
 /// ## Example { #example }
@@ -3,14 +3,16 @@ use std::str::FromStr;
 
 use time::{Month, PrimitiveDateTime};
 
-use typst::eval::{Datetime, Regex};
+use typst::eval::{Bytes, Datetime, Decimal, Duration, Quantity, Regex};
 
+use super::Readable;
 use crate::prelude::*;
 
 /// Convert a value to an integer.
 ///
 /// - Booleans are converted to `0` or `1`.
 /// - Floats are floored to the next 64-bit integer.
+/// - Decimals are truncated to their integer part.
 /// - Strings are parsed in base 10.
 ///
 /// ## Example { #example }
@@ -39,13 +41,14 @@ cast! {
     v: bool => Self(v as i64),
     v: i64 => Self(v),
     v: f64 => Self(v as i64),
+    v: Decimal => Self(v.to_i64().ok_or("number too large")?),
     v: EcoString => Self(v.parse().map_err(|_| eco_format!("invalid integer: {}", v))?),
 }
 
 /// Convert a value to a float.
 ///
 /// - Booleans are converted to `0.0` or `1.0`.
-/// - Integers are converted to the closest 64-bit float.
+/// - Integers and decimals are converted to the closest 64-bit float.
 /// - Ratios are divided by 100%.
 /// - Strings are parsed in base 10 to the closest 64-bit float.
 ///   Exponential notation is supported.
@@ -78,10 +81,52 @@ cast! {
     v: bool => Self(v as i64 as f64),
     v: i64 => Self(v as f64),
     v: f64 => Self(v),
+    v: Decimal => Self(v.to_f64()),
     v: Ratio => Self(v.get()),
     v: EcoString => Self(v.parse().map_err(|_| eco_format!("invalid float: {}", v))?),
 }
 
+/// Create an exact decimal number.
+///
+/// - Integers are converted exactly.
+/// - Floats are converted to the decimal with the same shortest
+///   representation, so `{decimal(0.1)}` is exactly one tenth.
+/// - Strings are parsed in base 10. They may have a sign and a decimal point,
+///   but no exponent. Digits beyond the 28th after the decimal point are
+///   rounded off.
+///
+/// Strings are the best way to create decimals, as they never go through a
+/// float. See the [decimal]($type/decimal) type for more details.
+///
+/// ## Example { #example }
+/// ```example
+/// #decimal("1.23456789012345678901") \
+/// #decimal(5) \
+/// #(decimal("100.10") - decimal("0.1"))
+/// ```
+///
+/// Display: Decimal
+/// Category: construct
+#[func]
+pub fn decimal(
+    /// The value that should be converted to a decimal.
+    value: ToDecimal,
+) -> Decimal {
+    value.0
+}
+
+/// A value that can be cast to a decimal.
+pub struct ToDecimal(Decimal);
+
+cast! {
+    ToDecimal,
+    v: Decimal => Self(v),
+    v: f64 => Self(Decimal::from_f64(v)
+        .ok_or("float is not representable as a decimal")?),
+    v: EcoString => Self(Decimal::parse(&v)
+        .ok_or_else(|| eco_format!("invalid decimal: {}", v))?),
+}
+
 /// Create a grayscale color.
 ///
 /// ## Example { #example }
@@ -103,17 +148,15 @@ pub fn luma(
 
 /// Create an RGB(A) color.
 ///
-/// The color is specified in the sRGB color space.
-///
-/// _Note:_ While you can specify transparent colors and Typst's preview will
-/// render them correctly, the PDF export does not handle them properly at the
-/// moment. This will be fixed in the future.
+/// The color is specified in the sRGB color space. Transparent colors are
+/// supported by both the preview and the PDF export.
 ///
 /// ## Example { #example }
 /// ```example
 /// #square(fill: rgb("#b1f2eb"))
 /// #square(fill: rgb(87, 127, 230))
 /// #square(fill: rgb(25%, 13%, 65%))
+/// #square(fill: rgb(0, 0, 255, 50%))
 /// ```
 ///
 /// Display: RGB
@@ -169,6 +212,7 @@ pub struct Component(u8);
 
 cast! {
     Component,
+    self => self.0.into_value(),
     v: i64 => match v {
         0 ..= 255 => Self(v as u8),
         _ => bail!("number must be between 0 and 255"),
@@ -216,6 +260,7 @@ cast! {
 #[func]
 #[scope(
     scope.define("today", datetime_today_func());
+    scope.define("parse", datetime_parse_func());
     scope
 )]
 pub fn datetime(
@@ -338,6 +383,121 @@ pub fn datetime_today(
         .ok_or("unable to get the current date")?)
 }
 
+/// Parses a date and/or time from a string.
+///
+/// The pattern uses the same format as the [`display`]($type/datetime.display)
+/// method. Whether the result is a date, a time or both depends on the
+/// components in the pattern. Without a pattern, the default formats of
+/// `display` are tried.
+///
+/// ## Example
+/// ```example
+/// #datetime.parse("2023-07-14").display() \
+/// #datetime.parse(
+///   "14.07.2023",
+///   "[day].[month].[year]",
+/// ).display()
+/// ```
+///
+/// Display: Parse Datetime
+/// Category: construct
+#[func]
+pub fn datetime_parse(
+    /// The string to parse.
+    text: Str,
+    /// The format of the string.
+    #[default]
+    pattern: Option<EcoString>,
+) -> StrResult<Datetime> {
+    Datetime::parse(&text, pattern)
+}
+
+/// Create a new duration.
+///
+/// A [duration]($type/duration) is a span of time. Adding it to or
+/// subtracting it from a [datetime]($type/datetime) moves the datetime, while
+/// subtracting two datetimes of the same kind yields the duration between
+/// them.
+///
+/// ## Example
+/// ```example
+/// #let date = datetime(year: 2023, month: 7, day: 14)
+/// #let later = date + duration(weeks: 2, days: 3)
+/// #later.display() \
+/// #(later - date).days() days
+/// ```
+///
+/// Display: Duration
+/// Category: construct
+#[func]
+pub fn duration(
+    /// The number of seconds.
+    #[named]
+    #[default(0)]
+    seconds: i64,
+    /// The number of minutes.
+    #[named]
+    #[default(0)]
+    minutes: i64,
+    /// The number of hours.
+    #[named]
+    #[default(0)]
+    hours: i64,
+    /// The number of days.
+    #[named]
+    #[default(0)]
+    days: i64,
+    /// The number of weeks.
+    #[named]
+    #[default(0)]
+    weeks: i64,
+) -> StrResult<Duration> {
+    Duration::from_parts(weeks, days, hours, minutes, seconds)
+        .ok_or_else(|| "duration is out of range".into())
+}
+
+/// Create a number with a physical unit.
+///
+/// Quantities support unit algebra: Multiplying or dividing two quantities
+/// combines their units, while quantities of the same dimension can be added,
+/// subtracted and compared. When units cancel out completely, the result is a
+/// plain float. With the `to` method, a quantity can be converted into another
+/// unit of the same dimension.
+///
+/// Units are written as symbols joined by `*` and `/`, where a `/` only
+/// applies to the symbol directly following it. Symbols can be raised to
+/// integer powers with `^`. All SI units with their prefixes are supported,
+/// along with a few common non-SI units like `min`, `h`, `L`, `bar`, `eV` and
+/// `deg`. Use `u` and `Ohm` to type `µ` and `Ω`.
+///
+/// When inserted into the document, a quantity is typeset according to SI
+/// conventions: Digit groups and units are separated by narrow spaces, very
+/// large and small numbers use exponent notation and the decimal marker
+/// depends on the [text language]($func/text.lang). This works in text and
+/// in math alike.
+///
+/// ## Example { #example }
+/// ```example
+/// #let force = quantity(3.5, "kN")
+/// #let arm = quantity(0.4, "m")
+/// The torque is #(force * arm). \
+/// That's $M = #(force * arm).to("N*m")$. \
+/// #set text(lang: "de")
+/// Das sind #quantity(1234.5, "J").
+/// ```
+///
+/// Display: Quantity
+/// Category: construct
+#[func]
+pub fn quantity(
+    /// The numeric value.
+    value: f64,
+    /// The unit of the value, like `{"kN*m"}` or `{"m/s^2"}`.
+    unit: Spanned<EcoString>,
+) -> SourceResult<Quantity> {
+    Quantity::new(value, &unit.v).at(unit.span)
+}
+
 /// Create a CMYK color.
 ///
 /// This is useful if you want to target a specific printer. The conversion
@@ -379,6 +539,85 @@ cast! {
     },
 }
 
+/// Create an HSL color.
+///
+/// The color is specified by its hue, saturation and lightness and converted
+/// to the sRGB color space. This makes it easy to build palettes of related
+/// colors: Colors with the same hue but different lightness are shades of one
+/// another.
+///
+/// ## Example { #example }
+/// ```example
+/// #for h in range(0, 360, step: 60) {
+///   box(square(size: 20pt, fill: hsl(h * 1deg, 70%, 50%)))
+/// }
+/// ```
+///
+/// Display: HSL
+/// Category: construct
+#[func]
+pub fn hsl(
+    /// The hue, as an angle on the color wheel. Red is at `{0deg}`, green at
+    /// `{120deg}` and blue at `{240deg}`.
+    hue: Angle,
+    /// The saturation, from gray at `{0%}` to the pure hue at `{100%}`.
+    saturation: UnitRatio,
+    /// The lightness, from black at `{0%}` to white at `{100%}`.
+    lightness: UnitRatio,
+    /// The alpha component.
+    #[default(Component(255))]
+    alpha: Component,
+) -> Color {
+    RgbaColor::from_hsl(hue.to_deg(), saturation.0, lightness.0, alpha.0).into()
+}
+
+/// Create a color in the Oklab color space.
+///
+/// Oklab is a perceptual color space: Colors with the same lightness look
+/// equally bright, regardless of their hue. The `a` and `b` coordinates
+/// describe how green or red and how blue or yellow the color is,
+/// respectively. They typically lie between `{-0.4}` and `{0.4}`.
+///
+/// The color is converted to the sRGB color space. Colors that don't fit into
+/// it are mapped into it by reducing their chroma, while keeping their
+/// lightness and hue.
+///
+/// ## Example { #example }
+/// ```example
+/// #square(fill: oklab(70%, 0.1, -0.1))
+/// #square(fill: oklab(70%, -0.1, 0.1))
+/// ```
+///
+/// Display: Oklab
+/// Category: construct
+#[func]
+pub fn oklab(
+    /// The perceived lightness, from black at `{0%}` to white at `{100%}`.
+    lightness: UnitRatio,
+    /// The position on the green-red axis.
+    a: f64,
+    /// The position on the blue-yellow axis.
+    b: f64,
+    /// The alpha component.
+    #[default(Component(255))]
+    alpha: Component,
+) -> Color {
+    RgbaColor::from_oklab(lightness.0, a, b, alpha.0).into()
+}
+
+/// A ratio between zero and one.
+pub struct UnitRatio(f64);
+
+cast! {
+    UnitRatio,
+    self => Ratio::new(self.0).into_value(),
+    v: Ratio => if (0.0 ..= 1.0).contains(&v.get()) {
+        Self(v.get())
+    } else {
+        bail!("ratio must be between 0% and 100%");
+    },
+}
+
 /// Create a custom symbol with modifiers.
 ///
 /// ## Example { #example }
@@ -447,7 +686,9 @@ cast! {
 /// - Integers are formatted in base 10. This can be overridden with the
 ///   optional `base` parameter.
 /// - Floats are formatted in base 10 and never in exponential notation.
+/// - Decimals are formatted with all their digits.
 /// - From labels the name is extracted.
+/// - Bytes are decoded as UTF-8.
 ///
 /// If you wish to convert from and to Unicode code points, see
 /// [`str.to-unicode`]($func/str.to-unicode) and
@@ -506,8 +747,12 @@ cast! {
     ToStr,
     v: i64 => Self::Int(v),
     v: f64 => Self::Str(format_str!("{}", v)),
+    v: Decimal => Self::Str(format_str!("{}", v)),
     v: Label => Self::Str(v.0.into()),
     v: Str => Self::Str(v),
+    v: Bytes => Self::Str(
+        std::str::from_utf8(&v).map_err(|_| "bytes are not valid utf-8")?.into()
+    ),
 }
 
 /// Format an integer in a base.
@@ -595,6 +840,212 @@ cast! {
     },
 }
 
+/// Convert a value to bytes.
+///
+/// - Strings are encoded in UTF-8.
+/// - Arrays of integers between `{0}` and `{255}` are converted directly.
+///
+/// Bytes can be [read]($func/read) from files and [written]($func/write) to
+/// them. To convert them back to integers, use the `to-array` method.
+///
+/// ## Example { #example }
+/// ```example
+/// #let data = bytes("Hello")
+/// #data.len() \
+/// #data.at(0) \
+/// #data.slice(1, 3).to-array() \
+/// #str(bytes((84, 121, 112, 115, 116)))
+/// ```
+///
+/// Display: Bytes
+/// Category: construct
+#[func]
+pub fn bytes(
+    /// The value that should be converted to bytes.
+    value: ToBytes,
+) -> Bytes {
+    value.0
+}
+
+/// A value that can be cast to bytes.
+pub struct ToBytes(Bytes);
+
+cast! {
+    ToBytes,
+    v: Str => Self(v.as_bytes().into()),
+    v: Array => Self(
+        v.into_iter()
+            .map(|item| match item {
+                Value::Int(byte @ 0..=255) => Ok(byte as u8),
+                Value::Int(_) => bail!("number must be between 0 and 255"),
+                v => bail!("expected integer, found {}", v.type_name()),
+            })
+            .collect::<StrResult<Vec<u8>>>()?
+            .into()
+    ),
+    v: Bytes => Self(v),
+}
+
+/// Compute the SHA-256 hash of a string or bytes.
+///
+/// Strings are hashed in their UTF-8 encoding. The hash is returned as a
+/// string of 64 lowercase hexadecimal digits.
+///
+/// ## Example { #example }
+/// ```example
+/// #sha256("Hello")
+/// ```
+///
+/// Display: SHA-256
+/// Category: construct
+#[func]
+pub fn sha256(
+    /// The data to hash.
+    data: Readable,
+) -> Str {
+    use sha2::Digest;
+    encode_hex(&sha2::Sha256::digest(data.as_slice()))
+}
+
+/// Compute the MD5 hash of a string or bytes.
+///
+/// Strings are hashed in their UTF-8 encoding. The hash is returned as a
+/// string of 32 lowercase hexadecimal digits. MD5 is not secure against
+/// deliberate collisions, but still useful for checksums and short names.
+///
+/// ## Example { #example }
+/// ```example
+/// #md5("Hello")
+/// ```
+///
+/// Display: MD5
+/// Category: construct
+#[func]
+pub fn md5(
+    /// The data to hash.
+    data: Readable,
+) -> Str {
+    use ::md5::Digest;
+    encode_hex(&::md5::Md5::digest(data.as_slice()))
+}
+
+/// Encode a string or bytes in Base64.
+///
+/// Strings are encoded in UTF-8 first. The result uses the standard alphabet
+/// with padding. To decode Base64, use [`base64.decode`]($func/base64.decode).
+///
+/// ## Example { #example }
+/// ```example
+/// #let data = base64("Hello")
+/// #data \
+/// #str(base64.decode(data))
+/// ```
+///
+/// Display: Base64
+/// Category: construct
+#[func]
+#[scope(
+    scope.define("decode", base64_decode_func());
+    scope
+)]
+pub fn base64(
+    /// The data to encode.
+    data: Readable,
+) -> Str {
+    use ::base64::Engine;
+    ::base64::engine::general_purpose::STANDARD
+        .encode(data.as_slice())
+        .into()
+}
+
+/// Decode a Base64 string into bytes.
+///
+/// ## Example { #example }
+/// ```example
+/// #str(base64.decode("VHlwc3Q="))
+/// ```
+///
+/// Display: Decode Base64
+/// Category: construct
+#[func]
+pub fn base64_decode(
+    /// The Base64 string, in the standard alphabet with padding.
+    text: Str,
+) -> StrResult<Bytes> {
+    use ::base64::Engine;
+    let data = ::base64::engine::general_purpose::STANDARD
+        .decode(text.as_str())
+        .map_err(|_| "invalid base64 string")?;
+    Ok(data.into())
+}
+
+/// Encode a string or bytes as hexadecimal digits.
+///
+/// Strings are encoded in UTF-8 first. Each byte becomes two lowercase digits.
+/// To decode hexadecimal digits, use [`hex.decode`]($func/hex.decode).
+///
+/// ## Example { #example }
+/// ```example
+/// #hex("Typst") \
+/// #str(hex.decode("5479707374"))
+/// ```
+///
+/// Display: Hex
+/// Category: construct
+#[func]
+#[scope(
+    scope.define("decode", hex_decode_func());
+    scope
+)]
+pub fn hex(
+    /// The data to encode.
+    data: Readable,
+) -> Str {
+    encode_hex(data.as_slice())
+}
+
+/// Decode a string of hexadecimal digits into bytes.
+///
+/// ## Example { #example }
+/// ```example
+/// #hex.decode("ff00").to-array()
+/// ```
+///
+/// Display: Decode Hex
+/// Category: construct
+#[func]
+pub fn hex_decode(
+    /// The hexadecimal digits. Both lowercase and uppercase digits are
+    /// allowed.
+    text: Str,
+) -> StrResult<Bytes> {
+    if let Some(c) = text.chars().find(|c| !c.is_ascii_hexdigit()) {
+        bail!("invalid hex digit `{c}`");
+    }
+
+    if text.len() % 2 != 0 {
+        bail!("hex string must have an even number of digits");
+    }
+
+    let digit = |c: u8| (c as char).to_digit(16).unwrap() as u8;
+    let data: Vec<u8> = text
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| digit(pair[0]) << 4 | digit(pair[1]))
+        .collect();
+    Ok(data.into())
+}
+
+/// Encode bytes as lowercase hexadecimal digits.
+fn encode_hex(data: &[u8]) -> Str {
+    use std::fmt::Write;
+    let mut hex = EcoString::with_capacity(2 * data.len());
+    for byte in data {
+        write!(hex, "{byte:02x}").unwrap();
+    }
+    hex.into()
+}
+
 /// Create a label from a string.
 ///
 /// Inserting a label into content attaches it to the closest previous element
@@ -1,12 +1,25 @@
+use std::path::Path;
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
 use typst::diag::{format_xml_like_error, FileError};
-use typst::eval::Datetime;
-use typst::util::{hash128, AccessMode};
+use typst::eval::{Bytes, Datetime, Decoder, Records};
+use typst::util::{AccessMode, Buffer};
 
+use crate::layout::{
+    BlockElem, EnumElem, EnumItem, ListElem, ListItem, PadElem, ParbreakElem, Sizing,
+    TableElem, TrackSizings,
+};
+use crate::meta::{HeadingElem, LinkElem, LinkTarget};
 use crate::prelude::*;
+use crate::text::{
+    EmphElem, LinebreakElem, RawElem, SpaceElem, StrikeElem, StrongElem, TextElem,
+};
+use crate::visualize::{ImageElem, LineElem};
 
-/// Read plain text from a file.
+/// Read plain text or raw bytes from a file.
 ///
-/// The file will be read and returned as a string.
+/// By default, the file will be read as UTF-8 and returned as a string. With
+/// `{encoding: none}`, it is returned as [bytes]($func/bytes) instead.
 ///
 /// ## Example { #example }
 /// ```example
@@ -22,51 +35,153 @@ use crate::prelude::*;
 pub fn read(
     /// Path to a file.
     path: Spanned<EcoString>,
+    /// The encoding to read the file with.
+    ///
+    /// If set to `{none}`, this function returns raw bytes.
+    #[named]
+    #[default(Some(Encoding::Utf8))]
+    encoding: Option<Encoding>,
     /// The virtual machine.
     vm: &mut Vm,
-) -> SourceResult<Str> {
+) -> SourceResult<Readable> {
     let Spanned { v: path, span } = path;
     let path = vm.locate(&path, AccessMode::R).at(span)?;
     let data = vm.world().read(&path).at(span)?;
-    let text = std::str::from_utf8(&data)
-        .map_err(|_| "file is not valid utf-8")
-        .at(span)?;
-    Ok(text.into())
+    decode(data, encoding).at(span)
+}
+
+/// Decode the contents of a file with the given encoding.
+pub(super) fn decode(data: Buffer, encoding: Option<Encoding>) -> StrResult<Readable> {
+    Ok(match encoding {
+        None => Readable::Bytes(data.into()),
+        Some(Encoding::Utf8) => Readable::Str(
+            std::str::from_utf8(&data)
+                .map_err(|_| "file is not valid utf-8")?
+                .into(),
+        ),
+    })
+}
+
+/// An encoding of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum Encoding {
+    /// The Unicode UTF-8 encoding.
+    Utf8,
+}
+
+/// A value that can be read from or written to a file.
+pub enum Readable {
+    /// A decoded string.
+    Str(Str),
+    /// Raw bytes.
+    Bytes(Bytes),
+}
+
+impl Readable {
+    /// The raw bytes of the value. Strings are encoded in UTF-8.
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Str(v) => v.as_bytes(),
+            Self::Bytes(v) => v,
+        }
+    }
 }
 
-/// Write plain text to a file.
+cast! {
+    Readable,
+    self => match self {
+        Self::Str(v) => v.into_value(),
+        Self::Bytes(v) => v.into_value(),
+    },
+    v: Str => Self::Str(v),
+    v: Bytes => Self::Bytes(v),
+}
+
+/// Read the value of an environment variable.
 ///
-/// The text will be added to a buffer and written once compilation is over.
-/// Please note that this function does not ensure the call's order. Instead, you should make sure to add identifiers to your calls, if you want to find them later.
-/// The file you write to will be named "record.txt", found in the same directory as your generated PDF/PNG(s).
-/// We require a location to reduce de amount of code that depends on the
+/// Returns `{none}` if the variable is not set. For reproducibility, access to
+/// environment variables is denied unless explicitly allowed. On the command
+/// line, variables are allowed with `--allow-env`, which takes a
+/// comma-separated list of names and patterns like `CI_*`.
 ///
 /// ## Example { #example }
-/// ```example
-/// #let text = write("data.html")
+/// ```typ
+/// Build #env("BUILD_NUMBER")
+/// ```
 ///
-/// An example for a HTML file:\
-/// #raw(text, lang: "html")
+/// Display: Environment Variable
+/// Category: data-loading
+#[func]
+pub fn env(
+    /// The name of the variable.
+    name: Spanned<EcoString>,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<Option<EcoString>> {
+    let Spanned { v: name, span } = name;
+    vm.world().env(&name).at(span)
+}
+
+/// Run an external program and return what it prints.
+///
+/// The program's standard output is returned as a string. Compilation fails if
+/// the program exits unsuccessfully. Because external programs can do anything
+/// and make compilation depend on more than its inputs, running them is denied
+/// unless explicitly allowed. On the command line, this is done with
+/// `--allow-exec`.
+///
+/// ## Example { #example }
+/// ```typ
+/// Revision #exec("git", ("rev-parse", "--short", "HEAD")).trim()
 /// ```
 ///
-/// Note to self: Could use macro Locatable instead
+/// Display: Execute
+/// Category: data-loading
+#[func]
+pub fn exec(
+    /// The program to run.
+    program: Spanned<EcoString>,
+    /// The arguments to pass to the program.
+    #[default]
+    args: Vec<EcoString>,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<EcoString> {
+    let Spanned { v: program, span } = program;
+    vm.world().exec(&program, &args).at(span)
+}
+
+/// Fetch a remote resource over HTTP or HTTPS.
+///
+/// Like with [`read`]($func/read), the resource is returned as a string by
+/// default and as [bytes]($func/bytes) with `{encoding: none}`. Network access
+/// is denied unless explicitly allowed. On the command line, this is done with
+/// `--allow-net`, optionally restricted to a comma-separated list of hosts.
+/// Fetched resources are cached, so that documents still compile offline.
 ///
-/// Display: Write
+/// ## Example { #example }
+/// ```typ
+/// #let data = fetch("https://example.com/data.json")
+/// ```
+///
+/// Display: Fetch
 /// Category: data-loading
 #[func]
-pub fn write(
-    /// The text to write.
-    text: Spanned<EcoString>,
-    /// The location one is writing from
-    location: Location,
+pub fn fetch(
+    /// The URL of the resource.
+    url: Spanned<EcoString>,
+    /// The encoding to read the resource with.
+    ///
+    /// If set to `{none}`, this function returns raw bytes.
+    #[named]
+    #[default(Some(Encoding::Utf8))]
+    encoding: Option<Encoding>,
     /// The virtual machine.
     vm: &mut Vm,
-) -> SourceResult<()> {
-    let Spanned { v: text, span } = text;
-    let path = "/record.txt";
-    let path = vm.locate(path, AccessMode::W).at(span)?;
-    vm.world().write(&path, hash128(&location), text.as_bytes().to_vec()).at(span)?;
-    Ok(())
+) -> SourceResult<Readable> {
+    let Spanned { v: url, span } = url;
+    let data = vm.world().fetch(&url).at(span)?;
+    decode(data, encoding).at(span)
 }
 
 /// Read structured data from a CSV file.
@@ -74,7 +189,7 @@ pub fn write(
 /// The CSV file will be read and parsed into a 2-dimensional array of strings:
 /// Each row in the CSV file will be represented as an array of strings, and all
 /// rows will be collected into a single array. Header rows will not be
-/// stripped.
+/// stripped, unless `header` is set.
 ///
 /// ## Example { #example }
 /// ```example
@@ -87,9 +202,25 @@ pub fn write(
 /// )
 /// ```
 ///
+/// With a header row, each row is instead a dictionary from column names to
+/// fields:
+///
+/// ```example
+/// #let monkeys = csv("monkeys.csv", header: true, infer: true)
+/// #let total = monkeys.map(row => row.count).sum()
+/// We counted #total monkeys.
+/// ```
+///
+/// Huge files can be processed row by row with [`csv.iter`]($func/csv.iter)
+/// instead.
+///
 /// Display: CSV
 /// Category: data-loading
 #[func]
+#[scope(
+    scope.define("iter", csv_iter_func());
+    scope
+)]
 pub fn csv(
     /// Path to a CSV file.
     path: Spanned<EcoString>,
@@ -98,35 +229,156 @@ pub fn csv(
     #[named]
     #[default]
     delimiter: Delimiter,
+    /// Whether the first row contains the names of the columns.
+    ///
+    /// If `{true}`, the first row is not returned. Instead, each of the other
+    /// rows is a dictionary that maps the column names to the row's fields.
+    #[named]
+    #[default(false)]
+    header: bool,
+    /// Whether to remove leading and trailing whitespace from each field.
+    #[named]
+    #[default(false)]
+    trim: bool,
+    /// Whether to convert fields that are numbers into integers and floats.
+    /// All other fields stay strings.
+    #[named]
+    #[default(false)]
+    infer: bool,
     /// The virtual machine.
     vm: &mut Vm,
 ) -> SourceResult<Array> {
     let Spanned { v: path, span } = path;
     let path = vm.locate(&path, AccessMode::R).at(span)?;
     let data = vm.world().read(&path).at(span)?;
+    let decoder = CsvDecoder { delimiter: delimiter.0 as u8, header, trim, infer };
+    decoder.decode(&data).collect::<StrResult<_>>().at(span)
+}
 
-    let mut builder = csv::ReaderBuilder::new();
-    builder.has_headers(false);
-    builder.delimiter(delimiter.0 as u8);
+/// Lazily read the rows of a CSV file.
+///
+/// Takes the same arguments as [`csv`]($func/csv), but returns
+/// [records]($type/records) instead of an array. The rows are only decoded
+/// while they are iterated over, which keeps memory usage low for huge files.
+///
+/// ## Example
+/// ```example
+/// #let rows = csv.iter("monkeys.csv", header: true, infer: true)
+/// #for row in rows.filter(row => row.count > 10) [
+///   - #row.species
+/// ]
+/// ```
+///
+/// Display: Iterate CSV
+/// Category: data-loading
+#[func]
+pub fn csv_iter(
+    /// Path to a CSV file.
+    path: Spanned<EcoString>,
+    /// The delimiter that separates columns in the CSV file.
+    #[named]
+    #[default]
+    delimiter: Delimiter,
+    /// Whether the first row contains the names of the columns.
+    #[named]
+    #[default(false)]
+    header: bool,
+    /// Whether to remove leading and trailing whitespace from each field.
+    #[named]
+    #[default(false)]
+    trim: bool,
+    /// Whether to convert fields that are numbers into integers and floats.
+    #[named]
+    #[default(false)]
+    infer: bool,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<Records> {
+    let Spanned { v: path, span } = path;
+    let path = vm.locate(&path, AccessMode::R).at(span)?;
+    let data = vm.world().read(&path).at(span)?;
+    let decoder = CsvDecoder { delimiter: delimiter.0 as u8, header, trim, infer };
+    Ok(Records::new(data, decoder))
+}
 
-    let mut reader = builder.from_reader(data.as_slice());
-    let mut array = Array::new();
+/// Decodes the rows of a CSV file.
+#[derive(Debug, Hash)]
+struct CsvDecoder {
+    delimiter: u8,
+    header: bool,
+    trim: bool,
+    infer: bool,
+}
 
-    for (line, result) in reader.records().enumerate() {
-        // Original solution use line from error, but that is incorrect with
-        // `has_headers` set to `false`. See issue:
-        // https://github.com/BurntSushi/rust-csv/issues/184
-        let line = line + 1; // Counting lines from 1
-        let row = result.map_err(|err| format_csv_error(err, line)).at(span)?;
-        let sub = row.into_iter().map(|field| field.into_value()).collect();
-        array.push(Value::Array(sub))
+impl Decoder for CsvDecoder {
+    fn format(&self) -> &'static str {
+        "csv"
     }
 
-    Ok(array)
+    fn decode<'a>(
+        &'a self,
+        data: &'a [u8],
+    ) -> Box<dyn Iterator<Item = StrResult<Value>> + 'a> {
+        let mut builder = csv::ReaderBuilder::new();
+        builder.has_headers(false);
+        builder.delimiter(self.delimiter);
+        if self.trim {
+            builder.trim(csv::Trim::All);
+        }
+
+        let mut columns: Option<Vec<Str>> = None;
+        let records = builder.from_reader(data).into_records().enumerate();
+        Box::new(records.filter_map(move |(line, result)| {
+            // Original solution use line from error, but that is incorrect with
+            // `has_headers` set to `false`. See issue:
+            // https://github.com/BurntSushi/rust-csv/issues/184
+            let line = line + 1; // Counting lines from 1
+            let row = match result {
+                Ok(row) => row,
+                Err(err) => return Some(Err(format_csv_error(err, line))),
+            };
+
+            let fields = row.iter().map(|field| convert_csv_field(field, self.infer));
+            if !self.header {
+                return Some(Ok(Value::Array(fields.collect())));
+            } else if let Some(columns) = &columns {
+                let dict = columns.iter().cloned().zip(fields).collect();
+                return Some(Ok(Value::Dict(dict)));
+            }
+
+            let names: Vec<Str> = row.iter().map(Str::from).collect();
+            for (i, name) in names.iter().enumerate() {
+                if names[..i].contains(name) {
+                    return Some(Err(eco_format!(
+                        "failed to parse csv file: duplicate column name \
+                         `{name}` in column {} of line {line}",
+                        i + 1,
+                    )));
+                }
+            }
+
+            columns = Some(names);
+            None
+        }))
+    }
+}
+
+/// Convert a CSV field into a value, turning numbers into integers and floats
+/// if `infer` is set.
+fn convert_csv_field(field: &str, infer: bool) -> Value {
+    if infer && field.bytes().any(|c| c.is_ascii_digit()) {
+        if let Ok(int) = field.parse::<i64>() {
+            return int.into_value();
+        } else if let Ok(float) = field.parse::<f64>() {
+            return float.into_value();
+        }
+    }
+
+    field.into_value()
 }
 
 /// The delimiter to use when parsing CSV files.
-pub struct Delimiter(char);
+pub struct Delimiter(pub(super) char);
 
 impl Default for Delimiter {
     fn default() -> Self {
@@ -155,13 +407,16 @@ cast! {
 /// Format the user-facing CSV error message.
 fn format_csv_error(error: csv::Error, line: usize) -> EcoString {
     match error.kind() {
-        csv::ErrorKind::Utf8 { .. } => "file is not valid utf-8".into(),
+        csv::ErrorKind::Utf8 { err, .. } => eco_format!(
+            "file is not valid utf-8 in column {} of line {line}",
+            err.field() + 1
+        ),
         csv::ErrorKind::UnequalLengths { expected_len, len, .. } => {
             eco_format!(
                 "failed to parse csv file: found {len} instead of {expected_len} fields in line {line}"
             )
         }
-        _ => "failed to parse csv file".into(),
+        _ => eco_format!("failed to parse csv file in line {line}"),
     }
 }
 
@@ -206,6 +461,10 @@ fn format_csv_error(error: csv::Error, line: usize) -> EcoString {
 /// Display: JSON
 /// Category: data-loading
 #[func]
+#[scope(
+    scope.define("iter", json_iter_func());
+    scope
+)]
 pub fn json(
     /// Path to a JSON file.
     path: Spanned<EcoString>,
@@ -217,11 +476,113 @@ pub fn json(
     let data = vm.world().read(&path).at(span)?;
     let value: serde_json::Value =
         serde_json::from_slice(&data).map_err(format_json_error).at(span)?;
+    warn_lossy_json(vm, span, &value);
     Ok(convert_json(value))
 }
 
+/// Lazily read the items of a JSON array.
+///
+/// The file must contain an array. Instead of the array,
+/// [records]($type/records) are returned, whose items are only decoded while
+/// they are iterated over. This keeps memory usage low for huge files.
+///
+/// ## Example
+/// ```example
+/// #for animal in json.iter("zoo.json").take(2) [
+///   - #animal.name
+/// ]
+/// ```
+///
+/// Display: Iterate JSON
+/// Category: data-loading
+#[func]
+pub fn json_iter(
+    /// Path to a JSON file.
+    path: Spanned<EcoString>,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<Records> {
+    let Spanned { v: path, span } = path;
+    let path = vm.locate(&path, AccessMode::R).at(span)?;
+    let data = vm.world().read(&path).at(span)?;
+    Ok(Records::new(data, JsonDecoder))
+}
+
+/// Decodes the items of a JSON array one by one.
+#[derive(Debug, Hash)]
+struct JsonDecoder;
+
+impl Decoder for JsonDecoder {
+    fn format(&self) -> &'static str {
+        "json"
+    }
+
+    fn decode<'a>(
+        &'a self,
+        data: &'a [u8],
+    ) -> Box<dyn Iterator<Item = StrResult<Value>> + 'a> {
+        let skip_whitespace = |pos: usize| {
+            pos + data[pos..].iter().take_while(|c| c.is_ascii_whitespace()).count()
+        };
+        let line = |pos: usize| 1 + data[..pos].iter().filter(|&&c| c == b'\n').count();
+
+        let mut pos = skip_whitespace(0);
+        if data.get(pos) != Some(&b'[') {
+            let error = "failed to parse json file: expected an array";
+            return Box::new(std::iter::once(Err(error.into())));
+        }
+
+        pos += 1;
+        let mut first = true;
+        let mut done = false;
+        Box::new(std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+
+            pos = skip_whitespace(pos);
+            match data.get(pos) {
+                Some(b']') => {
+                    done = true;
+                    return None;
+                }
+                Some(b',') if !first => pos = skip_whitespace(pos + 1),
+                _ if first => {}
+                _ => {
+                    done = true;
+                    return Some(Err(eco_format!(
+                        "failed to parse json file: syntax error in line {}",
+                        line(pos)
+                    )));
+                }
+            }
+
+            first = false;
+            let mut stream = serde_json::Deserializer::from_slice(&data[pos..])
+                .into_iter::<serde_json::Value>();
+            let result = stream.next();
+            let start = pos;
+            pos += stream.byte_offset();
+            match result {
+                Some(Ok(value)) => Some(Ok(convert_json(value))),
+                Some(Err(err)) => {
+                    done = true;
+                    Some(Err(eco_format!(
+                        "failed to parse json file: syntax error in line {}",
+                        line(start) + err.line() - 1
+                    )))
+                }
+                None => {
+                    done = true;
+                    Some(Err("failed to parse json file: unexpected end of file".into()))
+                }
+            }
+        }))
+    }
+}
+
 /// Convert a JSON value to a Typst value.
-fn convert_json(value: serde_json::Value) -> Value {
+pub(super) fn convert_json(value: serde_json::Value) -> Value {
     match value {
         serde_json::Value::Null => Value::None,
         serde_json::Value::Bool(v) => v.into_value(),
@@ -241,79 +602,28 @@ fn convert_json(value: serde_json::Value) -> Value {
     }
 }
 
-fn convert_back_json(value: Value) -> StrResult<serde_json::Value> {
-    Ok(match value {
-        Value::None => serde_json::Value::Null,
-        Value::Bool(v) => serde_json::Value::Bool(v),
-        Value::Int(v) => serde_json::Value::Number(serde_json::Number::from(v)),
-        Value::Float(v) => serde_json::Value::Number(
-            serde_json::Number::from_f64(v).ok_or("cannot write NaN or Infinite as json")?),
-        Value::Str(v) => serde_json::Value::String(v.to_string()),
-        Value::Array(v) => {
-            let mut ser_v = Vec::new();
-            for val in v.into_iter() {
-                ser_v.push(convert_back_json(val)?);
-            }
-            serde_json::Value::Array(ser_v)
-        },
-        Value::Dict(v) => {
-            let mut ser_v = serde_json::Map::new();
-            for (s, val) in v.into_iter() {
-                ser_v.insert(s.to_string(), convert_back_json(val)?);
-            }
-            serde_json::Value::Object(ser_v)
-        },
-
-        _ => return Err("unsupported type".into()),
-    })
-}
-
-/// Display: JSON_Write
-/// Category: data-loading
-#[func]
-pub fn write_json(
-    /// Path to a JSON file.
-    path: Spanned<EcoString>,
-    /// The dictionary key.
-    key: Str,
-    /// The data to write.
-    val: Spanned<ToJSON>,
-    /// The virtual machine.
-    vm: &mut Vm,
-) -> SourceResult<()> {
-    let Spanned { v: path, span: p_span } = path;
-    let Spanned { v: val, span: v_span } = val;
-    let path = vm.locate(&path, AccessMode::W).at(p_span)?;
-    let value = convert_back_json(val.0).at(v_span)?;
-    let text = serde_json::to_string(&value).map_err(format_json_error).at(v_span)?;
-    // Do a nasty manual conversion to prefix the key..
-    let text = "\"".to_owned() + &key + "\": " + &text + ",\n"; //TODO :(
-
-    // Not a great way to do this.. 
-    // but writing upon call also means doing it à la state?
-    // or instead, could add a world::create...
-    vm.world().write(&path, u128::MIN, "{".as_bytes().to_vec()).at(p_span)?;
-    vm.world().write(&path, u128::MAX, "}".as_bytes().to_vec()).at(p_span)?;
-
-    vm.world().write(&path, hash128(&key), text.as_bytes().to_vec()).at(p_span)?;
-    Ok(())
-}
-
-pub struct ToJSON(Value);
+/// Warn if a JSON value contains an integer that can only be represented
+/// approximately, as a float.
+pub(super) fn warn_lossy_json(vm: &mut Vm, span: Span, value: &serde_json::Value) {
+    fn find(value: &serde_json::Value) -> Option<&serde_json::Number> {
+        match value {
+            serde_json::Value::Number(v) if v.is_u64() && v.as_i64().is_none() => Some(v),
+            serde_json::Value::Array(v) => v.iter().find_map(find),
+            serde_json::Value::Object(v) => v.values().find_map(find),
+            _ => None,
+        }
+    }
 
-cast! {
-    ToJSON,
-    _: () => Self(Value::None),
-    v: bool => Self(Value::Bool(v)),
-    v: i64 => Self(Value::Int(v)),
-    v: f64 => Self(Value::Float(v)),
-    v: Str => Self(Value::Str(v)),
-    v: Array => Self(Value::Array(v)),
-    v: Dict => Self(Value::Dict(v)),
+    if let Some(number) = find(value) {
+        vm.warn(
+            span,
+            eco_format!("integer {number} is too large and was converted to a float"),
+        );
+    }
 }
 
 /// Format the user-facing JSON error message.
-fn format_json_error(error: serde_json::Error) -> EcoString {
+pub(super) fn format_json_error(error: serde_json::Error) -> EcoString {
     assert!(error.is_syntax() || error.is_eof());
     eco_format!("failed to parse json file: syntax error in line {}", error.line())
 }
@@ -597,3 +907,576 @@ fn convert_xml(node: roxmltree::Node) -> Value {
 fn format_xml_error(error: roxmltree::Error) -> EcoString {
     format_xml_like_error("xml file", error)
 }
+
+/// Read a Markdown file and convert it into content.
+///
+/// The file is parsed as [CommonMark](https://commonmark.org) with tables,
+/// strikethrough and task lists. Headings, emphasis, lists, code, links,
+/// images, tables, block quotes and thematic breaks become the corresponding
+/// Typst elements, so that they are styled like the rest of the document.
+/// Inline HTML is ignored. Images are resolved relative to the Markdown file.
+///
+/// ## Example { #example }
+/// ```example
+/// #set heading(numbering: "1.")
+/// #markdown("notes.md")
+/// ```
+///
+/// Display: Markdown
+/// Category: data-loading
+#[func]
+pub fn markdown(
+    /// Path to a Markdown file.
+    path: Spanned<EcoString>,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<Content> {
+    let Spanned { v: path, span } = path;
+    let full = vm.locate(&path, AccessMode::R).at(span)?;
+    let data = vm.world().read(&full).at(span)?;
+    let text = std::str::from_utf8(&data).map_err(FileError::from).at(span)?;
+    let base = Path::new(path.as_str()).parent().unwrap_or(Path::new(""));
+
+    // Each open tag collects its children until it is closed.
+    let mut stack: Vec<Vec<Content>> = vec![vec![]];
+    let mut columns = 0;
+    let mut head = false;
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(text, options) {
+        let elem = match event {
+            Event::Start(tag) => {
+                match tag {
+                    Tag::Table(alignments) => columns = alignments.len(),
+                    Tag::TableHead => head = true,
+                    _ => {}
+                }
+                stack.push(vec![]);
+                continue;
+            }
+            Event::End(tag) => {
+                let children = stack.pop().unwrap_or_default();
+                let parent = stack.last_mut().unwrap();
+                let body = Content::sequence(children.iter().cloned());
+                match tag {
+                    // The cells of a table are its direct children.
+                    Tag::TableHead | Tag::TableRow => {
+                        head = false;
+                        parent.extend(children);
+                        continue;
+                    }
+                    Tag::TableCell if head => StrongElem::new(body).pack(),
+                    Tag::Paragraph => Content::sequence([
+                        ParbreakElem::new().pack(),
+                        body,
+                        ParbreakElem::new().pack(),
+                    ]),
+                    Tag::Heading(level, ..) => HeadingElem::new(body)
+                        .with_level(NonZeroUsize::new(level as usize).unwrap())
+                        .pack(),
+                    Tag::BlockQuote => {
+                        PadElem::new(body).with_left(Em::new(1.0).into()).pack()
+                    }
+                    Tag::CodeBlock(kind) => {
+                        let lang = match kind {
+                            CodeBlockKind::Fenced(info) => {
+                                info.split_whitespace().next().map(EcoString::from)
+                            }
+                            CodeBlockKind::Indented => None,
+                        };
+                        let mut text = body.plain_text();
+                        if text.ends_with('\n') {
+                            text.pop();
+                        }
+                        RawElem::new(text).with_block(true).with_lang(lang).pack()
+                    }
+                    Tag::List(Some(start)) => {
+                        let items = children.into_iter().map(EnumItem::new).collect();
+                        EnumElem::new(items).with_start(start as usize).pack()
+                    }
+                    Tag::List(None) => {
+                        ListElem::new(children.into_iter().map(ListItem::new).collect())
+                            .pack()
+                    }
+                    Tag::Table(_) => TableElem::new(children)
+                        .with_columns(TrackSizings(vec![Sizing::Auto; columns]))
+                        .pack(),
+                    Tag::Emphasis => EmphElem::new(body).pack(),
+                    Tag::Strong => StrongElem::new(body).pack(),
+                    Tag::Strikethrough => StrikeElem::new(body).pack(),
+                    Tag::Link(_, dest, _) => {
+                        let dest = Destination::Url(dest.as_ref().into());
+                        LinkElem::new(LinkTarget::Dest(dest), body).pack()
+                    }
+                    Tag::Image(_, dest, _) => {
+                        // Remote images can't be loaded, so they become links.
+                        let alt = body.plain_text();
+                        if dest.contains("://") {
+                            let dest = Destination::Url(dest.as_ref().into());
+                            LinkElem::new(LinkTarget::Dest(dest), body).pack()
+                        } else {
+                            let path = base.join(dest.as_ref());
+                            let path = vm
+                                .locate(&path.to_string_lossy(), AccessMode::R)
+                                .at(span)?;
+                            ImageElem::resolved(vm.world(), path.to_string_lossy().into())
+                                .at(span)?
+                                .with_alt(Some(alt))
+                                .pack()
+                        }
+                    }
+                    Tag::Item | Tag::TableCell | Tag::FootnoteDefinition(_) => body,
+                }
+            }
+            Event::Text(text) => TextElem::packed(text.as_ref()),
+            Event::Code(code) => RawElem::new(code.as_ref().into()).pack(),
+            Event::SoftBreak => SpaceElem::new().pack(),
+            Event::HardBreak => LinebreakElem::new().pack(),
+            Event::Rule => {
+                let line = LineElem::new().with_length(Ratio::one().into()).pack();
+                BlockElem::new().with_body(Some(line)).pack()
+            }
+            Event::TaskListMarker(checked) => {
+                TextElem::packed(if checked { "☒ " } else { "☐ " })
+            }
+            Event::Html(_) | Event::FootnoteReference(_) => continue,
+        };
+
+        stack.last_mut().unwrap().push(elem);
+    }
+
+    Ok(Content::sequence(stack.into_iter().flatten()))
+}
+
+/// Read structured data from an Excel workbook.
+///
+/// The cells of a worksheet in an Office Open XML workbook (`.xlsx`) are read
+/// into a 2-dimensional array: Each row is represented as an array of cell
+/// values, and all rows are collected into a single array. Like for
+/// [`csv`]($func/csv), a header row can instead be used to turn each of the
+/// other rows into a dictionary.
+///
+/// Cells are converted as follows:
+/// - Text becomes a string and booleans become booleans.
+/// - Numbers become integers if they are whole and floats otherwise.
+/// - Dates and times become [datetimes]($type/datetime).
+/// - Empty cells become `{none}`.
+/// - Errors, like `#DIV/0!`, become their text as a string.
+///
+/// ## Example { #example }
+/// ```example
+/// #let sales = xlsx("sales.xlsx", sheet: "Q2", range: "A1:C4", header: true)
+/// #table(
+///   columns: 2,
+///   [*Region*], [*Total*],
+///   ..sales.map(row => (row.Region, str(row.Total))).flatten(),
+/// )
+/// ```
+///
+/// Display: XLSX
+/// Category: data-loading
+#[cfg(feature = "xlsx")]
+#[func]
+pub fn xlsx(
+    /// Path to an XLSX file.
+    path: Spanned<EcoString>,
+    /// The name of the worksheet to read. Defaults to the first worksheet.
+    #[named]
+    sheet: Option<Spanned<EcoString>>,
+    /// The rectangle of cells to read in A1 notation, like `{"A1:D20"}`.
+    /// Defaults to all cells from the first to the last one with data.
+    #[named]
+    range: Option<Spanned<EcoString>>,
+    /// Whether the first row contains the names of the columns.
+    ///
+    /// If `{true}`, the first row is not returned. Instead, each of the other
+    /// rows is a dictionary that maps the column names to the row's values.
+    #[named]
+    #[default(false)]
+    header: bool,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<Array> {
+    use calamine::Reader;
+
+    let Spanned { v: path, span } = path;
+    let path = vm.locate(&path, AccessMode::R).at(span)?;
+    let data = vm.world().read(&path).at(span)?;
+
+    let mut workbook: calamine::Xlsx<_> =
+        calamine::open_workbook_from_rs(std::io::Cursor::new(data.as_slice()))
+            .map_err(format_xlsx_error)
+            .at(span)?;
+
+    let cells = match &sheet {
+        Some(Spanned { v: name, span }) => workbook
+            .worksheet_range(name)
+            .ok_or_else(|| eco_format!("worksheet `{name}` does not exist"))
+            .at(*span)?,
+        None => workbook
+            .worksheet_range_at(0)
+            .ok_or("workbook does not contain any worksheets")
+            .at(span)?,
+    }
+    .map_err(format_xlsx_error)
+    .at(span)?;
+
+    let cells = match &range {
+        Some(Spanned { v: range, span }) => {
+            let (start, end) = parse_cell_range(range)
+                .ok_or_else(|| eco_format!("invalid cell range `{range}`"))
+                .at(*span)?;
+            cells.range(start, end)
+        }
+        None => cells,
+    };
+
+    let mut rows = cells.rows().map(|row| row.iter().map(convert_xlsx_cell));
+    if !header {
+        return Ok(rows.map(|row| Value::Array(row.collect())).collect());
+    }
+
+    let Some(names) = rows.next() else { return Ok(Array::new()) };
+    let names: Vec<Str> = names
+        .map(|name| match name {
+            Value::Str(name) => name,
+            Value::None => Str::new(),
+            name => name.repr(),
+        })
+        .collect();
+
+    Ok(rows
+        .map(|row| Value::Dict(names.iter().cloned().zip(row).collect()))
+        .collect())
+}
+
+/// Convert a cell of an Excel workbook to a Typst value.
+#[cfg(feature = "xlsx")]
+fn convert_xlsx_cell(cell: &calamine::DataType) -> Value {
+    use calamine::DataType;
+    match cell {
+        DataType::Empty => Value::None,
+        DataType::Bool(v) => v.into_value(),
+        DataType::Int(v) => v.into_value(),
+        DataType::Float(v) if v.fract() == 0.0 && v.abs() < i64::MAX as f64 => {
+            (*v as i64).into_value()
+        }
+        DataType::Float(v) => v.into_value(),
+        DataType::String(v) | DataType::DateTimeIso(v) | DataType::DurationIso(v) => {
+            v.as_str().into_value()
+        }
+        DataType::DateTime(v) => excel_datetime(*v).into_value(),
+        DataType::Duration(v) => {
+            let seconds = (v * 86400.0).round();
+            typst::eval::Duration::from_parts(0, 0, 0, 0, seconds as i64).into_value()
+        }
+        DataType::Error(err) => eco_format!("{err}").into_value(),
+    }
+}
+
+/// Convert an Excel serial date, which counts the days since 1899-12-30, to a
+/// datetime. Serial dates below one are times without a date.
+#[cfg(feature = "xlsx")]
+fn excel_datetime(serial: f64) -> Option<Datetime> {
+    let seconds = (serial * 86400.0).round();
+    if !seconds.is_finite() || seconds.abs() >= i64::MAX as f64 {
+        return None;
+    }
+
+    let seconds = seconds as i64;
+    let (days, seconds) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    let time = time::Time::from_hms(
+        (seconds / 3600) as u8,
+        (seconds / 60 % 60) as u8,
+        (seconds % 60) as u8,
+    )
+    .ok()?;
+
+    if days == 0 {
+        return Some(Datetime::Time(time));
+    }
+
+    let epoch = time::Date::from_calendar_date(1899, time::Month::December, 30).ok()?;
+    let date = epoch.checked_add(time::Duration::days(days))?;
+    Some(if seconds == 0 {
+        Datetime::Date(date)
+    } else {
+        Datetime::Datetime(time::PrimitiveDateTime::new(date, time))
+    })
+}
+
+/// Parse a cell range in A1 notation, like `A1:D20` or `B2`, into the
+/// zero-based rows and columns of its first and last cell.
+#[cfg(feature = "xlsx")]
+fn parse_cell_range(range: &str) -> Option<((u32, u32), (u32, u32))> {
+    let cell = |cell: &str| {
+        let split = cell.find(|c: char| c.is_ascii_digit())?;
+        let (letters, digits) = cell.split_at(split);
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        let mut column = 0_u32;
+        for c in letters.chars() {
+            let digit = c.to_ascii_uppercase() as u32 - 'A' as u32 + 1;
+            column = column.checked_mul(26)?.checked_add(digit)?;
+        }
+
+        let row = digits.parse::<u32>().ok()?.checked_sub(1)?;
+        Some((row, column - 1))
+    };
+
+    let (start, end) = range.split_once(':').unwrap_or((range, range));
+    let (start, end) = (cell(start)?, cell(end)?);
+    (start.0 <= end.0 && start.1 <= end.1).then_some((start, end))
+}
+
+/// Format the user-facing XLSX error message.
+#[cfg(feature = "xlsx")]
+fn format_xlsx_error(error: calamine::XlsxError) -> EcoString {
+    eco_format!("failed to parse xlsx file: {error}")
+}
+
+/// Query an SQLite database.
+///
+/// The query is run against a read-only copy of the database, so statements
+/// that would modify it fail. Each row of the result is represented as a
+/// dictionary that maps the column names to the row's values.
+///
+/// The columns' values are converted as follows:
+/// - `NULL` becomes `{none}`.
+/// - Integers, reals and text become integers, floats and strings.
+/// - Blobs become [bytes]($func/bytes).
+///
+/// ## Example { #example }
+/// ```example
+/// #let sales = sql("sales.sqlite", "SELECT name, total FROM sales")
+/// #table(
+///   columns: 2,
+///   [*Name*], [*Total*],
+///   ..sales.map(row => (row.name, str(row.total))).flatten(),
+/// )
+/// ```
+///
+/// Display: SQL
+/// Category: data-loading
+#[cfg(feature = "sqlite")]
+#[func]
+pub fn sql(
+    /// Path to an SQLite database file.
+    path: Spanned<EcoString>,
+    /// The SQL query to run.
+    query: Spanned<EcoString>,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<Array> {
+    let Spanned { v: path, span } = path;
+    let path = vm.locate(&path, AccessMode::R).at(span)?;
+    let data = vm.world().read(&path).at(span)?;
+    let conn = open_sqlite(data.as_slice()).map_err(format_sql_error).at(span)?;
+
+    let Spanned { v: query, span } = query;
+    let mut stmt = conn.prepare(&query).map_err(format_sql_error).at(span)?;
+    if !stmt.readonly() {
+        bail!(span, "only read-only queries are allowed");
+    }
+
+    let names: Vec<Str> = stmt.column_names().into_iter().map(Str::from).collect();
+    let mut rows = stmt.query([]).map_err(format_sql_error).at(span)?;
+    let mut array = Array::new();
+    while let Some(row) = rows.next().map_err(format_sql_error).at(span)? {
+        let mut dict = Dict::new();
+        for (i, name) in names.iter().enumerate() {
+            let value = row.get_ref(i).map_err(format_sql_error).at(span)?;
+            dict.insert(name.clone(), convert_sql_value(value));
+        }
+        array.push(Value::Dict(dict));
+    }
+
+    Ok(array)
+}
+
+/// Open an in-memory, read-only SQLite database with the given contents.
+///
+/// The connection can't attach further databases, as that would allow queries
+/// to read files outside of the root directory.
+#[cfg(feature = "sqlite")]
+fn open_sqlite(data: &[u8]) -> rusqlite::Result<rusqlite::Connection> {
+    use rusqlite::ffi;
+    use rusqlite::limits::Limit;
+    use rusqlite::serialize::OwnedData;
+    use rusqlite::OpenFlags;
+
+    // SQLite takes ownership of the database's memory, so it must be
+    // allocated by SQLite.
+    let ptr = unsafe { ffi::sqlite3_malloc64(data.len().max(1) as u64) };
+    let Some(ptr) = std::ptr::NonNull::new(ptr.cast::<u8>()) else {
+        return Err(rusqlite::Error::SqliteFailure(
+            ffi::Error::new(ffi::SQLITE_NOMEM),
+            None,
+        ));
+    };
+
+    // Safety: The allocation is at least `data.len()` bytes long and is freed
+    // by SQLite once the connection is closed.
+    let owned = unsafe {
+        std::ptr::copy_nonoverlapping(data.as_ptr(), ptr.as_ptr(), data.len());
+        OwnedData::from_raw_nonnull(ptr, data.len())
+    };
+
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let mut conn = rusqlite::Connection::open_in_memory_with_flags(flags)?;
+    conn.set_limit(Limit::SQLITE_LIMIT_ATTACHED, 0);
+    conn.deserialize(rusqlite::DatabaseName::Main, owned, true)?;
+    Ok(conn)
+}
+
+/// Convert a value from an SQLite database to a Typst value.
+#[cfg(feature = "sqlite")]
+fn convert_sql_value(value: rusqlite::types::ValueRef) -> Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => Value::None,
+        ValueRef::Integer(v) => v.into_value(),
+        ValueRef::Real(v) => v.into_value(),
+        ValueRef::Text(v) => String::from_utf8_lossy(v).as_ref().into_value(),
+        ValueRef::Blob(v) => Bytes::from(v).into_value(),
+    }
+}
+
+/// Format the user-facing SQL error message.
+#[cfg(feature = "sqlite")]
+fn format_sql_error(error: rusqlite::Error) -> EcoString {
+    eco_format!("failed to query sqlite database: {error}")
+}
+
+/// Open a ZIP archive.
+///
+/// The archive is read from the root directory, just like with
+/// [`read`]($func/read). Its entries can then be read with the handle's
+/// `read` method, which also takes an `encoding`. Each entry is only
+/// decompressed once, no matter how often it is read.
+///
+/// ## Example { #example }
+/// ```typ
+/// #let assets = zip("assets.zip")
+/// #let logo = assets.read("images/logo.svg")
+/// #assets.entries().len() files
+/// ```
+///
+/// Display: ZIP
+/// Category: data-loading
+#[cfg(feature = "zip")]
+#[func]
+pub fn zip(
+    /// Path to a ZIP archive.
+    path: Spanned<EcoString>,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<Archive> {
+    let Spanned { v: path, span } = path;
+    let path = vm.locate(&path, AccessMode::R).at(span)?;
+    let data = vm.world().read(&path).at(span)?;
+    zip::ZipArchive::new(std::io::Cursor::new(data.as_slice()))
+        .map_err(format_zip_error)
+        .at(span)?;
+    Ok(Archive(data))
+}
+
+/// A handle to a ZIP archive, created with [`zip`]($func/zip).
+#[cfg(feature = "zip")]
+#[derive(Clone, PartialEq, Hash)]
+pub struct Archive(Buffer);
+
+#[cfg(feature = "zip")]
+impl Archive {
+    /// Call a method on an archive.
+    pub fn call_method(
+        self,
+        method: &str,
+        mut args: Args,
+        span: Span,
+    ) -> SourceResult<Value> {
+        let value = match method {
+            "read" => {
+                let Spanned { v: name, span } = args.expect::<Spanned<Str>>("name")?;
+                let encoding = args.named("encoding")?.unwrap_or(Some(Encoding::Utf8));
+                let data = extract_zip_entry(&self.0, &name).at(span)?;
+                decode(data, encoding).at(span)?.into_value()
+            }
+            "entries" => self.entries().at(span)?.into_value(),
+            _ => bail!(span, "type archive has no method `{}`", method),
+        };
+        args.finish()?;
+        Ok(value)
+    }
+
+    /// The names of the archive's files, in the order they are stored in.
+    pub fn entries(&self) -> StrResult<Array> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(self.0.as_slice()))
+            .map_err(format_zip_error)?;
+        let mut entries = Array::new();
+        for i in 0..archive.len() {
+            let file = archive.by_index_raw(i).map_err(format_zip_error)?;
+            if !file.is_dir() {
+                entries.push(file.name().into_value());
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl Debug for Archive {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("archive(..)")
+    }
+}
+
+#[cfg(feature = "zip")]
+cast! {
+    type Archive: "archive",
+}
+
+/// The maximum size of a decompressed ZIP entry in bytes.
+///
+/// This guards against entries that decompress to much more data than their
+/// size in the archive suggests.
+#[cfg(feature = "zip")]
+const ZIP_ENTRY_LIMIT: u64 = 256 * 1024 * 1024;
+
+/// Decompress a file from a ZIP archive.
+#[cfg(feature = "zip")]
+#[comemo::memoize]
+fn extract_zip_entry(data: &Buffer, name: &str) -> StrResult<Buffer> {
+    use std::io::Read;
+
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data.as_slice()))
+        .map_err(format_zip_error)?;
+    let mut file = archive.by_name(name).map_err(|err| match err {
+        zip::result::ZipError::FileNotFound => {
+            eco_format!("archive does not contain `{name}`")
+        }
+        err => format_zip_error(err),
+    })?;
+
+    // The size stated in the archive can't be trusted, so it only serves as
+    // a hint and the limit is enforced while decompressing.
+    let mut buf = Vec::with_capacity(file.size().min(ZIP_ENTRY_LIMIT) as usize);
+    file.by_ref()
+        .take(ZIP_ENTRY_LIMIT + 1)
+        .read_to_end(&mut buf)
+        .map_err(|err| format_zip_error(err.into()))?;
+    if buf.len() as u64 > ZIP_ENTRY_LIMIT {
+        bail!("archive entry `{name}` is too large");
+    }
+
+    Ok(buf.into())
+}
+
+/// Format the user-facing ZIP error message.
+#[cfg(feature = "zip")]
+fn format_zip_error(error: zip::result::ZipError) -> EcoString {
+    eco_format!("failed to read zip archive: {error}")
+}
@@ -1,3 +1,5 @@
+use typst::eval::Datetime;
+
 use crate::prelude::*;
 
 /// Determine a value's type.
@@ -213,3 +215,305 @@ pub fn eval(
     let Spanned { v: text, span } = source;
     typst::eval::eval_string(vm.world(), &text, span)
 }
+
+/// Format values into a string.
+///
+/// Each placeholder in curly braces is replaced by one of the values. An empty
+/// placeholder `{}` takes the next positional value, `{0}` takes a positional
+/// value by its index and `{name}` takes the named value with that name. To
+/// insert a literal brace, double it: `{{` or `}}`.
+///
+/// Strings are inserted as-is, numbers and [datetimes]($type/datetime) in their
+/// default display format and all other values in their
+/// [representation]($func/repr). Content cannot be formatted.
+///
+/// ## Format specifiers { #specifiers }
+/// After a colon, a placeholder can describe how to format its value:
+/// `{:[[fill]align][+][0][width][.precision][kind]}`.
+///
+/// - An _align_ of `<`, `^` or `>` pads the value to the _width_ at the
+///   right, at both sides or at the left with the _fill_ character, which
+///   defaults to a space. Numbers are right-aligned by default, everything
+///   else is left-aligned.
+/// - A `+` shows the sign of positive numbers, too.
+/// - A `0` pads numbers with zeros between the sign and the digits.
+/// - The _width_ is the minimum number of characters of the result.
+/// - The _precision_ is the number of digits after the decimal point for
+///   numbers and the maximum number of characters for other values. Width
+///   and precision can be at most 65535.
+/// - The _kind_ selects a different notation for numbers: `f` for fixed-point
+///   notation (the default), `e` for scientific notation and `x`, `X`, `o` or
+///   `b` for integers in hexadecimal, octal or binary. A `?` formats any value
+///   in its representation.
+///
+/// ## Example { #example }
+/// ```example
+/// #let date = datetime(year: 2023, month: 7, day: 14)
+/// #strfmt("{:.2f} EUR on {date}", 3.14159, date: date) \
+/// #strfmt("[{:>5}] [{:<5}] [{:^5}]", 1, 2, 3) \
+/// #strfmt("{:*^9}", "hi") \
+/// #strfmt("{:+08.3}", 2.5) \
+/// #strfmt("{0:x} {0:b} {0:?}", 255)
+/// ```
+///
+/// Display: Format String
+/// Category: foundations
+#[func]
+pub fn strfmt(
+    /// The format string with placeholders.
+    format: Spanned<Str>,
+    /// The values to insert into the placeholders, positional or named.
+    #[external]
+    #[variadic]
+    values: Vec<Value>,
+    /// The arguments.
+    args: Args,
+) -> SourceResult<Str> {
+    let Spanned { v: format, span } = format;
+    let mut used = vec![false; args.items.len()];
+    let mut next = 0;
+    let mut output = EcoString::new();
+    let mut rest = format.as_str();
+
+    while let Some(i) = rest.find(['{', '}']) {
+        output.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        // Escaped braces.
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            output.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        } else if rest.starts_with('}') {
+            bail!(span, "unmatched closing brace in format string");
+        }
+
+        let Some(end) = rest.find('}') else {
+            bail!(span, "unclosed placeholder in format string");
+        };
+
+        let placeholder = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let (key, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+        let spec = FormatSpec::parse(spec).at(span)?;
+
+        let positional = |n: usize| {
+            args.items
+                .iter()
+                .enumerate()
+                .filter(|(_, arg)| arg.name.is_none())
+                .nth(n)
+                .map(|(i, _)| i)
+        };
+
+        let index = if key.is_empty() {
+            next += 1;
+            positional(next - 1)
+        } else if let Ok(n) = key.parse::<usize>() {
+            positional(n)
+        } else {
+            args.items.iter().position(|arg| arg.name.as_deref() == Some(key))
+        };
+
+        let Some(index) = index else {
+            bail!(span, "missing value for placeholder `{{{placeholder}}}`");
+        };
+
+        let value = &args.items[index].value;
+        used[index] = true;
+        output.push_str(&spec.apply(&value.v).at(value.span)?);
+    }
+
+    output.push_str(rest);
+
+    if let Some((arg, _)) = args.items.iter().zip(&used).find(|(_, used)| !**used) {
+        bail!(arg.span, "unused value in format string");
+    }
+
+    Ok(output.into())
+}
+
+/// How to format a value in a placeholder of [`strfmt`].
+#[derive(Default)]
+struct FormatSpec {
+    /// The character to pad with.
+    fill: Option<char>,
+    /// Where to place the value within the padding.
+    align: Option<Align>,
+    /// Whether to show the sign of positive numbers.
+    sign: bool,
+    /// Whether to pad numbers with zeros.
+    zero: bool,
+    /// The minimum number of characters.
+    width: usize,
+    /// The number of digits after the decimal point or maximum number of
+    /// characters.
+    precision: Option<usize>,
+    /// The notation to format the value in.
+    kind: Option<char>,
+}
+
+impl FormatSpec {
+    /// Parse the part of a placeholder after the colon.
+    fn parse(spec: &str) -> StrResult<Self> {
+        let chars: Vec<char> = spec.chars().collect();
+        let align = |c: Option<&char>| match c {
+            Some('<') => Some(Align::Left),
+            Some('^') => Some(Align::Center),
+            Some('>') => Some(Align::Right),
+            _ => None,
+        };
+
+        let mut parsed = Self::default();
+        let mut i = 0;
+        if let Some(align) = align(chars.get(1)) {
+            parsed.fill = Some(chars[0]);
+            parsed.align = Some(align);
+            i = 2;
+        } else if let Some(align) = align(chars.first()) {
+            parsed.align = Some(align);
+            i = 1;
+        }
+
+        if chars.get(i) == Some(&'+') {
+            parsed.sign = true;
+            i += 1;
+        }
+
+        if chars.get(i) == Some(&'0') {
+            parsed.zero = true;
+            i += 1;
+        }
+
+        // Widths and precisions are bounded so that padding stays reasonable.
+        let number = |i: &mut usize, what: &str| -> StrResult<Option<usize>> {
+            let start = *i;
+            while chars.get(*i).map_or(false, char::is_ascii_digit) {
+                *i += 1;
+            }
+            if start == *i {
+                return Ok(None);
+            }
+            match chars[start..*i].iter().collect::<String>().parse::<u16>() {
+                Ok(n) => Ok(Some(n.into())),
+                Err(_) => bail!("format {what} must be at most {}", u16::MAX),
+            }
+        };
+
+        let invalid = || eco_format!("invalid format specifier `{spec}`");
+        parsed.width = number(&mut i, "width")?.unwrap_or(0);
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            parsed.precision = Some(number(&mut i, "precision")?.ok_or_else(invalid)?);
+        }
+
+        if let Some(&kind) = chars.get(i) {
+            if !matches!(kind, 'f' | 'e' | 'x' | 'X' | 'o' | 'b' | '?') {
+                return Err(invalid());
+            }
+            parsed.kind = Some(kind);
+            i += 1;
+        }
+
+        if i != chars.len() {
+            return Err(invalid());
+        }
+
+        Ok(parsed)
+    }
+
+    /// Format a value according to the specifier.
+    fn apply(&self, value: &Value) -> StrResult<EcoString> {
+        let numeric =
+            matches!(value, Value::Int(_) | Value::Float(_) | Value::Decimal(_));
+        let text = match (self.kind, value) {
+            (Some('?'), v) => v.repr().into(),
+            (Some(kind @ ('x' | 'X' | 'o' | 'b')), &Value::Int(n)) => {
+                let sign = if n < 0 { "-" } else { "" };
+                let n = n.unsigned_abs();
+                match kind {
+                    'x' => eco_format!("{sign}{n:x}"),
+                    'X' => eco_format!("{sign}{n:X}"),
+                    'o' => eco_format!("{sign}{n:o}"),
+                    _ => eco_format!("{sign}{n:b}"),
+                }
+            }
+            (Some('x' | 'X' | 'o' | 'b'), v) => {
+                bail!("cannot format {} as integer", v.type_name())
+            }
+            (Some('e'), v) => {
+                let v = match *v {
+                    Value::Int(v) => v as f64,
+                    Value::Float(v) => v,
+                    Value::Decimal(v) => v.to_f64(),
+                    _ => bail!("cannot format {} as number", v.type_name()),
+                };
+                match self.precision {
+                    Some(p) => eco_format!("{v:.p$e}"),
+                    None => eco_format!("{v:e}"),
+                }
+            }
+            (Some('f'), v) if !numeric => {
+                bail!("cannot format {} as number", v.type_name())
+            }
+            (_, Value::Int(v)) => match self.precision {
+                Some(p) if p > 0 => eco_format!("{v}.{}", "0".repeat(p)),
+                _ => eco_format!("{v}"),
+            },
+            (_, Value::Float(v)) => match self.precision {
+                Some(p) => eco_format!("{v:.p$}"),
+                None => eco_format!("{v}"),
+            },
+            (_, Value::Decimal(v)) => match self.precision {
+                Some(p) => {
+                    let rounded = v.round(p as i64).ok_or("value is too large")?;
+                    let text = eco_format!("{rounded}");
+                    let digits = text.split_once('.').map_or(0, |(_, fract)| fract.len());
+                    let point = if digits == 0 && p > 0 { "." } else { "" };
+                    eco_format!("{text}{point}{}", "0".repeat(p - digits))
+                }
+                None => eco_format!("{v}"),
+            },
+            (_, Value::Content(_)) => bail!("cannot format content"),
+            (_, v) => {
+                let text: EcoString = match v {
+                    Value::Str(v) => v.as_str().into(),
+                    Value::Dyn(v) if v.is::<Datetime>() => {
+                        v.downcast::<Datetime>().unwrap().display(None)?
+                    }
+                    v => v.repr().into(),
+                };
+                match self.precision {
+                    Some(p) => text.chars().take(p).collect(),
+                    None => text,
+                }
+            }
+        };
+
+        // Split off the sign so that zeros can be inserted after it.
+        let (sign, body) = match text.strip_prefix('-') {
+            Some(body) if numeric => ("-", body),
+            _ if numeric && self.sign => ("+", text.as_str()),
+            _ => ("", text.as_str()),
+        };
+
+        let len = sign.len() + body.chars().count();
+        let padding = self.width.saturating_sub(len);
+        if self.zero && numeric && self.align.is_none() {
+            return Ok(eco_format!("{sign}{}{body}", "0".repeat(padding)));
+        }
+
+        let fill = self.fill.unwrap_or(' ');
+        let align =
+            self.align.unwrap_or(if numeric { Align::Right } else { Align::Left });
+        let (before, after) = match align {
+            Align::Left => (0, padding),
+            Align::Center => (padding / 2, padding - padding / 2),
+            _ => (padding, 0),
+        };
+
+        let pad = |n: usize| fill.to_string().repeat(n);
+        Ok(eco_format!("{}{sign}{body}{}", pad(before), pad(after)))
+    }
+}
@@ -20,25 +20,49 @@ pub(super) fn define(global: &mut Scope) {
     global.define("panic", panic_func());
     global.define("assert", assert_func());
     global.define("eval", eval_func());
+    global.define("strfmt", strfmt_func());
     global.define("int", int_func());
     global.define("float", float_func());
+    global.define("decimal", decimal_func());
     global.define("luma", luma_func());
     global.define("rgb", rgb_func());
     global.define("cmyk", cmyk_func());
+    global.define("hsl", hsl_func());
+    global.define("oklab", oklab_func());
     global.define("datetime", datetime_func());
+    global.define("duration", duration_func());
+    global.define("quantity", quantity_func());
     global.define("symbol", symbol_func());
     global.define("str", str_func());
+    global.define("bytes", bytes_func());
+    global.define("sha256", sha256_func());
+    global.define("md5", md5_func());
+    global.define("base64", base64_func());
+    global.define("hex", hex_func());
     global.define("label", label_func());
     global.define("regex", regex_func());
     global.define("range", range_func());
     global.define("read", read_func());
-    global.define("record", write_func());
+    global.define("env", env_func());
+    global.define("exec", exec_func());
+    global.define("fetch", fetch_func());
+    global.define("write", write_func());
     global.define("csv", csv_func());
     global.define("json", json_func());
     global.define("write_json", write_json_func());
+    global.define("write_yaml", write_yaml_func());
+    global.define("write_toml", write_toml_func());
+    global.define("write_csv", write_csv_func());
     global.define("toml", toml_func());
     global.define("yaml", yaml_func());
     global.define("xml", xml_func());
+    global.define("markdown", markdown_func());
+    #[cfg(feature = "xlsx")]
+    global.define("xlsx", xlsx_func());
+    #[cfg(feature = "sqlite")]
+    global.define("sql", sql_func());
+    #[cfg(feature = "zip")]
+    global.define("zip", zip_func());
     global.define("calc", calc::module());
     global.define("open", open_func())
 }
@@ -1,55 +1,483 @@
 use std::fmt::{self, Debug, Formatter, Write};
-use std::path::PathBuf;
 
-use typst::diag::{format_xml_like_error, FileError};
 use typst::eval::Datetime;
-use typst::util::{hash128, AccessMode};
+use typst::util::{AccessMode, Buffer, WriteMode};
 
+use super::data::{convert_json, decode, format_json_error, warn_lossy_json};
+use super::{Delimiter, Encoding, Readable};
 use crate::prelude::*;
 
-/// Write plain text to a file.
+/// Write plain text or bytes to a file.
 ///
-/// The text will be added to a buffer and written once compilation is over.
-/// Please note that this function does not ensure the call's order. Instead, you should make sure to add identifiers to your calls, if you want to find them later.
-/// The file you write to will be named "record.txt", found in the same directory as your generated PDF/PNG(s).
-/// We require a location to reduce de amount of code that depends on the
+/// The data will be added to a buffer and written once compilation is over.
+/// Strings are written as UTF-8, [bytes]($func/bytes) are written as-is.
+/// The path is relative to the destination directory (configured with
+/// `--dest` on the command line) and may not leave it.
 ///
-/// ## Example { #example }
-/// ```example
-/// #let text = write("data.html")
+/// Data written to the same file is ordered by its `at` key. Data without a
+/// key is placed at `{0}`. Data with the same key is written in the order of
+/// the calls.
 ///
-/// An example for a HTML file:\
-/// #raw(text, lang: "html")
-/// ```
+/// By default, an existing file is overwritten. With the `mode` argument, the
+/// data can instead be appended to the existing file, for instance to
+/// accumulate a log across compilations, or the compilation can fail if the
+/// file already exists. All writes to a file must use the same mode. When
+/// recompiling in watch mode, the file is considered as it was before the
+/// first compilation.
 ///
-/// Note to self: Could use macro Locatable instead
+/// ## Example { #example }
+/// ```typ
+/// #write("notes.txt", "Second\n", at: 2)
+/// #write("notes.txt", "First\n", at: 1)
+/// #write("copy.png", read("image.png", encoding: none))
+/// ```
 ///
 /// Display: Write
 /// Category: data-loading
-/*#[func]
+#[func]
 pub fn write(
-    /// The text to write.
-    text: Spanned<EcoString>,
-    /// The location one is writing from
-    location: Location,
+    /// Path to the file, relative to the destination directory.
+    path: Spanned<EcoString>,
+    /// The text or bytes to write.
+    data: Readable,
+    /// Where to place the data relative to other data written to the same
+    /// file.
+    #[named]
+    #[default]
+    at: Option<i64>,
+    /// What to do if the file already exists.
+    #[named]
+    #[default]
+    mode: WriteMode,
     /// The virtual machine.
     vm: &mut Vm,
 ) -> SourceResult<()> {
-    let Spanned { v: text, span } = text;
-    let path = "/record.txt";
-    let path = vm.locate(path, AccessMode::W).at(span)?;
-    vm.world().write(&path, hash128(&location), text.as_bytes().to_vec()).at(span)?;
+    let Spanned { v: path, span } = path;
+    let path = vm.locate(&path, AccessMode::W).at(span)?;
+    let order = Some(at.unwrap_or_default());
+    vm.world()
+        .write(&path, order, mode, data.as_slice().to_vec(), span)
+        .at(span)?;
     Ok(())
-}*/
+}
+
+/// Write structured data to a JSON file.
+///
+/// The data is serialized once and written when compilation is over, just like
+/// with [`write`]($func/write). Unlike with `write`, nothing else can be
+/// written to the same file and writing structured data to it again replaces
+/// the earlier data. To collect entries from across the document in one file,
+/// gather them in a [state]($func/state) and write its final value.
+///
+/// Dictionaries are written as JSON objects and arrays as JSON arrays.
+/// Strings, numbers and booleans are written as their JSON equivalents,
+/// `{none}` is written as `null` and datetimes are written as strings.
+///
+/// ## Example { #example }
+/// ```typ
+/// #write_json("forecast.json", (
+///   temperature: 12,
+///   unit: "C",
+///   weather: "sunny",
+/// ), pretty: true)
+/// ```
+///
+/// Collecting the pages of marked places in one file:
+/// ```typ
+/// #let marks = state("marks", (:))
+/// #let mark(id) = locate(loc => marks.update(m => {
+///   m.insert(id, loc.page())
+///   m
+/// }))
+///
+/// #locate(loc => write_json("marks.json", marks.final(loc)))
+/// ```
+///
+/// Display: Write JSON
+/// Category: data-loading
+#[func]
+pub fn write_json(
+    /// Path to the JSON file, relative to the destination directory.
+    path: Spanned<EcoString>,
+    /// The data to write.
+    data: Spanned<Value>,
+    /// Whether to indent the output for readability.
+    #[named]
+    #[default(false)]
+    pretty: bool,
+    /// What to do if the file already exists.
+    #[named]
+    #[default]
+    mode: WriteMode,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<()> {
+    let Spanned { v: data, span } = data;
+    let value = convert_back_json(data).at(span)?;
+    let text = if pretty {
+        serde_json::to_string_pretty(&value)
+    } else {
+        serde_json::to_string(&value)
+    };
+    let text = text
+        .map_err(|err| eco_format!("failed to write json: {err}"))
+        .at(span)?;
+    write_data(vm, path, mode, text.into_bytes())
+}
+
+/// Convert a Typst value to a JSON value.
+fn convert_back_json(value: Value) -> StrResult<serde_json::Value> {
+    Ok(match value {
+        Value::None => serde_json::Value::Null,
+        Value::Bool(v) => serde_json::Value::Bool(v),
+        Value::Int(v) => serde_json::Value::Number(v.into()),
+        Value::Float(v) => serde_json::Value::Number(
+            serde_json::Number::from_f64(v)
+                .ok_or("cannot write NaN or infinite number")?,
+        ),
+        Value::Str(v) => serde_json::Value::String(v.into()),
+        Value::Array(v) => serde_json::Value::Array(
+            v.into_iter().map(convert_back_json).collect::<StrResult<_>>()?,
+        ),
+        Value::Dict(v) => serde_json::Value::Object(
+            v.into_iter()
+                .map(|(key, value)| Ok((key.into(), convert_back_json(value)?)))
+                .collect::<StrResult<_>>()?,
+        ),
+        Value::Dyn(v) if v.is::<Datetime>() => {
+            let datetime: &Datetime = v.downcast().unwrap();
+            serde_json::Value::String(datetime.display(None)?.into())
+        }
+        v => bail!("cannot write {}", v.type_name()),
+    })
+}
 
+/// Write structured data to a YAML file.
+///
+/// The data is converted like for [`write_json`]($func/write_json).
+///
+/// ## Example { #example }
+/// ```typ
+/// #write_yaml("authors.yaml", (
+///   "Arthur C. Clarke": (
+///     (title: "Childhood's End", published: 1953),
+///   ),
+/// ))
+/// ```
+///
+/// Display: Write YAML
+/// Category: data-loading
+#[func]
+pub fn write_yaml(
+    /// Path to the YAML file, relative to the destination directory.
+    path: Spanned<EcoString>,
+    /// The data to write.
+    data: Spanned<Value>,
+    /// What to do if the file already exists.
+    #[named]
+    #[default]
+    mode: WriteMode,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<()> {
+    let Spanned { v: data, span } = data;
+    let value = convert_back_json(data).at(span)?;
+    let text = serde_yaml::to_string(&value)
+        .map_err(|err| eco_format!("failed to write yaml: {err}"))
+        .at(span)?;
+    write_data(vm, path, mode, text.into_bytes())
+}
+
+/// Write structured data to a TOML file.
+///
+/// The data must be a dictionary, which is written as the TOML file's
+/// top-level table. Datetimes are written as TOML datetimes. TOML has no
+/// equivalent to `{none}`, so it cannot be written.
+///
+/// ## Example { #example }
+/// ```typ
+/// #write_toml("config.toml", (
+///   title: "Typst",
+///   date: datetime(year: 2023, month: 6, day: 1),
+/// ), pretty: true)
+/// ```
+///
+/// Display: Write TOML
+/// Category: data-loading
+#[func]
+pub fn write_toml(
+    /// Path to the TOML file, relative to the destination directory.
+    path: Spanned<EcoString>,
+    /// The table to write.
+    data: Spanned<Dict>,
+    /// Whether to write arrays over multiple lines for readability.
+    #[named]
+    #[default(false)]
+    pretty: bool,
+    /// What to do if the file already exists.
+    #[named]
+    #[default]
+    mode: WriteMode,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<()> {
+    let Spanned { v: data, span } = data;
+    let value = convert_back_toml(Value::Dict(data)).at(span)?;
+    let text =
+        if pretty { toml::to_string_pretty(&value) } else { toml::to_string(&value) };
+    let text = text
+        .map_err(|err| eco_format!("failed to write toml: {err}"))
+        .at(span)?;
+    write_data(vm, path, mode, text.into_bytes())
+}
 
-/// File descriptor used for convenience
+/// Convert a Typst value to a TOML value.
+fn convert_back_toml(value: Value) -> StrResult<toml::Value> {
+    Ok(match value {
+        Value::Bool(v) => toml::Value::Boolean(v),
+        Value::Int(v) => toml::Value::Integer(v),
+        Value::Float(v) => toml::Value::Float(v),
+        Value::Str(v) => toml::Value::String(v.into()),
+        Value::Array(v) => toml::Value::Array(
+            v.into_iter().map(convert_back_toml).collect::<StrResult<_>>()?,
+        ),
+        Value::Dict(v) => toml::Value::Table(
+            v.into_iter()
+                .map(|(key, value)| Ok((key.into(), convert_back_toml(value)?)))
+                .collect::<StrResult<_>>()?,
+        ),
+        Value::Dyn(v) if v.is::<Datetime>() => {
+            let datetime: &Datetime = v.downcast().unwrap();
+            let date = match (datetime.year(), datetime.month(), datetime.day()) {
+                (Some(year), Some(month), Some(day)) => Some(toml::value::Date {
+                    year: year.try_into().map_err(|_| "year is out of range for toml")?,
+                    month,
+                    day,
+                }),
+                _ => None,
+            };
+            let time = match (datetime.hour(), datetime.minute(), datetime.second()) {
+                (Some(hour), Some(minute), Some(second)) => {
+                    Some(toml::value::Time { hour, minute, second, nanosecond: 0 })
+                }
+                _ => None,
+            };
+            toml::Value::Datetime(toml::value::Datetime { date, time, offset: None })
+        }
+        v => bail!("cannot write {}", v.type_name()),
+    })
+}
+
+/// Write structured data to a CSV file.
+///
+/// The data must be an array of rows. Each row is either an array of fields,
+/// which is written as is, or a dictionary. If the rows are dictionaries, a
+/// header row with the keys of the first row is written first and the fields
+/// of each row are written in the order of the header. Missing fields are left
+/// empty.
+///
+/// Fields can be strings, numbers, booleans, datetimes or `{none}`, which is
+/// written as an empty field.
+///
+/// ## Example { #example }
+/// ```typ
+/// #write_csv("results.csv", (
+///   (condition: "A", result: 1.2),
+///   (condition: "B", result: 3.4),
+/// ))
+/// ```
+///
+/// Display: Write CSV
+/// Category: data-loading
+#[func]
+pub fn write_csv(
+    /// Path to the CSV file, relative to the destination directory.
+    path: Spanned<EcoString>,
+    /// The rows to write.
+    data: Spanned<Array>,
+    /// The delimiter that separates columns in the CSV file.
+    /// Must be a single ASCII character.
+    #[named]
+    #[default]
+    delimiter: Delimiter,
+    /// What to do if the file already exists.
+    #[named]
+    #[default]
+    mode: WriteMode,
+    /// The virtual machine.
+    vm: &mut Vm,
+) -> SourceResult<()> {
+    let Spanned { v: data, span } = data;
+    let buf = convert_back_csv(data, delimiter).at(span)?;
+    write_data(vm, path, mode, buf)
+}
+
+/// Serialize an array of rows into CSV.
+fn convert_back_csv(rows: Array, delimiter: Delimiter) -> StrResult<Vec<u8>> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter.0 as u8)
+        .flexible(true)
+        .from_writer(vec![]);
+
+    let mut header: Option<Vec<Str>> = None;
+    for row in rows {
+        let fields = match row {
+            Value::Array(fields) => {
+                fields.into_iter().map(csv_field).collect::<StrResult<Vec<_>>>()?
+            }
+            Value::Dict(mut fields) => {
+                if header.is_none() {
+                    let keys: Vec<Str> =
+                        fields.iter().map(|(key, _)| key.clone()).collect();
+                    writer
+                        .write_record(keys.iter().map(|key| key.as_str()))
+                        .map_err(|err| eco_format!("failed to write csv: {err}"))?;
+                    header = Some(keys);
+                }
+                header
+                    .iter()
+                    .flatten()
+                    .map(|key| csv_field(fields.remove(key).unwrap_or_default()))
+                    .collect::<StrResult<Vec<_>>>()?
+            }
+            v => bail!("expected array or dictionary as row, found {}", v.type_name()),
+        };
+        writer
+            .write_record(fields.iter().map(|field| field.as_str()))
+            .map_err(|err| eco_format!("failed to write csv: {err}"))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|err| eco_format!("failed to write csv: {}", err.error()))
+}
+
+/// Convert a Typst value to a single CSV field.
+fn csv_field(value: Value) -> StrResult<EcoString> {
+    Ok(match value {
+        Value::None => EcoString::new(),
+        Value::Bool(v) => eco_format!("{v}"),
+        Value::Int(v) => eco_format!("{v}"),
+        Value::Float(v) => eco_format!("{v}"),
+        Value::Str(v) => v.into(),
+        Value::Dyn(v) if v.is::<Datetime>() => {
+            let datetime: &Datetime = v.downcast().unwrap();
+            datetime.display(None)?
+        }
+        v => bail!("cannot write {} as a field", v.type_name()),
+    })
+}
+
+/// Queue serialized data to be written to a file in the destination
+/// directory.
+///
+/// The data must be the file's only content. Writing structured data to the
+/// same file again replaces it, while writing other data to it fails.
+fn write_data(
+    vm: &mut Vm,
+    path: Spanned<EcoString>,
+    mode: WriteMode,
+    data: Vec<u8>,
+) -> SourceResult<()> {
+    let Spanned { v: path, span } = path;
+    let path = vm.locate(&path, AccessMode::W).at(span)?;
+    vm.world().write(&path, None, mode, data, span).at(span)
+}
+
+/// A handle to a file, created with [`open`]($func/open).
 #[derive(Clone, PartialEq, Hash)]
 pub struct File(Str);
 
 impl File {
-    pub fn new(key: Str) -> Self {
-        Self(key)
+    /// Create a handle to the file at the given path.
+    pub fn new(path: Str) -> Self {
+        Self(path)
+    }
+
+    /// The path the handle was opened with.
+    pub fn path(&self) -> &Str {
+        &self.0
+    }
+
+    /// Call a method on a file handle.
+    pub fn call_method(
+        self,
+        vm: &mut Vm,
+        method: &str,
+        mut args: Args,
+        span: Span,
+    ) -> SourceResult<Value> {
+        let value = match method {
+            "read" => {
+                let encoding = args.named("encoding")?.unwrap_or(Some(Encoding::Utf8));
+                self.read(vm, encoding, span)?.into_value()
+            }
+            "lines" => self.lines(vm, span)?.into_value(),
+            "json" => self.json(vm, span)?,
+            "write" => {
+                let data = args.expect("data")?;
+                let at = args.named("at")?;
+                let mode = args.named("mode")?.unwrap_or_default();
+                self.write(vm, data, at, mode, span)?;
+                Value::None
+            }
+            // Handles don't hold on to any resources, so there is nothing to
+            // release. Closing is supported for symmetry with `open`.
+            "close" => Value::None,
+            _ => bail!(span, "type file has no method `{}`", method),
+        };
+        args.finish()?;
+        Ok(value)
+    }
+
+    /// Read the file as a string or, without an encoding, as bytes.
+    pub fn read(
+        &self,
+        vm: &mut Vm,
+        encoding: Option<Encoding>,
+        span: Span,
+    ) -> SourceResult<Readable> {
+        decode(self.load(vm, span)?, encoding).at(span)
+    }
+
+    /// Read the file as UTF-8 and split it into its lines.
+    pub fn lines(&self, vm: &mut Vm, span: Span) -> SourceResult<Array> {
+        let data = self.load(vm, span)?;
+        let text = std::str::from_utf8(&data)
+            .map_err(|_| "file is not valid utf-8")
+            .at(span)?;
+        Ok(text.lines().map(|line| line.into_value()).collect())
+    }
+
+    /// Read the file and parse it as JSON.
+    pub fn json(&self, vm: &mut Vm, span: Span) -> SourceResult<Value> {
+        let data = self.load(vm, span)?;
+        let value: serde_json::Value =
+            serde_json::from_slice(&data).map_err(format_json_error).at(span)?;
+        warn_lossy_json(vm, span, &value);
+        Ok(convert_json(value))
+    }
+
+    /// Queue data to be written to the file in the destination directory.
+    pub fn write(
+        &self,
+        vm: &mut Vm,
+        data: Readable,
+        at: Option<i64>,
+        mode: WriteMode,
+        span: Span,
+    ) -> SourceResult<()> {
+        let path = vm.locate(&self.0, AccessMode::W).at(span)?;
+        let order = Some(at.unwrap_or_default());
+        vm.world()
+            .write(&path, order, mode, data.as_slice().to_vec(), span)
+            .at(span)
+    }
+
+    /// Load the file's contents from the root directory.
+    fn load(&self, vm: &mut Vm, span: Span) -> SourceResult<Buffer> {
+        let path = vm.locate(&self.0, AccessMode::R).at(span)?;
+        vm.world().read(&path).at(span)
     }
 }
 
@@ -65,11 +493,32 @@ cast! {
     type File: "file",
 }
 
-/// Display: File
-/// Category: data
+/// Open a handle to a file.
+///
+/// The handle bundles a path so that it can be read from and written to
+/// repeatedly. Reading with its `read`, `lines` and `json` methods resolves the
+/// path relative to the root directory, just like [`read`]($func/read).
+/// Writing with its `write` method resolves the path relative to the
+/// destination directory, just like [`write`]($func/write). Opening a file
+/// does not access it, so it may not exist yet.
+///
+/// ## Example { #example }
+/// ```typ
+/// #let log = open("log.txt")
+/// #log.write("Compiled\n", mode: "append")
+///
+/// #let data = open("data.json")
+/// #for (key, value) in data.json() [
+///   - #key: #value
+/// ]
+/// ```
+///
+/// Display: Open
+/// Category: data-loading
 #[func]
 pub fn open(
-    file: Str,
+    /// Path to the file.
+    path: Str,
 ) -> File {
-    File::new(file)
+    File::new(path)
 }
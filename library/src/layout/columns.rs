@@ -1,13 +1,17 @@
+use super::{
+    realize_block, BlockElem, FlowElem, ParbreakElem, Scratch, Sizing, Spacing,
+    TrackSizings,
+};
 use crate::prelude::*;
-use crate::text::TextElem;
+use crate::text::{SpaceElem, TextElem};
 
 /// Separate a region into multiple equally sized columns.
 ///
 /// The `column` function allows to separate the interior of any container into
-/// multiple columns. It will not equalize the height of the columns, instead,
-/// the columns will take up the height of their container or the remaining
-/// height on the page. The columns function can break across pages if
-/// necessary.
+/// multiple columns. By default, it will not equalize the height of the
+/// columns, instead, the columns will take up the height of their container or
+/// the remaining height on the page. The columns function can break across
+/// pages if necessary.
 ///
 /// ## Example { #example }
 /// ```example
@@ -34,15 +38,115 @@ use crate::text::TextElem;
 /// Category: layout
 #[element(Layout)]
 pub struct ColumnsElem {
-    /// The number of columns.
+    /// The number of columns or their widths.
+    ///
+    /// Given a number, the columns are equally wide. Given an array of sizes,
+    /// there is one column per size: Lengths and relative lengths are fixed,
+    /// while fractions share the remaining space proportionally. An `{auto}`
+    /// column takes one fraction.
+    ///
+    /// Content flows from one column into the next, even if they differ in
+    /// width. A block that would continue in a column of a different width
+    /// moves to that column as a whole, unless it is too tall for all the
+    /// columns of its own width. Then, it is broken at that width.
+    ///
+    /// ```example
+    /// #columns((1fr, 2fr))[
+    ///   A narrow column with notes.
+    ///   #colbreak()
+    ///   And a wide column with the
+    ///   main text, which is twice as
+    ///   wide as the notes.
+    /// ]
+    /// ```
     #[positional]
+    #[parse(
+        let sizing = args.find::<ColumnSizing>()?;
+        sizing.as_ref().map(ColumnSizing::count)
+    )]
     #[default(NonZeroUsize::new(2).unwrap())]
     pub count: NonZeroUsize,
 
+    /// The widths of the columns if they were given as an array.
+    #[internal]
+    #[parse(sizing.map(ColumnSizing::widths))]
+    pub widths: Option<TrackSizings>,
+
     /// The size of the gutter space between each column.
+    ///
+    /// Given an array, the gutters are sized one after another. If there are
+    /// more gutters than sizes, the last size is repeated. Fractions share the
+    /// remaining space with the columns.
+    #[default(TrackSizings(vec![Sizing::Rel(Ratio::new(0.04).into())]))]
+    pub gutter: TrackSizings,
+
+    /// Whether to equalize the heights of the columns in the last region.
+    ///
+    /// Normally, the last region's columns are filled one after another, so
+    /// that the last column may end up much shorter than the others. When
+    /// balancing, the content of the last region is distributed such that its
+    /// columns are about equally tall. Columns of different widths are not
+    /// balanced.
+    ///
+    /// ```example
+    /// #set page(height: auto)
+    /// #columns(3, balance: true)[
+    ///   Balanced columns end at about
+    ///   the same height, even if there
+    ///   is not enough content to fill
+    ///   the page.
+    /// ]
+    /// ```
+    #[default(false)]
+    pub balance: bool,
+
+    /// How to stroke the rules between the columns.
+    ///
+    /// A rule is centered in each gutter and is as tall as the taller of the
+    /// two columns next to it. See the [line's documentation]($func/line.stroke)
+    /// for more details on strokes.
+    ///
+    /// ```example
+    /// #set page(height: auto)
+    /// #columns(2, rule: 0.5pt + gray)[
+    ///   Newspapers separate their
+    ///   columns with thin rules.
+    ///   #colbreak()
+    ///   The rule only reaches as far
+    ///   as the text.
+    /// ]
+    /// ```
+    #[resolve]
+    #[fold]
+    #[default(None)]
+    pub rule: Option<PartialStroke>,
+
+    /// The minimum space between a rule and the columns next to it.
+    ///
+    /// Fixed gutters that are too narrow to fit the rule with this much space
+    /// on either side are widened.
     #[resolve]
-    #[default(Ratio::new(0.04).into())]
-    pub gutter: Rel<Length>,
+    #[default(Length::zero())]
+    pub rule_gutter: Length,
+
+    /// The direction in which the columns are filled.
+    ///
+    /// Column widths, gutters and rules are given in this order. By default,
+    /// columns follow the [text direction]($func/text.dir), so that they match
+    /// the reading order. Setting a direction here does not change the
+    /// direction of the text within the columns.
+    ///
+    /// ```example
+    /// #columns((1fr, 2fr), dir: rtl)[
+    ///   The narrow column is on
+    ///   the right.
+    ///   #colbreak()
+    ///   The wide column follows it
+    ///   to the left.
+    /// ]
+    /// ```
+    #[default(Smart::Auto)]
+    pub dir: Smart<Dir>,
 
     /// The content that should be layouted into the columns.
     #[required]
@@ -65,36 +169,64 @@ impl Layout for ColumnsElem {
             return body.layout(vt, styles, regions);
         }
 
-        // Determine the width of the gutter and each column.
-        let columns = self.count(styles).get();
-        let gutter = self.gutter(styles).relative_to(regions.base().x);
-        let width = (regions.size.x - gutter * (columns - 1) as f64) / columns as f64;
+        // Blocks spanning all columns interrupt the column flow.
+        if let Some(segments) = self.segments() {
+            return self.layout_segments(vt, styles, regions, segments);
+        }
 
-        let backlog: Vec<_> = std::iter::once(&regions.size.y)
-            .chain(regions.backlog)
-            .flat_map(|&height| std::iter::repeat(height).take(columns))
-            .skip(1)
-            .collect();
+        // Determine the width of each column and the gutters between them.
+        let (widths, gutters) = self.resolve_tracks(styles, regions);
+        if widths.is_empty() {
+            return body.layout(vt, styles, regions);
+        }
 
-        // Create the pod regions.
-        let pod = Regions {
-            size: Size::new(width, regions.size.y),
-            full: regions.full,
-            backlog: &backlog,
-            last: regions.last,
-            expand: Axes::new(true, regions.expand.y),
-            root: regions.root,
+        // Layout the children, balancing the final region's columns if
+        // requested. Columns of different widths can't share a region
+        // sequence, so they are filled run by run instead.
+        let columns = widths.len();
+        let uneven = widths.windows(2).any(|pair| pair[0] != pair[1]);
+        let mut frames = if uneven {
+            self.layout_uneven(vt, styles, regions, &widths)?
+        } else {
+            self.layout_columns(vt, styles, regions, widths[0], columns, None, false)?
         };
 
-        // Layout the children.
-        let mut frames = body.layout(vt, styles, pod)?.into_iter();
+        if self.balance(styles) && !uneven && !frames.is_empty() {
+            let index = (frames.len() - 1) / columns;
+            if let Some(height) =
+                self.balanced_height(vt, styles, regions, widths[0], columns, index)?
+            {
+                frames = self.layout_columns(
+                    vt,
+                    styles,
+                    regions,
+                    widths[0],
+                    columns,
+                    Some((index, height)),
+                    false,
+                )?;
+            }
+        }
+
+        let mut frames = frames.into_iter().peekable();
         let mut finished = vec![];
 
-        let dir = TextElem::dir_in(styles);
-        let total_regions = (frames.len() as f32 / columns as f32).ceil() as usize;
+        let dir = match self.dir(styles) {
+            Smart::Auto => TextElem::dir_in(styles),
+            Smart::Custom(dir) if dir.axis() == Axis::X => dir,
+            Smart::Custom(_) => bail!(self.span(), "column direction must be horizontal"),
+        };
+        let rule = self.rule(styles).map(PartialStroke::unwrap_or_default);
+
+        // Stitch together the columns for each region. Columns beyond the
+        // available regions are stitched into regions as tall as the last one.
+        let mut sizes = regions.iter();
+        let mut region = regions.size;
+        while frames.peek().is_some() {
+            if let Some(size) = sizes.next() {
+                region = size;
+            }
 
-        // Stitch together the columns for each region.
-        for region in regions.iter().take(total_regions) {
             // The height should be the parent height if we should expand.
             // Otherwise its the maximum column height for the frame. In that
             // case, the frame is first created with zero height and then
@@ -102,24 +234,41 @@ impl Layout for ColumnsElem {
             let height = if regions.expand.y { region.y } else { Abs::zero() };
             let mut output = Frame::new(Size::new(regions.size.x, height));
             let mut cursor = Abs::zero();
+            let mut rules = vec![];
+            let mut prev = None;
 
-            for _ in 0..columns {
+            let gutters = gutters.iter().copied().chain(std::iter::once(Abs::zero()));
+            for (&width, gutter) in widths.iter().zip(gutters) {
                 let Some(frame) = frames.next() else { break };
                 if !regions.expand.y {
                     output.size_mut().y.set_max(frame.height());
                 }
 
-                let width = frame.width();
+                // A rule sits between this column and the previous one.
+                if let Some((x, height)) = prev {
+                    rules.push((x, frame.height().max(height)));
+                }
+
                 let x = if dir == Dir::LTR {
                     cursor
                 } else {
                     regions.size.x - cursor - width
                 };
 
+                prev = Some((cursor + width + gutter / 2.0, frame.height()));
                 output.push_frame(Point::with_x(x), frame);
                 cursor += width + gutter;
             }
 
+            if let Some(stroke) = &rule {
+                for (x, height) in rules {
+                    let x = if dir == Dir::LTR { x } else { regions.size.x - x };
+                    let line =
+                        Geometry::Line(Point::with_y(height)).stroked(stroke.clone());
+                    output.prepend(Point::with_x(x), FrameItem::Shape(line, self.span()));
+                }
+            }
+
             finished.push(output);
         }
 
@@ -127,6 +276,617 @@ impl Layout for ColumnsElem {
     }
 }
 
+/// How many times the balanced height is refined at most.
+const BALANCE_ITERATIONS: usize = 12;
+
+/// How close the balanced height has to come to the optimal one.
+const BALANCE_TOLERANCE: Abs = Abs::raw(0.5);
+
+impl ColumnsElem {
+    /// Resolve the widths of the columns and of the gutters between them.
+    fn resolve_tracks(
+        &self,
+        styles: StyleChain,
+        regions: Regions,
+    ) -> (Vec<Abs>, Vec<Abs>) {
+        let sizings = match self.widths(styles) {
+            Some(widths) => widths.0,
+            None => vec![Sizing::Auto; self.count(styles).get()],
+        };
+        let gutters = self.gutter(styles).0;
+
+        // Interleave the columns with the gutters between them. An auto
+        // column takes one fraction and an auto gutter is empty.
+        let mut tracks = vec![];
+        for (i, &sizing) in sizings.iter().enumerate() {
+            if i > 0 {
+                let gutter = gutters.get(i - 1).or(gutters.last());
+                tracks.push(match gutter {
+                    Some(Sizing::Auto) | None => Sizing::Rel(Rel::zero()),
+                    Some(&gutter) => gutter,
+                });
+            }
+            tracks.push(match sizing {
+                Sizing::Auto => Sizing::Fr(Fr::one()),
+                sizing => sizing,
+            });
+        }
+
+        // Fixed gutters leave room for the rule.
+        let min_gutter = match self.rule(styles) {
+            Some(stroke) => {
+                stroke.unwrap_or_default().thickness + 2.0 * self.rule_gutter(styles)
+            }
+            None => Abs::zero(),
+        };
+
+        // Fixed tracks are sized first, fractional ones share the rest.
+        let base = regions.base().x;
+        let resolve = |i: usize, rel: Rel<Length>| {
+            let size = rel.resolve(styles).relative_to(base);
+            if i % 2 == 1 {
+                size.max(min_gutter)
+            } else {
+                size
+            }
+        };
+        let fixed: Abs = tracks
+            .iter()
+            .enumerate()
+            .map(|(i, &track)| match track {
+                Sizing::Rel(rel) => resolve(i, rel),
+                _ => Abs::zero(),
+            })
+            .sum();
+        let fr: Fr = tracks
+            .iter()
+            .filter_map(|&track| match track {
+                Sizing::Fr(fr) => Some(fr),
+                _ => None,
+            })
+            .sum();
+        let remaining = (regions.size.x - fixed).max(Abs::zero());
+
+        let mut widths = vec![];
+        let mut gutters = vec![];
+        for (i, &track) in tracks.iter().enumerate() {
+            let size = match track {
+                Sizing::Auto => Abs::zero(),
+                Sizing::Rel(rel) => resolve(i, rel),
+                Sizing::Fr(v) => v.share(fr, remaining),
+            };
+            if i % 2 == 0 {
+                widths.push(size);
+            } else {
+                gutters.push(size);
+            }
+        }
+
+        (widths, gutters)
+    }
+
+    /// Layout the body into a number of columns of the given width.
+    ///
+    /// If `balanced` is given as a region index and a height, the columns of
+    /// that region and of any further regions have that height. When
+    /// measuring, the layout has no side effects and the columns don't expand.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_columns(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        regions: Regions,
+        width: Abs,
+        columns: usize,
+        balanced: Option<(usize, Abs)>,
+        measure: bool,
+    ) -> SourceResult<Vec<Frame>> {
+        let heights: Vec<Abs> = match balanced {
+            Some((index, height)) => regions
+                .iter()
+                .take(index)
+                .map(|size| size.y)
+                .chain(std::iter::once(height))
+                .collect(),
+            None => std::iter::once(&regions.size.y)
+                .chain(regions.backlog)
+                .copied()
+                .collect(),
+        };
+
+        let backlog: Vec<_> = heights
+            .iter()
+            .flat_map(|&height| std::iter::repeat(height).take(columns))
+            .skip(1)
+            .collect();
+
+        // Create the pod regions.
+        let pod = Regions {
+            size: Size::new(width, heights[0]),
+            full: regions.full,
+            backlog: &backlog,
+            last: if balanced.is_some() { None } else { regions.last },
+            expand: Axes::new(true, regions.expand.y && !measure),
+            root: regions.root,
+        };
+
+        let body = self.body();
+        let fragment = if measure {
+            body.measure(vt, styles, pod)?
+        } else {
+            body.layout(vt, styles, pod)?
+        };
+
+        Ok(fragment.into_frames())
+    }
+
+    /// Layout the body into columns of different widths.
+    ///
+    /// The body's blocks flow through the columns in runs of consecutive
+    /// columns with the same width. The blocks that fit into a run are laid
+    /// out into it at once, so that they break across its columns as usual.
+    fn layout_uneven(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        regions: Regions,
+        widths: &[Abs],
+    ) -> SourceResult<Vec<Frame>> {
+        let columns = widths.len();
+        let height =
+            |column: usize| regions.iter().nth(column / columns).map(|size| size.y);
+        let flow = |children: &[Content]| FlowElem::new(children.to_vec()).pack();
+
+        let body = self.body();
+        let scratch = Scratch::default();
+        let (realized, styles) = realize_block(vt, &scratch, &body, styles)?;
+        let mut rest = match realized.to::<FlowElem>() {
+            Some(flow) => flow.children(),
+            None => vec![realized.clone()],
+        };
+
+        let mut frames = vec![];
+        while !rest.is_empty() {
+            let start = frames.len();
+            let width = widths[start % columns];
+
+            // A column break at the start of a column leaves it empty.
+            if is_colbreak(&rest[0]) && height(start + 1).is_some() {
+                frames.push(Frame::new(Size::new(width, Abs::zero())));
+                rest.remove(0);
+                continue;
+            }
+
+            // Find the run of columns with the current width and the height
+            // of the column after it, if there is any.
+            let mut heights: Vec<Abs> = (start..)
+                .take_while(|column| widths[column % columns] == width)
+                .map_while(height)
+                .collect();
+            let run = heights.len();
+            let next = height(start + run);
+            heights.extend(next);
+
+            // Blocks fit into the run if they don't spill into the column
+            // after it. Without a column after it, the run's last column takes
+            // all the blocks.
+            let pod = column_pod(regions, width, &heights, None);
+            let fits = |vt: &mut Vt, count: usize| -> SourceResult<bool> {
+                let fragment = flow(&rest[..count]).measure(vt, styles, pod)?;
+                Ok(fragment.len() <= run)
+            };
+
+            if next.is_none() || fits(vt, rest.len())? {
+                frames.extend(flow(&rest).layout(vt, styles, pod)?);
+                break;
+            }
+
+            // Find how many blocks fit into the run.
+            let (mut lo, mut hi) = (0, rest.len());
+            while hi - lo > 1 {
+                let mid = (lo + hi) / 2;
+                if fits(vt, mid)? {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+
+            if lo > 0 {
+                // The remaining blocks move to the column after the run.
+                let fragment = flow(&rest[..lo]).layout(vt, styles, pod)?;
+                let filled = fragment.len();
+                frames.extend(fragment);
+                frames.extend(
+                    (filled..run).map(|_| Frame::new(Size::new(width, Abs::zero()))),
+                );
+                rest.drain(..lo);
+            } else {
+                // The first block is too tall for the run, so it is broken
+                // across all remaining columns at this width. If it fills them
+                // up, the blocks after it overflow along with it.
+                let end = ((1 + regions.backlog.len()) * columns).max(start + 1);
+                let heights: Vec<Abs> = (start..end).map_while(height).collect();
+                let pod = column_pod(regions, width, &heights, regions.last);
+                let fragment = flow(&rest[..1]).layout(vt, styles, pod)?;
+                if height(start + fragment.len()).is_none() {
+                    frames.extend(flow(&rest).layout(vt, styles, pod)?);
+                    break;
+                }
+
+                frames.extend(fragment);
+                rest.remove(0);
+            }
+
+            // A column break right after the moved blocks was already taken.
+            if rest.first().map_or(false, is_colbreak) {
+                rest.remove(0);
+            }
+        }
+
+        Ok(frames)
+    }
+
+    /// Determine the smallest column height with which the content of the
+    /// region with the given index still fits into that region.
+    ///
+    /// Returns `None` if no height shorter than the region fits the content.
+    fn balanced_height(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        regions: Regions,
+        width: Abs,
+        columns: usize,
+        index: usize,
+    ) -> SourceResult<Option<Abs>> {
+        let Some(region) = regions.iter().nth(index) else { return Ok(None) };
+
+        // Measure how tall the region's content is when stacked.
+        let natural: Abs = self
+            .layout_columns(vt, styles, regions, width, columns, None, true)?
+            .iter()
+            .skip(index * columns)
+            .map(Frame::height)
+            .sum();
+
+        // The content will not fit into columns shorter than an equal share
+        // of its natural height.
+        let mut lo = natural / columns as f64;
+        let mut hi = natural.min(region.y);
+        let mut best = None;
+
+        for _ in 0..BALANCE_ITERATIONS {
+            if hi - lo < BALANCE_TOLERANCE {
+                break;
+            }
+
+            let mid = (lo + hi) / 2.0;
+            let frames = self.layout_columns(
+                vt,
+                styles,
+                regions,
+                width,
+                columns,
+                Some((index, mid)),
+                true,
+            )?;
+
+            if frames.len() <= (index + 1) * columns {
+                best = Some(mid);
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Split the body around the blocks that span all columns.
+    ///
+    /// Returns `None` if the body contains no such block.
+    fn segments(&self) -> Option<Vec<Segment>> {
+        let mut pieces = vec![];
+        flatten(&self.body(), &mut pieces);
+        if !pieces.iter().any(is_span) {
+            return None;
+        }
+
+        let mut segments = vec![];
+        let mut run = vec![];
+        for piece in pieces {
+            if is_span(&piece) {
+                if !run.iter().all(is_trivial) {
+                    segments.push(Segment::Columns(Content::sequence(run)));
+                }
+                run = vec![];
+                segments.push(Segment::Span(piece));
+            } else {
+                run.push(piece);
+            }
+        }
+
+        if !run.iter().all(is_trivial) {
+            segments.push(Segment::Columns(Content::sequence(run)));
+        }
+
+        Some(segments)
+    }
+
+    /// Layout the segments one below the other.
+    ///
+    /// Runs of ordinary content are set in columns, which are balanced if a
+    /// spanning block follows them. Only the last segment expands to fill the
+    /// region.
+    fn layout_segments(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        regions: Regions,
+        segments: Vec<Segment>,
+    ) -> SourceResult<Fragment> {
+        let resolve = |spacing: Spacing| match spacing {
+            Spacing::Rel(rel) => rel.resolve(styles).relative_to(regions.base().y),
+            Spacing::Fr(_) => Abs::zero(),
+        };
+
+        let mut pod = regions;
+        let mut items: Vec<(Abs, Frame)> = vec![];
+        let mut cursor = Abs::zero();
+        let mut finished = vec![];
+        let count = segments.len();
+
+        for (i, segment) in segments.into_iter().enumerate() {
+            let last = i + 1 == count;
+            let mut sub = pod;
+            sub.expand.y = regions.expand.y && last;
+
+            let (fragment, below) = match segment {
+                Segment::Columns(body) => {
+                    let mut elem = self.clone().with_body(body);
+                    if !last {
+                        elem.push_balance(true);
+                    }
+                    (elem.layout(vt, styles, sub)?, Abs::zero())
+                }
+                Segment::Span(body) => {
+                    let above = if items.is_empty() {
+                        Abs::zero()
+                    } else {
+                        resolve(BlockElem::above_in(styles).amount())
+                    };
+                    let below = if last {
+                        Abs::zero()
+                    } else {
+                        resolve(BlockElem::below_in(styles).amount())
+                    };
+
+                    sub.size.y = (sub.size.y - above).max(Abs::zero());
+                    let mut frames = body.layout(vt, styles, sub)?.into_frames();
+
+                    // A block that doesn't fit below the previous segments
+                    // moves to the next region. If it is breakable, it
+                    // already left its first frame empty.
+                    let moves = !items.is_empty()
+                        && !pod.in_last()
+                        && match frames.as_slice() {
+                            [frame] => !sub.size.y.fits(frame.height()),
+                            [first, ..] => first.is_empty(),
+                            [] => false,
+                        };
+
+                    if moves {
+                        if frames.len() > 1 {
+                            frames.remove(0);
+                        }
+                        finished.push(std::mem::take(&mut items));
+                        pod.next();
+                        cursor = Abs::zero();
+                    } else {
+                        cursor += above;
+                        pod.size.y = (pod.size.y - above).max(Abs::zero());
+                    }
+
+                    (Fragment::frames(frames), below)
+                }
+            };
+
+            for (k, frame) in fragment.into_iter().enumerate() {
+                if k > 0 {
+                    finished.push(std::mem::take(&mut items));
+                    pod.next();
+                    cursor = Abs::zero();
+                }
+                let height = frame.height();
+                items.push((cursor, frame));
+                cursor += height;
+                pod.size.y = (pod.size.y - height).max(Abs::zero());
+            }
+
+            cursor += below;
+            pod.size.y = (pod.size.y - below).max(Abs::zero());
+        }
+
+        finished.push(items);
+
+        // Stack the segments' frames in each region.
+        let mut sizes = regions.iter();
+        let frames = finished
+            .into_iter()
+            .map(|items| {
+                let natural = items
+                    .iter()
+                    .map(|(y, frame)| *y + frame.height())
+                    .max()
+                    .unwrap_or_default();
+                let height = match sizes.next() {
+                    Some(region) if regions.expand.y => region.y,
+                    _ => natural,
+                };
+                let mut output = Frame::new(Size::new(regions.size.x, height));
+                for (y, frame) in items {
+                    output.push_frame(Point::with_y(y), frame);
+                }
+                output
+            })
+            .collect();
+
+        Ok(Fragment::frames(frames))
+    }
+}
+
+/// The number of columns or their widths, as given to a columns element.
+enum ColumnSizing {
+    /// A number of equally wide columns.
+    Count(NonZeroUsize),
+    /// The widths of the individual columns.
+    Widths(TrackSizings),
+}
+
+impl ColumnSizing {
+    /// The number of columns.
+    fn count(&self) -> NonZeroUsize {
+        match self {
+            Self::Count(count) => *count,
+            Self::Widths(widths) => NonZeroUsize::new(widths.0.len()).unwrap(),
+        }
+    }
+
+    /// The widths of the columns if they were given individually.
+    fn widths(self) -> Option<TrackSizings> {
+        match self {
+            Self::Count(_) => None,
+            Self::Widths(widths) => Some(widths),
+        }
+    }
+}
+
+cast! {
+    ColumnSizing,
+    count: NonZeroUsize => Self::Count(count),
+    values: Array => match values.len() {
+        0 => bail!("must have at least one column"),
+        _ => Self::Widths(TrackSizings(
+            values.into_iter().map(Value::cast).collect::<StrResult<_>>()?,
+        )),
+    },
+}
+
+/// Create regions for consecutive columns of the same width.
+fn column_pod<'a>(
+    regions: Regions,
+    width: Abs,
+    heights: &'a [Abs],
+    last: Option<Abs>,
+) -> Regions<'a> {
+    Regions {
+        size: Size::new(width, heights[0]),
+        full: regions.full,
+        backlog: &heights[1..],
+        last,
+        expand: Axes::new(true, regions.expand.y),
+        root: regions.root,
+    }
+}
+
+/// A part of a columns element's body.
+enum Segment {
+    /// Content that is set in columns.
+    Columns(Content),
+    /// A block that spans all columns.
+    Span(Content),
+}
+
+/// Collect the top-level pieces of content, applying the styles of styled
+/// sequences to each of their pieces.
+fn flatten(content: &Content, pieces: &mut Vec<Content>) {
+    if let Some(children) = content.to_sequence() {
+        for child in children {
+            flatten(child, pieces);
+        }
+    } else if let Some((child, map)) = content.to_styled() {
+        let start = pieces.len();
+        flatten(child, pieces);
+        for piece in &mut pieces[start..] {
+            *piece = piece.clone().styled_with_map(map.clone());
+        }
+    } else {
+        pieces.push(content.clone());
+    }
+}
+
+/// Whether a piece of content is a block that spans all columns.
+fn is_span(content: &Content) -> bool {
+    match content.to_styled() {
+        Some((child, _)) => is_span(child),
+        None => content.is::<ColspanElem>(),
+    }
+}
+
+/// Whether a piece of content is a column break.
+fn is_colbreak(content: &Content) -> bool {
+    match content.to_styled() {
+        Some((child, _)) => is_colbreak(child),
+        None => content.is::<ColbreakElem>(),
+    }
+}
+
+/// Whether a piece of content produces nothing in a flow on its own.
+fn is_trivial(content: &Content) -> bool {
+    match content.to_styled() {
+        Some((child, _)) => is_trivial(child),
+        None => content.is::<SpaceElem>() || content.is::<ParbreakElem>(),
+    }
+}
+
+/// A block that spans all columns.
+///
+/// Within [columns]($func/columns), the content before the block is set in
+/// balanced columns, the block is placed below them across the full width and
+/// the columns resume below the block. Outside of columns, it behaves like a
+/// plain [block]($func/block).
+///
+/// Only blocks directly in the body of the columns span them. A block nested
+/// deeper, for example in a box, stays in its column.
+///
+/// ## Example { #example }
+/// ```example
+/// #set page(height: auto)
+/// #columns(2)[
+///   The introduction of the paper
+///   is set in two columns.
+///
+///   #colspan(align(center)[
+///     = Results
+///   ])
+///
+///   The results are set in two
+///   columns again.
+/// ]
+/// ```
+///
+/// Display: Column Span
+/// Category: layout
+#[element(Layout)]
+pub struct ColspanElem {
+    /// The content that spans all columns.
+    #[required]
+    pub body: Content,
+}
+
+impl Layout for ColspanElem {
+    #[tracing::instrument(name = "ColspanElem::layout", skip_all)]
+    fn layout(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        self.body().layout(vt, styles, regions)
+    }
+}
+
 /// A forced column break.
 ///
 /// The function will behave like a [page break]($func/pagebreak) when used in a
@@ -324,12 +324,21 @@ pub struct BlockElem {
     #[positional]
     pub body: Option<Content>,
 
-    /// Whether this block must stick to the following one.
+    /// Whether this block must stay in the same region as the following one.
     ///
-    /// Use this to prevent page breaks between e.g. a heading and its body.
-    #[internal]
+    /// Use this to prevent page or column breaks between e.g. a heading and
+    /// its body. Headings keep with the next block by default.
+    ///
+    /// ```example
+    /// #set page(height: 80pt)
+    /// #v(40pt)
+    /// #block(keep-with-next: true)[
+    ///   *Summary*
+    /// ]
+    /// #lorem(12)
+    /// ```
     #[default(false)]
-    pub sticky: bool,
+    pub keep_with_next: bool,
 }
 
 impl Layout for BlockElem {
@@ -99,6 +99,8 @@ struct FlowLayouter<'a> {
     last_was_par: bool,
     /// Spacing and layouted blocks for the current region.
     items: Vec<FlowItem>,
+    /// Floating content that waits for a region with enough space.
+    pending_floats: Vec<FlowItem>,
     /// Whether we have any footnotes in the current region.
     has_footnotes: bool,
     /// Footnote configuration.
@@ -127,6 +129,9 @@ enum FlowItem {
     Frame { frame: Frame, aligns: Axes<Align>, sticky: bool, movable: bool },
     /// An absolutely placed frame.
     Placed(Frame),
+    /// A floating frame, whether it goes to the top or bottom of the region
+    /// and the space between it and the flow.
+    Float { frame: Frame, y_align: Align, clearance: Abs },
     /// A footnote frame (can also be the separator).
     Footnote(Frame),
 }
@@ -138,6 +143,7 @@ impl FlowItem {
             Self::Absolute(v, _) => *v,
             Self::Fractional(_) | Self::Placed(_) => Abs::zero(),
             Self::Frame { frame, .. } | Self::Footnote(frame) => frame.height(),
+            Self::Float { frame, clearance, .. } => frame.height() + *clearance,
         }
     }
 }
@@ -159,6 +165,7 @@ impl<'a> FlowLayouter<'a> {
             initial: regions.size,
             last_was_par: false,
             items: vec![],
+            pending_floats: vec![],
             has_footnotes: false,
             footnote_config: FootnoteConfig {
                 separator: FootnoteEntry::separator_in(styles),
@@ -204,22 +211,9 @@ impl<'a> FlowLayouter<'a> {
             .layout(vt, styles, consecutive, self.regions.base(), self.regions.expand.x)?
             .into_frames();
 
-        let mut sticky = self.items.len();
-        for (i, item) in self.items.iter().enumerate().rev() {
-            match *item {
-                FlowItem::Absolute(_, _) => {}
-                FlowItem::Frame { sticky: true, .. } => sticky = i,
-                _ => break,
-            }
-        }
-
         if let Some(first) = lines.first() {
             if !self.regions.size.y.fits(first.height()) && !self.regions.in_last() {
-                let carry: Vec<_> = self.items.drain(sticky..).collect();
-                self.finish_region()?;
-                for item in carry {
-                    self.layout_item(vt, item)?;
-                }
+                self.carry_sticky(vt)?;
             }
         }
 
@@ -247,9 +241,15 @@ impl<'a> FlowLayouter<'a> {
         styles: StyleChain,
     ) -> SourceResult<()> {
         let aligns = AlignElem::alignment_in(styles).resolve(styles);
-        let sticky = BlockElem::sticky_in(styles);
+        let sticky = BlockElem::keep_with_next_in(styles);
         let pod = Regions::one(self.regions.base(), Axes::splat(false));
         let frame = content.layout(vt, styles, pod)?.into_frame();
+        if !self.regions.size.y.fits(frame.height())
+            && !self.regions.in_last()
+            && self.has_sticky()
+        {
+            self.carry_sticky(vt)?;
+        }
         self.layout_item(vt, FlowItem::Frame { frame, aligns, sticky, movable: true })?;
         self.last_was_par = false;
         Ok(())
@@ -265,7 +265,9 @@ impl<'a> FlowLayouter<'a> {
         // Placed elements that are out of flow produce placed items which
         // aren't aligned later.
         if let Some(placed) = block.to::<PlaceElem>() {
-            if placed.out_of_flow(styles) {
+            if placed.float(styles) {
+                return self.layout_float(vt, placed, styles);
+            } else if placed.out_of_flow(styles) {
                 let frame = block.layout(vt, styles, self.regions)?.into_frame();
                 self.layout_item(vt, FlowItem::Placed(frame))?;
                 return Ok(());
@@ -292,9 +294,18 @@ impl<'a> FlowLayouter<'a> {
             self.regions.root = true;
         }
 
-        // Layout the block itself.
-        let sticky = BlockElem::sticky_in(styles);
-        let fragment = block.layout(vt, styles, self.regions)?;
+        // Layout the block itself. If it doesn't start in this region, the
+        // blocks that keep with it move along and it is layouted again below
+        // them.
+        let sticky = BlockElem::keep_with_next_in(styles);
+        let mut fragment = block.layout(vt, styles, self.regions)?;
+        if fragment.len() > 1
+            && fragment.iter().next().map_or(false, Frame::is_empty)
+            && self.has_sticky()
+        {
+            self.carry_sticky(vt)?;
+            fragment = block.layout(vt, styles, self.regions)?;
+        }
         let mut notes = Vec::new();
 
         for (i, frame) in fragment.into_iter().enumerate() {
@@ -325,6 +336,60 @@ impl<'a> FlowLayouter<'a> {
         Ok(())
     }
 
+    /// Layout floating content.
+    ///
+    /// If it doesn't fit into the current region, it waits for the next one
+    /// with enough space.
+    fn layout_float(
+        &mut self,
+        vt: &mut Vt,
+        placed: &PlaceElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let alignment = placed.alignment(styles);
+        let y_align = placed.float_alignment(styles)?;
+        let clearance = placed.clearance(styles);
+
+        let base = self.regions.base();
+        let expand = Axes::new(base.x.is_finite(), false);
+        let child = placed
+            .body()
+            .moved(Axes::new(placed.dx(styles), placed.dy(styles)))
+            .aligned(Axes::with_x(alignment.x));
+        let frame = child.layout(vt, styles, Regions::one(base, expand))?.into_frame();
+
+        // Without an explicit alignment, float to the closer edge.
+        let y_align = y_align.unwrap_or_else(|| {
+            let used = self.initial.y - self.regions.size.y;
+            if used <= self.regions.size.y {
+                Align::Top
+            } else {
+                Align::Bottom
+            }
+        });
+
+        let item = FlowItem::Float { frame, y_align, clearance };
+        if self.pending_floats.is_empty() && self.fits_float(&item) {
+            self.layout_item(vt, item)?;
+        } else {
+            self.pending_floats.push(item);
+        }
+
+        Ok(())
+    }
+
+    /// Whether floating content fits into the current region.
+    ///
+    /// It always fits into a region without other floats and frames, so that
+    /// it doesn't wait forever.
+    fn fits_float(&self, item: &FlowItem) -> bool {
+        self.regions.size.y.fits(item.height())
+            || self.regions.in_last()
+            || !self.items.iter().any(|other| {
+                matches!(other, FlowItem::Frame { .. } | FlowItem::Float { .. })
+            })
+    }
+
     /// Layout a finished frame.
     #[tracing::instrument(name = "FlowLayouter::layout_item", skip_all)]
     fn layout_item(&mut self, vt: &mut Vt, item: FlowItem) -> SourceResult<()> {
@@ -363,6 +428,7 @@ impl<'a> FlowLayouter<'a> {
                 }
             }
             FlowItem::Placed(_) => {}
+            FlowItem::Float { .. } => self.regions.size.y -= item.height(),
             FlowItem::Footnote(_) => {}
         }
 
@@ -370,6 +436,36 @@ impl<'a> FlowLayouter<'a> {
         Ok(())
     }
 
+    /// Whether the last frame in the current region keeps with the next one.
+    fn has_sticky(&self) -> bool {
+        self.items
+            .iter()
+            .rev()
+            .find(|item| !matches!(item, FlowItem::Absolute(_, _)))
+            .map_or(false, |item| matches!(item, FlowItem::Frame { sticky: true, .. }))
+    }
+
+    /// Finish the current region, moving the frames that keep with the next
+    /// one to the next region.
+    fn carry_sticky(&mut self, vt: &mut Vt) -> SourceResult<()> {
+        let mut sticky = self.items.len();
+        for (i, item) in self.items.iter().enumerate().rev() {
+            match *item {
+                FlowItem::Absolute(_, _) => {}
+                FlowItem::Frame { sticky: true, .. } => sticky = i,
+                _ => break,
+            }
+        }
+
+        let carry: Vec<_> = self.items.drain(sticky..).collect();
+        self.finish_region()?;
+        for item in carry {
+            self.layout_item(vt, item)?;
+        }
+
+        Ok(())
+    }
+
     /// Finish the frame for one region.
     fn finish_region(&mut self) -> SourceResult<()> {
         // Trim weak spacing.
@@ -386,9 +482,18 @@ impl<'a> FlowLayouter<'a> {
         let mut used = Size::zero();
         let mut footnote_height = Abs::zero();
         let mut first_footnote = true;
+        let mut float_top_height = Abs::zero();
+        let mut float_bottom_height = Abs::zero();
         for item in &self.items {
             match item {
                 FlowItem::Absolute(v, _) => used.y += *v,
+                FlowItem::Float { frame, y_align, .. } => {
+                    match y_align {
+                        Align::Bottom => float_bottom_height += item.height(),
+                        _ => float_top_height += item.height(),
+                    }
+                    used.x.set_max(frame.width());
+                }
                 FlowItem::Fractional(v) => fr += *v,
                 FlowItem::Frame { frame, .. } => {
                     let size = frame.size();
@@ -407,7 +512,7 @@ impl<'a> FlowLayouter<'a> {
                 }
             }
         }
-        used.y += footnote_height;
+        used.y += footnote_height + float_top_height + float_bottom_height;
 
         // Determine the size of the flow in this region depending on whether
         // the region expands. Also account for fractional spacing and
@@ -418,9 +523,11 @@ impl<'a> FlowLayouter<'a> {
         }
 
         let mut output = Frame::new(size);
-        let mut offset = Abs::zero();
+        let mut offset = float_top_height;
         let mut ruler = Align::Top;
         let mut footnote_offset = size.y - footnote_height;
+        let mut float_top_offset = Abs::zero();
+        let mut float_bottom_offset = footnote_offset - float_bottom_height;
 
         // Place all frames.
         for item in self.items.drain(..) {
@@ -448,6 +555,23 @@ impl<'a> FlowLayouter<'a> {
                 FlowItem::Placed(frame) => {
                     output.push_frame(Point::zero(), frame);
                 }
+                FlowItem::Float { frame, y_align, clearance } => {
+                    let height = frame.height();
+                    let y = match y_align {
+                        Align::Bottom => {
+                            float_bottom_offset += clearance;
+                            let y = float_bottom_offset;
+                            float_bottom_offset += height;
+                            y
+                        }
+                        _ => {
+                            let y = float_top_offset;
+                            float_top_offset += height + clearance;
+                            y
+                        }
+                    };
+                    output.push_frame(Point::with_y(y), frame);
+                }
             }
         }
 
@@ -456,11 +580,29 @@ impl<'a> FlowLayouter<'a> {
         self.regions.next();
         self.initial = self.regions.size;
         self.has_footnotes = false;
+
+        // Place the floats that waited for this region.
+        let mut pending = mem::take(&mut self.pending_floats).into_iter();
+        for item in pending.by_ref() {
+            if !self.fits_float(&item) {
+                self.pending_floats.push(item);
+                break;
+            }
+            self.regions.size.y -= item.height();
+            self.items.push(item);
+        }
+        self.pending_floats.extend(pending);
+
         Ok(())
     }
 
     /// Finish layouting and return the resulting fragment.
     fn finish(mut self) -> SourceResult<Fragment> {
+        // Make room for the floats that are still waiting.
+        while !self.pending_floats.is_empty() {
+            self.finish_region()?;
+        }
+
         if self.expand.y {
             while !self.regions.backlog.is_empty() {
                 self.finish_region()?;
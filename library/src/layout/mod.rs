@@ -81,6 +81,7 @@ pub(super) fn define(global: &mut Scope) {
     global.define("grid", GridElem::func());
     global.define("columns", ColumnsElem::func());
     global.define("colbreak", ColbreakElem::func());
+    global.define("colspan", ColspanElem::func());
     global.define("place", PlaceElem::func());
     global.define("align", AlignElem::func());
     global.define("pad", PadElem::func());
@@ -88,6 +89,7 @@ pub(super) fn define(global: &mut Scope) {
     global.define("move", MoveElem::func());
     global.define("scale", ScaleElem::func());
     global.define("rotate", RotateElem::func());
+    global.define("skew", SkewElem::func());
     global.define("hide", HideElem::func());
     global.define("measure", measure_func());
     global.define("ltr", Dir::LTR);
@@ -280,6 +280,38 @@ pub struct PageElem {
     /// ```
     pub foreground: Option<Content>,
 
+    /// How far the page's fill and background extend beyond its edges.
+    ///
+    /// After printing, the sheets are trimmed to the page's size. Designs that
+    /// reach up to the edge of the page need a bleed so that no unprinted
+    /// slivers remain when the cut is slightly off. The exported page grows by
+    /// the bleed on each side and the PDF records the page's trim and bleed
+    /// boxes.
+    ///
+    /// ```example
+    /// #set page(bleed: 3mm, fill: aqua)
+    /// Printed edge to edge.
+    /// ```
+    #[resolve]
+    pub bleed: Length,
+
+    /// Which printer's marks to draw around the page.
+    ///
+    /// Either `{none}` or a dictionary with the following keys:
+    /// - `crop`: Whether to draw crop marks at the corners of the page, which
+    ///   show where to trim the sheet.
+    /// - `registration`: Whether to draw registration marks at the centers of
+    ///   the page's sides, which help to align the printing plates.
+    ///
+    /// The marks are drawn in an additional area outside of the
+    /// [bleed]($func/page.bleed), by which the exported page grows.
+    ///
+    /// ```example
+    /// #set page(bleed: 3mm, marks: (crop: true, registration: true))
+    /// Ready for the print shop.
+    /// ```
+    pub marks: Marks,
+
     /// The contents of the page(s).
     ///
     /// Multiple pages will be created if the content does not fit on a single
@@ -379,6 +411,9 @@ impl PageElem {
             })
         });
         let footer_descent = self.footer_descent(styles);
+        let bleed = self.bleed(styles);
+        let marks = self.marks(styles);
+        let slug = marks.slug();
 
         let numbering_meta = FrameItem::Meta(
             Meta::PageNumbering(self.numbering(styles).into_value()),
@@ -431,8 +466,8 @@ impl PageElem {
                     area = Size::new(pw, margin.bottom - descent);
                     align = Align::Top.into();
                 } else {
-                    pos = Point::zero();
-                    area = size;
+                    pos = Point::splat(-bleed);
+                    area = size + Size::splat(2.0 * bleed);
                     align = Align::CENTER_HORIZON.into();
                 };
 
@@ -450,8 +485,35 @@ impl PageElem {
                 }
             }
 
+            // Realize the bleed and the area for printer's marks.
+            let offset = bleed + slug;
+            if !offset.is_zero() {
+                frame.set_size(size + Size::splat(2.0 * offset));
+                frame.translate(Point::splat(offset));
+            }
+
             if let Some(fill) = &fill {
-                frame.fill(fill.clone());
+                let shape =
+                    Geometry::Rect(size + Size::splat(2.0 * bleed)).filled(fill.clone());
+                frame.prepend(
+                    Point::splat(slug),
+                    FrameItem::Shape(shape, Span::detached()),
+                );
+            }
+
+            if !offset.is_zero() {
+                marks.draw(frame, size, bleed);
+                frame.push(
+                    Point::splat(slug),
+                    FrameItem::Meta(
+                        Meta::PageBox(PageBox::Bleed),
+                        size + Size::splat(2.0 * bleed),
+                    ),
+                );
+                frame.push(
+                    Point::splat(offset),
+                    FrameItem::Meta(Meta::PageBox(PageBox::Trim), size),
+                );
             }
 
             number = number.saturating_add(1);
@@ -563,6 +625,103 @@ cast! {
     }
 }
 
+/// Specification of the printer's marks drawn around a page.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Marks {
+    /// Whether to draw crop marks at the corners.
+    pub crop: bool,
+    /// Whether to draw registration marks at the centers of the sides.
+    pub registration: bool,
+}
+
+impl Marks {
+    /// Whether no marks are drawn.
+    pub fn is_empty(self) -> bool {
+        !self.crop && !self.registration
+    }
+
+    /// The width of the area outside of the bleed that holds the marks.
+    fn slug(self) -> Abs {
+        if self.is_empty() {
+            Abs::zero()
+        } else {
+            Abs::mm(10.0)
+        }
+    }
+
+    /// Draw the marks around a page of the given trim size. The frame must
+    /// already include the bleed and the slug.
+    fn draw(self, frame: &mut Frame, size: Size, bleed: Abs) {
+        let slug = self.slug();
+        let offset = bleed + slug;
+        let gap = Abs::pt(3.0);
+        let stroke = Stroke { thickness: Abs::pt(0.25), ..Stroke::default() };
+        let mut shapes = vec![];
+
+        // Crop marks extend the page's edges into the slug, starting just
+        // outside of the bleed.
+        if self.crop {
+            let start = bleed + gap;
+            let len = slug - gap;
+            for (x, sx) in [(offset, -1.0), (offset + size.x, 1.0)] {
+                for (y, sy) in [(offset, -1.0), (offset + size.y, 1.0)] {
+                    let horizontal = Geometry::Line(Point::with_x(sx * len));
+                    let vertical = Geometry::Line(Point::with_y(sy * len));
+                    shapes.push((Point::new(x + sx * start, y), horizontal));
+                    shapes.push((Point::new(x, y + sy * start), vertical));
+                }
+            }
+        }
+
+        // Registration marks are crosshairs in circles, centered in the slug.
+        if self.registration {
+            let radius = slug * 0.2;
+            let arm = slug * 0.3;
+            let mid = Size::splat(offset) + size / 2.0;
+            let end = frame.size() - Size::splat(slug / 2.0);
+            let circle = ellipse(Size::splat(2.0 * radius), None, None).geometry;
+            for pos in [
+                Point::new(mid.x, slug / 2.0),
+                Point::new(mid.x, end.y),
+                Point::new(slug / 2.0, mid.y),
+                Point::new(end.x, mid.y),
+            ] {
+                shapes.push((
+                    pos - Point::with_x(arm),
+                    Geometry::Line(Point::with_x(2.0 * arm)),
+                ));
+                shapes.push((
+                    pos - Point::with_y(arm),
+                    Geometry::Line(Point::with_y(2.0 * arm)),
+                ));
+                shapes.push((pos - Point::splat(radius), circle.clone()));
+            }
+        }
+
+        for (pos, geometry) in shapes {
+            let shape = geometry.stroked(stroke.clone());
+            frame.push(pos, FrameItem::Shape(shape, Span::detached()));
+        }
+    }
+}
+
+cast! {
+    Marks,
+    self => if self.is_empty() {
+        Value::None
+    } else {
+        dict! { "crop" => self.crop, "registration" => self.registration }.into_value()
+    },
+    _: NoneValue => Self::default(),
+    mut dict: Dict => {
+        let mut take = |key| dict.take(key).ok().map(Value::cast).transpose();
+        let crop = take("crop")?.unwrap_or(false);
+        let registration = take("registration")?.unwrap_or(false);
+        dict.finish(&["crop", "registration"])?;
+        Self { crop, registration }
+    },
+}
+
 /// Specification of the page's binding.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum Binding {
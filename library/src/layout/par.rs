@@ -12,10 +12,11 @@ use unicode_script::{Script, UnicodeScript};
 use super::{BoxElem, HElem, Sizing, Spacing};
 use crate::layout::AlignElem;
 use crate::math::EquationElem;
+use crate::meta::{Counter, Numbering};
 use crate::prelude::*;
 use crate::text::{
-    is_gb_style, shape, LinebreakElem, Quoter, Quotes, ShapedText, SmartQuoteElem,
-    SpaceElem, TextElem,
+    check_families, is_gb_style, load_patterns, shape, HyphenationPatterns,
+    LinebreakElem, Quoter, Quotes, ShapedText, SmartQuoteElem, SpaceElem, TextElem,
 };
 
 /// Arrange text, spacing and inline-level elements into a paragraph.
@@ -47,6 +48,10 @@ use crate::text::{
 /// Display: Paragraph
 /// Category: layout
 #[element(Construct)]
+#[scope(
+    scope.define("line", ParLine::func());
+    scope
+)]
 pub struct ParElem {
     /// The spacing between lines.
     #[resolve]
@@ -91,6 +96,29 @@ pub struct ParElem {
     #[default]
     pub linebreaks: Smart<Linebreaks>,
 
+    /// Whether to apply microtypographic refinements when breaking and
+    /// justifying lines.
+    ///
+    /// When enabled, opening punctuation like quotes and parentheses hangs
+    /// into the start margin and the line breaker takes punctuation hanging
+    /// into the end margin (see [`overhang`]($func/text.overhang)) into
+    /// account. Moreover, justified lines are not only adjusted through their
+    /// spaces, but also by slightly expanding or compressing the glyphs
+    /// themselves. Together, this reduces the number of very loose or tight
+    /// lines and keeps the margins visually straight.
+    ///
+    /// ```example
+    /// #set page(width: 180pt)
+    /// #set par(justify: true, microtype: true)
+    /// "Some texts are frustratingly
+    /// challenging to break in a
+    /// visually pleasing way." This
+    /// very aesthetic example is one
+    /// of them.
+    /// ```
+    #[default(false)]
+    pub microtype: bool,
+
     /// The indent the first line of a paragraph should have.
     ///
     /// Only the first line of a consecutive paragraph will be indented (not
@@ -107,6 +135,45 @@ pub struct ParElem {
     #[resolve]
     pub hanging_indent: Length,
 
+    /// The minimum number of lines at the start of a paragraph that stay
+    /// together when the paragraph breaks across pages or columns.
+    ///
+    /// If fewer lines would fit at the end of a page or column, they move to
+    /// the next one. Set this to `{1}` to allow a single line to stay behind.
+    ///
+    /// ```example
+    /// #set page(height: 60pt)
+    /// #set par(orphans: 3)
+    /// #v(24pt)
+    /// #lorem(12)
+    /// ```
+    #[default(NonZeroUsize::new(2).unwrap())]
+    pub orphans: NonZeroUsize,
+
+    /// The minimum number of lines at the end of a paragraph that stay
+    /// together when the paragraph breaks across pages or columns.
+    ///
+    /// If fewer lines would start a new page or column, more lines move
+    /// along with them.
+    #[default(NonZeroUsize::new(2).unwrap())]
+    pub widows: NonZeroUsize,
+
+    /// How to number the lines of paragraphs.
+    ///
+    /// When set, each line gets a number in the gutter before it. The lines
+    /// are counted throughout the document. To restart the numbering, update
+    /// the [counter]($func/counter) of [`par.line`]($func/par.line).
+    ///
+    /// ```example
+    /// #set par(numbering: "1")
+    /// #set page(margin: (left: 40pt))
+    /// #lorem(20)
+    ///
+    /// #counter(par.line).update(0)
+    /// #lorem(8)
+    /// ```
+    pub numbering: Option<Numbering>,
+
     /// The contents of the paragraph.
     #[external]
     #[required]
@@ -119,11 +186,11 @@ pub struct ParElem {
 }
 
 impl Construct for ParElem {
-    fn construct(_: &mut Vm, args: &mut Args) -> SourceResult<Content> {
+    fn construct(vm: &mut Vm, args: &mut Args) -> SourceResult<Content> {
         // The paragraph constructor is special: It doesn't create a paragraph
         // element. Instead, it just ensures that the passed content lives in a
         // separate paragraph and styles it.
-        let styles = Self::set(args)?;
+        let styles = Self::set(vm, args)?;
         let body = args.expect::<Content>("body")?;
         Ok(Content::sequence([
             ParbreakElem::new().pack(),
@@ -201,6 +268,37 @@ impl ParElem {
     }
 }
 
+/// A line number in the gutter of a paragraph.
+///
+/// This function is not intended to be called directly. Instead, it is used
+/// in show rules to customize line numbers and with the
+/// [counter]($func/counter) function to restart the line numbering.
+///
+/// ## Example { #example }
+/// ```example
+/// #set par(numbering: "1")
+/// #show par.line: set text(gray)
+/// #lorem(10)
+/// ```
+///
+/// Display: Paragraph Line
+/// Category: layout
+#[element(Locatable, Show)]
+pub struct ParLine {
+    /// How to number the line.
+    #[required]
+    pub numbering: Numbering,
+}
+
+impl Show for ParLine {
+    #[tracing::instrument(name = "ParLine::show", skip_all)]
+    fn show(&self, vt: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        let loc = self.0.location().unwrap();
+        let counter = Counter::of(Self::func());
+        counter.at(vt, loc)?.display(vt, &self.numbering())
+    }
+}
+
 /// How to determine line breaks in a paragraph.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
 pub enum Linebreaks {
@@ -266,12 +364,18 @@ struct Preparation<'a> {
     hyphenate: Option<bool>,
     /// The text language if it's the same for all children.
     lang: Option<Lang>,
+    /// Custom hyphenation patterns used in the paragraph, by path.
+    patterns: Vec<(EcoString, Arc<HyphenationPatterns>)>,
     /// The paragraph's resolved alignment.
     align: Align,
     /// Whether to justify the paragraph.
     justify: bool,
+    /// Whether to apply character protrusion and font expansion.
+    microtype: bool,
     /// The paragraph's hanging indent.
     hang: Abs,
+    /// How to number the paragraph's lines.
+    numbering: Option<Numbering>,
 }
 
 impl<'a> Preparation<'a> {
@@ -522,6 +626,43 @@ impl<'a> Line<'a> {
         self.items().filter_map(Item::text).map(|s| s.shrinkability()).sum()
     }
 
+    /// How much can the line be adjusted by scaling its glyphs.
+    fn expandability(&self) -> Abs {
+        self.items().filter_map(Item::text).map(|s| s.expandability()).sum()
+    }
+
+    /// How far the glyphs at the start and end of the line may hang into the
+    /// margins.
+    fn protrusion(&self) -> Abs {
+        let mut amount = Abs::zero();
+
+        if let Some(Item::Text(text)) = self.items().next() {
+            let glyph = if text.dir.is_positive() {
+                text.glyphs.first()
+            } else {
+                text.glyphs.last()
+            };
+            if let Some(glyph) = glyph {
+                amount += start_overhang(glyph.c) * glyph.x_advance.at(text.size);
+            }
+        }
+
+        if let Some(Item::Text(text)) = self.items().last() {
+            let glyph = if text.dir.is_positive() {
+                text.glyphs.last()
+            } else {
+                text.glyphs.first()
+            };
+            if let Some(glyph) = glyph {
+                if TextElem::overhang_in(text.styles) {
+                    amount += overhang(glyph.c) * glyph.x_advance.at(text.size);
+                }
+            }
+        }
+
+        amount
+    }
+
     /// The sum of fractions in the line.
     fn fr(&self) -> Fr {
         self.items()
@@ -677,13 +818,24 @@ fn prepare<'a>(
 
     let mut cursor = 0;
     let mut items = vec![];
+    let mut patterns: Vec<(EcoString, Arc<HyphenationPatterns>)> = vec![];
 
     // Shape / layout the children and collect them into items.
     for (segment, styles) in segments {
         let end = cursor + segment.len();
         match segment {
             Segment::Text(_) => {
+                let span = spans.span_at(cursor).0;
+                check_families(vt, span, styles);
                 shape_range(&mut items, vt, &bidi, cursor..end, &spans, styles);
+
+                // Load custom hyphenation patterns for use during line breaking.
+                if let Some(path) = TextElem::hyphenation_patterns_in(styles) {
+                    if !patterns.iter().any(|(loaded, _)| *loaded == path) {
+                        let loaded = load_patterns(vt.world, &path).at(span)?;
+                        patterns.push((path, loaded));
+                    }
+                }
             }
             Segment::Spacing(spacing) => match spacing {
                 Spacing::Rel(v) => {
@@ -727,9 +879,12 @@ fn prepare<'a>(
         styles,
         hyphenate: shared_get(styles, children, TextElem::hyphenate_in),
         lang: shared_get(styles, children, TextElem::lang_in),
+        patterns,
         align: AlignElem::alignment_in(styles).x.resolve(styles),
         justify: ParElem::justify_in(styles),
+        microtype: ParElem::microtype_in(styles),
         hang: ParElem::hanging_indent_in(styles),
+        numbering: ParElem::numbering_in(styles),
     })
 }
 
@@ -830,6 +985,13 @@ fn linebreak_simple<'a>(vt: &Vt, p: &'a Preparation<'a>, width: Abs) -> Vec<Line
     let mut start = 0;
     let mut last = None;
 
+    // With microtypography, glyphs hanging into the margins don't count
+    // towards the line's width.
+    let fits = |line: &Line| {
+        let protrusion = if p.microtype { line.protrusion() } else { Abs::zero() };
+        width.fits(line.width - protrusion)
+    };
+
     for (end, mandatory, hyphen) in breakpoints(p) {
         // Compute the line and its size.
         let mut attempt = line(vt, p, start..end, mandatory, hyphen);
@@ -837,7 +999,7 @@ fn linebreak_simple<'a>(vt: &Vt, p: &'a Preparation<'a>, width: Abs) -> Vec<Line
         // If the line doesn't fit anymore, we push the last fitting attempt
         // into the stack and rebuild the line from the attempt's end. The
         // resulting line cannot be broken up further.
-        if !width.fits(attempt.width) {
+        if !fits(&attempt) {
             if let Some((last_attempt, last_end)) = last.take() {
                 lines.push(last_attempt);
                 start = last_end;
@@ -848,7 +1010,7 @@ fn linebreak_simple<'a>(vt: &Vt, p: &'a Preparation<'a>, width: Abs) -> Vec<Line
         // Finish the current line if there is a mandatory line break (i.e.
         // due to "\n") or if the line doesn't fit horizontally already
         // since then no shorter line will be possible.
-        if mandatory || !width.fits(attempt.width) {
+        if mandatory || !fits(&attempt) {
             lines.push(attempt);
             start = end;
             last = None;
@@ -920,14 +1082,23 @@ fn linebreak_optimized<'a>(vt: &Vt, p: &'a Preparation<'a>, width: Abs) -> Vec<L
             let attempt = line(vt, p, start..end, mandatory, hyphen);
 
             // Determine how much the line's spaces would need to be stretched
-            // to make it the desired width.
-            let delta = width - attempt.width;
-            // Determine how much stretch are permitted.
-            let adjust = if delta >= Abs::zero() {
+            // to make it the desired width. With microtypography, glyphs
+            // hanging into the margins don't count towards the line's width.
+            let mut delta = width - attempt.width;
+            if p.microtype {
+                delta += attempt.protrusion();
+            }
+
+            // Determine how much stretch are permitted. With microtypography,
+            // the glyphs themselves can also be expanded or compressed.
+            let mut adjust = if delta >= Abs::zero() {
                 attempt.stretchability()
             } else {
                 attempt.shrinkability()
             };
+            if p.microtype {
+                adjust += attempt.expandability();
+            }
             // Ideally, the ratio should between -1.0 and 1.0, but sometimes a value above 1.0
             // is possible, in which case the line is underfull.
             let mut ratio = delta / adjust;
@@ -1086,7 +1257,7 @@ struct Breakpoints<'a> {
     /// The inner iterator over the unicode line break opportunities.
     linebreaks: LineBreakIteratorUtf8<'a, 'a>,
     /// Iterator over syllables of the current word.
-    syllables: Option<hypher::Syllables<'a>>,
+    syllables: Option<Syllables<'a>>,
     /// The current text offset.
     offset: usize,
     /// The trimmed end of the current word.
@@ -1102,8 +1273,8 @@ impl Iterator for Breakpoints<'_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         // If we're currently in a hyphenated "word", process the next syllable.
-        if let Some(syllable) = self.syllables.as_mut().and_then(Iterator::next) {
-            self.offset += syllable.len();
+        if let Some(len) = self.syllables.as_mut().and_then(Iterator::next) {
+            self.offset += len;
             if self.offset == self.suffix {
                 self.offset = self.end;
             }
@@ -1135,12 +1306,12 @@ impl Iterator for Breakpoints<'_> {
 
         // Hyphenate the next word.
         if self.p.hyphenate != Some(false) {
-            if let Some(lang) = self.lang(self.offset) {
-                let word = &self.p.bidi.text[self.offset..self.end];
-                let trimmed = word.trim_end_matches(|c: char| !c.is_alphabetic());
-                if !trimmed.is_empty() {
+            let word = &self.p.bidi.text[self.offset..self.end];
+            let trimmed = word.trim_end_matches(|c: char| !c.is_alphabetic());
+            if !trimmed.is_empty() {
+                if let Some(syllables) = self.syllables(trimmed) {
                     self.suffix = self.offset + trimmed.len();
-                    self.syllables = Some(hypher::hyphenate(trimmed, lang));
+                    self.syllables = Some(syllables);
                     return self.next();
                 }
             }
@@ -1163,6 +1334,29 @@ impl Breakpoints<'_> {
             .unwrap_or(false)
     }
 
+    /// Split a word at the current offset into syllables, using custom
+    /// patterns if there are any and the built-in ones for the text language
+    /// otherwise.
+    fn syllables(&self, word: &'a str) -> Option<Syllables<'a>> {
+        if let Some(patterns) = self.patterns(self.offset) {
+            return Some(Syllables::Custom(patterns.hyphenate(word).into_iter()));
+        }
+
+        let lang = self.lang(self.offset)?;
+        Some(Syllables::Builtin(hypher::hyphenate(word, lang)))
+    }
+
+    /// The custom hyphenation patterns at the given offset.
+    fn patterns(&self, offset: usize) -> Option<&'a HyphenationPatterns> {
+        let shaped = self.p.find(offset)?.text()?;
+        let path = TextElem::hyphenation_patterns_in(shaped.styles)?;
+        self.p
+            .patterns
+            .iter()
+            .find(|(loaded, _)| *loaded == path)
+            .map(|(_, patterns)| &**patterns)
+    }
+
     /// The text language at the given offset.
     fn lang(&self, offset: usize) -> Option<hypher::Lang> {
         let lang = self.p.lang.or_else(|| {
@@ -1175,6 +1369,25 @@ impl Breakpoints<'_> {
     }
 }
 
+/// The syllables of a word that is being hyphenated.
+enum Syllables<'a> {
+    /// Syllables determined by the built-in patterns for a language.
+    Builtin(hypher::Syllables<'a>),
+    /// The lengths of syllables determined by custom patterns.
+    Custom(std::vec::IntoIter<usize>),
+}
+
+impl Iterator for Syllables<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Builtin(syllables) => syllables.next().map(str::len),
+            Self::Custom(lengths) => lengths.next(),
+        }
+    }
+}
+
 /// Create a line which spans the given range.
 fn line<'a>(
     vt: &Vt,
@@ -1341,20 +1554,24 @@ fn finalize(
         .map(|line| commit(vt, p, line, width, region.y))
         .collect::<SourceResult<_>>()?;
 
-    // Prevent orphans.
+    // Prevent orphans and widows by merging the lines that must stay in the
+    // same region.
     let leading = ParElem::leading_in(p.styles);
-    if frames.len() >= 2 && !frames[1].is_empty() {
-        let second = frames.remove(1);
-        let first = &mut frames[0];
-        merge(first, second, leading);
+    for _ in 1..ParElem::orphans_in(p.styles).get() {
+        if frames.len() >= 2 && !frames[1].is_empty() {
+            let second = frames.remove(1);
+            let first = &mut frames[0];
+            merge(first, second, leading);
+        }
     }
 
-    // Prevent widows.
-    let len = frames.len();
-    if len >= 2 && !frames[len - 2].is_empty() {
-        let second = frames.pop().unwrap();
-        let first = frames.last_mut().unwrap();
-        merge(first, second, leading);
+    for _ in 1..ParElem::widows_in(p.styles).get() {
+        let len = frames.len();
+        if len >= 2 && !frames[len - 2].is_empty() {
+            let second = frames.pop().unwrap();
+            let first = frames.last_mut().unwrap();
+            merge(first, second, leading);
+        }
     }
 
     Ok(Fragment::frames(frames))
@@ -1412,17 +1629,39 @@ fn commit(
         }
     }
 
+    // With microtypography, let opening punctuation hang into the start
+    // margin.
+    if p.microtype {
+        if let Some(Item::Text(text)) = line.items().next() {
+            let glyph = if text.dir.is_positive() {
+                text.glyphs.first()
+            } else {
+                text.glyphs.last()
+            };
+            if let Some(glyph) = glyph {
+                let amount = start_overhang(glyph.c) * glyph.x_advance.at(text.size);
+                if text.dir.is_positive() {
+                    offset -= amount;
+                }
+                remaining += amount;
+            }
+        }
+    }
+
     // Determine how much additional space is needed.
     // The justicication_ratio is for the first step justification,
     // extra_justification is for the last step.
     // For more info on multi-step justification, see Procedures for Inter-
     // Character Space Expansion in W3C document Chinese Layout Requirements.
+    // With microtypography, the glyphs are scaled along with the spaces by
+    // the same ratio.
     let fr = line.fr();
     let mut justification_ratio = 0.0;
     let mut extra_justification = Abs::zero();
 
-    let shrink = line.shrinkability();
-    let stretch = line.stretchability();
+    let expand = if p.microtype { line.expandability() } else { Abs::zero() };
+    let shrink = line.shrinkability() + expand;
+    let stretch = line.stretchability() + expand;
     if remaining < Abs::zero() && shrink > Abs::zero() {
         // Attempt to reduce the length of the line, using shrinkability.
         justification_ratio = (remaining / shrink).max(-1.0);
@@ -1442,6 +1681,8 @@ fn commit(
         }
     }
 
+    let expansion_ratio = if p.microtype { justification_ratio } else { 0.0 };
+
     let mut top = Abs::zero();
     let mut bottom = Abs::zero();
 
@@ -1473,7 +1714,12 @@ fn commit(
                 }
             }
             Item::Text(shaped) => {
-                let frame = shaped.build(vt, justification_ratio, extra_justification);
+                let frame = shaped.build(
+                    vt,
+                    justification_ratio,
+                    extra_justification,
+                    expansion_ratio,
+                );
                 push(&mut offset, frame);
             }
             Item::Frame(frame) | Item::Meta(frame) => {
@@ -1498,9 +1744,42 @@ fn commit(
         output.push_frame(Point::new(x, y), frame);
     }
 
+    // Put the line number into the gutter, with its baseline aligned to the
+    // line's baseline.
+    if let Some(numbering) = &p.numbering {
+        let mut local = Styles::new();
+        local.set(ParElem::set_numbering(None));
+        let styles = p.styles.chain(&local);
+
+        let pod = Regions::one(Size::splat(Abs::inf()), Axes::splat(false));
+        let number = ParLine::new(numbering.clone())
+            .pack()
+            .layout(vt, styles, pod)?
+            .into_frame();
+
+        let gap = Em::one().resolve(styles);
+        let x = if TextElem::dir_in(styles).is_positive() {
+            -gap - number.width()
+        } else {
+            width + gap
+        };
+
+        let y = top - first_baseline(&number).unwrap_or(number.height());
+        output.push_frame(Point::new(x, y), number);
+    }
+
     Ok(output)
 }
 
+/// The baseline of the first text in a frame.
+fn first_baseline(frame: &Frame) -> Option<Abs> {
+    frame.items().find_map(|(pos, item)| match item {
+        FrameItem::Text(_) => Some(pos.y),
+        FrameItem::Group(group) => first_baseline(&group.frame).map(|y| pos.y + y),
+        _ => None,
+    })
+}
+
 /// Return a line's items in visual order.
 fn reorder<'a>(line: &'a Line<'a>) -> (Vec<&Item<'a>>, bool) {
     let mut reordered = vec![];
@@ -1541,6 +1820,20 @@ fn reorder<'a>(line: &'a Line<'a>) -> (Vec<&Item<'a>>, bool) {
     (reordered, starts_rtl)
 }
 
+/// How much a character should hang into the start margin.
+fn start_overhang(c: char) -> f64 {
+    match c {
+        // Quotes.
+        '“' | '‘' | '„' | '‚' | '"' | '\'' => 0.5,
+        '«' | '‹' => 0.3,
+
+        // Brackets.
+        '(' | '[' => 0.1,
+
+        _ => 0.0,
+    }
+}
+
 /// How much a character should hang into the end margin.
 ///
 /// For more discussion, see:
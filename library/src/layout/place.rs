@@ -30,10 +30,39 @@ pub struct PlaceElem {
     /// When an axis of the page is `{auto}` sized, all alignments relative to that
     /// axis will be ignored, instead, the item will be placed in the origin of the
     /// axis.
+    ///
+    /// Floating content can only be aligned to the `{top}` or `{bottom}`. Without
+    /// a vertical alignment, it floats to whichever is closer.
     #[positional]
     #[default(Axes::with_x(Some(GenAlign::Start)))]
     pub alignment: Axes<Option<GenAlign>>,
 
+    /// Whether the content floats to the top or bottom of its page or column.
+    ///
+    /// Floating content takes up space in the flow, but the content around it
+    /// flows past it. If it does not fit into the current page or column
+    /// anymore, it moves to the next one while the flow continues.
+    ///
+    /// ```example
+    /// #set page(height: 120pt)
+    /// Hello, world!
+    ///
+    /// #place(
+    ///   top + right,
+    ///   float: true,
+    ///   square(size: 20pt, fill: aqua),
+    /// )
+    ///
+    /// The square floats to the top.
+    /// ```
+    #[default(false)]
+    pub float: bool,
+
+    /// The space between floating content and the flow.
+    #[resolve]
+    #[default(Em::new(1.5).into())]
+    pub clearance: Length,
+
     /// The horizontal displacement of the placed content.
     ///
     /// ```example
@@ -92,7 +121,21 @@ impl PlaceElem {
     /// base origin. Instead of relative to the parent's current flow/cursor
     /// position.
     pub fn out_of_flow(&self, styles: StyleChain) -> bool {
-        self.alignment(styles).y.is_some()
+        self.alignment(styles).y.is_some() || self.float(styles)
+    }
+
+    /// Where floating content goes: To the top, the bottom or, if `None`,
+    /// whichever is closer.
+    pub fn float_alignment(&self, styles: StyleChain) -> SourceResult<Option<Align>> {
+        match self.alignment(styles).y {
+            None => Ok(None),
+            Some(GenAlign::Specific(align @ (Align::Top | Align::Bottom))) => {
+                Ok(Some(align))
+            }
+            Some(_) => {
+                bail!(self.span(), "floating placement must be `top` or `bottom`")
+            }
+        }
     }
 }
 
@@ -56,7 +56,7 @@ impl Layout for MoveElem {
 /// Rotate content without affecting layout.
 ///
 /// Rotate an element by a given angle. The layout will act as if the element
-/// was not rotated.
+/// was not rotated, unless [`reflow`]($func/rotate.reflow) is enabled.
 ///
 /// ## Example { #example }
 /// ```example
@@ -101,6 +101,19 @@ pub struct RotateElem {
     #[default(Align::CENTER_HORIZON)]
     pub origin: Axes<Option<GenAlign>>,
 
+    /// Whether the rotation impacts the layout.
+    ///
+    /// If set to `{false}`, the rotated content will retain the bounding box of
+    /// the original content. If set to `{true}`, the bounding box will take
+    /// the rotation of the content into account and adjust the layout
+    /// accordingly.
+    ///
+    /// ```example
+    /// Hello #rotate(90deg, reflow: true)[World]!
+    /// ```
+    #[default(false)]
+    pub reflow: bool,
+
     /// The content to rotate.
     #[required]
     pub body: Content,
@@ -121,7 +134,7 @@ impl Layout for RotateElem {
         let ts = Transform::translate(x, y)
             .pre_concat(Transform::rotate(self.angle(styles)))
             .pre_concat(Transform::translate(-x, -y));
-        frame.transform(ts);
+        apply(&mut frame, ts, self.reflow(styles));
         Ok(Fragment::frame(frame))
     }
 }
@@ -129,8 +142,7 @@ impl Layout for RotateElem {
 /// Scale content without affecting layout.
 ///
 /// The `scale` function allows you to scale and mirror content without
-/// affecting the layout.
-///
+/// affecting the layout, unless [`reflow`]($func/scale.reflow) is enabled.
 ///
 /// ## Example { #example }
 /// ```example
@@ -170,6 +182,18 @@ pub struct ScaleElem {
     #[default(Align::CENTER_HORIZON)]
     pub origin: Axes<Option<GenAlign>>,
 
+    /// Whether the scaling impacts the layout.
+    ///
+    /// If set to `{false}`, the scaled content will be allowed to overlap
+    /// other content. If set to `{true}`, it will compute the new size of the
+    /// scaled content and adjust the layout accordingly.
+    ///
+    /// ```example
+    /// Hello #scale(x: 20%, y: 40%, reflow: true)[World]!
+    /// ```
+    #[default(false)]
+    pub reflow: bool,
+
     /// The content to scale.
     #[required]
     pub body: Content,
@@ -190,7 +214,118 @@ impl Layout for ScaleElem {
         let transform = Transform::translate(x, y)
             .pre_concat(Transform::scale(self.x(styles), self.y(styles)))
             .pre_concat(Transform::translate(-x, -y));
-        frame.transform(transform);
+        apply(&mut frame, transform, self.reflow(styles));
+        Ok(Fragment::frame(frame))
+    }
+}
+
+/// Skew content without affecting layout.
+///
+/// The `skew` function slants content along the horizontal and vertical axes.
+/// Like with the other transformations, the layout acts as if the content was
+/// not skewed, unless [`reflow`]($func/skew.reflow) is enabled.
+///
+/// ## Example { #example }
+/// ```example
+/// #skew(ax: -12deg)[
+///   This is some fake italic text.
+/// ]
+/// ```
+///
+/// Display: Skew
+/// Category: layout
+#[element(Layout)]
+pub struct SkewElem {
+    /// The horizontal skewing angle.
+    ///
+    /// Positive angles slant the content's top to the left.
+    ///
+    /// ```example
+    /// #skew(ax: 30deg)[Skewed]
+    /// ```
+    #[default(Angle::zero())]
+    pub ax: Angle,
+
+    /// The vertical skewing angle.
+    ///
+    /// Positive angles slant the content's right side downwards.
+    ///
+    /// ```example
+    /// #skew(ay: 30deg)[Skewed]
+    /// ```
+    #[default(Angle::zero())]
+    pub ay: Angle,
+
+    /// The origin of the skew transformation.
+    ///
+    /// The origin stays in place, while everything else is slanted around it.
+    ///
+    /// ```example
+    /// X#box(skew(ax: -30deg, origin: center + horizon)[X])X \
+    /// X#box(skew(ax: -30deg, origin: bottom + left)[X])X
+    /// ```
+    #[resolve]
+    #[fold]
+    #[default(Align::CENTER_HORIZON)]
+    pub origin: Axes<Option<GenAlign>>,
+
+    /// Whether the skew transformation impacts the layout.
+    ///
+    /// If set to `{false}`, the skewed content will retain the bounding box of
+    /// the original content. If set to `{true}`, the bounding box will take
+    /// the transformation of the content into account and adjust the layout
+    /// accordingly.
+    ///
+    /// ```example
+    /// Hello #skew(ay: 30deg, reflow: true)[World]!
+    /// ```
+    #[default(false)]
+    pub reflow: bool,
+
+    /// The content to skew.
+    #[required]
+    pub body: Content,
+}
+
+impl Layout for SkewElem {
+    #[tracing::instrument(name = "SkewElem::layout", skip_all)]
+    fn layout(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        let pod = Regions::one(regions.base(), Axes::splat(false));
+        let mut frame = self.body().layout(vt, styles, pod)?.into_frame();
+        let Axes { x, y } =
+            self.origin(styles).zip(frame.size()).map(|(o, s)| o.position(s));
+        let transform = Transform::translate(x, y)
+            .pre_concat(Transform::skew(self.ax(styles), self.ay(styles)))
+            .pre_concat(Transform::translate(-x, -y));
+        apply(&mut frame, transform, self.reflow(styles));
         Ok(Fragment::frame(frame))
     }
 }
+
+/// Apply a transformation to a frame.
+///
+/// When reflowing, the frame is resized to the bounding box of the transformed
+/// content and the content is moved into that box.
+fn apply(frame: &mut Frame, ts: Transform, reflow: bool) {
+    if !reflow {
+        frame.transform(ts);
+        return;
+    }
+
+    let size = frame.size();
+    let corners =
+        [Point::zero(), Point::with_x(size.x), Point::with_y(size.y), size.to_point()]
+            .map(|corner| corner.transform(ts));
+
+    let min = corners.iter().fold(corners[0], |min, &c| min.min(c));
+    let max = corners.iter().fold(corners[0], |max, &c| max.max(c));
+
+    frame.transform(ts);
+    frame.translate(-min);
+    frame.set_size((max - min).to_size());
+}
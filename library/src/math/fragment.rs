@@ -235,6 +235,7 @@ impl GlyphFragment {
             fill: self.fill,
             lang: self.lang,
             text: self.c.into(),
+            x_scale: Ratio::one(),
             glyphs: vec![Glyph {
                 id: self.id.0,
                 x_advance: Em::from_length(self.width, self.font_size),
@@ -9,7 +9,7 @@ use crate::prelude::*;
 /// rule must appear before any of the document's contents.
 ///
 /// ```example
-/// #set document(title: "Hello")
+/// #set document(title: "Hello", keywords: ("greeting", "example"))
 ///
 /// This has no visible output, but
 /// embeds metadata into the PDF!
@@ -29,6 +29,10 @@ pub struct DocumentElem {
     /// The document's authors.
     pub author: Author,
 
+    /// The document's keywords. They are used by search engines and document
+    /// management systems to find and categorize the document.
+    pub keywords: Keywords,
+
     /// The page runs.
     #[internal]
     #[variadic]
@@ -58,9 +62,12 @@ impl LayoutRoot for DocumentElem {
             }
 
             if let Some(page) = child.to::<PageElem>() {
+                // An error in one page doesn't stop the layout of the others,
+                // so that independent errors are reported together.
                 let number = NonZeroUsize::ONE.saturating_add(pages.len());
-                let fragment = page.layout(vt, styles, number)?;
-                pages.extend(fragment);
+                let frames =
+                    vt.delayed(|vt| Ok(page.layout(vt, styles, number)?.into_frames()));
+                pages.extend(frames);
             } else {
                 bail!(child.span(), "unexpected document child");
             }
@@ -70,6 +77,9 @@ impl LayoutRoot for DocumentElem {
             pages,
             title: self.title(styles),
             author: self.author(styles).0,
+            keywords: self.keywords(styles).0,
+            date: None,
+            content: Content::empty(),
         })
     }
 }
@@ -84,3 +94,14 @@ cast! {
     v: EcoString => Self(vec![v]),
     v: Array => Self(v.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
 }
+
+/// A list of keywords.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct Keywords(Vec<EcoString>);
+
+cast! {
+    Keywords,
+    self => self.0.into_value(),
+    v: EcoString => Self(vec![v]),
+    v: Array => Self(v.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
+}
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use super::{
     Count, Counter, CounterKey, CounterUpdate, LocalName, Numbering, NumberingPattern,
 };
-use crate::layout::{BlockElem, VElem};
+use crate::layout::{BlockElem, PlaceElem, VElem};
 use crate::meta::{Outlinable, Refable, Supplement};
 use crate::prelude::*;
 use crate::text::TextElem;
@@ -86,6 +86,30 @@ pub struct FigureElem {
     /// The figure's caption.
     pub caption: Option<Content>,
 
+    /// Where the figure floats to.
+    ///
+    /// By default, the figure stays where it is in the flow. With `{top}` or
+    /// `{bottom}`, it floats to the top or bottom of the current page or
+    /// column. If it doesn't fit there, it moves to the next one while the
+    /// text continues. With `{auto}`, it floats to whichever edge is closer.
+    /// The caption and number always stay with the figure.
+    ///
+    /// To change the space between the figure and the text, use a set rule
+    /// on [`place`]($func/place.clearance).
+    ///
+    /// ```example
+    /// #set page(height: 200pt)
+    ///
+    /// = Introduction
+    /// #figure(
+    ///   placement: bottom,
+    ///   caption: [A glacier],
+    ///   image("glacier.jpg", width: 60%),
+    /// )
+    /// #lorem(40)
+    /// ```
+    pub placement: Option<Smart<GenAlign>>,
+
     /// The kind of the figure this is.
     ///
     /// If set to `{auto}`, the figure will try to automatically determine its
@@ -244,10 +268,24 @@ impl Show for FigureElem {
         }
 
         // Wrap the contents in a block.
-        Ok(BlockElem::new()
+        realized = BlockElem::new()
             .with_body(Some(realized))
             .pack()
-            .aligned(Axes::with_x(Some(Align::Center.into()))))
+            .aligned(Axes::with_x(Some(Align::Center.into())));
+
+        // Let the figure float if it has a placement.
+        if let Some(placement) = self.placement(styles) {
+            realized = PlaceElem::new(realized)
+                .with_alignment(Axes::new(
+                    Some(Align::Center.into()),
+                    placement.as_custom(),
+                ))
+                .with_float(true)
+                .pack()
+                .spanned(self.span());
+        }
+
+        Ok(realized)
     }
 }
 
@@ -149,7 +149,7 @@ impl Finalize for HeadingElem {
         styles.set(TextElem::set_weight(FontWeight::BOLD));
         styles.set(BlockElem::set_above(VElem::block_around(above.into())));
         styles.set(BlockElem::set_below(VElem::block_around(below.into())));
-        styles.set(BlockElem::set_sticky(true));
+        styles.set(BlockElem::set_keep_with_next(true));
         realized.styled_with_map(styles)
     }
 }
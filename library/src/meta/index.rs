@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use super::{Counter, CounterKey, HeadingElem, LocalName, Numbering, NumberingPattern};
+use crate::layout::{BlockElem, HElem, ParbreakElem};
+use crate::prelude::*;
+use crate::text::{LinebreakElem, TextElem};
+
+/// Marks a term for the back-of-book index.
+///
+/// The marker itself is invisible. The [`make-index`]($func/make-index)
+/// function collects all markers in the document and lists their terms
+/// alongside the pages they appear on.
+///
+/// ## Example { #example }
+/// ```example
+/// #make-index()
+///
+/// = Fruits
+/// Apples#index("Apple") grow on
+/// trees#index("Tree", "Apple tree").
+/// ```
+///
+/// Display: Index Entry
+/// Category: meta
+#[element(Locatable, Show)]
+pub struct IndexElem {
+    /// The term to list in the index.
+    #[required]
+    pub term: EcoString,
+
+    /// An optional subterm to list below the term.
+    #[positional]
+    pub subterm: Option<EcoString>,
+}
+
+impl Show for IndexElem {
+    #[tracing::instrument(name = "IndexElem::show", skip_all)]
+    fn show(&self, _: &mut Vt, _: StyleChain) -> SourceResult<Content> {
+        Ok(Content::empty())
+    }
+}
+
+/// A back-of-book index.
+///
+/// Lists all terms marked with [`index`]($func/index) in alphabetical order,
+/// grouped by their initial letter. Each term is followed by the pages it
+/// appears on, where consecutive pages are collapsed into a range. Subterms
+/// are listed indented below their term.
+///
+/// ## Example { #example }
+/// ```example
+/// = Vegetables
+/// Carrots#index("Carrot") are
+/// orange#index("Color", "Orange").
+/// Peas#index("Pea") are
+/// green#index("Color", "Green").
+///
+/// #make-index()
+/// ```
+///
+/// Display: Index
+/// Category: meta
+#[element(Show, Finalize, LocalName)]
+pub struct MakeIndexElem {
+    /// The title of the index.
+    ///
+    /// - When set to `{auto}`, an appropriate title for the
+    ///   [text language]($func/text.lang) will be used. This is the default.
+    /// - When set to `{none}`, the index will not have a title.
+    /// - A custom title can be set by passing content.
+    #[default(Some(Smart::Auto))]
+    pub title: Option<Smart<Content>>,
+
+    /// Whether to show the initial letter above each group of terms.
+    ///
+    /// ```example
+    /// #make-index(title: none, letters: false)
+    /// Cats#index("Cat") and dogs#index("Dog").
+    /// ```
+    #[default(true)]
+    pub letters: bool,
+}
+
+impl Show for MakeIndexElem {
+    #[tracing::instrument(name = "MakeIndexElem::show", skip_all)]
+    fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let mut seq = vec![ParbreakElem::new().pack()];
+        if let Some(title) = self.title(styles) {
+            let title = title.unwrap_or_else(|| {
+                TextElem::packed(self.local_name_in(styles)).spanned(self.span())
+            });
+
+            seq.push(HeadingElem::new(title).with_level(NonZeroUsize::ONE).pack());
+        }
+
+        // Collect the terms in document order, so that the pages of each
+        // term are sorted.
+        let mut entries: BTreeMap<(EcoString, EcoString), Entry> = BTreeMap::new();
+        for elem in vt.introspector.query(&Selector::Elem(IndexElem::func(), None)) {
+            let Some(location) = elem.location() else { continue };
+            let elem = elem.to::<IndexElem>().unwrap();
+            let term = elem.term();
+            if term.is_empty() {
+                continue;
+            }
+
+            let page = vt.introspector.page(location);
+            let mut entry = entries.entry(sort_key(&term)).or_default();
+            if let Some(subterm) = elem.subterm(StyleChain::default()) {
+                entry = entry.subentries.entry(sort_key(&subterm)).or_default();
+            }
+
+            if entry.pages.last().map_or(true, |&(last, _)| last != page) {
+                entry.pages.push((page, location));
+            }
+        }
+
+        let letters = self.letters(styles);
+        let mut group = None;
+        let mut lines = vec![];
+        for ((key, term), entry) in entries {
+            let initial: Option<EcoString> =
+                key.chars().next().map(|c| c.to_uppercase().collect());
+            if letters && group != initial {
+                if !lines.is_empty() {
+                    seq.push(Content::sequence(std::mem::take(&mut lines)));
+                    seq.push(ParbreakElem::new().pack());
+                }
+
+                if let Some(initial) = &initial {
+                    let letter = TextElem::packed(initial.clone()).strong();
+                    seq.push(BlockElem::new().with_body(Some(letter)).pack());
+                }
+
+                group = initial;
+            }
+
+            if !lines.is_empty() {
+                lines.push(LinebreakElem::new().pack());
+            }
+            lines.push(entry.display(vt, term)?);
+
+            for ((_, subterm), subentry) in entry.subentries {
+                lines.push(LinebreakElem::new().pack());
+                lines.push(HElem::new(Em::new(1.0).into()).pack());
+                lines.push(subentry.display(vt, subterm)?);
+            }
+        }
+
+        seq.extend(lines);
+        seq.push(ParbreakElem::new().pack());
+        Ok(Content::sequence(seq))
+    }
+}
+
+impl Finalize for MakeIndexElem {
+    fn finalize(&self, realized: Content, _: StyleChain) -> Content {
+        realized
+            .styled(HeadingElem::set_outlined(false))
+            .styled(HeadingElem::set_numbering(None))
+    }
+}
+
+impl LocalName for MakeIndexElem {
+    fn local_name(&self, lang: Lang, _: Option<Region>) -> &'static str {
+        match lang {
+            Lang::DUTCH => "Register",
+            Lang::GERMAN => "Stichwortverzeichnis",
+            Lang::ITALIAN => "Indice analitico",
+            Lang::PORTUGUESE => "Índice remissivo",
+            Lang::SPANISH => "Índice alfabético",
+            Lang::ENGLISH | Lang::FRENCH | _ => "Index",
+        }
+    }
+}
+
+/// A term in the index along with its pages and subterms.
+#[derive(Default)]
+struct Entry {
+    /// The pages the term appears on, along with the location of its first
+    /// occurrence on the page.
+    pages: Vec<(NonZeroUsize, Location)>,
+    /// The subterms of this term.
+    subentries: BTreeMap<(EcoString, EcoString), Entry>,
+}
+
+impl Entry {
+    /// Display the term followed by its pages.
+    fn display(&self, vt: &mut Vt, term: EcoString) -> SourceResult<Content> {
+        let mut seq = vec![TextElem::packed(term)];
+        let mut i = 0;
+        while i < self.pages.len() {
+            // Find the end of the run of consecutive pages.
+            let mut j = i;
+            while j + 1 < self.pages.len()
+                && self.pages[j + 1].0.get() == self.pages[j].0.get() + 1
+            {
+                j += 1;
+            }
+
+            seq.push(TextElem::packed(", "));
+            seq.push(page_number(vt, self.pages[i].1)?);
+            if j > i {
+                seq.push(TextElem::packed("–"));
+                seq.push(page_number(vt, self.pages[j].1)?);
+            }
+
+            i = j + 1;
+        }
+
+        Ok(Content::sequence(seq))
+    }
+}
+
+/// The key by which terms are sorted: Case-insensitive, with the term itself
+/// as a tie breaker.
+fn sort_key(term: &str) -> (EcoString, EcoString) {
+    (term.to_lowercase().into(), term.into())
+}
+
+/// Display the page number at a location, linked to it.
+fn page_number(vt: &mut Vt, location: Location) -> SourceResult<Content> {
+    let numbering = vt
+        .introspector
+        .page_numbering(location)
+        .cast::<Option<Numbering>>()
+        .unwrap()
+        .unwrap_or_else(|| Numbering::Pattern(NumberingPattern::from_str("1").unwrap()));
+
+    Ok(Counter::new(CounterKey::Page)
+        .at(vt, location)?
+        .display(vt, &numbering)?
+        .linked(Destination::Location(location)))
+}
@@ -7,12 +7,14 @@ mod document;
 mod figure;
 mod footnote;
 mod heading;
+mod index;
 mod link;
 mod numbering;
 mod outline;
 mod query;
 mod reference;
 mod state;
+mod theorem;
 
 pub use self::bibliography::*;
 pub use self::context::*;
@@ -21,12 +23,14 @@ pub use self::document::*;
 pub use self::figure::*;
 pub use self::footnote::*;
 pub use self::heading::*;
+pub use self::index::*;
 pub use self::link::*;
 pub use self::numbering::*;
 pub use self::outline::*;
 pub use self::query::*;
 pub use self::reference::*;
 pub use self::state::*;
+pub use self::theorem::*;
 
 use crate::prelude::*;
 use crate::text::TextElem;
@@ -37,11 +41,18 @@ pub(super) fn define(global: &mut Scope) {
     global.define("ref", RefElem::func());
     global.define("link", LinkElem::func());
     global.define("outline", OutlineElem::func());
+    global.define("index", IndexElem::func());
+    global.define("make-index", MakeIndexElem::func());
     global.define("heading", HeadingElem::func());
     global.define("figure", FigureElem::func());
     global.define("footnote", FootnoteElem::func());
     global.define("cite", CiteElem::func());
     global.define("bibliography", BibliographyElem::func());
+    global.define("theorem", TheoremElem::func());
+    global.define("lemma", lemma_func());
+    global.define("corollary", corollary_func());
+    global.define("definition", definition_func());
+    global.define("proof", proof_func());
     global.define("locate", locate_func());
     global.define("style", style_func());
     global.define("layout", layout_func());
@@ -0,0 +1,387 @@
+use std::str::FromStr;
+
+use ecow::eco_vec;
+
+use super::{
+    Count, Counter, CounterKey, CounterUpdate, HeadingElem, Numbering, NumberingPattern,
+    Refable, Supplement,
+};
+use crate::layout::{BlockElem, HElem, Spacing};
+use crate::prelude::*;
+use crate::text::{SpaceElem, TextElem};
+
+/// A theorem-like environment, such as a theorem, lemma or definition.
+///
+/// Environments are numbered per `kind`. They can be referenced with a
+/// [reference]($func/ref), which produces something like "Theorem 2.3". The
+/// functions [`lemma`]($func/lemma), [`corollary`]($func/corollary),
+/// [`definition`]($func/definition) and [`proof`]($func/proof) create
+/// environments of the respective kind and take the same arguments.
+///
+/// ## Example { #example }
+/// ```example
+/// #set heading(numbering: "1.")
+/// #set theorem(level: 1)
+///
+/// = Triangles
+/// #theorem(title: [Pythagoras])[
+///   In a right triangle, $a^2 + b^2 = c^2$.
+/// ] <pythagoras>
+///
+/// #proof[
+///   Compare the areas of squares.
+/// ]
+///
+/// #corollary[
+///   The hypotenuse is the longest side.
+/// ]
+///
+/// This follows from @pythagoras.
+/// ```
+///
+/// ## Modifying the appearance { #modifying-appearance }
+/// Environments of a specific kind can be restyled with a show rule on
+/// `{theorem.where(kind: ..)}`. The number is available through the
+/// `counter` field.
+///
+/// ```example
+/// #show theorem.where(kind: "definition"): it => block(
+///   stroke: (left: 2pt + blue),
+///   inset: (left: 8pt),
+/// )[
+///   *#it.supplement #it.counter.display(it.numbering).*
+///   #it.body
+/// ]
+///
+/// #definition[
+///   A triangle is _right_ if one of its angles is $90 degree$.
+/// ]
+/// ```
+///
+/// Display: Theorem
+/// Category: meta
+#[element(Locatable, Synthesize, Count, Show, Refable)]
+pub struct TheoremElem {
+    /// The statement of the environment.
+    #[required]
+    pub body: Content,
+
+    /// An optional title, shown in parentheses after the number.
+    pub title: Option<Content>,
+
+    /// The kind of the environment.
+    ///
+    /// The kinds `{"theorem"}`, `{"lemma"}`, `{"corollary"}`,
+    /// `{"proposition"}`, `{"definition"}`, `{"example"}`, `{"remark"}` and
+    /// `{"proof"}` have built-in supplements. For other kinds, the supplement
+    /// defaults to the capitalized kind.
+    #[default("theorem".into())]
+    pub kind: EcoString,
+
+    /// The environment's supplement.
+    ///
+    /// If set to `{auto}`, the supplement is determined from the `kind` and
+    /// the active [text language]($func/text.lang). If a function is
+    /// specified, it is passed the environment and should return content.
+    pub supplement: Smart<Option<Supplement>>,
+
+    /// How to number the environment. Accepts a
+    /// [numbering pattern or function]($func/numbering).
+    ///
+    /// If set to `{auto}`, proofs are not numbered and all other kinds are
+    /// numbered with `{"1.1"}`.
+    pub numbering: Smart<Option<Numbering>>,
+
+    /// The kind whose counter the environment uses.
+    ///
+    /// If set to `{auto}`, each kind is counted on its own. Setting this to
+    /// the same kind for multiple kinds numbers them in one sequence.
+    ///
+    /// ```example
+    /// #set theorem(group: "theorem")
+    ///
+    /// #theorem[Every number is interesting.]
+    /// #lemma[Zero is interesting.]
+    /// #theorem[One is interesting.]
+    /// ```
+    pub group: Smart<EcoString>,
+
+    /// The heading level within which the numbering restarts.
+    ///
+    /// When set, the numbers of all enclosing numbered headings up to the
+    /// level are prepended to the environment's number. With `{none}`, the
+    /// environments are numbered throughout the whole document.
+    ///
+    /// ```example
+    /// #set heading(numbering: "1.")
+    /// #set theorem(level: 1)
+    ///
+    /// = Basics
+    /// #lemma[A lemma.]
+    /// #lemma[Another lemma.]
+    ///
+    /// = Advanced
+    /// #lemma[And another one.]
+    /// ```
+    pub level: Option<NonZeroUsize>,
+
+    /// Convenience field to get access to the counter for this environment.
+    ///
+    /// The counter only depends on the `group` and the `level`. This is the
+    /// counter you'll need to modify if you want to skip a number or reset
+    /// the counter.
+    #[synthesized]
+    pub counter: Option<Counter>,
+}
+
+impl Synthesize for TheoremElem {
+    fn synthesize(&mut self, vt: &mut Vt, styles: StyleChain) -> SourceResult<()> {
+        let kind = self.kind(styles);
+        let group = self.group(styles).unwrap_or_else(|| kind.clone());
+        let level = self.level(styles);
+
+        let numbering = self.numbering(styles).unwrap_or_else(|| {
+            (kind != "proof").then(|| NumberingPattern::from_str("1.1").unwrap().into())
+        });
+
+        // Resolve the supplement.
+        let supplement = match self.supplement(styles) {
+            Smart::Auto => TextElem::packed(local_name(&kind, TextElem::lang_in(styles))),
+            Smart::Custom(None) => Content::empty(),
+            Smart::Custom(Some(supplement)) => supplement.resolve(vt, [self.clone()])?,
+        };
+
+        // Construct the counter: It counts environments of the same group
+        // and restarts at the numbered headings up to the level.
+        let mut selectors = eco_vec![Selector::Elem(
+            Self::func(),
+            Some(dict! { "group" => group.clone() }),
+        )];
+        for i in 1..=level.map_or(0, NonZeroUsize::get) {
+            selectors.push(Selector::Elem(
+                HeadingElem::func(),
+                Some(dict! { "level" => NonZeroUsize::new(i).unwrap() }),
+            ));
+        }
+        let counter = Counter::new(CounterKey::Selector(Selector::Or(selectors)));
+
+        self.push_title(self.title(styles));
+        self.push_kind(kind);
+        self.push_supplement(Smart::Custom(Some(Supplement::Content(supplement))));
+        self.push_numbering(Smart::Custom(numbering));
+        self.push_group(Smart::Custom(group));
+        self.push_level(level);
+        self.push_counter(Some(counter));
+
+        Ok(())
+    }
+}
+
+impl Show for TheoremElem {
+    #[tracing::instrument(name = "TheoremElem::show", skip_all)]
+    fn show(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let mut head = Refable::supplement(self);
+        if let (Some(numbering), Some(counter)) =
+            (Refable::numbering(self), self.counter())
+        {
+            if !head.is_empty() {
+                head += TextElem::packed("\u{a0}");
+            }
+            head += counter.display(Some(numbering), false).spanned(self.span());
+        }
+
+        if let Some(title) = self.title(styles) {
+            head += TextElem::packed(" (") + title + TextElem::packed(")");
+        }
+
+        let proof = self.kind(styles) == "proof";
+        if !head.is_empty() {
+            head += TextElem::packed(".");
+            head = if proof { head.emph() } else { head.strong() };
+            head += SpaceElem::new().pack();
+        }
+
+        let mut realized = head + self.body();
+
+        // End proofs with a tombstone at the end of the last line.
+        if proof {
+            realized += HElem::new(Spacing::Fr(Fr::one())).pack();
+            realized += TextElem::packed("∎");
+        }
+
+        Ok(BlockElem::new().with_body(Some(realized)).pack())
+    }
+}
+
+impl Count for TheoremElem {
+    fn update(&self) -> Option<CounterUpdate> {
+        // Step the counter below the levels of the restarting headings.
+        let level = self.level(StyleChain::default()).map_or(0, NonZeroUsize::get);
+        Refable::numbering(self)
+            .is_some()
+            .then(|| CounterUpdate::Step(NonZeroUsize::new(level + 1).unwrap()))
+    }
+}
+
+impl Refable for TheoremElem {
+    fn supplement(&self) -> Content {
+        // After synthesis, this should always be custom content.
+        match self.supplement(StyleChain::default()) {
+            Smart::Custom(Some(Supplement::Content(content))) => content,
+            _ => Content::empty(),
+        }
+    }
+
+    fn counter(&self) -> Counter {
+        self.counter().unwrap_or_else(|| Counter::of(Self::func()))
+    }
+
+    fn numbering(&self) -> Option<Numbering> {
+        self.numbering(StyleChain::default()).unwrap_or_default()
+    }
+}
+
+/// Create a lemma.
+///
+/// Takes the same arguments as [`theorem`]($func/theorem).
+///
+/// ## Example { #example }
+/// ```example
+/// #lemma[Every group has an identity.]
+/// ```
+///
+/// Display: Lemma
+/// Category: meta
+#[func]
+pub fn lemma(
+    /// The statement of the lemma.
+    #[external]
+    body: Content,
+    /// The virtual machine.
+    vm: &mut Vm,
+    /// The arguments.
+    args: Args,
+) -> SourceResult<Content> {
+    construct_theorem(vm, args, "lemma")
+}
+
+/// Create a corollary.
+///
+/// Takes the same arguments as [`theorem`]($func/theorem).
+///
+/// ## Example { #example }
+/// ```example
+/// #corollary[The identity is unique.]
+/// ```
+///
+/// Display: Corollary
+/// Category: meta
+#[func]
+pub fn corollary(
+    /// The statement of the corollary.
+    #[external]
+    body: Content,
+    /// The virtual machine.
+    vm: &mut Vm,
+    /// The arguments.
+    args: Args,
+) -> SourceResult<Content> {
+    construct_theorem(vm, args, "corollary")
+}
+
+/// Create a definition.
+///
+/// Takes the same arguments as [`theorem`]($func/theorem).
+///
+/// ## Example { #example }
+/// ```example
+/// #definition[A _group_ is a set with an associative operation.]
+/// ```
+///
+/// Display: Definition
+/// Category: meta
+#[func]
+pub fn definition(
+    /// The definition.
+    #[external]
+    body: Content,
+    /// The virtual machine.
+    vm: &mut Vm,
+    /// The arguments.
+    args: Args,
+) -> SourceResult<Content> {
+    construct_theorem(vm, args, "definition")
+}
+
+/// Create a proof.
+///
+/// Takes the same arguments as [`theorem`]($func/theorem). Proofs are not
+/// numbered by default and end with a tombstone.
+///
+/// ## Example { #example }
+/// ```example
+/// #proof[Left as an exercise.]
+/// ```
+///
+/// Display: Proof
+/// Category: meta
+#[func]
+pub fn proof(
+    /// The proof.
+    #[external]
+    body: Content,
+    /// The virtual machine.
+    vm: &mut Vm,
+    /// The arguments.
+    args: Args,
+) -> SourceResult<Content> {
+    construct_theorem(vm, args, "proof")
+}
+
+/// Construct a theorem environment of a specific kind.
+fn construct_theorem(vm: &mut Vm, mut args: Args, kind: &str) -> SourceResult<Content> {
+    let mut elem = TheoremElem::func().construct(vm, &mut args)?;
+    elem.push_field("kind", kind);
+    Ok(elem)
+}
+
+/// The default supplement for a kind of environment.
+fn local_name(kind: &str, lang: Lang) -> EcoString {
+    let name = match (kind, lang) {
+        ("theorem", Lang::GERMAN) => "Satz",
+        ("theorem", Lang::FRENCH) => "Théorème",
+        ("theorem", Lang::SPANISH | Lang::ITALIAN) => "Teorema",
+        ("lemma", Lang::FRENCH) => "Lemme",
+        ("lemma", Lang::SPANISH) => "Lema",
+        ("corollary", Lang::GERMAN) => "Korollar",
+        ("corollary", Lang::FRENCH) => "Corollaire",
+        ("corollary", Lang::SPANISH) => "Corolario",
+        ("corollary", Lang::ITALIAN) => "Corollario",
+        ("proposition", Lang::SPANISH) => "Proposición",
+        ("proposition", Lang::ITALIAN) => "Proposizione",
+        ("definition", Lang::FRENCH) => "Définition",
+        ("definition", Lang::SPANISH) => "Definición",
+        ("definition", Lang::ITALIAN) => "Definizione",
+        ("example", Lang::GERMAN) => "Beispiel",
+        ("example", Lang::FRENCH) => "Exemple",
+        ("example", Lang::SPANISH) => "Ejemplo",
+        ("example", Lang::ITALIAN) => "Esempio",
+        ("remark", Lang::GERMAN) => "Bemerkung",
+        ("remark", Lang::FRENCH) => "Remarque",
+        ("remark", Lang::SPANISH) => "Observación",
+        ("remark", Lang::ITALIAN) => "Osservazione",
+        ("proof", Lang::GERMAN) => "Beweis",
+        ("proof", Lang::FRENCH) => "Démonstration",
+        ("proof", Lang::SPANISH) => "Demostración",
+        ("proof", Lang::ITALIAN) => "Dimostrazione",
+        _ => {
+            // Capitalize the kind.
+            let mut chars = kind.chars();
+            return match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => EcoString::new(),
+            };
+        }
+    };
+    name.into()
+}
@@ -1,3 +1,5 @@
+use typst::eval::Quantity;
+
 use super::TextElem;
 use crate::prelude::*;
 
@@ -328,3 +330,37 @@ pub fn lorem(
 ) -> Str {
     lipsum::lipsum(words).replace("--", "–").into()
 }
+
+/// A number with a physical unit, typeset according to SI conventions.
+///
+/// This element is created when a [quantity]($func/quantity) is inserted into
+/// the document. The decimal marker depends on the
+/// [text language]($func/text.lang).
+///
+/// Display: Quantity
+/// Category: text
+#[element(Show)]
+pub struct QuantityElem {
+    /// The quantity to typeset.
+    #[required]
+    pub quantity: Quantity,
+}
+
+impl Show for QuantityElem {
+    #[tracing::instrument(name = "QuantityElem::show", skip(self))]
+    fn show(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+        let marker = decimal_marker(TextElem::lang_in(styles));
+        Ok(TextElem::packed(self.quantity().format(marker)))
+    }
+}
+
+/// The character separating the integer and fractional part of a number in
+/// the given language.
+fn decimal_marker(lang: Lang) -> char {
+    match lang.as_str() {
+        "bg" | "ca" | "cs" | "da" | "de" | "el" | "es" | "et" | "fi" | "fr" | "hr"
+        | "hu" | "id" | "it" | "lt" | "lv" | "nb" | "nl" | "nn" | "pl" | "pt" | "ro"
+        | "ru" | "sk" | "sl" | "sr" | "sv" | "tr" | "uk" | "vi" => ',',
+        _ => '.',
+    }
+}
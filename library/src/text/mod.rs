@@ -2,6 +2,7 @@
 
 mod deco;
 mod misc;
+mod patterns;
 mod quotes;
 mod raw;
 mod shaping;
@@ -9,6 +10,7 @@ mod shift;
 
 pub use self::deco::*;
 pub use self::misc::*;
+pub use self::patterns::*;
 pub use self::quotes::*;
 pub use self::raw::*;
 pub use self::shaping::*;
@@ -179,6 +181,16 @@ pub struct TextElem {
     #[default(Color::BLACK.into())]
     pub fill: Paint,
 
+    /// How opaque the text and its decorations are. Values outside of `{0%}`
+    /// to `{100%}` are clamped.
+    ///
+    /// ```example
+    /// #set text(fill: blue)
+    /// Solid and #text(opacity: 40%)[faded] text.
+    /// ```
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
+
     /// The amount of space that should be added between characters.
     ///
     /// ```example
@@ -338,6 +350,27 @@ pub struct TextElem {
     #[resolve]
     pub hyphenate: Hyphenate,
 
+    /// Custom hyphenation patterns to use instead of the built-in ones for the
+    /// [text language]($func/text.lang). Setting this to `{none}` restores the
+    /// built-in patterns.
+    ///
+    /// This is useful for languages that Typst doesn't ship patterns for.
+    /// The file should contain Knuth–Liang patterns in the format of TeX's
+    /// hyphenation files, as distributed by the
+    /// [hyph-utf8](https://www.hyphenation.org/) project. Exception words can
+    /// be listed with explicit hyphens in a `\hyphenation{...}` group.
+    ///
+    /// ```example
+    /// #set page(width: 40pt)
+    /// #set text(
+    ///   hyphenate: true,
+    ///   hyphenation-patterns: "/hyph-liang.tex",
+    /// )
+    /// Hyphenation
+    /// ```
+    #[parse(parse_patterns(vm, args)?)]
+    pub hyphenation_patterns: Option<EcoString>,
+
     /// Whether to apply kerning.
     ///
     /// When enabled, specific letter pairings move closer together or further
@@ -520,11 +553,11 @@ impl TextElem {
 }
 
 impl Construct for TextElem {
-    fn construct(_: &mut Vm, args: &mut Args) -> SourceResult<Content> {
+    fn construct(vm: &mut Vm, args: &mut Args) -> SourceResult<Content> {
         // The text constructor is special: It doesn't create a text element.
         // Instead, it leaves the passed argument structurally unchanged, but
         // styles all text in it.
-        let styles = Self::set(args)?;
+        let styles = Self::set(vm, args)?;
         let body = args.expect::<Content>("body")?;
         Ok(body.styled_with_map(styles))
     }
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use typst::util::AccessMode;
+
+use crate::prelude::*;
+
+/// Knuth–Liang hyphenation patterns loaded from a file.
+///
+/// The file uses the format of TeX's hyphenation files: Whitespace-separated
+/// patterns, optionally wrapped in `\patterns{...}`, and hyphenated exception
+/// words wrapped in `\hyphenation{...}`. Comments start with `%`.
+#[derive(Debug, Clone)]
+pub struct HyphenationPatterns {
+    /// Maps from the letters of a pattern to its inter-letter values.
+    patterns: HashMap<EcoString, Vec<u8>>,
+    /// Maps from exception words to the character indices at which they may
+    /// be broken.
+    exceptions: HashMap<EcoString, Vec<usize>>,
+    /// The number of characters in the longest pattern.
+    longest: usize,
+}
+
+impl HyphenationPatterns {
+    /// The minimum number of characters before a hyphen.
+    const LEFT_MIN: usize = 2;
+
+    /// The minimum number of characters after a hyphen.
+    const RIGHT_MIN: usize = 3;
+
+    /// Parse patterns from the contents of a TeX hyphenation file.
+    pub fn parse(src: &str) -> StrResult<Self> {
+        let mut patterns = HashMap::new();
+        let mut exceptions = HashMap::new();
+        let mut longest = 0;
+        let mut in_exceptions = false;
+
+        let uncommented = src.lines().map(|line| line.split('%').next().unwrap_or(""));
+        for token in uncommented.flat_map(str::split_whitespace) {
+            let mut token = token;
+            if let Some(rest) = token.strip_prefix("\\patterns{") {
+                in_exceptions = false;
+                token = rest;
+            } else if let Some(rest) = token.strip_prefix("\\hyphenation{") {
+                in_exceptions = true;
+                token = rest;
+            }
+
+            let token = token.trim_end_matches('}');
+            if token.is_empty() {
+                continue;
+            }
+
+            if token.starts_with('\\') {
+                bail!("unsupported command in hyphenation patterns: {token}");
+            }
+
+            if in_exceptions {
+                let mut word = EcoString::new();
+                let mut breaks = vec![];
+                for c in token.chars() {
+                    if c == '-' {
+                        breaks.push(word.chars().count());
+                    } else {
+                        word.extend(c.to_lowercase());
+                    }
+                }
+                exceptions.insert(word, breaks);
+            } else {
+                let mut letters = EcoString::new();
+                let mut values = vec![0];
+                for c in token.chars() {
+                    if let Some(digit) = c.to_digit(10) {
+                        *values.last_mut().unwrap() = digit as u8;
+                    } else {
+                        letters.push(c);
+                        values.push(0);
+                    }
+                }
+
+                if letters.is_empty() {
+                    bail!("invalid hyphenation pattern: {token}");
+                }
+
+                longest = longest.max(values.len() - 1);
+                patterns.insert(letters, values);
+            }
+        }
+
+        if patterns.is_empty() && exceptions.is_empty() {
+            bail!("file contains no hyphenation patterns");
+        }
+
+        Ok(Self { patterns, exceptions, longest })
+    }
+
+    /// Split a word into syllables and return their lengths in bytes.
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let lower: EcoString = word.chars().flat_map(char::to_lowercase).collect();
+        let chars: Vec<char> = lower.chars().collect();
+        let n = chars.len();
+
+        // Lowercasing must not change the number of characters or the break
+        // positions wouldn't apply to the original word.
+        if n != word.chars().count() {
+            return vec![word.len()];
+        }
+
+        let breaks = match self.exceptions.get(&lower) {
+            Some(breaks) => breaks.clone(),
+            None => self.breaks(&chars),
+        };
+
+        let mut lengths = vec![];
+        let mut last = 0;
+        for (i, (offset, _)) in word.char_indices().enumerate() {
+            if breaks.contains(&i) && offset > last {
+                lengths.push(offset - last);
+                last = offset;
+            }
+        }
+
+        lengths.push(word.len() - last);
+        lengths
+    }
+
+    /// Find the character indices at which a lowercase word may be broken.
+    fn breaks(&self, chars: &[char]) -> Vec<usize> {
+        let n = chars.len();
+        if n < Self::LEFT_MIN + Self::RIGHT_MIN {
+            return vec![];
+        }
+
+        // Apply all matching patterns to the word surrounded by dots.
+        let dotted: Vec<char> = std::iter::once('.')
+            .chain(chars.iter().copied())
+            .chain(['.'])
+            .collect();
+        let mut points = vec![0; dotted.len() + 1];
+        for start in 0..dotted.len() {
+            let mut key = EcoString::new();
+            for &c in dotted[start..].iter().take(self.longest) {
+                key.push(c);
+                if let Some(values) = self.patterns.get(&key) {
+                    for (point, &value) in points[start..].iter_mut().zip(values) {
+                        *point = (*point).max(value);
+                    }
+                }
+            }
+        }
+
+        // The point before the i-th character is shifted by one because of
+        // the leading dot. Odd values allow a break.
+        (Self::LEFT_MIN..=n - Self::RIGHT_MIN)
+            .filter(|&i| points[i + 1] % 2 == 1)
+            .collect()
+    }
+}
+
+/// Parse the `hyphenation-patterns` argument, resolving the path and checking
+/// that the patterns can be loaded.
+pub(super) fn parse_patterns(
+    vm: &mut Vm,
+    args: &mut Args,
+) -> SourceResult<Option<Option<EcoString>>> {
+    let Some(Spanned { v: path, span }) =
+        args.named::<Spanned<Option<EcoString>>>("hyphenation-patterns")?
+    else {
+        return Ok(None);
+    };
+
+    let Some(path) = path else { return Ok(Some(None)) };
+    let path: EcoString =
+        vm.locate(&path, AccessMode::R).at(span)?.to_string_lossy().into();
+
+    // Check that parsing works.
+    let _ = load_patterns(vm.world(), &path).at(span)?;
+    Ok(Some(Some(path)))
+}
+
+/// Load hyphenation patterns from a file.
+#[comemo::memoize]
+pub fn load_patterns(
+    world: Tracked<dyn World + '_>,
+    path: &str,
+) -> StrResult<Arc<HyphenationPatterns>> {
+    let buffer = world.read(Path::new(path))?;
+    let src = std::str::from_utf8(&buffer).map_err(|_| "file is not valid utf-8")?;
+    HyphenationPatterns::parse(src).map(Arc::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn test(patterns: &str, word: &str, syllables: &[&str]) {
+        let patterns = HyphenationPatterns::parse(patterns).unwrap();
+        let mut offset = 0;
+        let mut found = vec![];
+        for len in patterns.hyphenate(word) {
+            found.push(&word[offset..offset + len]);
+            offset += len;
+        }
+        assert_eq!(found, syllables);
+    }
+
+    #[test]
+    fn test_hyphenation_patterns() {
+        let patterns = r"
+            % Some patterns from Liang's thesis.
+            \patterns{ .hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n }
+            \hyphenation{ ta-ble }
+        ";
+
+        test(patterns, "hyphenation", &["hy", "phen", "ation"]);
+        test(patterns, "Table", &["Ta", "ble"]);
+        test(patterns, "ion", &["ion"]);
+    }
+}
@@ -1,12 +1,18 @@
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Arc;
+
 use once_cell::sync::Lazy;
 use syntect::highlighting as synt;
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
 use typst::syntax::{self, LinkedNode};
+use typst::util::AccessMode;
 
 use super::{
     FontFamily, FontList, Hyphenate, LinebreakElem, SmartQuoteElem, TextElem, TextSize,
 };
-use crate::layout::BlockElem;
-use crate::meta::{Figurable, LocalName};
+use crate::layout::{BlockElem, GridElem, ParElem, Sizing, TrackSizings};
+use crate::meta::{Figurable, LocalName, Numbering};
 use crate::prelude::*;
 
 /// Raw text with optional syntax highlighting.
@@ -126,6 +132,69 @@ pub struct RawElem {
     /// ````
     #[default(HorizontalAlign(GenAlign::Start))]
     pub align: HorizontalAlign,
+
+    /// How to number the lines of a raw block.
+    ///
+    /// The numbers are put into a gutter before the lines and restart at one
+    /// for each block. A line that is too long and wraps only receives one
+    /// number. Numbered raw blocks are excluded from the [line
+    /// numbering]($func/par.numbering) of paragraphs. This option is ignored
+    /// if this is not a raw block.
+    ///
+    /// ````example
+    /// #set raw(numbering: "1")
+    ///
+    /// ```rust
+    /// fn main() {
+    ///     println!("Hello World!");
+    /// }
+    /// ```
+    /// ````
+    pub numbering: Option<Numbering>,
+
+    /// One or multiple additional syntax definitions to load. The syntax
+    /// definitions should be in the [`sublime-syntax` file
+    /// format](https://www.sublimetext.com/docs/syntax.html). Their file
+    /// extensions can then be used as the [language]($func/raw.lang) of raw
+    /// text.
+    ///
+    /// ````example
+    /// #set raw(syntaxes: "/SExpressions.sublime-syntax")
+    ///
+    /// ```sexp
+    /// (defun factorial (x)
+    ///   (if (zerop x)
+    ///     ; with a comment
+    ///     1
+    ///     (* x (factorial (- x 1)))))
+    /// ```
+    /// ````
+    #[parse(parse_syntaxes(vm, args)?)]
+    pub syntaxes: SyntaxPaths,
+
+    /// The theme to use for syntax highlighting. Theme files should be in the
+    /// [`tmTheme` file format](https://www.sublimetext.com/docs/color_schemes_tmtheme.html).
+    ///
+    /// Only the theme's colors and font styles for the individual scopes are
+    /// applied. Its background color is ignored, so that you can set it with
+    /// a show rule on the raw element instead.
+    ///
+    /// ````example
+    /// #set raw(theme: "/halcyon.tmTheme")
+    /// #show raw: it => block(
+    ///   fill: rgb("#1d2433"),
+    ///   inset: 8pt,
+    ///   radius: 5pt,
+    ///   text(fill: rgb("#a2aabc"), it)
+    /// )
+    ///
+    /// ```typ
+    /// = Chapter 1
+    /// #let hi = "Hello World"
+    /// ```
+    /// ````
+    #[parse(parse_theme(vm, args)?)]
+    pub theme: Option<EcoString>,
 }
 
 impl RawElem {
@@ -154,53 +223,39 @@ impl Synthesize for RawElem {
 
 impl Show for RawElem {
     #[tracing::instrument(name = "RawElem::show", skip_all)]
-    fn show(&self, _: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
+    fn show(&self, vt: &mut Vt, styles: StyleChain) -> SourceResult<Content> {
         let text = self.text();
         let lang = self.lang(styles).as_ref().map(|s| s.to_lowercase());
-        let foreground = THEME
-            .settings
-            .foreground
-            .map(to_typst)
-            .map_or(Color::BLACK, Color::from);
-
-        let mut realized = if matches!(lang.as_deref(), Some("typ" | "typst" | "typc")) {
-            let root = match lang.as_deref() {
-                Some("typc") => syntax::parse_code(&text),
-                _ => syntax::parse(&text),
-            };
-
-            let mut seq = vec![];
-            let highlighter = synt::Highlighter::new(&THEME);
-            highlight_themed(
-                &LinkedNode::new(&root),
-                vec![],
-                &highlighter,
-                &mut |node, style| {
-                    seq.push(styled(&text[node.range()], foreground.into(), style));
-                },
-            );
-
-            Content::sequence(seq)
-        } else if let Some(syntax) =
-            lang.and_then(|token| SYNTAXES.find_syntax_by_token(&token))
-        {
-            let mut seq = vec![];
-            let mut highlighter = syntect::easy::HighlightLines::new(syntax, &THEME);
-            for (i, line) in text.lines().enumerate() {
-                if i != 0 {
-                    seq.push(LinebreakElem::new().pack());
-                }
+        let numbering = self.numbering(styles).filter(|_| self.block(styles));
+
+        let paths = self.syntaxes(styles);
+        let loaded = if paths.0.is_empty() {
+            None
+        } else {
+            Some(load_syntaxes(vt.world, &paths).at(self.span())?)
+        };
 
-                for (style, piece) in
-                    highlighter.highlight_line(line, &SYNTAXES).into_iter().flatten()
-                {
-                    seq.push(styled(piece, foreground.into(), style));
+        let custom = match self.theme(styles) {
+            Some(path) => Some(load_theme(vt.world, &path).at(self.span())?),
+            None => None,
+        };
+
+        let syntaxes = loaded.as_deref().unwrap_or(&SYNTAXES);
+        let theme = custom.as_deref().unwrap_or(&THEME);
+        let lines = highlight(&text, lang.as_deref(), syntaxes, theme);
+
+        let mut realized = match &numbering {
+            Some(numbering) => number_lines(vt, styles, lines, numbering)?,
+            None => {
+                let mut seq = vec![];
+                for (i, line) in lines.into_iter().enumerate() {
+                    if i != 0 {
+                        seq.push(LinebreakElem::new().pack());
+                    }
+                    seq.push(line);
                 }
+                Content::sequence(seq)
             }
-
-            Content::sequence(seq)
-        } else {
-            TextElem::packed(text)
         };
 
         if self.block(styles) {
@@ -260,6 +315,171 @@ impl PlainText for RawElem {
     }
 }
 
+/// Highlight raw text in a language and split it into lines.
+fn highlight(
+    text: &str,
+    lang: Option<&str>,
+    syntaxes: &SyntaxSet,
+    theme: &synt::Theme,
+) -> Vec<Content> {
+    let foreground = theme
+        .settings
+        .foreground
+        .map(to_typst)
+        .map_or(Color::BLACK, Color::from);
+
+    if matches!(lang, Some("typ" | "typst" | "typc")) {
+        let root = match lang {
+            Some("typc") => syntax::parse_code(text),
+            _ => syntax::parse(text),
+        };
+
+        let mut lines = vec![vec![]];
+        let highlighter = synt::Highlighter::new(theme);
+        highlight_themed(
+            &LinkedNode::new(&root),
+            vec![],
+            &highlighter,
+            &mut |node, style| {
+                // A single node, like a comment, can span multiple lines.
+                for (i, piece) in text[node.range()].split('\n').enumerate() {
+                    if i != 0 {
+                        lines.push(vec![]);
+                    }
+
+                    let piece = piece.trim_end_matches('\r');
+                    if !piece.is_empty() {
+                        let line = lines.last_mut().unwrap();
+                        line.push(styled(piece, foreground.into(), style));
+                    }
+                }
+            },
+        );
+
+        lines.into_iter().map(Content::sequence).collect()
+    } else if let Some(syntax) =
+        lang.and_then(|token| syntaxes.find_syntax_by_token(token))
+    {
+        let mut highlighter = syntect::easy::HighlightLines::new(syntax, theme);
+        text.lines()
+            .map(|line| {
+                let pieces = highlighter.highlight_line(line, syntaxes).into_iter();
+                Content::sequence(
+                    pieces
+                        .flatten()
+                        .map(|(style, piece)| styled(piece, foreground.into(), style)),
+                )
+            })
+            .collect()
+    } else {
+        text.split('\n')
+            .map(|line| TextElem::packed(line.trim_end_matches('\r')))
+            .collect()
+    }
+}
+
+/// Put the lines of a raw block into a grid with a line number before each.
+fn number_lines(
+    vt: &mut Vt,
+    styles: StyleChain,
+    lines: Vec<Content>,
+    numbering: &Numbering,
+) -> SourceResult<Content> {
+    // The rows must be spaced like the lines of a paragraph, in the text size
+    // that is active once the raw element is finalized.
+    let mut local = Styles::new();
+    local.set(TextElem::set_size(TextSize(Em::new(0.8).into())));
+    let leading = ParElem::leading_in(styles.chain(&local));
+
+    let mut cells = vec![];
+    for (i, line) in lines.into_iter().enumerate() {
+        let number = numbering.apply_vt(vt, &[i + 1])?.display();
+        cells.push(number.aligned(Axes::with_x(Some(Align::Right.into()))));
+        cells.push(line);
+    }
+
+    Ok(GridElem::new(cells)
+        .with_columns(TrackSizings(vec![Sizing::Auto, Sizing::Fr(Fr::one())]))
+        .with_column_gutter(TrackSizings(vec![Sizing::Rel(Em::one().into())]))
+        .with_row_gutter(TrackSizings(vec![Sizing::Rel(leading.into())]))
+        .pack()
+        .styled(ParElem::set_numbering(None)))
+}
+
+/// A list of paths to syntax definition files.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct SyntaxPaths(Vec<EcoString>);
+
+cast! {
+    SyntaxPaths,
+    self => self.0.into_value(),
+    v: EcoString => Self(vec![v]),
+    v: Array => Self(v.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
+}
+
+/// Parse the `syntaxes` argument, resolving the paths and checking that the
+/// definitions can be loaded.
+fn parse_syntaxes(vm: &mut Vm, args: &mut Args) -> SourceResult<Option<SyntaxPaths>> {
+    let Some(Spanned { v: mut paths, span }) =
+        args.named::<Spanned<SyntaxPaths>>("syntaxes")?
+    else {
+        return Ok(None);
+    };
+
+    for path in &mut paths.0 {
+        *path = vm.locate(path, AccessMode::R).at(span)?.to_string_lossy().into();
+    }
+
+    // Check that parsing works.
+    let _ = load_syntaxes(vm.world(), &paths).at(span)?;
+    Ok(Some(paths))
+}
+
+/// Parse the `theme` argument, resolving the path and checking that the theme
+/// can be loaded.
+fn parse_theme(vm: &mut Vm, args: &mut Args) -> SourceResult<Option<Option<EcoString>>> {
+    let Some(Spanned { v: path, span }) =
+        args.named::<Spanned<Option<EcoString>>>("theme")?
+    else {
+        return Ok(None);
+    };
+
+    let Some(path) = path else { return Ok(Some(None)) };
+    let path: EcoString =
+        vm.locate(&path, AccessMode::R).at(span)?.to_string_lossy().into();
+
+    // Check that parsing works.
+    let _ = load_theme(vm.world(), &path).at(span)?;
+    Ok(Some(Some(path)))
+}
+
+/// Load the built-in syntaxes together with additional syntax definitions.
+#[comemo::memoize]
+fn load_syntaxes(
+    world: Tracked<dyn World + '_>,
+    paths: &SyntaxPaths,
+) -> StrResult<Arc<SyntaxSet>> {
+    let mut builder = SYNTAXES.clone().into_builder();
+    for path in &paths.0 {
+        let buffer = world.read(Path::new(path.as_str()))?;
+        let src = std::str::from_utf8(&buffer).map_err(|_| "file is not valid utf-8")?;
+        let syntax = SyntaxDefinition::load_from_str(src, false, None)
+            .map_err(|err| eco_format!("failed to parse syntax ({err})"))?;
+        builder.add(syntax);
+    }
+
+    Ok(Arc::new(builder.build()))
+}
+
+/// Load a syntax highlighting theme.
+#[comemo::memoize]
+fn load_theme(world: Tracked<dyn World + '_>, path: &str) -> StrResult<Arc<synt::Theme>> {
+    let buffer = world.read(Path::new(path))?;
+    let theme = synt::ThemeSet::load_from_reader(&mut Cursor::new(buffer.as_slice()))
+        .map_err(|err| eco_format!("failed to parse theme ({err})"))?;
+    Ok(Arc::new(theme))
+}
+
 /// Highlight a syntax node in a theme by calling `f` with ranges and their
 /// styles.
 fn highlight_themed<F>(
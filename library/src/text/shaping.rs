@@ -4,6 +4,7 @@ use std::str::FromStr;
 
 use az::SaturatingAs;
 use rustybuzz::{Feature, Tag, UnicodeBuffer};
+use typst::diag::{ErrorKind, SourceError};
 use typst::font::{Font, FontStyle, FontVariant};
 use typst::util::SliceExt;
 use unicode_script::{Script, UnicodeScript};
@@ -199,6 +200,10 @@ impl ShapedGlyph {
     }
 }
 
+/// How much glyphs may be expanded or compressed horizontally relative to
+/// their width when justifying with microtypography.
+const MAX_EXPANSION: f64 = 0.02;
+
 /// A side you can go toward.
 enum Side {
     /// To the left-hand side.
@@ -211,12 +216,16 @@ impl<'a> ShapedText<'a> {
     /// Build the shaped text's frame.
     ///
     /// The `justification` defines how much extra advance width each
-    /// [justifiable glyph](ShapedGlyph::is_justifiable) will get.
+    /// [justifiable glyph](ShapedGlyph::is_justifiable) will get. The
+    /// `expansion_ratio` between `-1.0` and `1.0` defines how much the glyphs
+    /// are compressed or expanded relative to their
+    /// [expandability](Self::expandability).
     pub fn build(
         &self,
         vt: &Vt,
         justification_ratio: f64,
         extra_justification: Abs,
+        expansion_ratio: f64,
     ) -> Frame {
         let (top, bottom) = self.measure(vt);
         let size = Size::new(self.width, top + bottom);
@@ -229,6 +238,7 @@ impl<'a> ShapedText<'a> {
         let lang = TextElem::lang_in(self.styles);
         let decos = TextElem::deco_in(self.styles);
         let fill = TextElem::fill_in(self.styles);
+        let x_scale = 1.0 + expansion_ratio * MAX_EXPANSION;
 
         for ((font, y_offset), group) in
             self.glyphs.as_ref().group_by_key(|g| (g.font.clone(), g.y_offset))
@@ -243,6 +253,12 @@ impl<'a> ShapedText<'a> {
             let glyphs = group
                 .iter()
                 .map(|glyph| {
+                    // Spaces are not expanded since they are justified anyway.
+                    let scale = if glyph.is_space() { 1.0 } else { x_scale };
+                    let x_advance = glyph.x_advance * scale;
+                    let x_offset = glyph.x_offset * scale;
+                    frame.size_mut().x += (x_advance - glyph.x_advance).at(self.size);
+
                     let adjustability_left = if justification_ratio < 0.0 {
                         glyph.shrinkability().0
                     } else {
@@ -267,10 +283,8 @@ impl<'a> ShapedText<'a> {
 
                     Glyph {
                         id: glyph.glyph_id,
-                        x_advance: glyph.x_advance
-                            + justification_left
-                            + justification_right,
-                        x_offset: glyph.x_offset + justification_left,
+                        x_advance: x_advance + justification_left + justification_right,
+                        x_offset: x_offset + justification_left,
                         range: (glyph.range.start - range.start).saturating_as()
                             ..(glyph.range.end - range.start).saturating_as(),
                         span: glyph.span,
@@ -284,6 +298,7 @@ impl<'a> ShapedText<'a> {
                 lang,
                 fill: fill.clone(),
                 text: self.text[range.start - self.base..range.end - self.base].into(),
+                x_scale: Ratio::new(x_scale),
                 glyphs,
             };
 
@@ -299,6 +314,9 @@ impl<'a> ShapedText<'a> {
             offset += width;
         }
 
+        // Fade the text and its decorations.
+        frame.fade(TextElem::opacity_in(self.styles));
+
         // Apply metadata.
         frame.meta(self.styles, false);
 
@@ -376,6 +394,17 @@ impl<'a> ShapedText<'a> {
             .at(self.size)
     }
 
+    /// How much the text can be expanded or compressed by scaling its glyphs
+    /// horizontally.
+    pub fn expandability(&self) -> Abs {
+        self.glyphs
+            .iter()
+            .filter(|g| !g.is_space())
+            .map(|g| g.x_advance * MAX_EXPANSION)
+            .sum::<Em>()
+            .at(self.size)
+    }
+
     /// Reshape a range of the shaped text, reusing information from this
     /// shaping process if possible.
     ///
@@ -839,6 +868,39 @@ pub fn families(styles: StyleChain) -> impl Iterator<Item = FontFamily> + Clone
         .chain(tail.iter().copied().map(FontFamily::new))
 }
 
+/// Warn about font families that were requested but are not available,
+/// suggesting similarly named ones.
+///
+/// Text without a location in the source is not checked. Its families are
+/// typically also used by other text, where the warning is more helpful.
+pub(crate) fn check_families(vt: &mut Vt, span: Span, styles: StyleChain) {
+    if span.is_detached() {
+        return;
+    }
+
+    let book = vt.world.book();
+    for family in TextElem::font_in(styles) {
+        let family = family.as_str();
+        if book.select_family(family).next().is_some() {
+            continue;
+        }
+
+        let mut message = eco_format!("unknown font family: {family}");
+        let similar = book.similar(family, 3);
+        if !similar.is_empty() {
+            let names: Vec<_> =
+                similar.iter().map(|name| eco_format!("\"{name}\"")).collect();
+            message.push_str(&eco_format!(
+                " (did you mean {}?)",
+                typst::util::separated_list(&names, "or")
+            ));
+        }
+
+        let warning = SourceError::new(span, message);
+        vt.tracer.warn(warning.with_kind(ErrorKind::UnknownFontFamily));
+    }
+}
+
 /// Collect the tags of the OpenType features to apply.
 fn tags(styles: StyleChain) -> Vec<Feature> {
     let mut tags = vec![];
@@ -0,0 +1,422 @@
+use qrcodegen::{QrCode, QrCodeEcc};
+
+use crate::prelude::*;
+
+/// A QR code.
+///
+/// The code is drawn as vector graphics, so it stays sharp at any size and in
+/// any export format. It includes the quiet zone of four modules that readers
+/// need to find the code.
+///
+/// ## Example { #example }
+/// ```example
+/// #qrcode("https://typst.app", size: 3cm, ec: "H")
+/// ```
+///
+/// Display: QR Code
+/// Category: visualize
+#[element(Layout)]
+pub struct QrCodeElem {
+    /// The text to encode.
+    #[required]
+    pub data: EcoString,
+
+    /// The width and height of the code, including its quiet zone.
+    #[resolve]
+    #[default(Abs::cm(2.0).into())]
+    pub size: Length,
+
+    /// How much of the code may be damaged while it stays readable. Higher
+    /// levels make the code more robust, but also denser.
+    #[default(QrCorrection::Medium)]
+    pub ec: QrCorrection,
+
+    /// How to fill the dark modules.
+    #[default(Color::BLACK.into())]
+    pub fill: Paint,
+}
+
+impl Layout for QrCodeElem {
+    #[tracing::instrument(name = "QrCodeElem::layout", skip_all)]
+    fn layout(
+        &self,
+        _: &mut Vt,
+        styles: StyleChain,
+        _: Regions,
+    ) -> SourceResult<Fragment> {
+        let ecc = match self.ec(styles) {
+            QrCorrection::Low => QrCodeEcc::Low,
+            QrCorrection::Medium => QrCodeEcc::Medium,
+            QrCorrection::Quartile => QrCodeEcc::Quartile,
+            QrCorrection::High => QrCodeEcc::High,
+        };
+
+        let rows = encode_qr(&self.data(), ecc).at(self.span())?;
+        let n = rows.len() as f64;
+        let side = self.size(styles);
+        let modules = Size::splat(side / (n + 2.0 * f64::from(QR_QUIET_ZONE)));
+        let frame = layout_modules(
+            rows,
+            Size::splat(side),
+            modules,
+            modules.to_point() * f64::from(QR_QUIET_ZONE),
+            self.fill(styles),
+            self.span(),
+        );
+        Ok(Fragment::frame(frame))
+    }
+}
+
+/// How many modules a QR code's quiet zone is wide.
+const QR_QUIET_ZONE: i32 = 4;
+
+/// How much of a QR code may be damaged while it stays readable.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum QrCorrection {
+    /// About 7% of the code.
+    #[string("L")]
+    Low,
+    /// About 15% of the code.
+    #[string("M")]
+    Medium,
+    /// About 25% of the code.
+    #[string("Q")]
+    Quartile,
+    /// About 30% of the code.
+    #[string("H")]
+    High,
+}
+
+/// A one-dimensional barcode.
+///
+/// The code is drawn as vector graphics, so it stays sharp at any size and in
+/// any export format. It includes the quiet zones that readers need to find
+/// the code, but no human-readable text.
+///
+/// ## Example { #example }
+/// ```example
+/// #barcode("400638133393", format: "ean13")
+/// #barcode("Typst 0.6", format: "code128", height: 1cm)
+/// ```
+///
+/// Display: Barcode
+/// Category: visualize
+#[element(Layout)]
+pub struct BarcodeElem {
+    /// The data to encode.
+    ///
+    /// For EAN-13, this must be 12 digits or 13 digits including the check
+    /// digit. The check digit is computed if it is missing. For Code 128, any
+    /// printable ASCII text is allowed.
+    #[required]
+    pub data: EcoString,
+
+    /// The barcode symbology to use.
+    #[default(BarcodeFormat::Code128)]
+    pub format: BarcodeFormat,
+
+    /// The width of the code, including its quiet zones. If `{auto}`, each
+    /// module (the narrowest bar) is `{0.33mm}` wide.
+    #[resolve]
+    pub width: Smart<Length>,
+
+    /// The height of the bars.
+    #[resolve]
+    #[default(Abs::cm(1.5).into())]
+    pub height: Length,
+
+    /// How to fill the bars.
+    #[default(Color::BLACK.into())]
+    pub fill: Paint,
+}
+
+impl Layout for BarcodeElem {
+    #[tracing::instrument(name = "BarcodeElem::layout", skip_all)]
+    fn layout(
+        &self,
+        _: &mut Vt,
+        styles: StyleChain,
+        _: Regions,
+    ) -> SourceResult<Fragment> {
+        let format = self.format(styles);
+        let (bars, quiet) = match format {
+            BarcodeFormat::Ean13 => (encode_ean13(&self.data()), (11, 7)),
+            BarcodeFormat::Code128 => (encode_code128(&self.data()), (10, 10)),
+        };
+
+        let bars = bars.at(self.span())?;
+        let count = (bars.len() + quiet.0 + quiet.1) as f64;
+        let module = match self.width(styles) {
+            Smart::Auto => Abs::mm(0.33),
+            Smart::Custom(width) => width / count,
+        };
+
+        let height = self.height(styles);
+        let frame = layout_modules(
+            vec![bars],
+            Size::new(module * count, height),
+            Size::new(module, height),
+            Point::with_x(module * quiet.0 as f64),
+            self.fill(styles),
+            self.span(),
+        );
+        Ok(Fragment::frame(frame))
+    }
+}
+
+/// A barcode symbology.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum BarcodeFormat {
+    /// EAN-13, used for retail products.
+    #[string("ean13")]
+    Ean13,
+    /// Code 128, a compact code for arbitrary ASCII text.
+    #[string("code128")]
+    Code128,
+}
+
+/// Draw rows of dark and light modules into a frame.
+///
+/// All dark modules are combined into a single path, so that no seams appear
+/// between neighbouring modules when the frame is rasterized.
+fn layout_modules(
+    rows: Vec<Vec<bool>>,
+    size: Size,
+    module: Size,
+    origin: Point,
+    fill: Paint,
+    span: Span,
+) -> Frame {
+    let mut path = Path::new();
+    for (y, row) in rows.iter().enumerate() {
+        let top = origin.y + module.y * y as f64;
+        let mut x = 0;
+        while x < row.len() {
+            if !row[x] {
+                x += 1;
+                continue;
+            }
+
+            let start = x;
+            while x < row.len() && row[x] {
+                x += 1;
+            }
+
+            let left = origin.x + module.x * start as f64;
+            let right = origin.x + module.x * x as f64;
+            path.move_to(Point::new(left, top));
+            path.line_to(Point::new(right, top));
+            path.line_to(Point::new(right, top + module.y));
+            path.line_to(Point::new(left, top + module.y));
+            path.close_path();
+        }
+    }
+
+    let mut frame = Frame::new(size);
+    let shape = Geometry::Path(path).filled(fill);
+    frame.push(Point::zero(), FrameItem::Shape(shape, span));
+    frame
+}
+
+/// Encode text as the rows of modules of a QR code.
+fn encode_qr(data: &str, ecc: QrCodeEcc) -> StrResult<Vec<Vec<bool>>> {
+    let Ok(code) = QrCode::encode_text(data, ecc) else {
+        bail!("data is too long for a qr code");
+    };
+
+    let n = code.size();
+    Ok((0..n)
+        .map(|y| (0..n).map(|x| code.get_module(x, y)).collect())
+        .collect())
+}
+
+/// Encode digits as the modules of an EAN-13 barcode.
+fn encode_ean13(data: &str) -> StrResult<Vec<bool>> {
+    if !data.bytes().all(|c| c.is_ascii_digit()) || !matches!(data.len(), 12 | 13) {
+        bail!("ean-13 code must consist of 12 or 13 digits");
+    }
+
+    let mut digits: Vec<usize> = data.bytes().map(|c| usize::from(c - b'0')).collect();
+    let sum: usize = digits[..12]
+        .iter()
+        .enumerate()
+        .map(|(i, &d)| if i % 2 == 0 { d } else { 3 * d })
+        .sum();
+    let check = (10 - sum % 10) % 10;
+    match digits.get(12) {
+        Some(&digit) if digit != check => {
+            bail!("ean-13 code has invalid check digit (expected {check})")
+        }
+        Some(_) => {}
+        None => digits.push(check),
+    }
+
+    let mut bars = vec![];
+    let mut push = |pattern: u8, len: usize| {
+        bars.extend((0..len).rev().map(|i| pattern & (1 << i) != 0));
+    };
+
+    // The first digit determines whether the digits of the left half use the
+    // odd or the even encoding.
+    push(0b101, 3);
+    for (i, &digit) in digits[1..7].iter().enumerate() {
+        let even = EAN_PARITY[digits[0]] & (1 << (5 - i)) != 0;
+        let pattern = if even {
+            (!EAN_LEFT[digit] & 0x7f).reverse_bits() >> 1
+        } else {
+            EAN_LEFT[digit]
+        };
+        push(pattern, 7);
+    }
+    push(0b01010, 5);
+    for &digit in &digits[7..] {
+        push(!EAN_LEFT[digit] & 0x7f, 7);
+    }
+    push(0b101, 3);
+
+    Ok(bars)
+}
+
+/// The odd encodings of the digits in the left half of an EAN-13 code.
+const EAN_LEFT: [u8; 10] = [
+    0b0001101, 0b0011001, 0b0010011, 0b0111101, 0b0100011, 0b0110001, 0b0101111,
+    0b0111011, 0b0110111, 0b0001011,
+];
+
+/// Which digits of the left half use the even encoding, by first digit.
+const EAN_PARITY: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011, 0b011001, 0b011100, 0b010101,
+    0b010110, 0b011010,
+];
+
+/// Encode text as the modules of a Code 128 barcode.
+///
+/// Text that consists of an even number of digits is encoded in the compact
+/// code set C. All other text is encoded in code set B.
+fn encode_code128(data: &str) -> StrResult<Vec<bool>> {
+    if data.is_empty() {
+        bail!("code 128 data must not be empty");
+    }
+
+    let mut values = vec![];
+    if data.len() % 2 == 0 && data.bytes().all(|c| c.is_ascii_digit()) {
+        values.push(105);
+        values.extend(
+            data.as_bytes().chunks(2).map(|pair| {
+                usize::from(pair[0] - b'0') * 10 + usize::from(pair[1] - b'0')
+            }),
+        );
+    } else {
+        values.push(104);
+        for c in data.chars() {
+            if !matches!(c, ' '..='\x7f') {
+                bail!("code 128 cannot encode `{c}`");
+            }
+            values.push(c as usize - 32);
+        }
+    }
+
+    let check = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| i.max(1) * value)
+        .sum::<usize>()
+        % 103;
+    values.push(check);
+
+    let mut bars = vec![];
+    let widths = values.iter().flat_map(|&value| CODE128[value]).chain(CODE128_STOP);
+    for (i, width) in widths.enumerate() {
+        bars.extend(std::iter::repeat(i % 2 == 0).take(usize::from(width - b'0')));
+    }
+
+    Ok(bars)
+}
+
+/// The bar and space widths of the Code 128 symbols.
+const CODE128: [&[u8; 6]; 106] = [
+    b"212222", b"222122", b"222221", b"121223", b"121322", b"131222", b"122213",
+    b"122312", b"132212", b"221213", b"221312", b"231212", b"112232", b"122132",
+    b"122231", b"113222", b"123122", b"123221", b"223211", b"221132", b"221231",
+    b"213212", b"223112", b"312131", b"311222", b"321122", b"321221", b"312212",
+    b"322112", b"322211", b"212123", b"212321", b"232121", b"111323", b"131123",
+    b"131321", b"112313", b"132113", b"132311", b"211313", b"231113", b"231311",
+    b"112133", b"112331", b"132131", b"113123", b"113321", b"133121", b"313121",
+    b"211331", b"231131", b"213113", b"213311", b"213131", b"311123", b"311321",
+    b"331121", b"312113", b"312311", b"332111", b"314111", b"221411", b"431111",
+    b"111224", b"111422", b"121124", b"121421", b"141122", b"141221", b"112214",
+    b"112412", b"122114", b"122411", b"142112", b"142211", b"241211", b"221114",
+    b"413111", b"241112", b"134111", b"111242", b"121142", b"121241", b"114212",
+    b"124112", b"124211", b"411212", b"421112", b"421211", b"212141", b"214121",
+    b"412121", b"111143", b"111341", b"131141", b"114113", b"114311", b"411113",
+    b"411311", b"113141", b"114131", b"311141", b"411131", b"211412", b"211214",
+    b"211232",
+];
+
+/// The bar and space widths of the Code 128 stop symbol.
+const CODE128_STOP: &[u8; 7] = b"2331112";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write modules as ones and zeros.
+    fn bits(modules: &[bool]) -> String {
+        modules.iter().map(|&dark| if dark { '1' } else { '0' }).collect()
+    }
+
+    #[test]
+    fn test_encode_qr() {
+        let rows = encode_qr("HELLO", QrCodeEcc::Low).unwrap();
+        assert_eq!(rows.len(), 21);
+        assert!(rows.iter().all(|row| row.len() == 21));
+
+        // The finder patterns in three of the corners.
+        let finder = ["1111111", "1000001", "1011101", "1011101", "1011101", "1000001"];
+        for (y, pattern) in finder.iter().enumerate() {
+            assert_eq!(&bits(&rows[y][..7]), pattern);
+            assert_eq!(&bits(&rows[y][14..]), pattern);
+            assert_eq!(&bits(&rows[20 - y][..7]), pattern);
+        }
+
+        // The timing patterns between them.
+        assert_eq!(bits(&rows[6][6..15]), "101010101");
+        let column: Vec<bool> = rows[6..15].iter().map(|row| row[6]).collect();
+        assert_eq!(bits(&column), "101010101");
+
+        // More error correction needs a larger code.
+        let rows = encode_qr("HELLO", QrCodeEcc::High).unwrap();
+        assert_eq!(rows.len(), 21);
+        let rows = encode_qr("https://typst.app", QrCodeEcc::High).unwrap();
+        assert_eq!(rows.len(), 29);
+
+        assert!(encode_qr(&"x".repeat(3000), QrCodeEcc::High).is_err());
+    }
+
+    #[test]
+    fn test_encode_ean13() {
+        let expected = "10100011010100111010111101111010001001011001101010100001010000\
+                        101000010111010010000101100110101";
+        assert_eq!(bits(&encode_ean13("400638133393").unwrap()), expected);
+        assert_eq!(bits(&encode_ean13("4006381333931").unwrap()), expected);
+        assert!(encode_ean13("4006381333932").is_err());
+        assert!(encode_ean13("12345").is_err());
+    }
+
+    #[test]
+    fn test_encode_code128() {
+        // Start B, five characters, the check symbol and the stop symbol.
+        let bars = bits(&encode_code128("Typst").unwrap());
+        assert_eq!(bars.len(), 7 * 11 + 13);
+        assert!(bars.starts_with("11010010000"));
+        assert!(bars.ends_with("1100011101011"));
+
+        // An even number of digits is encoded in pairs with start C.
+        let bars = bits(&encode_code128("123456").unwrap());
+        assert_eq!(bars.len(), 5 * 11 + 13);
+        assert!(bars.starts_with("11010011100"));
+
+        assert!(encode_code128("").is_err());
+        assert!(encode_code128("ä").is_err());
+    }
+}
@@ -56,6 +56,37 @@ pub struct ImageElem {
     /// How the image should adjust itself to a given area.
     #[default(ImageFit::Cover)]
     pub fit: ImageFit,
+
+    /// The resolution of the image in dots per inch.
+    ///
+    /// By default, an image without a width and height fills the available
+    /// width. With a resolution, it instead has its natural size, which is its
+    /// size in pixels divided by the resolution. It still shrinks to fit into
+    /// the available space.
+    ///
+    /// ```example
+    /// #image("tiger.jpg", dpi: 600)
+    /// ```
+    #[default(Smart::Auto)]
+    pub dpi: Smart<Dpi>,
+
+    /// How opaque the image is. Values outside of `{0%}` to `{100%}` are
+    /// clamped.
+    ///
+    /// ```example
+    /// #image("tiger.jpg", width: 50%, opacity: 40%)
+    /// ```
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
+}
+
+impl ImageElem {
+    /// Create an image element from an already resolved path, failing if the
+    /// image can't be loaded.
+    pub fn resolved(world: Tracked<dyn World + '_>, path: EcoString) -> StrResult<Self> {
+        load(world, &path, None, None)?;
+        Ok(Self::new(path))
+    }
 }
 
 impl Layout for ImageElem {
@@ -70,18 +101,34 @@ impl Layout for ImageElem {
         let fallback_family = first.as_ref().map(|f| f.as_str());
         let image =
             load(vt.world, &self.path(), fallback_family, self.alt(styles)).unwrap();
+        let pxw = image.width() as f64;
+        let pxh = image.height() as f64;
+
         let sizing = Axes::new(self.width(styles), self.height(styles));
-        let region = sizing
+        let mut region = sizing
             .zip(regions.base())
             .map(|(s, r)| s.map(|v| v.resolve(styles).relative_to(r)))
             .unwrap_or(regions.base());
 
-        let expand = sizing.as_ref().map(Smart::is_custom) | regions.expand;
+        let mut expand = sizing.as_ref().map(Smart::is_custom) | regions.expand;
+
+        // With a resolution, an image without a size has its natural size,
+        // scaled down to fit into the region.
+        if let Smart::Custom(Dpi(dpi)) = self.dpi(styles) {
+            if sizing.x.is_auto() && sizing.y.is_auto() {
+                let natural = Size::new(Abs::inches(pxw / dpi), Abs::inches(pxh / dpi));
+                let scale = [region.x / natural.x, region.y / natural.y]
+                    .into_iter()
+                    .filter(|scale| scale.is_finite())
+                    .fold(1.0, f64::min);
+                region = natural * scale;
+                expand = Axes::splat(true);
+            }
+        }
+
         let region_ratio = region.x / region.y;
 
         // Find out whether the image is wider or taller than the target size.
-        let pxw = image.width() as f64;
-        let pxh = image.height() as f64;
         let px_ratio = pxw / pxh;
         let wide = px_ratio > region_ratio;
 
@@ -121,6 +168,9 @@ impl Layout for ImageElem {
             frame.clip();
         }
 
+        // Fade the image.
+        frame.fade(self.opacity(styles));
+
         // Apply metadata.
         frame.meta(styles, false);
 
@@ -158,6 +208,21 @@ impl LocalName for ImageElem {
 
 impl Figurable for ImageElem {}
 
+/// The resolution of an image in dots per inch, which must be positive.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Dpi(f64);
+
+cast! {
+    Dpi,
+    self => self.0.into_value(),
+    v: f64 => {
+        if !(v > 0.0 && v.is_finite()) {
+            bail!("dpi must be positive");
+        }
+        Self(v)
+    },
+}
+
 /// How an image should adjust itself to a given area.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
 pub enum ImageFit {
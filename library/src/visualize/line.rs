@@ -82,6 +82,11 @@ pub struct LineElem {
     #[resolve]
     #[fold]
     pub stroke: PartialStroke,
+
+    /// How opaque the line is. See the [rectangle's
+    /// documentation]($func/rect.opacity) for more details.
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
 }
 
 impl Layout for LineElem {
@@ -113,6 +118,7 @@ impl Layout for LineElem {
         let mut frame = Frame::new(target);
         let shape = Geometry::Line(delta.to_point()).stroked(stroke);
         frame.push(start.to_point(), FrameItem::Shape(shape, self.span()));
+        frame.fade(self.opacity(styles));
         Ok(Fragment::frame(frame))
     }
 }
@@ -3,10 +3,18 @@ use typst::eval::Reflect;
 
 use crate::prelude::*;
 
-use PathVertex::{AllControlPoints, MirroredControlPoint, Vertex};
+use PathVertex::{AllControlPoints, Command, MirroredControlPoint, Vertex};
 
 /// A path through a list of points, connected by Bezier curves.
 ///
+/// A path can either be given as a list of vertices, which are connected by
+/// smooth curves, or as a list of drawing commands. The commands are created
+/// with [`path.move-to`]($func/path.move-to),
+/// [`path.line-to`]($func/path.line-to),
+/// [`path.cubic-to`]($func/path.cubic-to) and [`path.close`]($func/path.close)
+/// and give full control over the path's segments. A path can't mix vertices
+/// and commands.
+///
 /// ## Example { #example }
 /// ```example
 /// #path(
@@ -17,11 +25,27 @@ use PathVertex::{AllControlPoints, MirroredControlPoint, Vertex};
 ///   (100%, 50pt),
 ///   ((50%, 0pt), (40pt, 0pt)),
 /// )
+///
+/// #path(
+///   fill: red.lighten(80%),
+///   stroke: red,
+///   path.move-to((0pt, 40pt)),
+///   path.line-to((30pt, 0pt)),
+///   path.cubic-to((50pt, -10pt), (70pt, 50pt), (90pt, 40pt)),
+///   path.close(),
+/// )
 /// ```
 ///
 /// Display: Path
 /// Category: visualize
 #[element(Layout)]
+#[scope(
+    scope.define("move-to", path_move_to_func());
+    scope.define("line-to", path_line_to_func());
+    scope.define("cubic-to", path_cubic_to_func());
+    scope.define("close", path_close_func());
+    scope
+)]
 pub struct PathElem {
     /// How to fill the path. See the
     /// [rectangle's documentation]($func/rect.fill) for more details.
@@ -43,9 +67,18 @@ pub struct PathElem {
     /// takes into account the adjacent control points. If you want to close
     /// with a straight line, simply add one last point that's the same as the
     /// start point.
+    ///
+    /// This has no effect on paths that are given as commands. Use
+    /// [`path.close`]($func/path.close) to close them instead.
     #[default(false)]
     pub closed: bool,
 
+    /// How opaque the path is. Where the path's stroke overlaps its fill, the
+    /// fill doesn't shine through. See the [rectangle's
+    /// documentation]($func/rect.opacity) for more details.
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
+
     /// The vertices of the path.
     ///
     /// Each vertex can be defined in 3 ways:
@@ -61,6 +94,8 @@ pub struct PathElem {
     /// - An array of three points, the first being the vertex and the next
     ///   being the control points (control point for curves coming in and out,
     ///   respectively)
+    ///
+    /// Alternatively, the path can be given as a list of drawing commands.
     #[variadic]
     pub vertices: Vec<PathVertex>,
 }
@@ -81,57 +116,15 @@ impl Layout for PathElem {
         };
 
         let vertices: Vec<PathVertex> = self.vertices();
-        let points: Vec<Point> = vertices.iter().map(|c| resolve(c.vertex())).collect();
-
-        let mut size = Size::zero();
-        if points.is_empty() {
-            return Ok(Fragment::frame(Frame::new(size)));
+        if vertices.is_empty() {
+            return Ok(Fragment::frame(Frame::new(Size::zero())));
         }
 
-        // Only create a path if there are more than zero points.
-        // Construct a closed path given all points.
-        let mut path = Path::new();
-        path.move_to(points[0]);
-
-        let mut add_cubic =
-            |from_point: Point, to_point: Point, from: PathVertex, to: PathVertex| {
-                let from_control_point = resolve(from.control_point_from()) + from_point;
-                let to_control_point = resolve(to.control_point_to()) + to_point;
-                path.cubic_to(from_control_point, to_control_point, to_point);
-
-                let p0 = kurbo::Point::new(from_point.x.to_raw(), from_point.y.to_raw());
-                let p1 = kurbo::Point::new(
-                    from_control_point.x.to_raw(),
-                    from_control_point.y.to_raw(),
-                );
-                let p2 = kurbo::Point::new(
-                    to_control_point.x.to_raw(),
-                    to_control_point.y.to_raw(),
-                );
-                let p3 = kurbo::Point::new(to_point.x.to_raw(), to_point.y.to_raw());
-                let extrema = CubicBez::new(p0, p1, p2, p3).bounding_box();
-                size.x.set_max(Abs::raw(extrema.x1));
-                size.y.set_max(Abs::raw(extrema.y1));
-            };
-
-        for (vertex_window, point_window) in vertices.windows(2).zip(points.windows(2)) {
-            let from = vertex_window[0];
-            let to = vertex_window[1];
-            let from_point = point_window[0];
-            let to_point = point_window[1];
-
-            add_cubic(from_point, to_point, from, to);
-        }
-
-        if self.closed(styles) {
-            let from = *vertices.last().unwrap(); // We checked that we have at least one element.
-            let to = vertices[0];
-            let from_point = *points.last().unwrap();
-            let to_point = points[0];
-
-            add_cubic(from_point, to_point, from, to);
-            path.close_path();
-        }
+        let (path, size) = if vertices.iter().any(PathVertex::is_command) {
+            layout_commands(&vertices, resolve).at(self.span())?
+        } else {
+            layout_vertices(&vertices, resolve, self.closed(styles))
+        };
 
         // Prepare fill and stroke.
         let fill = self.fill(styles);
@@ -144,16 +137,126 @@ impl Layout for PathElem {
         let mut frame = Frame::new(size);
         let shape = Shape { geometry: Geometry::Path(path), stroke, fill };
         frame.push(Point::zero(), FrameItem::Shape(shape, self.span()));
+        frame.fade(self.opacity(styles));
 
         Ok(Fragment::frame(frame))
     }
 }
 
+/// Build a path that connects vertices with Bezier curves.
+fn layout_vertices(
+    vertices: &[PathVertex],
+    resolve: impl Fn(Axes<Rel<Length>>) -> Point,
+    closed: bool,
+) -> (Path, Size) {
+    let points: Vec<Point> = vertices.iter().map(|c| resolve(c.vertex())).collect();
+
+    // Construct a closed path given all points.
+    let mut size = Size::zero();
+    let mut path = Path::new();
+    path.move_to(points[0]);
+
+    let mut add_cubic =
+        |from_point: Point, to_point: Point, from: PathVertex, to: PathVertex| {
+            let from_control_point = resolve(from.control_point_from()) + from_point;
+            let to_control_point = resolve(to.control_point_to()) + to_point;
+            path.cubic_to(from_control_point, to_control_point, to_point);
+            size = size.max(cubic_extent(
+                from_point,
+                from_control_point,
+                to_control_point,
+                to_point,
+            ));
+        };
+
+    for (vertex_window, point_window) in vertices.windows(2).zip(points.windows(2)) {
+        let from = vertex_window[0];
+        let to = vertex_window[1];
+        let from_point = point_window[0];
+        let to_point = point_window[1];
+
+        add_cubic(from_point, to_point, from, to);
+    }
+
+    if closed {
+        let from = *vertices.last().unwrap(); // We checked that we have at least one element.
+        let to = vertices[0];
+        let from_point = *points.last().unwrap();
+        let to_point = points[0];
+
+        add_cubic(from_point, to_point, from, to);
+        path.close_path();
+    }
+
+    (path, size)
+}
+
+/// Build a path from drawing commands.
+fn layout_commands(
+    vertices: &[PathVertex],
+    resolve: impl Fn(Axes<Rel<Length>>) -> Point,
+) -> StrResult<(Path, Size)> {
+    let mut size = Size::zero();
+    let mut path = Path::new();
+    let mut start = Point::zero();
+    let mut current = None;
+
+    for vertex in vertices {
+        let PathVertex::Command(command) = vertex else {
+            bail!("path cannot mix vertices and commands");
+        };
+
+        if current.is_none() && !matches!(command, PathCommand::MoveTo(_)) {
+            bail!("path must start with a move-to command");
+        }
+
+        match *command {
+            PathCommand::MoveTo(point) => {
+                let point = resolve(point);
+                path.move_to(point);
+                size = size.max(point.to_size());
+                start = point;
+                current = Some(point);
+            }
+            PathCommand::LineTo(point) => {
+                let point = resolve(point);
+                path.line_to(point);
+                size = size.max(point.to_size());
+                current = Some(point);
+            }
+            PathCommand::CubicTo(control_from, control_to, point) => {
+                let (control_from, control_to) =
+                    (resolve(control_from), resolve(control_to));
+                let point = resolve(point);
+                path.cubic_to(control_from, control_to, point);
+                let from = current.unwrap_or_default();
+                size = size.max(cubic_extent(from, control_from, control_to, point));
+                current = Some(point);
+            }
+            PathCommand::Close => {
+                path.close_path();
+                current = Some(start);
+            }
+        }
+    }
+
+    Ok((path, size))
+}
+
+/// The bottom right corner of a cubic Bezier curve's bounding box.
+fn cubic_extent(p0: Point, p1: Point, p2: Point, p3: Point) -> Size {
+    let convert = |p: Point| kurbo::Point::new(p.x.to_raw(), p.y.to_raw());
+    let extrema =
+        CubicBez::new(convert(p0), convert(p1), convert(p2), convert(p3)).bounding_box();
+    Size::new(Abs::raw(extrema.x1), Abs::raw(extrema.y1))
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum PathVertex {
     Vertex(Axes<Rel<Length>>),
     MirroredControlPoint(Axes<Rel<Length>>, Axes<Rel<Length>>),
     AllControlPoints(Axes<Rel<Length>>, Axes<Rel<Length>>, Axes<Rel<Length>>),
+    Command(PathCommand),
 }
 
 impl PathVertex {
@@ -162,12 +265,13 @@ impl PathVertex {
             Vertex(x) => *x,
             MirroredControlPoint(x, _) => *x,
             AllControlPoints(x, _, _) => *x,
+            Command(c) => c.end(),
         }
     }
 
     pub fn control_point_from(&self) -> Axes<Rel<Length>> {
         match self {
-            Vertex(_) => Axes::new(Rel::zero(), Rel::zero()),
+            Vertex(_) | Command(_) => Axes::new(Rel::zero(), Rel::zero()),
             MirroredControlPoint(_, a) => a.map(|x| -x),
             AllControlPoints(_, _, b) => *b,
         }
@@ -175,11 +279,15 @@ impl PathVertex {
 
     pub fn control_point_to(&self) -> Axes<Rel<Length>> {
         match self {
-            Vertex(_) => Axes::new(Rel::zero(), Rel::zero()),
+            Vertex(_) | Command(_) => Axes::new(Rel::zero(), Rel::zero()),
             MirroredControlPoint(_, a) => *a,
             AllControlPoints(_, a, _) => *a,
         }
     }
+
+    pub fn is_command(&self) -> bool {
+        matches!(self, Command(_))
+    }
 }
 
 cast! {
@@ -188,7 +296,9 @@ cast! {
         Vertex(x) => x.into_value(),
         MirroredControlPoint(x, c) => array![x, c].into_value(),
         AllControlPoints(x, c1, c2) => array![x, c1, c2].into_value(),
+        Command(c) => c.into_value(),
     },
+    command: PathCommand => Command(command),
     array: Array => {
         let mut iter = array.into_iter();
         match (iter.next(), iter.next(), iter.next(), iter.next()) {
@@ -209,3 +319,118 @@ cast! {
         }
     },
 }
+
+/// A drawing command of a path.
+///
+/// Created with the functions in the scope of [`path`]($func/path).
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PathCommand {
+    /// Start a new subpath at a point.
+    MoveTo(Axes<Rel<Length>>),
+    /// Draw a straight line to a point.
+    LineTo(Axes<Rel<Length>>),
+    /// Draw a cubic Bezier curve through two control points to a point.
+    CubicTo(Axes<Rel<Length>>, Axes<Rel<Length>>, Axes<Rel<Length>>),
+    /// Close the current subpath with a straight line to its start.
+    Close,
+}
+
+impl PathCommand {
+    /// The point at which the command ends, or the origin for `close`.
+    pub fn end(&self) -> Axes<Rel<Length>> {
+        match self {
+            Self::MoveTo(p) | Self::LineTo(p) | Self::CubicTo(_, _, p) => *p,
+            Self::Close => Axes::new(Rel::zero(), Rel::zero()),
+        }
+    }
+}
+
+impl Debug for PathCommand {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let repr = |p: &Axes<Rel<Length>>| p.into_value().repr();
+        match self {
+            Self::MoveTo(p) => write!(f, "path.move-to({})", repr(p)),
+            Self::LineTo(p) => write!(f, "path.line-to({})", repr(p)),
+            Self::CubicTo(c1, c2, p) => {
+                write!(f, "path.cubic-to({}, {}, {})", repr(c1), repr(c2), repr(p))
+            }
+            Self::Close => f.write_str("path.close()"),
+        }
+    }
+}
+
+cast! {
+    type PathCommand: "path command",
+}
+
+/// Start a new subpath of a [path]($func/path) at a point.
+///
+/// ## Example { #example }
+/// ```example
+/// #path(
+///   stroke: blue,
+///   path.move-to((0pt, 0pt)),
+///   path.line-to((40pt, 20pt)),
+///   path.move-to((0pt, 20pt)),
+///   path.line-to((40pt, 0pt)),
+/// )
+/// ```
+///
+/// Display: Move To
+/// Category: visualize
+#[func]
+pub fn path_move_to(
+    /// The point at which the subpath starts.
+    point: Axes<Rel<Length>>,
+) -> PathCommand {
+    PathCommand::MoveTo(point)
+}
+
+/// Draw a straight line from the current point of a [path]($func/path) to
+/// another point.
+///
+/// Display: Line To
+/// Category: visualize
+#[func]
+pub fn path_line_to(
+    /// The point at which the line ends.
+    point: Axes<Rel<Length>>,
+) -> PathCommand {
+    PathCommand::LineTo(point)
+}
+
+/// Draw a cubic Bezier curve from the current point of a [path]($func/path)
+/// to another point.
+///
+/// ## Example { #example }
+/// ```example
+/// #path(
+///   stroke: red,
+///   path.move-to((0pt, 30pt)),
+///   path.cubic-to((20pt, -20pt), (60pt, 60pt), (80pt, 0pt)),
+/// )
+/// ```
+///
+/// Display: Cubic To
+/// Category: visualize
+#[func]
+pub fn path_cubic_to(
+    /// The control point for the curve going out of the current point.
+    control_from: Axes<Rel<Length>>,
+    /// The control point for the curve coming into the end point.
+    control_to: Axes<Rel<Length>>,
+    /// The point at which the curve ends.
+    point: Axes<Rel<Length>>,
+) -> PathCommand {
+    PathCommand::CubicTo(control_from, control_to, point)
+}
+
+/// Close the current subpath of a [path]($func/path) with a straight line to
+/// its start.
+///
+/// Display: Close
+/// Category: visualize
+#[func]
+pub fn path_close() -> PathCommand {
+    PathCommand::Close
+}
@@ -0,0 +1,816 @@
+use crate::prelude::*;
+use crate::text::TextElem;
+
+/// A chart of one or more data series.
+///
+/// Data can be drawn as lines, bars or scattered points. The chart is drawn
+/// with native vector graphics and includes axes with tick labels, optional
+/// axis labels and a legend.
+///
+/// Each series can be given as an array of numbers, which are plotted against
+/// their indices, or as an array of `(x, y)` pairs. To give a series a label
+/// for the legend or a custom color, wrap it in a dictionary. Data loaded with
+/// [`csv`]($func/csv) can be converted into pairs with the array's `map`
+/// method.
+///
+/// Instead of setting the `kind`, you can also use the shorthands
+/// [`plot.line`]($func/plot.line), [`plot.bar`]($func/plot.bar) and
+/// [`plot.scatter`]($func/plot.scatter).
+///
+/// ## Example { #example }
+/// ```example
+/// #plot(
+///   x-label: [Year],
+///   y-label: [Visitors],
+///   (label: [Zoo], data: ((2019, 12), (2020, 5), (2021, 9), (2022, 15))),
+///   (label: [Museum], data: ((2019, 8), (2020, 3), (2021, 7), (2022, 10))),
+/// )
+/// ```
+///
+/// Display: Plot
+/// Category: visualize
+#[element(Layout)]
+#[scope(
+    scope.define("line", plot_line_func());
+    scope.define("bar", plot_bar_func());
+    scope.define("scatter", plot_scatter_func());
+    scope
+)]
+pub struct PlotElem {
+    /// How to draw the data series.
+    #[default(PlotKind::Line)]
+    pub kind: PlotKind,
+
+    /// The width of the chart, including its axes and labels.
+    #[resolve]
+    #[default(Abs::cm(8.0).into())]
+    pub width: Length,
+
+    /// The height of the chart, including its axes and labels.
+    #[resolve]
+    #[default(Abs::cm(5.0).into())]
+    pub height: Length,
+
+    /// The label of the horizontal axis.
+    pub x_label: Option<Content>,
+
+    /// The label of the vertical axis.
+    pub y_label: Option<Content>,
+
+    /// How the horizontal axis maps values to positions. Bar charts ignore
+    /// this, as their horizontal axis shows categories.
+    #[default(PlotScale::Linear)]
+    pub x_scale: PlotScale,
+
+    /// How the vertical axis maps values to positions.
+    #[default(PlotScale::Linear)]
+    pub y_scale: PlotScale,
+
+    /// Where to place ticks on the horizontal axis.
+    ///
+    /// - `{auto}`: About five ticks at round values.
+    /// - An integer: About this many ticks at round values.
+    /// - An array of numbers: Ticks at exactly these values.
+    #[default(PlotTicks::Auto)]
+    pub x_ticks: PlotTicks,
+
+    /// Where to place ticks on the vertical axis. See `x-ticks` for the
+    /// possible values.
+    #[default(PlotTicks::Auto)]
+    pub y_ticks: PlotTicks,
+
+    /// In which corner of the chart to place the legend. Only series with a
+    /// label appear in the legend. If `{none}`, no legend is shown.
+    #[default(Some(LegendPosition::TopRight))]
+    pub legend: Option<LegendPosition>,
+
+    /// The colors of the data series. The colors are used in order and
+    /// repeated if there are more series than colors. A series with its own
+    /// `color` does not consume a color from this list.
+    #[default(vec![
+        Color::BLUE.into(),
+        Color::RED.into(),
+        Color::GREEN.into(),
+        Color::ORANGE.into(),
+        Color::PURPLE.into(),
+        Color::TEAL.into(),
+    ])]
+    pub colors: Vec<Paint>,
+
+    /// The data series to plot.
+    ///
+    /// Each series can be:
+    /// - An array of numbers, which are plotted against their indices.
+    /// - An array of `(x, y)` pairs. For bar charts, `x` can also be a string
+    ///   that names a category.
+    /// - A dictionary with the key `data`, which contains one of the above,
+    ///   and the optional keys `label` and `color`.
+    #[variadic]
+    pub series: Vec<PlotSeries>,
+}
+
+impl Layout for PlotElem {
+    #[tracing::instrument(name = "PlotElem::layout", skip_all)]
+    fn layout(
+        &self,
+        vt: &mut Vt,
+        styles: StyleChain,
+        _: Regions,
+    ) -> SourceResult<Fragment> {
+        let span = self.span();
+        let kind = self.kind(styles);
+        let series = self.series();
+        let points = || series.iter().flat_map(|series| &series.points);
+
+        // Bar charts have categories on their horizontal axis, the other
+        // kinds have numbers.
+        let mut categories = vec![];
+        let mut x_range = None;
+        if kind == PlotKind::Bar {
+            for (x, _) in points() {
+                if !categories.contains(x) {
+                    categories.push(x.clone());
+                }
+            }
+        } else {
+            let mut xs = vec![];
+            for (x, _) in points() {
+                if !matches!(x, Value::Int(_) | Value::Float(_)) {
+                    bail!(span, "x values of a {} plot must be numbers", kind.name());
+                }
+                xs.push(x.clone().cast::<f64>().at(span)?);
+            }
+
+            let scale = self.x_scale(styles);
+            x_range =
+                Some(Range::new(&xs, scale, &self.x_ticks(styles), false).at(span)?);
+        }
+
+        let ys: Vec<f64> = points().map(|(_, y)| y.0).collect();
+        let bar = kind == PlotKind::Bar;
+        let scale = self.y_scale(styles);
+        let y_range = Range::new(&ys, scale, &self.y_ticks(styles), bar).at(span)?;
+
+        // Lay out all labels first to find out how much space they need.
+        let pod = Regions::one(Axes::splat(Abs::inf()), Axes::splat(false));
+        let measure = |vt: &mut Vt, body: Content| -> SourceResult<Frame> {
+            Ok(body.layout(vt, styles, pod)?.into_frame())
+        };
+
+        let mut y_labels = vec![];
+        for &tick in &y_range.ticks {
+            y_labels.push((tick, measure(vt, TextElem::packed(format_tick(tick)))?));
+        }
+
+        let mut x_labels = vec![];
+        if let Some(range) = &x_range {
+            for &tick in &range.ticks {
+                x_labels.push(measure(vt, TextElem::packed(format_tick(tick)))?);
+            }
+        } else {
+            for category in &categories {
+                x_labels.push(measure(vt, category.clone().display())?);
+            }
+        }
+
+        let x_title = self.x_label(styles).map(|body| measure(vt, body)).transpose()?;
+        let y_title = self.y_label(styles).map(|body| measure(vt, body)).transpose()?;
+
+        // Reserve space for the labels around the plot area.
+        let widest = y_labels.iter().map(|(_, f)| f.width()).fold(Abs::zero(), Abs::max);
+        let tallest = x_labels.iter().map(Frame::height).fold(Abs::zero(), Abs::max);
+        let left = widest + TICK + GAP;
+        let right = x_labels.last().map_or(Abs::zero(), |f| f.width() / 2.0);
+        let top = match &y_title {
+            Some(title) => title.height() + GAP,
+            None => y_labels.last().map_or(Abs::zero(), |(_, f)| f.height() / 2.0),
+        };
+
+        let mut bottom = TICK + GAP + tallest;
+        if let Some(title) = &x_title {
+            bottom += GAP + title.height();
+        }
+
+        let size = Size::new(self.width(styles), self.height(styles));
+        let area = Size::new(size.x - left - right, size.y - top - bottom);
+        if area.x <= Abs::zero() || area.y <= Abs::zero() {
+            bail!(span, "plot is too small for its labels");
+        }
+
+        let mut frame = Frame::new(size);
+        let origin = Point::new(left, top + area.y);
+        let to_x = |range: &Range, x: &Value| {
+            let x = x.clone().cast::<f64>().unwrap_or_default();
+            left + area.x * range.map(x)
+        };
+        let to_y = |y: f64| origin.y - area.y * y_range.map(y);
+
+        // Draw the axes with their ticks and labels.
+        let stroke = Stroke {
+            paint: Color::BLACK.into(),
+            thickness: AXIS,
+            ..Stroke::default()
+        };
+        let line = |frame: &mut Frame, from: Point, delta: Point| {
+            let shape = Geometry::Line(delta).stroked(stroke.clone());
+            frame.push(from, FrameItem::Shape(shape, span));
+        };
+
+        line(&mut frame, Point::new(left, top), Point::with_y(area.y));
+        line(&mut frame, origin, Point::with_x(area.x));
+
+        for (tick, label) in y_labels {
+            let y = to_y(tick);
+            line(&mut frame, Point::new(left - TICK, y), Point::with_x(TICK));
+            let x = left - TICK - GAP - label.width();
+            frame.push_frame(Point::new(x, y - label.height() / 2.0), label);
+        }
+
+        let slot = area.x / categories.len().max(1) as f64;
+        for (i, label) in x_labels.into_iter().enumerate() {
+            let x = match &x_range {
+                Some(range) => left + area.x * range.map(range.ticks[i]),
+                None => left + slot * (i as f64 + 0.5),
+            };
+            line(&mut frame, Point::new(x, origin.y), Point::with_y(TICK));
+            let pos = Point::new(x - label.width() / 2.0, origin.y + TICK + GAP);
+            frame.push_frame(pos, label);
+        }
+
+        if let Some(title) = x_title {
+            let pos = Point::new(
+                left + (area.x - title.width()) / 2.0,
+                size.y - title.height(),
+            );
+            frame.push_frame(pos, title);
+        }
+
+        if let Some(title) = y_title {
+            frame.push_frame(Point::zero(), title);
+        }
+
+        // Pick the colors of the series. Series with their own color don't
+        // consume a color from the palette.
+        let palette = self.colors(styles);
+        let mut next = palette.iter().cycle();
+        let paints: Vec<Paint> = series
+            .iter()
+            .map(|series| match &series.color {
+                Some(paint) => paint.clone(),
+                None => next.next().cloned().unwrap_or_else(|| Color::BLACK.into()),
+            })
+            .collect();
+
+        // Draw the data.
+        for (j, (series, paint)) in series.iter().zip(&paints).enumerate() {
+            match (kind, &x_range) {
+                (PlotKind::Line, Some(range)) => {
+                    let mut path = Path::new();
+                    for (i, (x, y)) in series.points.iter().enumerate() {
+                        let point = Point::new(to_x(range, x), to_y(y.0));
+                        if i == 0 {
+                            path.move_to(point);
+                        } else {
+                            path.line_to(point);
+                        }
+                    }
+
+                    let stroke = Stroke {
+                        paint: paint.clone(),
+                        thickness: LINE,
+                        ..Stroke::default()
+                    };
+                    let shape = Geometry::Path(path).stroked(stroke);
+                    frame.push(Point::zero(), FrameItem::Shape(shape, span));
+                }
+                (PlotKind::Scatter, Some(range)) => {
+                    for (x, y) in &series.points {
+                        let center = Point::new(to_x(range, x), to_y(y.0));
+                        let dot = ellipse(Size::splat(DOT), Some(paint.clone()), None);
+                        let pos = center - Point::splat(DOT / 2.0);
+                        frame.push(pos, FrameItem::Shape(dot, span));
+                    }
+                }
+                _ => {
+                    let width = slot * 0.8 / paints.len() as f64;
+                    let base = to_y(y_range.baseline());
+                    for (x, y) in &series.points {
+                        let i =
+                            categories.iter().position(|c| c == x).unwrap_or_default();
+                        let x = left + slot * (i as f64 + 0.1) + width * j as f64;
+                        let y = to_y(y.0);
+                        let size = Size::new(width, (y - base).abs());
+                        let shape = Geometry::Rect(size).filled(paint.clone());
+                        frame.push(
+                            Point::new(x, y.min(base)),
+                            FrameItem::Shape(shape, span),
+                        );
+                    }
+                }
+            }
+        }
+
+        // Draw the legend for the labelled series.
+        if let Some(position) = self.legend(styles) {
+            let mut entries = vec![];
+            for (series, paint) in series.iter().zip(&paints) {
+                if let Some(label) = &series.label {
+                    entries.push((paint.clone(), measure(vt, label.clone())?));
+                }
+            }
+
+            if !entries.is_empty() {
+                let legend = layout_legend(entries, span);
+                let x = match position {
+                    LegendPosition::TopLeft | LegendPosition::BottomLeft => left + GAP,
+                    _ => left + area.x - GAP - legend.width(),
+                };
+                let y = match position {
+                    LegendPosition::TopLeft | LegendPosition::TopRight => top + GAP,
+                    _ => origin.y - GAP - legend.height(),
+                };
+                frame.push_frame(Point::new(x, y), legend);
+            }
+        }
+
+        Ok(Fragment::frame(frame))
+    }
+}
+
+/// The length of the ticks on the axes.
+const TICK: Abs = Abs::pt(3.0);
+
+/// The gap between the parts of a plot.
+const GAP: Abs = Abs::pt(4.0);
+
+/// The thickness of the axes.
+const AXIS: Abs = Abs::pt(0.5);
+
+/// The thickness of the lines of a line plot.
+const LINE: Abs = Abs::pt(1.5);
+
+/// The diameter of the dots of a scatter plot.
+const DOT: Abs = Abs::pt(4.0);
+
+/// The side length of the color swatches in the legend.
+const SWATCH: Abs = Abs::pt(8.0);
+
+/// Draw a line plot.
+///
+/// Takes the same arguments as [`plot`]($func/plot).
+///
+/// ## Example { #example }
+/// ```example
+/// #plot.line((1, 4, 2, 5, 3))
+/// ```
+///
+/// Display: Line Plot
+/// Category: visualize
+#[func]
+pub fn plot_line(
+    /// The data series to plot.
+    #[external]
+    #[variadic]
+    series: Vec<PlotSeries>,
+    /// The virtual machine.
+    vm: &mut Vm,
+    /// The arguments.
+    args: Args,
+) -> SourceResult<Content> {
+    construct_plot(vm, args, PlotKind::Line)
+}
+
+/// Draw a bar chart.
+///
+/// Takes the same arguments as [`plot`]($func/plot).
+///
+/// ## Example { #example }
+/// ```example
+/// #plot.bar((("Mon", 3), ("Tue", 5), ("Wed", 2)))
+/// ```
+///
+/// Display: Bar Chart
+/// Category: visualize
+#[func]
+pub fn plot_bar(
+    /// The data series to plot.
+    #[external]
+    #[variadic]
+    series: Vec<PlotSeries>,
+    /// The virtual machine.
+    vm: &mut Vm,
+    /// The arguments.
+    args: Args,
+) -> SourceResult<Content> {
+    construct_plot(vm, args, PlotKind::Bar)
+}
+
+/// Draw a scatter plot.
+///
+/// Takes the same arguments as [`plot`]($func/plot).
+///
+/// ## Example { #example }
+/// ```example
+/// #plot.scatter(((1, 2), (1.5, 3.5), (3, 2.5), (4, 4)))
+/// ```
+///
+/// Display: Scatter Plot
+/// Category: visualize
+#[func]
+pub fn plot_scatter(
+    /// The data series to plot.
+    #[external]
+    #[variadic]
+    series: Vec<PlotSeries>,
+    /// The virtual machine.
+    vm: &mut Vm,
+    /// The arguments.
+    args: Args,
+) -> SourceResult<Content> {
+    construct_plot(vm, args, PlotKind::Scatter)
+}
+
+/// Construct a plot of a specific kind.
+fn construct_plot(vm: &mut Vm, args: Args, kind: PlotKind) -> SourceResult<Content> {
+    let mut args = args;
+    let plot = PlotElem::construct(vm, &mut args)?;
+    args.finish()?;
+    Ok(plot.with_field("kind", kind))
+}
+
+/// How to draw the data series of a plot.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PlotKind {
+    /// Connect the points of each series with lines.
+    Line,
+    /// Draw bars that are grouped by their category.
+    Bar,
+    /// Draw a dot for each point.
+    Scatter,
+}
+
+impl PlotKind {
+    /// The name of the kind.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Line => "line",
+            Self::Bar => "bar",
+            Self::Scatter => "scatter",
+        }
+    }
+}
+
+/// How an axis maps values to positions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PlotScale {
+    /// Equal differences have equal distances.
+    Linear,
+    /// Equal ratios have equal distances. Only works for positive values.
+    Log,
+}
+
+/// Where to place the ticks on an axis.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum PlotTicks {
+    /// About five ticks at round values.
+    Auto,
+    /// About this many ticks at round values.
+    Count(NonZeroUsize),
+    /// Ticks at exactly these values.
+    Values(Vec<Scalar>),
+}
+
+cast! {
+    PlotTicks,
+    self => match self {
+        Self::Auto => Value::Auto,
+        Self::Count(count) => count.into_value(),
+        Self::Values(values) => {
+            values.into_iter().map(|v| v.0).collect::<Vec<_>>().into_value()
+        }
+    },
+    _: AutoValue => Self::Auto,
+    count: NonZeroUsize => Self::Count(count),
+    values: Vec<f64> => {
+        if !values.iter().all(|v| v.is_finite()) {
+            bail!("tick values must be finite");
+        }
+        Self::Values(values.into_iter().map(Scalar).collect())
+    },
+}
+
+/// In which corner of a plot to place the legend.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum LegendPosition {
+    /// The top left corner.
+    TopLeft,
+    /// The top right corner.
+    TopRight,
+    /// The bottom left corner.
+    BottomLeft,
+    /// The bottom right corner.
+    BottomRight,
+}
+
+/// A data series of a plot.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct PlotSeries {
+    /// The label of the series in the legend.
+    pub label: Option<Content>,
+    /// The color of the series, overriding the plot's palette.
+    pub color: Option<Paint>,
+    /// The `(x, y)` points of the series.
+    pub points: Vec<(Value, Scalar)>,
+}
+
+cast! {
+    PlotSeries,
+    self => {
+        let data: Array = self
+            .points
+            .into_iter()
+            .map(|(x, y)| array![x, y.0].into_value())
+            .collect();
+        let mut dict = dict! { "data" => data };
+        if let Some(label) = self.label {
+            dict.insert("label".into(), label.into_value());
+        }
+        if let Some(color) = self.color {
+            dict.insert("color".into(), color.into_value());
+        }
+        dict.into_value()
+    },
+    data: Array => Self { label: None, color: None, points: parse_points(data)? },
+    mut dict: Dict => {
+        let data = dict.take("data")?.cast()?;
+        let label = dict.take("label").ok().map(Value::cast).transpose()?;
+        let color = dict.take("color").ok().map(Value::cast).transpose()?;
+        dict.finish(&["data", "label", "color"])?;
+        Self { label, color, points: parse_points(data)? }
+    },
+}
+
+/// Parse the points of a data series.
+fn parse_points(data: Array) -> StrResult<Vec<(Value, Scalar)>> {
+    let mut points = vec![];
+    for (i, item) in data.into_iter().enumerate() {
+        let (x, y) = match item {
+            Value::Int(_) | Value::Float(_) => (Value::Int(i as i64), item),
+            Value::Array(pair) if pair.len() == 2 => {
+                let mut pair = pair.into_iter();
+                (pair.next().unwrap(), pair.next().unwrap())
+            }
+            v => bail!(
+                "expected number or pair of x and y values, found {}",
+                v.type_name()
+            ),
+        };
+
+        match &x {
+            Value::Int(_) | Value::Str(_) => {}
+            Value::Float(v) if v.is_finite() => {}
+            Value::Float(_) => bail!("plot values must be finite"),
+            v => bail!("expected number or string as x value, found {}", v.type_name()),
+        }
+
+        let y = y.cast::<f64>()?;
+        if !y.is_finite() {
+            bail!("plot values must be finite");
+        }
+
+        points.push((x, Scalar(y)));
+    }
+    Ok(points)
+}
+
+/// The range of values that an axis of a plot covers.
+struct Range {
+    /// The smallest value on the axis.
+    min: f64,
+    /// The largest value on the axis.
+    max: f64,
+    /// How values are mapped to positions.
+    scale: PlotScale,
+    /// The values at which ticks are drawn.
+    ticks: Vec<f64>,
+}
+
+impl Range {
+    /// Determine the range that fits all values. If `zero` is true, the range
+    /// also includes zero on linear scales.
+    fn new(
+        values: &[f64],
+        scale: PlotScale,
+        ticks: &PlotTicks,
+        zero: bool,
+    ) -> StrResult<Self> {
+        let mut values = values.to_vec();
+        if let PlotTicks::Values(ticks) = ticks {
+            values.extend(ticks.iter().map(|v| v.0));
+        }
+
+        if scale == PlotScale::Log && values.iter().any(|&v| v <= 0.0) {
+            bail!("logarithmic scale requires positive values");
+        }
+
+        let (mut min, mut max) = match scale {
+            PlotScale::Linear => (0.0, 1.0),
+            PlotScale::Log => (1.0, 10.0),
+        };
+
+        if !values.is_empty() {
+            min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        }
+
+        if zero && scale == PlotScale::Linear {
+            min = min.min(0.0);
+            max = max.max(0.0);
+        }
+
+        if min == max {
+            match scale {
+                PlotScale::Linear => (min, max) = (min - 1.0, max + 1.0),
+                PlotScale::Log => (min, max) = (min / 10.0, max * 10.0),
+            }
+        }
+
+        let count = match ticks {
+            PlotTicks::Values(ticks) => {
+                let ticks = ticks.iter().map(|v| v.0).collect();
+                return Ok(Self { min, max, scale, ticks });
+            }
+            PlotTicks::Count(count) => count.get(),
+            PlotTicks::Auto => 5,
+        };
+
+        let ticks = match scale {
+            PlotScale::Linear => {
+                let step = nice_step((max - min) / count as f64);
+                min = (min / step).floor() * step;
+                max = (max / step).ceil() * step;
+                let n = ((max - min) / step).round() as usize;
+                (0..=n).map(|i| min + step * i as f64).collect()
+            }
+            PlotScale::Log => {
+                let lo = min.log10().floor() as i32;
+                let hi = (max.log10().ceil() as i32).max(lo + 1);
+                min = 10f64.powi(lo);
+                max = 10f64.powi(hi);
+                let stride = ((hi - lo) as usize + count - 1) / count;
+                (lo..=hi).step_by(stride).map(|k| 10f64.powi(k)).collect()
+            }
+        };
+
+        Ok(Self { min, max, scale, ticks })
+    }
+
+    /// Map a value to its relative position on the axis, between zero and
+    /// one.
+    fn map(&self, value: f64) -> f64 {
+        let (value, min, max) = match self.scale {
+            PlotScale::Linear => (value, self.min, self.max),
+            PlotScale::Log => (value.log10(), self.min.log10(), self.max.log10()),
+        };
+        (value - min) / (max - min)
+    }
+
+    /// The value from which bars extend.
+    fn baseline(&self) -> f64 {
+        match self.scale {
+            PlotScale::Linear => 0.0_f64.clamp(self.min, self.max),
+            PlotScale::Log => self.min,
+        }
+    }
+}
+
+/// A round step between ticks of about the given size.
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let factor = match raw / magnitude {
+        f if f <= 1.0 => 1.0,
+        f if f <= 2.0 => 2.0,
+        f if f <= 5.0 => 5.0,
+        _ => 10.0,
+    };
+    factor * magnitude
+}
+
+/// Format the value of a tick without floating point noise.
+fn format_tick(value: f64) -> EcoString {
+    let rounded = (value * 1e9).round() / 1e9;
+    let value = if rounded.is_finite() { rounded + 0.0 } else { value };
+    eco_format!("{value}")
+}
+
+/// Lay out the legend of a plot.
+fn layout_legend(entries: Vec<(Paint, Frame)>, span: Span) -> Frame {
+    let label_width = entries.iter().map(|(_, f)| f.width()).fold(Abs::zero(), Abs::max);
+    let heights: Vec<Abs> = entries.iter().map(|(_, f)| f.height().max(SWATCH)).collect();
+    let gaps = GAP * (entries.len() + 1) as f64;
+    let size = Size::new(
+        GAP + SWATCH + GAP + label_width + GAP,
+        heights.iter().copied().sum::<Abs>() + gaps,
+    );
+
+    let mut frame = Frame::new(size);
+    let background = Shape {
+        geometry: Geometry::Rect(size),
+        fill: Some(Color::WHITE.into()),
+        stroke: Some(Stroke {
+            paint: Color::GRAY.into(),
+            thickness: AXIS,
+            ..Stroke::default()
+        }),
+    };
+    frame.push(Point::zero(), FrameItem::Shape(background, span));
+
+    let mut y = GAP;
+    for ((paint, label), height) in entries.into_iter().zip(heights) {
+        let swatch = Geometry::Rect(Size::splat(SWATCH)).filled(paint);
+        let pos = Point::new(GAP, y + (height - SWATCH) / 2.0);
+        frame.push(pos, FrameItem::Shape(swatch, span));
+        let pos = Point::new(GAP + SWATCH + GAP, y + (height - label.height()) / 2.0);
+        frame.push_frame(pos, label);
+        y += height + GAP;
+    }
+
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The ticks of a range as they are labelled.
+    fn labels(range: &Range) -> Vec<EcoString> {
+        range.ticks.iter().copied().map(format_tick).collect()
+    }
+
+    #[test]
+    fn test_nice_step() {
+        assert_eq!(nice_step(1.0), 1.0);
+        assert_eq!(nice_step(1.5), 2.0);
+        assert_eq!(nice_step(2.8), 5.0);
+        assert_eq!(nice_step(7.0), 10.0);
+        assert_eq!(nice_step(14.0), 20.0);
+        assert_eq!(format_tick(nice_step(0.3)), "0.5");
+        assert_eq!(format_tick(nice_step(0.03)), "0.05");
+    }
+
+    #[test]
+    fn test_format_tick() {
+        assert_eq!(format_tick(3.0), "3");
+        assert_eq!(format_tick(2.5), "2.5");
+        assert_eq!(format_tick(0.1 + 0.2), "0.3");
+        assert_eq!(format_tick(-0.0), "0");
+        assert_eq!(format_tick(1e-12), "0");
+        assert_eq!(format_tick(-1.25), "-1.25");
+    }
+
+    #[test]
+    fn test_range_linear() {
+        let range =
+            Range::new(&[3.0, 17.0], PlotScale::Linear, &PlotTicks::Auto, false).unwrap();
+        assert_eq!((range.min, range.max), (0.0, 20.0));
+        assert_eq!(range.ticks, [0.0, 5.0, 10.0, 15.0, 20.0]);
+
+        let range =
+            Range::new(&[-3.0, 12.0], PlotScale::Linear, &PlotTicks::Auto, true).unwrap();
+        assert_eq!(range.ticks, [-5.0, 0.0, 5.0, 10.0, 15.0]);
+        assert_eq!(range.baseline(), 0.0);
+
+        let range =
+            Range::new(&[4.0], PlotScale::Linear, &PlotTicks::Auto, false).unwrap();
+        assert_eq!(labels(&range), ["3", "3.5", "4", "4.5", "5"]);
+    }
+
+    #[test]
+    fn test_range_log() {
+        let range =
+            Range::new(&[3.0, 2000.0], PlotScale::Log, &PlotTicks::Auto, true).unwrap();
+        assert_eq!((range.min, range.max), (1.0, 10000.0));
+        assert_eq!(labels(&range), ["1", "10", "100", "1000", "10000"]);
+        assert_eq!(range.map(100.0), 0.5);
+        assert_eq!(range.baseline(), 1.0);
+
+        let count = PlotTicks::Count(NonZeroUsize::new(2).unwrap());
+        let range = Range::new(&[3.0, 2000.0], PlotScale::Log, &count, false).unwrap();
+        assert_eq!(labels(&range), ["1", "100", "10000"]);
+
+        let result = Range::new(&[0.0, 1.0], PlotScale::Log, &PlotTicks::Auto, false);
+        assert_eq!(
+            result.err().as_deref(),
+            Some("logarithmic scale requires positive values")
+        );
+    }
+
+    #[test]
+    fn test_range_explicit_ticks() {
+        let ticks = PlotTicks::Values(vec![Scalar(0.0), Scalar(50.0)]);
+        let range = Range::new(&[10.0, 20.0], PlotScale::Linear, &ticks, false).unwrap();
+        assert_eq!((range.min, range.max), (0.0, 50.0));
+        assert_eq!(range.ticks, [0.0, 50.0]);
+        assert_eq!(range.map(10.0), 0.2);
+    }
+}
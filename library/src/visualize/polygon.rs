@@ -36,6 +36,11 @@ pub struct PolygonElem {
     #[fold]
     pub stroke: Smart<Option<PartialStroke>>,
 
+    /// How opaque the polygon is. See the [rectangle's
+    /// documentation]($func/rect.opacity) for more details.
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
+
     /// The vertices of the polygon. Each point is specified as an array of two
     /// [relative lengths]($type/relative-length).
     #[variadic]
@@ -87,6 +92,7 @@ impl Layout for PolygonElem {
 
         let shape = Shape { geometry: Geometry::Path(path), stroke, fill };
         frame.push(Point::zero(), FrameItem::Shape(shape, self.span()));
+        frame.fade(self.opacity(styles));
 
         Ok(Fragment::frame(frame))
     }
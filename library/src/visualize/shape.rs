@@ -124,6 +124,19 @@ pub struct RectElem {
     #[fold]
     pub outset: Sides<Option<Rel<Length>>>,
 
+    /// How opaque the rectangle and its content are. Everything in the
+    /// rectangle is drawn as one and then faded, so overlapping parts don't
+    /// shine through each other. Values outside of `{0%}` to `{100%}` are
+    /// clamped.
+    ///
+    /// ```example
+    /// #rect(fill: blue, opacity: 50%)[
+    ///   Faded, with a faded fill.
+    /// ]
+    /// ```
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
+
     /// The content to place into the rectangle.
     ///
     /// When this is omitted, the rectangle takes on a default size of at most
@@ -152,6 +165,7 @@ impl Layout for RectElem {
             self.inset(styles),
             self.outset(styles),
             self.radius(styles),
+            self.opacity(styles),
             self.span(),
         )
     }
@@ -232,6 +246,11 @@ pub struct SquareElem {
     #[fold]
     pub outset: Sides<Option<Rel<Length>>>,
 
+    /// How opaque the square and its content are. See the [rectangle's
+    /// documentation]($func/rect.opacity) for more details.
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
+
     /// The content to place into the square. The square expands to fit this
     /// content, keeping the 1-1 aspect ratio.
     ///
@@ -261,6 +280,7 @@ impl Layout for SquareElem {
             self.inset(styles),
             self.outset(styles),
             self.radius(styles),
+            self.opacity(styles),
             self.span(),
         )
     }
@@ -314,6 +334,11 @@ pub struct EllipseElem {
     #[fold]
     pub outset: Sides<Option<Rel<Length>>>,
 
+    /// How opaque the ellipse and its content are. See the [rectangle's
+    /// documentation]($func/rect.opacity) for more details.
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
+
     /// The content to place into the ellipse.
     ///
     /// When this is omitted, the ellipse takes on a default size of at most
@@ -342,6 +367,7 @@ impl Layout for EllipseElem {
             self.inset(styles),
             self.outset(styles),
             Corners::splat(Rel::zero()),
+            self.opacity(styles),
             self.span(),
         )
     }
@@ -422,6 +448,11 @@ pub struct CircleElem {
     #[fold]
     pub outset: Sides<Option<Rel<Length>>>,
 
+    /// How opaque the circle and its content are. See the [rectangle's
+    /// documentation]($func/rect.opacity) for more details.
+    #[default(Ratio::one())]
+    pub opacity: Ratio,
+
     /// The content to place into the circle. The circle expands to fit this
     /// content, keeping the 1-1 aspect ratio.
     #[positional]
@@ -448,6 +479,7 @@ impl Layout for CircleElem {
             self.inset(styles),
             self.outset(styles),
             Corners::splat(Rel::zero()),
+            self.opacity(styles),
             self.span(),
         )
     }
@@ -468,6 +500,7 @@ fn layout(
     mut inset: Sides<Rel<Abs>>,
     outset: Sides<Rel<Abs>>,
     radius: Corners<Rel<Abs>>,
+    opacity: Ratio,
     span: Span,
 ) -> SourceResult<Fragment> {
     let resolved = sizing
@@ -537,6 +570,9 @@ fn layout(
         }
     }
 
+    // Fade the shape and its content.
+    frame.fade(opacity);
+
     // Apply metadata.
     frame.meta(styles, false);
 
@@ -0,0 +1,325 @@
+//! Analysis of source files for language servers.
+//!
+//! The functions in this module answer what an editor asks about a position
+//! in a source file: What is there, where is it defined and what could be
+//! typed there. They work on the same incremental [sources](Source) that are
+//! compiled, so a language server can keep a single world around and edit its
+//! sources in place. Labels are looked up in the `frames` of the last
+//! compilation.
+
+pub use crate::ide::{autocomplete, tooltip, Completion, CompletionKind, Jump, Tooltip};
+
+use crate::doc::Frame;
+use crate::ide::resolve_import;
+use crate::model::Introspector;
+use crate::syntax::ast::{self, AstNode};
+use crate::syntax::{LinkedNode, Source, SyntaxKind};
+use crate::World;
+
+/// Find where the name or label under the cursor is defined.
+///
+/// Names are resolved to the `let` binding, loop variable, parameter or
+/// imported binding that defines them. References and labels are resolved to
+/// the label in the markup. Returns `None` for names from the standard
+/// library, which have no definition in a source file.
+pub fn definition(
+    world: &(dyn World + 'static),
+    frames: &[Frame],
+    source: &Source,
+    cursor: usize,
+) -> Option<Jump> {
+    let leaf = LinkedNode::new(source.root()).leaf_at(cursor)?;
+    match leaf.kind() {
+        SyntaxKind::Ident | SyntaxKind::MathIdent => {
+            binding_definition(world, source, &leaf)
+        }
+        SyntaxKind::RefMarker => {
+            label_definition(world, frames, source, &leaf.text()[1..])
+        }
+        SyntaxKind::Label => {
+            let text = leaf.text();
+            label_definition(world, frames, source, &text[1..text.len() - 1])
+        }
+        _ => None,
+    }
+}
+
+/// Find the binding that defines the identifier under the cursor.
+fn binding_definition(
+    world: &(dyn World + 'static),
+    source: &Source,
+    leaf: &LinkedNode,
+) -> Option<Jump> {
+    let name = leaf.text().as_str();
+    let jump = |node: &LinkedNode, ident: ast::Ident| {
+        let node = node.find(ident.span())?;
+        Some(Jump::Source(source.id(), node.offset()))
+    };
+
+    let mut ancestor = Some(leaf.clone());
+    while let Some(node) = &ancestor {
+        // Bindings are visible after their definition.
+        let mut sibling = node.prev_sibling();
+        while let Some(node) = &sibling {
+            if let Some(binding) = node.cast::<ast::LetBinding>() {
+                if let Some(ident) = binding
+                    .kind()
+                    .idents()
+                    .into_iter()
+                    .find(|ident| ident.as_str() == name)
+                {
+                    return jump(node, ident);
+                }
+            }
+
+            if let Some(import) = node.cast::<ast::ModuleImport>() {
+                if let Some(jump) = import_definition(world, source, &import, name) {
+                    return Some(jump);
+                }
+            }
+
+            sibling = node.prev_sibling();
+        }
+
+        let Some(parent) = node.parent() else { break };
+
+        // Loop variables are visible in the loop's body.
+        if let Some(v) = parent.cast::<ast::ForLoop>() {
+            if node.prev_sibling_kind() != Some(SyntaxKind::In) {
+                if let Some(ident) =
+                    v.pattern().idents().into_iter().find(|ident| ident.as_str() == name)
+                {
+                    return jump(parent, ident);
+                }
+            }
+        }
+
+        // Parameters and the closure's own name are visible in its body.
+        if let Some(v) = parent.cast::<ast::Closure>() {
+            let params = v.params().children().flat_map(|param| match param {
+                ast::Param::Pos(pattern) => pattern.idents(),
+                ast::Param::Named(named) => vec![named.name()],
+                ast::Param::Sink(spread) => spread.name().into_iter().collect(),
+            });
+            if let Some(ident) = v
+                .name()
+                .into_iter()
+                .chain(params)
+                .find(|ident| ident.as_str() == name)
+            {
+                return jump(parent, ident);
+            }
+        }
+
+        ancestor = Some(parent.clone());
+    }
+
+    None
+}
+
+/// Find the binding that an import brings into scope under the given name.
+fn import_definition(
+    world: &(dyn World + 'static),
+    source: &Source,
+    import: &ast::ModuleImport,
+    name: &str,
+) -> Option<Jump> {
+    match import.imports()? {
+        ast::Imports::Wildcard => {}
+        ast::Imports::Items(items) => {
+            if !items.iter().any(|ident| ident.as_str() == name) {
+                return None;
+            }
+        }
+    }
+
+    let ast::Expr::Str(path) = import.source() else { return None };
+    let id = resolve_import(world, source, &path.get())?;
+    let imported = world.source(id);
+
+    // Only top-level bindings of a file are exported.
+    let root = LinkedNode::new(imported.root());
+    root.children().rev().find_map(|node| {
+        let binding = node.cast::<ast::LetBinding>()?;
+        let ident = binding
+            .kind()
+            .idents()
+            .into_iter()
+            .find(|ident| ident.as_str() == name)?;
+        let node = node.find(ident.span())?;
+        Some(Jump::Source(id, node.offset()))
+    })
+}
+
+/// Find the label with the given name.
+///
+/// Labels in the current file are preferred. Labels in other files are found
+/// through the elements in the document they are attached to.
+fn label_definition(
+    world: &(dyn World + 'static),
+    frames: &[Frame],
+    source: &Source,
+    name: &str,
+) -> Option<Jump> {
+    if let Some(offset) = find_label(&LinkedNode::new(source.root()), name) {
+        return Some(Jump::Source(source.id(), offset));
+    }
+
+    let introspector = Introspector::new(frames);
+    let elem = introspector
+        .all()
+        .find(|elem| elem.label().map_or(false, |label| label.0.as_str() == name))?;
+    let span = elem.span();
+    if span.is_detached() {
+        let loc = elem.location()?;
+        return Some(Jump::Position(introspector.position(loc)));
+    }
+
+    let source = world.source(span.source());
+    let offset = match find_label(&LinkedNode::new(source.root()), name) {
+        Some(offset) => offset,
+        None => source.find(span)?.offset(),
+    };
+    Some(Jump::Source(source.id(), offset))
+}
+
+/// Find the offset of a label node with the given name.
+fn find_label(node: &LinkedNode, name: &str) -> Option<usize> {
+    if node.kind() == SyntaxKind::Label && node.parent_kind() == Some(SyntaxKind::Markup)
+    {
+        let text = node.text();
+        return (text[1..text.len() - 1] == *name).then(|| node.offset());
+    }
+
+    node.children().find_map(|child| find_label(&child, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use comemo::Prehashed;
+
+    use super::*;
+    use crate::diag::{FileError, FileResult};
+    use crate::eval::{Datetime, Library};
+    use crate::font::{Font, FontBook};
+    use crate::syntax::{SourceId, Span};
+    use crate::util::{Buffer, WriteMode};
+
+    /// A world with just a few sources.
+    struct TestWorld {
+        sources: Vec<Source>,
+        book: Prehashed<FontBook>,
+    }
+
+    impl TestWorld {
+        fn new(sources: &[(&str, &str)]) -> Self {
+            let sources = sources
+                .iter()
+                .enumerate()
+                .map(|(i, (path, text))| {
+                    Source::new(
+                        SourceId::from_u16(i as u16),
+                        Path::new(path),
+                        text.to_string(),
+                    )
+                })
+                .collect();
+            Self { sources, book: Prehashed::new(FontBook::new()) }
+        }
+    }
+
+    impl World for TestWorld {
+        fn library(&self) -> &Prehashed<Library> {
+            unreachable!("definitions don't depend on the standard library")
+        }
+
+        fn main(&self) -> &Source {
+            &self.sources[0]
+        }
+
+        fn resolve(&self, path: &Path) -> FileResult<SourceId> {
+            self.sources
+                .iter()
+                .find(|source| source.path() == path)
+                .map(Source::id)
+                .ok_or_else(|| FileError::NotFound(path.into()))
+        }
+
+        fn source(&self, id: SourceId) -> &Source {
+            &self.sources[id.as_u16() as usize]
+        }
+
+        fn book(&self) -> &Prehashed<FontBook> {
+            &self.book
+        }
+
+        fn font(&self, _: usize) -> Option<Font> {
+            None
+        }
+
+        fn read(&self, path: &Path) -> FileResult<Buffer> {
+            Err(FileError::NotFound(path.into()))
+        }
+
+        fn write(
+            &self,
+            path: &Path,
+            _: Option<i64>,
+            _: WriteMode,
+            _: Vec<u8>,
+            _: Span,
+        ) -> FileResult<()> {
+            Err(FileError::OutsideDest(path.into()))
+        }
+
+        fn today(&self, _: Option<i64>) -> Option<Datetime> {
+            None
+        }
+    }
+
+    /// Find the definition of the text after the first occurrence of `at` in
+    /// the main file and return the file and the text at the definition.
+    #[track_caller]
+    fn test(sources: &[(&str, &str)], at: &str) -> Option<(usize, String)> {
+        let world = TestWorld::new(sources);
+        let main = world.main();
+        let cursor = main.text().find(at).unwrap() + at.len();
+        match definition(&world, &[], main, cursor)? {
+            Jump::Source(id, offset) => {
+                let text = &world.source(id).text()[offset..];
+                let end = text.find(|c: char| !c.is_alphanumeric()).unwrap_or(text.len());
+                Some((id.as_u16() as usize, text[..end].into()))
+            }
+            jump => panic!("unexpected jump {jump:?}"),
+        }
+    }
+
+    #[test]
+    fn test_definition_local() {
+        let main = "#let x = 1\n#let f(y) = x + y\n#for z in () { z }\n#x";
+        assert_eq!(test(&[("/main.typ", main)], "= x"), Some((0, "x".into())));
+        assert_eq!(test(&[("/main.typ", main)], "+ y"), Some((0, "y".into())));
+        assert_eq!(test(&[("/main.typ", main)], "{ z"), Some((0, "z".into())));
+        assert_eq!(test(&[("/main.typ", main)], "}\n#x"), Some((0, "x".into())));
+    }
+
+    #[test]
+    fn test_definition_imported() {
+        let main = "#import \"lib.typ\": a\n#import \"other.typ\": *\n#a #b";
+        let lib = "#let b = 1\n#let a = 2";
+        let other = "#let b = 3";
+        let sources = [("/main.typ", main), ("/lib.typ", lib), ("/other.typ", other)];
+        assert_eq!(test(&sources, "#a"), Some((1, "a".into())));
+        assert_eq!(test(&sources, "#b"), Some((2, "b".into())));
+    }
+
+    #[test]
+    fn test_definition_std() {
+        let main = "#let x = rect\n#rect()\n$pi$";
+        assert_eq!(test(&[("/main.typ", main)], "= rect"), None);
+        assert_eq!(test(&[("/main.typ", main)], "#rect"), None);
+        assert_eq!(test(&[("/main.typ", main)], "$pi"), None);
+    }
+}
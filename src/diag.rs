@@ -80,6 +80,8 @@ pub struct SourceError {
     pub pos: ErrorPos,
     /// A diagnostic message describing the problem.
     pub message: EcoString,
+    /// What kind of problem this is.
+    pub kind: ErrorKind,
     /// The trace of function calls leading to the error.
     pub trace: Vec<Spanned<Tracepoint>>,
 }
@@ -92,6 +94,7 @@ impl SourceError {
             pos: ErrorPos::Full,
             trace: vec![],
             message: message.into(),
+            kind: ErrorKind::Other,
         }
     }
 
@@ -101,6 +104,12 @@ impl SourceError {
         self
     }
 
+    /// Set what kind of problem this is.
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
     /// The range in the source file identified by
     /// [`self.span.source()`](Span::source) where the error should be
     /// annotated.
@@ -114,6 +123,18 @@ impl SourceError {
     }
 }
 
+/// What kind of problem an error or warning describes.
+///
+/// This lets applications treat some problems specially without matching on
+/// their messages.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ErrorKind {
+    /// A requested font family is not available.
+    UnknownFontFamily,
+    /// Any other problem.
+    Other,
+}
+
 /// A part of an error's [trace](SourceError::trace).
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Tracepoint {
@@ -214,6 +235,14 @@ pub enum FileError {
     WrongMode,
     /// The file was not valid UTF-8, but should have been.
     InvalidUtf8,
+    /// The file is written to with different write modes.
+    ConflictingModes,
+    /// The file must be written to only once, but is written to again.
+    WrittenTwice,
+    /// The file should have been created, but already exists.
+    AlreadyExists(PathBuf),
+    /// The file would be written outside of the destination directory.
+    OutsideDest(PathBuf),
     /// Access to this file was disabled from within the source code
     /// Not returned by any function, but may be set manually by developpers.
     Disabled,
@@ -227,6 +256,7 @@ impl FileError {
         match error.kind() {
             io::ErrorKind::NotFound => Self::NotFound(path.into()),
             io::ErrorKind::PermissionDenied => Self::AccessDenied,
+            io::ErrorKind::AlreadyExists => Self::AlreadyExists(path.into()),
             io::ErrorKind::InvalidData
                 if error.to_string().contains("stream did not contain valid UTF-8") =>
             {
@@ -251,6 +281,20 @@ impl Display for FileError {
             Self::NotSource => f.pad("not a typst source file"),
             Self::WrongMode => f.pad("tried to read and write to the same file"),
             Self::InvalidUtf8 => f.pad("file is not valid utf-8"),
+            Self::ConflictingModes => {
+                f.pad("file is written to with conflicting write modes")
+            }
+            Self::WrittenTwice => f.pad("file can only be written to once"),
+            Self::AlreadyExists(path) => {
+                write!(f, "file already exists (at {})", path.display())
+            }
+            Self::OutsideDest(path) => {
+                write!(
+                    f,
+                    "cannot write outside of the destination directory (at {})",
+                    path.display()
+                )
+            }
             Self::Disabled => f.pad("access was disabled by devoppement team"), //maybe not the clearest message
             Self::Other => f.pad("failed to load file"),
         }
@@ -8,11 +8,11 @@ use std::sync::Arc;
 
 use ecow::EcoString;
 
-use crate::eval::{cast, dict, Dict, Value};
+use crate::eval::{cast, dict, Datetime, Dict, Value};
 use crate::font::Font;
 use crate::geom::{
     self, rounded_rect, Abs, Align, Axes, Color, Corners, Dir, Em, Geometry, Length,
-    Numeric, Paint, Point, Rel, RgbaColor, Shape, Sides, Size, Stroke, Transform,
+    Numeric, Paint, Point, Ratio, Rel, RgbaColor, Shape, Sides, Size, Stroke, Transform,
 };
 use crate::image::Image;
 use crate::model::{Content, Location, MetaElem, StyleChain};
@@ -27,6 +27,15 @@ pub struct Document {
     pub title: Option<EcoString>,
     /// The document's author.
     pub author: Vec<EcoString>,
+    /// The document's keywords.
+    pub keywords: Vec<EcoString>,
+    /// The document's creation date, in UTC. Not recorded if `None`, so that
+    /// exports are reproducible.
+    pub date: Option<Datetime>,
+    /// The content the document was typeset from, before show rules were
+    /// applied. Used by exporters that preserve the document's structure
+    /// instead of its layout.
+    pub content: Content,
 }
 
 /// A finished layout with items at fixed positions.
@@ -320,6 +329,16 @@ impl Frame {
         }
     }
 
+    /// Make the contents of a frame translucent.
+    ///
+    /// The opacity is clamped to the range from zero to one.
+    pub fn fade(&mut self, opacity: Ratio) {
+        let opacity = Ratio::new(opacity.get().clamp(0.0, 1.0));
+        if !self.is_empty() && !opacity.is_one() {
+            self.group(|g| g.opacity = opacity);
+        }
+    }
+
     /// Wrap the frame's contents in a group and modify that group with `f`.
     fn group<F>(&mut self, f: F)
     where
@@ -402,7 +421,7 @@ impl Debug for Frame {
 /// The building block frames are composed of.
 #[derive(Clone, Hash)]
 pub enum FrameItem {
-    /// A subframe with optional transformation and clipping.
+    /// A subframe with optional transformation, clipping and opacity.
     Group(GroupItem),
     /// A run of shaped text.
     Text(TextItem),
@@ -426,7 +445,7 @@ impl Debug for FrameItem {
     }
 }
 
-/// A subframe with optional transformation and clipping.
+/// A subframe with optional transformation, clipping and opacity.
 #[derive(Clone, Hash)]
 pub struct GroupItem {
     /// The group's frame.
@@ -435,6 +454,8 @@ pub struct GroupItem {
     pub transform: Transform,
     /// Whether the frame should be a clipping boundary.
     pub clips: bool,
+    /// How opaque the group's contents are, between zero and one.
+    pub opacity: Ratio,
 }
 
 impl GroupItem {
@@ -444,6 +465,7 @@ impl GroupItem {
             frame,
             transform: Transform::identity(),
             clips: false,
+            opacity: Ratio::one(),
         }
     }
 }
@@ -468,6 +490,10 @@ pub struct TextItem {
     pub lang: Lang,
     /// The item's plain text.
     pub text: EcoString,
+    /// The horizontal scale of the glyph outlines. This differs from one
+    /// when the glyphs were expanded or compressed during justification. The
+    /// glyphs' advances already include the scaling.
+    pub x_scale: Ratio,
     /// The glyphs.
     pub glyphs: Vec<Glyph>,
 }
@@ -623,6 +649,9 @@ pub enum Meta {
     Elem(Content),
     /// The numbering of the current page.
     PageNumbering(Value),
+    /// A print production box of the current page. The box spans the area
+    /// this metadata is attached to.
+    PageBox(PageBox),
     /// Indicates that content should be hidden. This variant doesn't appear
     /// in the final frames as it is removed alongside the content that should
     /// be hidden.
@@ -639,11 +668,22 @@ impl Debug for Meta {
             Self::Link(dest) => write!(f, "Link({dest:?})"),
             Self::Elem(content) => write!(f, "Elem({:?})", content.func()),
             Self::PageNumbering(value) => write!(f, "PageNumbering({value:?})"),
+            Self::PageBox(kind) => write!(f, "PageBox({kind:?})"),
             Self::Hide => f.pad("Hide"),
         }
     }
 }
 
+/// A box that delimits a region of a printed page.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PageBox {
+    /// The region to which the page's fill and background extend so that no
+    /// unprinted edges remain after trimming.
+    Bleed,
+    /// The intended dimensions of the page after trimming.
+    Trim,
+}
+
 /// A link destination.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Destination {
@@ -0,0 +1,128 @@
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Deref;
+
+use ecow::{eco_format, EcoString};
+
+use super::{cast, Array, IntoValue, Value};
+use crate::diag::StrResult;
+use crate::util::Buffer;
+
+/// A sequence of bytes.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct Bytes(Buffer);
+
+impl Bytes {
+    /// The number of bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether there are no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Extract the byte at the given index.
+    pub fn at(&self, index: i64, default: Option<Value>) -> StrResult<Value> {
+        self.locate(index)
+            .and_then(|i| self.0.get(i))
+            .map(|&byte| byte.into_value())
+            .or(default)
+            .ok_or_else(|| out_of_bounds_no_default(index, self.len()))
+    }
+
+    /// Extract a contiguous subregion of the bytes.
+    pub fn slice(&self, start: i64, end: Option<i64>) -> StrResult<Self> {
+        let len = self.len();
+        let start = self
+            .locate(start)
+            .filter(|&start| start <= len)
+            .ok_or_else(|| out_of_bounds(start, len))?;
+
+        let end = end.unwrap_or(len as i64);
+        let end = self
+            .locate(end)
+            .filter(|&end| end <= len)
+            .ok_or_else(|| out_of_bounds(end, len))?
+            .max(start);
+
+        Ok(Self::from(&self.0[start..end]))
+    }
+
+    /// The bytes as an array of integers.
+    pub fn to_array(&self) -> Array {
+        self.0.iter().map(|&byte| byte.into_value()).collect()
+    }
+
+    /// The underlying buffer.
+    pub fn buffer(&self) -> &Buffer {
+        &self.0
+    }
+
+    /// Resolve an index.
+    fn locate(&self, index: i64) -> Option<usize> {
+        usize::try_from(if index >= 0 {
+            index
+        } else {
+            (self.len() as i64).checked_add(index)?
+        })
+        .ok()
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl From<Buffer> for Bytes {
+    fn from(buffer: Buffer) -> Self {
+        Self(buffer)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(slice: &[u8]) -> Self {
+        Self(slice.into())
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(vec: Vec<u8>) -> Self {
+        Self(vec.into())
+    }
+}
+
+impl Debug for Bytes {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "bytes({})", self.len())
+    }
+}
+
+cast! {
+    type Bytes: "bytes",
+}
+
+/// The out of bounds access error message.
+#[cold]
+fn out_of_bounds(index: i64, len: usize) -> EcoString {
+    eco_format!("byte index out of bounds (index: {index}, len: {len})")
+}
+
+/// The out of bounds access error message when no default value was given.
+#[cold]
+fn out_of_bounds_no_default(index: i64, len: usize) -> EcoString {
+    eco_format!(
+        "byte index out of bounds (index: {index}, len: {len}) \
+         and no default value was specified",
+    )
+}
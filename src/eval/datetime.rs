@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 use std::hash::Hash;
@@ -6,7 +7,7 @@ use ecow::{eco_format, EcoString, EcoVec};
 use time::error::{Format, InvalidFormatDescription};
 use time::{format_description, PrimitiveDateTime};
 
-use crate::eval::cast;
+use crate::eval::{cast, Duration};
 use crate::util::pretty_array_like;
 
 /// A datetime object that represents either a date, a time or a combination of
@@ -43,6 +44,87 @@ impl Datetime {
         formatted_result.map_err(format_time_format_error)
     }
 
+    /// Parse a date and/or time from a string in a certain format.
+    ///
+    /// Without a pattern, the formats that `display` uses by default are
+    /// tried one after another. The kind of the result depends on which
+    /// components the pattern has.
+    pub fn parse(text: &str, pattern: Option<EcoString>) -> Result<Self, EcoString> {
+        let patterns = match &pattern {
+            Some(pattern) => vec![pattern.as_str()],
+            None => vec![
+                "[year]-[month]-[day] [hour]:[minute]:[second]",
+                "[year]-[month]-[day]",
+                "[hour]:[minute]:[second]",
+            ],
+        };
+
+        for pattern in patterns {
+            let format = format_description::parse(pattern)
+                .map_err(format_time_invalid_format_description_error)?;
+            if let Ok(datetime) = PrimitiveDateTime::parse(text, &format) {
+                return Ok(Self::Datetime(datetime));
+            } else if let Ok(date) = time::Date::parse(text, &format) {
+                return Ok(Self::Date(date));
+            } else if let Ok(time) = time::Time::parse(text, &format) {
+                return Ok(Self::Time(time));
+            }
+        }
+
+        Err(match pattern {
+            Some(pattern) => eco_format!("failed to parse {text:?} as {pattern:?}"),
+            None => eco_format!("failed to parse {text:?} as a date or time"),
+        })
+    }
+
+    /// Add a duration to the datetime.
+    ///
+    /// Times wrap around at midnight. Only whole days can be added to dates.
+    pub fn checked_add(self, duration: Duration) -> Result<Self, EcoString> {
+        let duration = time::Duration::from(duration);
+        Ok(match self {
+            Self::Date(date) => {
+                if duration.whole_seconds() % 86400 != 0 {
+                    return Err(
+                        "cannot add a duration shorter than a day to a date".into()
+                    );
+                }
+                Self::Date(date.checked_add(duration).ok_or("date is out of range")?)
+            }
+            Self::Time(time) => Self::Time(time + duration),
+            Self::Datetime(datetime) => Self::Datetime(
+                datetime.checked_add(duration).ok_or("datetime is out of range")?,
+            ),
+        })
+    }
+
+    /// Subtract a duration from the datetime.
+    pub fn checked_sub(self, duration: Duration) -> Result<Self, EcoString> {
+        self.checked_add(duration.checked_neg().ok_or("duration is out of range")?)
+    }
+
+    /// The duration between two datetimes of the same kind.
+    pub fn difference(self, other: Self) -> Option<Duration> {
+        Some(
+            match (self, other) {
+                (Self::Date(a), Self::Date(b)) => a - b,
+                (Self::Time(a), Self::Time(b)) => a - b,
+                (Self::Datetime(a), Self::Datetime(b)) => a - b,
+                _ => return None,
+            }
+            .into(),
+        )
+    }
+
+    /// The kind of datetime, for use in error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Date(_) => "date",
+            Self::Time(_) => "time",
+            Self::Datetime(_) => "datetime",
+        }
+    }
+
     /// Return the year of the datetime, if existing.
     pub fn year(&self) -> Option<i32> {
         match self {
@@ -136,6 +218,17 @@ impl Datetime {
     }
 }
 
+impl PartialOrd for Datetime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Date(a), Self::Date(b)) => a.partial_cmp(b),
+            (Self::Time(a), Self::Time(b)) => a.partial_cmp(b),
+            (Self::Datetime(a), Self::Datetime(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
 impl Debug for Datetime {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let year = self.year().map(|y| eco_format!("year: {y}"));
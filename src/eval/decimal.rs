@@ -0,0 +1,316 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// An exact decimal number.
+///
+/// The number is stored as an integer mantissa with up to 38 significant
+/// digits together with the number of digits after the decimal point.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Decimal {
+    /// The digits of the number, without the decimal point.
+    mantissa: i128,
+    /// How many of the mantissa's digits come after the decimal point.
+    scale: u32,
+}
+
+impl Decimal {
+    /// The maximum number of digits after the decimal point.
+    pub const MAX_SCALE: u32 = 28;
+
+    /// Create a decimal from a mantissa and a scale.
+    ///
+    /// Digits beyond the maximum scale are rounded off and trailing zeros are
+    /// removed, so that equal numbers have equal representations.
+    fn new(mut mantissa: i128, mut scale: u32) -> Self {
+        if scale > Self::MAX_SCALE {
+            mantissa = match pow10(scale - Self::MAX_SCALE) {
+                Some(divisor) => round_div(mantissa, divisor),
+                None => 0,
+            };
+            scale = Self::MAX_SCALE;
+        }
+
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+
+        Self { mantissa, scale }
+    }
+
+    /// Parse a decimal from its textual representation, like `-12.345`.
+    ///
+    /// Digits beyond the maximum scale are rounded off. Returns `None` if the
+    /// text is malformed or the number has too many digits.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+
+        let (int, fract) = digits.split_once('.').unwrap_or((digits, ""));
+        if (int.is_empty() && fract.is_empty())
+            || !int.bytes().chain(fract.bytes()).all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        let (kept, rest) = fract.split_at(fract.len().min(Self::MAX_SCALE as usize));
+        let mut mantissa = 0_i128;
+        for c in int.bytes().chain(kept.bytes()) {
+            mantissa = mantissa.checked_mul(10)?.checked_add(i128::from(c - b'0'))?;
+        }
+
+        if rest.bytes().next().map_or(false, |c| c >= b'5') {
+            mantissa = mantissa.checked_add(1)?;
+        }
+
+        if negative {
+            mantissa = -mantissa;
+        }
+
+        Some(Self::new(mantissa, kept.len() as u32))
+    }
+
+    /// Convert a float to the decimal with the same shortest representation.
+    ///
+    /// Returns `None` for non-finite floats and floats that are too large.
+    pub fn from_f64(v: f64) -> Option<Self> {
+        if !v.is_finite() {
+            return None;
+        }
+
+        Self::parse(&v.to_string())
+    }
+
+    /// Convert the decimal to the closest float.
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// The integer part of the decimal, if it fits into a 64-bit integer.
+    pub fn to_i64(self) -> Option<i64> {
+        let (int, _) = self.split();
+        int.try_into().ok()
+    }
+
+    /// Whether the decimal is zero.
+    pub fn is_zero(self) -> bool {
+        self.mantissa == 0
+    }
+
+    /// The absolute value, if that doesn't overflow.
+    pub fn checked_abs(self) -> Option<Self> {
+        Some(Self { mantissa: self.mantissa.checked_abs()?, ..self })
+    }
+
+    /// Negate the decimal, if that doesn't overflow.
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Self { mantissa: self.mantissa.checked_neg()?, ..self })
+    }
+
+    /// Add two decimals, if that doesn't overflow.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+        Some(Self::new(a.checked_add(b)?, scale))
+    }
+
+    /// Subtract a decimal from another, if that doesn't overflow.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+        Some(Self::new(a.checked_sub(b)?, scale))
+    }
+
+    /// Multiply two decimals, if that doesn't overflow.
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let mantissa = self.mantissa.checked_mul(other.mantissa)?;
+        Some(Self::new(mantissa, self.scale + other.scale))
+    }
+
+    /// Divide a decimal by another one, rounding to the maximum scale.
+    ///
+    /// Returns `None` if the divisor is zero or the result overflows.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+
+        let divisor = other.mantissa.unsigned_abs();
+        let mut rest = self.mantissa.unsigned_abs();
+        let mut quotient = rest / divisor;
+        rest %= divisor;
+
+        // Produce digits after the decimal point until the division is exact
+        // or no more digits would be kept.
+        let limit = (Self::MAX_SCALE + other.scale).saturating_sub(self.scale);
+        let mut digits = 0;
+        while rest != 0 && digits < limit {
+            let (Some(q), Some(r)) = (quotient.checked_mul(10), rest.checked_mul(10))
+            else {
+                break;
+            };
+            quotient = q.checked_add(r / divisor)?;
+            rest = r % divisor;
+            digits += 1;
+        }
+
+        if rest >= divisor - rest {
+            quotient = quotient.checked_add(1)?;
+        }
+
+        let mut mantissa = i128::try_from(quotient).ok()?;
+        if (self.mantissa < 0) != (other.mantissa < 0) {
+            mantissa = -mantissa;
+        }
+
+        let scale = i64::from(digits) + i64::from(self.scale) - i64::from(other.scale);
+        if scale < 0 {
+            mantissa = mantissa.checked_mul(pow10(scale.unsigned_abs() as u32)?)?;
+        }
+
+        Some(Self::new(mantissa, scale.max(0) as u32))
+    }
+
+    /// Round to a number of digits after the decimal point, with halves
+    /// rounded away from zero. Negative digits round to tens, hundreds, etc.
+    pub fn round(self, digits: i64) -> Option<Self> {
+        let drop = i64::from(self.scale) - digits;
+        if drop <= 0 {
+            return Some(self);
+        }
+
+        let mantissa = match u32::try_from(drop).ok().and_then(pow10) {
+            Some(divisor) => round_div(self.mantissa, divisor),
+            None => 0,
+        };
+
+        if digits >= 0 {
+            Some(Self::new(mantissa, digits as u32))
+        } else {
+            let factor = u32::try_from(-digits).ok().and_then(pow10);
+            let mantissa =
+                if mantissa == 0 { 0 } else { mantissa.checked_mul(factor?)? };
+            Some(Self::new(mantissa, 0))
+        }
+    }
+
+    /// Bring two decimals to the same scale, if that doesn't overflow.
+    fn align(self, other: Self) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        let a = self.mantissa.checked_mul(pow10(scale - self.scale)?)?;
+        let b = other.mantissa.checked_mul(pow10(scale - other.scale)?)?;
+        Some((a, b, scale))
+    }
+
+    /// Split the decimal into its integer part and its fractional part, the
+    /// latter scaled to the maximum scale.
+    fn split(self) -> (i128, i128) {
+        let unit = pow10(self.scale).unwrap();
+        let fract = self.mantissa % unit * pow10(Self::MAX_SCALE - self.scale).unwrap();
+        (self.mantissa / unit, fract)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(v: i64) -> Self {
+        Self { mantissa: v.into(), scale: 0 }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.split().cmp(&other.split())
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.mantissa < 0 {
+            f.write_str("-")?;
+        }
+
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            f.write_str(&digits)
+        } else if digits.len() <= scale {
+            write!(f, "0.{}{digits}", "0".repeat(scale - digits.len()))
+        } else {
+            let (int, fract) = digits.split_at(digits.len() - scale);
+            write!(f, "{int}.{fract}")
+        }
+    }
+}
+
+impl Debug for Decimal {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "decimal(\"{self}\")")
+    }
+}
+
+/// Ten to the power of `exp`, if it fits.
+fn pow10(exp: u32) -> Option<i128> {
+    10_i128.checked_pow(exp)
+}
+
+/// Divide two integers, rounding halves away from zero.
+fn round_div(n: i128, d: i128) -> i128 {
+    let (quotient, rest) = (n / d, n % d);
+    if rest.unsigned_abs() >= d.unsigned_abs() - rest.unsigned_abs() {
+        quotient + n.signum()
+    } else {
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn dec(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_decimal_parse_and_display() {
+        assert_eq!(dec("1.50").to_string(), "1.5");
+        assert_eq!(dec("-0.05").to_string(), "-0.05");
+        assert_eq!(dec("+12").to_string(), "12");
+        assert_eq!(dec(".5").to_string(), "0.5");
+        assert_eq!(dec("0.00").to_string(), "0");
+        assert_eq!(Decimal::parse(""), None);
+        assert_eq!(Decimal::parse("1.2.3"), None);
+        assert_eq!(Decimal::parse("1e5"), None);
+    }
+
+    #[test]
+    fn test_decimal_arithmetic() {
+        assert_eq!(dec("0.1").checked_add(dec("0.2")), Some(dec("0.3")));
+        assert_eq!(dec("1").checked_sub(dec("0.01")), Some(dec("0.99")));
+        assert_eq!(dec("1.5").checked_mul(dec("-2")), Some(dec("-3")));
+        assert_eq!(dec("1").checked_div(dec("4")), Some(dec("0.25")));
+        assert_eq!(dec("10").checked_div(dec("0.5")), Some(dec("20")));
+        assert_eq!(
+            dec("2").checked_div(dec("3")),
+            Some(dec("0.6666666666666666666666666667"))
+        );
+        assert_eq!(dec("1").checked_div(dec("0")), None);
+    }
+
+    #[test]
+    fn test_decimal_round_and_compare() {
+        assert_eq!(dec("2.345").round(2), Some(dec("2.35")));
+        assert_eq!(dec("-2.345").round(2), Some(dec("-2.35")));
+        assert_eq!(dec("1234.5").round(-2), Some(dec("1200")));
+        assert!(dec("-1.5") < dec("-1.2"));
+        assert!(dec("-0.5") < dec("0.2"));
+        assert!(dec("100000000000000000000000000000") > dec("1.5"));
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt::{self, Debug, Formatter};
+
+use ecow::{eco_format, EcoVec};
+
+use crate::eval::cast;
+use crate::util::pretty_array_like;
+
+/// A span of time, which may be negative.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration(time::Duration);
+
+impl Duration {
+    /// Create a duration from a number of weeks, days, hours, minutes and
+    /// seconds.
+    pub fn from_parts(
+        weeks: i64,
+        days: i64,
+        hours: i64,
+        minutes: i64,
+        seconds: i64,
+    ) -> Option<Self> {
+        let total = weeks
+            .checked_mul(7)?
+            .checked_add(days)?
+            .checked_mul(24)?
+            .checked_add(hours)?
+            .checked_mul(60)?
+            .checked_add(minutes)?
+            .checked_mul(60)?
+            .checked_add(seconds)?;
+        Some(Self(time::Duration::seconds(total)))
+    }
+
+    /// Whether the duration is zero.
+    pub fn is_zero(self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// The duration in seconds.
+    pub fn seconds(self) -> f64 {
+        self.0.as_seconds_f64()
+    }
+
+    /// The duration in minutes.
+    pub fn minutes(self) -> f64 {
+        self.seconds() / 60.0
+    }
+
+    /// The duration in hours.
+    pub fn hours(self) -> f64 {
+        self.minutes() / 60.0
+    }
+
+    /// The duration in days.
+    pub fn days(self) -> f64 {
+        self.hours() / 24.0
+    }
+
+    /// The duration in weeks.
+    pub fn weeks(self) -> f64 {
+        self.days() / 7.0
+    }
+
+    /// Negate the duration, if that doesn't overflow.
+    pub fn checked_neg(self) -> Option<Self> {
+        self.0.checked_neg().map(Self)
+    }
+
+    /// Add two durations, if that doesn't overflow.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    /// Subtract a duration from another, if that doesn't overflow.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    /// Scale the duration by a factor, if the result is representable.
+    pub fn checked_mul(self, factor: f64) -> Option<Self> {
+        let seconds = self.seconds() * factor;
+        (seconds.is_finite() && seconds.abs() < i64::MAX as f64)
+            .then(|| Self(time::Duration::seconds_f64(seconds)))
+    }
+
+    /// How often the other duration fits into this one.
+    pub fn ratio(self, other: Self) -> f64 {
+        self.seconds() / other.seconds()
+    }
+}
+
+impl From<time::Duration> for Duration {
+    fn from(duration: time::Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<Duration> for time::Duration {
+    fn from(duration: Duration) -> Self {
+        duration.0
+    }
+}
+
+impl Debug for Duration {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut rest = self.0.whole_seconds();
+        let mut parts = EcoVec::new();
+        for (unit, size) in [
+            ("weeks", 7 * 24 * 60 * 60),
+            ("days", 24 * 60 * 60),
+            ("hours", 60 * 60),
+            ("minutes", 60),
+            ("seconds", 1),
+        ] {
+            let amount = rest / size;
+            rest %= size;
+            if amount != 0 || (unit == "seconds" && parts.is_empty()) {
+                parts.push(eco_format!("{unit}: {amount}"));
+            }
+        }
+
+        write!(f, "duration{}", &pretty_array_like(&parts, false))
+    }
+}
+
+cast! {
+    type Duration: "duration",
+}
@@ -6,7 +6,7 @@ use comemo::Tracked;
 use ecow::EcoString;
 use std::sync::OnceLock;
 
-use super::{Args, Dynamic, Module, Value, Vm};
+use super::{Args, Dynamic, Module, Quantity, Value, Vm};
 use crate::diag::SourceResult;
 use crate::doc::Document;
 use crate::geom::{Abs, Dir};
@@ -105,6 +105,8 @@ pub struct LangItems {
     pub math_frac: fn(num: Content, denom: Content) -> Content,
     /// A root in math: `√x`, `∛x` or `∜x`.
     pub math_root: fn(index: Option<Content>, radicand: Content) -> Content,
+    /// A quantity inserted into the document.
+    pub quantity: fn(quantity: Quantity) -> Content,
     /// Dispatch a method on a library value.
     pub library_method: fn(
         vm: &mut Vm,
@@ -152,6 +154,7 @@ impl Hash for LangItems {
         self.math_accent.hash(state);
         self.math_frac.hash(state);
         self.math_root.hash(state);
+        self.quantity.hash(state);
         (self.library_method as usize).hash(state);
     }
 }
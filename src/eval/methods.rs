@@ -1,12 +1,13 @@
 //! Methods on values.
 
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
 
-use super::{Args, IntoValue, Str, Value, Vm};
-use crate::diag::{At, SourceResult};
-use crate::eval::Datetime;
+use super::{array, Args, IntoValue, Str, Value, Vm};
+use crate::diag::{bail, At, SourceResult};
+use crate::eval::{Bytes, Datetime, Duration, Quantity, Records};
+use crate::geom::{Angle, Ratio, RgbaColor};
 use crate::model::{Location, Selector};
-use crate::syntax::Span;
+use crate::syntax::{Span, Spanned};
 
 /// Call a method on a value.
 pub fn call(
@@ -24,6 +25,40 @@ pub fn call(
             "lighten" => color.lighten(args.expect("amount")?).into_value(),
             "darken" => color.darken(args.expect("amount")?).into_value(),
             "negate" => color.negate().into_value(),
+            "mix" => {
+                let other = args.expect("color")?;
+                let ratio = match args.eat::<Spanned<Ratio>>()? {
+                    Some(Spanned { v, span }) if !(0.0..=1.0).contains(&v.get()) => {
+                        bail!(span, "ratio must be between 0% and 100%")
+                    }
+                    Some(ratio) => ratio.v,
+                    None => Ratio::new(0.5),
+                };
+                color.mix(other, ratio).into_value()
+            }
+            "saturate" => color.saturate(args.expect("amount")?).into_value(),
+            "desaturate" => color.desaturate(args.expect("amount")?).into_value(),
+            "rotate" => color.rotate(args.expect("angle")?).into_value(),
+            "to-rgba" => {
+                let RgbaColor { r, g, b, a } = color.to_rgba();
+                array![r, g, b, a].into_value()
+            }
+            "to-hsl" => {
+                let (h, s, l) = color.to_rgba().to_hsl();
+                array![Angle::deg(h), Ratio::new(s), Ratio::new(l)].into_value()
+            }
+            "to-oklab" => {
+                let (l, a, b) = color.to_rgba().to_oklab();
+                array![Ratio::new(l), a, b].into_value()
+            }
+            "to-hex" => {
+                let RgbaColor { r, g, b, a } = color.to_rgba();
+                let mut hex = eco_format!("#{r:02x}{g:02x}{b:02x}");
+                if a != u8::MAX {
+                    hex.push_str(&eco_format!("{a:02x}"));
+                }
+                hex.into_value()
+            }
             _ => return missing(),
         },
 
@@ -65,7 +100,11 @@ pub fn call(
                 let repeat = args.named("repeat")?.unwrap_or(true);
                 string.trim(pattern, at, repeat).into_value()
             }
-            "split" => string.split(args.eat()?).into_value(),
+            "split" => {
+                let pattern = args.eat()?;
+                let count = args.named("count")?;
+                string.split(pattern, count).into_value()
+            }
             _ => return missing(),
         },
 
@@ -184,6 +223,25 @@ pub fn call(
                     }
                     _ => return missing(),
                 }
+            } else if let Some(bytes) = dynamic.downcast::<Bytes>() {
+                match method {
+                    "len" => bytes.len().into_value(),
+                    "at" => {
+                        let index = args.expect("index")?;
+                        let default = args.named::<Value>("default")?;
+                        bytes.at(index, default).at(span)?
+                    }
+                    "slice" => {
+                        let start = args.expect("start")?;
+                        let mut end = args.eat()?;
+                        if end.is_none() {
+                            end = args.named("count")?.map(|c: i64| start + c);
+                        }
+                        bytes.slice(start, end).at(span)?.into_value()
+                    }
+                    "to-array" => bytes.to_array().into_value(),
+                    _ => return missing(),
+                }
             } else if let Some(&datetime) = dynamic.downcast::<Datetime>() {
                 match method {
                     "display" => {
@@ -198,6 +256,33 @@ pub fn call(
                     "second" => datetime.second().into_value(),
                     _ => return missing(),
                 }
+            } else if let Some(&duration) = dynamic.downcast::<Duration>() {
+                match method {
+                    "seconds" => duration.seconds().into_value(),
+                    "minutes" => duration.minutes().into_value(),
+                    "hours" => duration.hours().into_value(),
+                    "days" => duration.days().into_value(),
+                    "weeks" => duration.weeks().into_value(),
+                    _ => return missing(),
+                }
+            } else if let Some(quantity) = dynamic.downcast::<Quantity>() {
+                match method {
+                    "value" => quantity.value().into_value(),
+                    "unit" => quantity.unit().to_string().into_value(),
+                    "to" => {
+                        let unit: Spanned<Str> = args.expect("unit")?;
+                        quantity.to(&unit.v).at(unit.span)?.into_value()
+                    }
+                    _ => return missing(),
+                }
+            } else if let Some(records) = dynamic.downcast::<Records>() {
+                match method {
+                    "skip" => records.skip(args.expect("count")?).into_value(),
+                    "take" => records.take(args.expect("count")?).into_value(),
+                    "filter" => records.filter(args.expect("function")?).into_value(),
+                    "collect" => records.collect(vm, span)?.into_value(),
+                    _ => return missing(),
+                }
             } else {
                 return (vm.items.library_method)(vm, &dynamic, method, args, span);
             }
@@ -294,7 +379,19 @@ fn missing_method(type_name: &str, method: &str) -> String {
 /// List the available methods for a type and whether they take arguments.
 pub fn methods_on(type_name: &str) -> &[(&'static str, bool)] {
     match type_name {
-        "color" => &[("lighten", true), ("darken", true), ("negate", false)],
+        "color" => &[
+            ("lighten", true),
+            ("darken", true),
+            ("negate", false),
+            ("mix", true),
+            ("saturate", true),
+            ("desaturate", true),
+            ("rotate", true),
+            ("to-rgba", false),
+            ("to-hsl", false),
+            ("to-oklab", false),
+            ("to-hex", false),
+        ],
         "string" => &[
             ("len", false),
             ("at", true),
@@ -356,6 +453,28 @@ pub fn methods_on(type_name: &str) -> &[(&'static str, bool)] {
             ("remove", true),
             ("values", false),
         ],
+        "bytes" => &[("len", false), ("at", true), ("slice", true), ("to-array", false)],
+        "datetime" => &[
+            ("display", true),
+            ("year", false),
+            ("month", false),
+            ("weekday", false),
+            ("day", false),
+            ("hour", false),
+            ("minute", false),
+            ("second", false),
+        ],
+        "duration" => &[
+            ("seconds", false),
+            ("minutes", false),
+            ("hours", false),
+            ("days", false),
+            ("weeks", false),
+        ],
+        "quantity" => &[("to", true), ("unit", false), ("value", false)],
+        "records" => {
+            &[("collect", false), ("filter", true), ("skip", true), ("take", true)]
+        }
         "function" => &[("where", true), ("with", true)],
         "arguments" => &[("named", false), ("pos", false)],
         "location" => &[("page", false), ("position", false), ("page-numbering", false)],
@@ -368,6 +487,14 @@ pub fn methods_on(type_name: &str) -> &[(&'static str, bool)] {
             ("update", true),
         ],
         "state" => &[("display", true), ("at", true), ("final", true), ("update", true)],
+        "file" => &[
+            ("read", false),
+            ("lines", false),
+            ("json", false),
+            ("write", true),
+            ("close", false),
+        ],
+        "archive" => &[("read", true), ("entries", false)],
         _ => &[],
     }
 }
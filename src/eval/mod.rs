@@ -14,13 +14,18 @@ mod str;
 mod value;
 mod args;
 mod auto;
+mod bytes;
 mod datetime;
+mod decimal;
+mod duration;
 mod func;
 mod int;
 mod methods;
 mod module;
 mod none;
 pub mod ops;
+mod quantity;
+mod records;
 mod scope;
 mod symbol;
 
@@ -38,16 +43,21 @@ pub use typst_macros::{func, symbols};
 pub use self::args::{Arg, Args};
 pub use self::array::{array, Array};
 pub use self::auto::AutoValue;
+pub use self::bytes::Bytes;
 pub use self::cast::{
     cast, Cast, CastInfo, FromValue, IntoResult, IntoValue, Never, Reflect, Variadics,
 };
 pub use self::datetime::Datetime;
+pub use self::decimal::Decimal;
 pub use self::dict::{dict, Dict};
+pub use self::duration::Duration;
 pub use self::func::{Func, FuncInfo, NativeFunc, Param, ParamInfo};
 pub use self::library::{set_lang_items, LangItems, Library};
 pub use self::methods::methods_on;
 pub use self::module::Module;
 pub use self::none::NoneValue;
+pub use self::quantity::{Quantity, Unit};
+pub use self::records::{Cursor, Decoder, Records};
 pub use self::scope::{Scope, Scopes};
 pub use self::str::{format_str, Regex, Str};
 pub use self::symbol::Symbol;
@@ -71,7 +81,7 @@ use crate::syntax::ast::AstNode;
 use crate::syntax::{
     ast, parse_code, Source, SourceId, Span, Spanned, SyntaxKind, SyntaxNode,
 };
-use crate::util::{Access as FAccess, AccessMode, PathExt};
+use crate::util::{Access as FAccess, AccessMode, PackageSpec, PathExt};
 use crate::World;
 use crate::{
     diag::{bail, error, At, SourceError, SourceResult, StrResult, Trace, Tracepoint},
@@ -243,6 +253,11 @@ impl<'a> Vm<'a> {
         self.scopes.top.define(var.take(), value);
     }
 
+    /// Report a warning that does not stop compilation.
+    pub fn warn(&mut self, span: Span, message: impl Into<EcoString>) {
+        self.vt.tracer.warn(SourceError::new(span, message));
+    }
+
     /// Resolve a user-entered path to be relative to the compilation
     /// environment's root.
     #[tracing::instrument(skip_all)]
@@ -265,6 +280,13 @@ impl<'a> Vm<'a> {
                 return Ok(abs.join(path));
             }
 
+            if options.contains(LocatePerm::RootRelative) {
+                // Allow path strings that start from 'abs' without a slash.
+                let path = PathBuf::from(path).normalize();
+                self._check_contents(&path, options)?;
+                return Ok(abs.join(path));
+            }
+
             if let Some(dir) = self.world().source(self.location).path().parent() {
                 if options.contains(LocatePerm::FileRelative) {
                     // Allow path strings that do not start from 'abs'.
@@ -287,6 +309,10 @@ impl<'a> Vm<'a> {
             if (c == Component::ParentDir) && !options.contains(LocatePerm::Upwards) {
                 bail!("path '{}' should not point to its parent", path.display())
             }
+            if matches!(c, Component::Prefix(_)) && !options.contains(LocatePerm::Global)
+            {
+                bail!("path '{}' should not have a drive prefix", path.display())
+            }
         }
 
         Ok(())
@@ -333,6 +359,7 @@ bitflags! {
         const Global       = 0b00000001; // Double slash paths, or paths that start from the filesystem's root, rather than typst's
         const FileRelative = 0b00000010; // No-slash paths, or paths that start from the file invoking locate.
         const Upwards      = 0b00000100; // Paths that allow ../ as a component //note: we may need a smarter system
+        const RootRelative = 0b00010000; // No-slash paths that start from the root of their mode, rather than from the file invoking locate.
         //const CrossOrigin  = 0b00001000; // Paths that allow access to another root than their own (i.e: Accessing to `dest` from within `root`) //not implemented
         // We could add SYMLINK (would require adding some kind of metadata function to world, to access information regarding wether a component is a symlink)
     }
@@ -341,7 +368,7 @@ bitflags! {
 impl From<AccessMode> for LocatePerm {
     fn from(value: AccessMode) -> Self {
         match value {
-            FAccess::Write(_) => LocatePerm::Local,
+            FAccess::Write(_) => LocatePerm::RootRelative,
             FAccess::Read(_) => LocatePerm::FileRelative,
         }
     }
@@ -420,11 +447,13 @@ impl<'a> Route<'a> {
     }
 }
 
-/// Traces which values existed for an expression at a span.
+/// Traces which values existed for an expression at a span and collects
+/// warnings produced during compilation.
 #[derive(Default, Clone)]
 pub struct Tracer {
     span: Option<Span>,
     values: Vec<Value>,
+    warnings: Vec<SourceError>,
 }
 
 impl Tracer {
@@ -433,13 +462,18 @@ impl Tracer {
 
     /// Create a new tracer, possibly with a span under inspection.
     pub fn new(span: Option<Span>) -> Self {
-        Self { span, values: vec![] }
+        Self { span, values: vec![], warnings: vec![] }
     }
 
     /// Get the traced values.
     pub fn finish(self) -> Vec<Value> {
         self.values
     }
+
+    /// The warnings collected so far.
+    pub fn warnings(&self) -> &[SourceError] {
+        &self.warnings
+    }
 }
 
 #[comemo::track]
@@ -459,6 +493,19 @@ impl Tracer {
             self.values.push(v);
         }
     }
+
+    /// Add a warning.
+    ///
+    /// Warnings with the same message are only kept once per source file, at
+    /// their first occurrence in it, so that the same problem isn't reported
+    /// over and over.
+    pub fn warn(&mut self, warning: SourceError) {
+        if !self.warnings.iter().any(|prev| {
+            prev.span.source() == warning.span.source() && prev.message == warning.message
+        }) {
+            self.warnings.push(warning);
+        }
+    }
 }
 
 /// Evaluate an expression.
@@ -1574,7 +1621,7 @@ impl Eval for ast::SetRule {
             })
             .at(target.span())?;
         let args = self.args().eval(vm)?;
-        Ok(target.set(args)?.spanned(self.span()))
+        Ok(target.set(vm, args)?.spanned(self.span()))
     }
 }
 
@@ -1694,10 +1741,14 @@ impl Eval for ast::ForLoop {
 
         macro_rules! iter {
             (for $pat:ident in $iter:expr) => {{
+                #[allow(unused_parens)]
+                let mut iter = $iter.into_iter();
+                iter!(while $pat = iter.next());
+            }};
+            (while $pat:ident = $next:expr) => {{
                 vm.scopes.enter();
 
-                #[allow(unused_parens)]
-                for value in $iter {
+                while let Some(value) = $next {
                     $pat.define(vm, value.into_value())?;
 
                     let body = self.body();
@@ -1735,6 +1786,12 @@ impl Eval for ast::ForLoop {
                 // Iterate over values of array.
                 iter!(for pattern in array);
             }
+            (_, Value::Dyn(dynamic)) if dynamic.is::<Records>() => {
+                // Decode records one by one.
+                let records = dynamic.downcast::<Records>().unwrap();
+                let mut cursor = records.cursor(self.iter().span());
+                iter!(while pattern = cursor.next(vm)?);
+            }
             (ast::Pattern::Normal(_), _) => {
                 bail!(self.iter().span(), "cannot loop over {}", iter.type_name());
             }
@@ -1851,9 +1908,15 @@ fn import(
         }
     };
 
-    // Load the source file.
+    // Load the source file. Paths starting with an `@` refer to the
+    // entrypoint of a package.
     let world = vm.world();
-    let full = vm.locate(&path, AccessMode::R).at(span)?;
+    let full = if path.starts_with('@') {
+        let spec: PackageSpec = path.parse().at(span)?;
+        world.package(&spec).at(span)?
+    } else {
+        vm.locate(&path, AccessMode::R).at(span)?
+    };
     let id = world.resolve(&full).at(span)?;
 
     // Prevent cyclic importing.
@@ -5,7 +5,7 @@ use std::fmt::Debug;
 
 use ecow::eco_format;
 
-use super::{format_str, Regex, Value};
+use super::{format_str, Datetime, Decimal, Duration, Quantity, Regex, Value};
 use crate::diag::{bail, StrResult};
 use crate::geom::{Axes, Axis, GenAlign, Length, Numeric, PartialStroke, Rel, Smart};
 use Value::*;
@@ -42,11 +42,13 @@ pub fn pos(value: Value) -> StrResult<Value> {
     Ok(match value {
         Int(v) => Int(v),
         Float(v) => Float(v),
+        Decimal(v) => Decimal(v),
         Length(v) => Length(v),
         Angle(v) => Angle(v),
         Ratio(v) => Ratio(v),
         Relative(v) => Relative(v),
         Fraction(v) => Fraction(v),
+        Dyn(v) if v.is::<Quantity>() => Dyn(v),
         v => mismatch!("cannot apply '+' to {}", v),
     })
 }
@@ -56,11 +58,19 @@ pub fn neg(value: Value) -> StrResult<Value> {
     Ok(match value {
         Int(v) => Int(v.checked_neg().ok_or("value is too large")?),
         Float(v) => Float(-v),
+        Decimal(v) => Decimal(v.checked_neg().ok_or("value is too large")?),
         Length(v) => Length(-v),
         Angle(v) => Angle(-v),
         Ratio(v) => Ratio(-v),
         Relative(v) => Relative(-v),
         Fraction(v) => Fraction(-v),
+        Dyn(v) if v.is::<Duration>() => {
+            let duration = *v.downcast::<Duration>().unwrap();
+            Value::dynamic(duration.checked_neg().ok_or("duration is out of range")?)
+        }
+        Dyn(v) if v.is::<Quantity>() => {
+            Value::dynamic(v.downcast::<Quantity>().unwrap().neg())
+        }
         v => mismatch!("cannot apply '-' to {}", v),
     })
 }
@@ -76,6 +86,10 @@ pub fn add(lhs: Value, rhs: Value) -> StrResult<Value> {
         (Float(a), Int(b)) => Float(a + b as f64),
         (Float(a), Float(b)) => Float(a + b),
 
+        (a @ Decimal(_), b @ (Int(_) | Decimal(_))) | (a @ Int(_), b @ Decimal(_)) => {
+            decimal_op(a, b, Decimal::checked_add)?
+        }
+
         (Angle(a), Angle(b)) => Angle(a + b),
 
         (Length(a), Length(b)) => Length(a + b),
@@ -128,6 +142,27 @@ pub fn add(lhs: Value, rhs: Value) -> StrResult<Value> {
                 }));
             };
 
+            // Durations can be added to each other and to datetimes.
+            if let Some(&b) = b.downcast::<Duration>() {
+                if let Some(&a) = a.downcast::<Duration>() {
+                    let sum = a.checked_add(b).ok_or("duration is out of range")?;
+                    return Ok(Value::dynamic(sum));
+                } else if let Some(&a) = a.downcast::<Datetime>() {
+                    return Ok(Value::dynamic(a.checked_add(b)?));
+                }
+            } else if let (Some(&a), Some(&b)) =
+                (a.downcast::<Duration>(), b.downcast::<Datetime>())
+            {
+                return Ok(Value::dynamic(b.checked_add(a)?));
+            }
+
+            // Quantities of the same dimension can be added.
+            if let (Some(a), Some(b)) =
+                (a.downcast::<Quantity>(), b.downcast::<Quantity>())
+            {
+                return Ok(Value::dynamic(a.checked_add(b)?));
+            }
+
             mismatch!("cannot add {} and {}", a, b);
         }
 
@@ -143,6 +178,10 @@ pub fn sub(lhs: Value, rhs: Value) -> StrResult<Value> {
         (Float(a), Int(b)) => Float(a - b as f64),
         (Float(a), Float(b)) => Float(a - b),
 
+        (a @ Decimal(_), b @ (Int(_) | Decimal(_))) | (a @ Int(_), b @ Decimal(_)) => {
+            decimal_op(a, b, Decimal::checked_sub)?
+        }
+
         (Angle(a), Angle(b)) => Angle(a - b),
 
         (Length(a), Length(b)) => Length(a - b),
@@ -159,6 +198,30 @@ pub fn sub(lhs: Value, rhs: Value) -> StrResult<Value> {
 
         (Fraction(a), Fraction(b)) => Fraction(a - b),
 
+        (Dyn(a), Dyn(b)) => {
+            if let Some(&b) = b.downcast::<Duration>() {
+                if let Some(&a) = a.downcast::<Duration>() {
+                    let diff = a.checked_sub(b).ok_or("duration is out of range")?;
+                    return Ok(Value::dynamic(diff));
+                } else if let Some(&a) = a.downcast::<Datetime>() {
+                    return Ok(Value::dynamic(a.checked_sub(b)?));
+                }
+            } else if let (Some(&a), Some(&b)) =
+                (a.downcast::<Datetime>(), b.downcast::<Datetime>())
+            {
+                let Some(diff) = a.difference(b) else {
+                    bail!("cannot subtract {} from {}", b.kind(), a.kind());
+                };
+                return Ok(Value::dynamic(diff));
+            } else if let (Some(a), Some(b)) =
+                (a.downcast::<Quantity>(), b.downcast::<Quantity>())
+            {
+                return Ok(Value::dynamic(a.checked_sub(b)?));
+            }
+
+            mismatch!("cannot subtract {1} from {0}", a, b);
+        }
+
         (a, b) => mismatch!("cannot subtract {1} from {0}", a, b),
     })
 }
@@ -171,6 +234,10 @@ pub fn mul(lhs: Value, rhs: Value) -> StrResult<Value> {
         (Float(a), Int(b)) => Float(a * b as f64),
         (Float(a), Float(b)) => Float(a * b),
 
+        (a @ Decimal(_), b @ (Int(_) | Decimal(_))) | (a @ Int(_), b @ Decimal(_)) => {
+            decimal_op(a, b, Decimal::checked_mul)?
+        }
+
         (Length(a), Int(b)) => Length(a * b as f64),
         (Length(a), Float(b)) => Length(a * b),
         (Length(a), Ratio(b)) => Length(a * b.get()),
@@ -212,6 +279,27 @@ pub fn mul(lhs: Value, rhs: Value) -> StrResult<Value> {
         (Content(a), b @ Int(_)) => Content(a.repeat(b.cast()?)),
         (a @ Int(_), Content(b)) => Content(b.repeat(a.cast()?)),
 
+        (Dyn(a), b @ (Int(_) | Float(_))) if a.is::<Duration>() => {
+            let a = *a.downcast::<Duration>().unwrap();
+            Value::dynamic(a.checked_mul(b.cast()?).ok_or("duration is out of range")?)
+        }
+        (a @ (Int(_) | Float(_)), Dyn(b)) if b.is::<Duration>() => {
+            let b = *b.downcast::<Duration>().unwrap();
+            Value::dynamic(b.checked_mul(a.cast()?).ok_or("duration is out of range")?)
+        }
+
+        (Dyn(a), b @ (Int(_) | Float(_))) if a.is::<Quantity>() => {
+            Value::dynamic(a.downcast::<Quantity>().unwrap().scale(b.cast()?))
+        }
+        (a @ (Int(_) | Float(_)), Dyn(b)) if b.is::<Quantity>() => {
+            Value::dynamic(b.downcast::<Quantity>().unwrap().scale(a.cast()?))
+        }
+        (Dyn(a), Dyn(b)) if a.is::<Quantity>() && b.is::<Quantity>() => {
+            let a = a.downcast::<Quantity>().unwrap();
+            let b = b.downcast::<Quantity>().unwrap();
+            quantity(a.mul(b)?)
+        }
+
         (a, b) => mismatch!("cannot multiply {} with {}", a, b),
     })
 }
@@ -228,6 +316,10 @@ pub fn div(lhs: Value, rhs: Value) -> StrResult<Value> {
         (Float(a), Int(b)) => Float(a / b as f64),
         (Float(a), Float(b)) => Float(a / b),
 
+        (a @ Decimal(_), b @ (Int(_) | Decimal(_))) | (a @ Int(_), b @ Decimal(_)) => {
+            decimal_op(a, b, Decimal::checked_div)?
+        }
+
         (Length(a), Int(b)) => Length(a / b as f64),
         (Length(a), Float(b)) => Length(a / b),
         (Length(a), Length(b)) => Float(try_div_length(a, b)?),
@@ -252,24 +344,73 @@ pub fn div(lhs: Value, rhs: Value) -> StrResult<Value> {
         (Fraction(a), Float(b)) => Fraction(a / b),
         (Fraction(a), Fraction(b)) => Float(a / b),
 
+        (Dyn(a), b @ (Int(_) | Float(_))) if a.is::<Duration>() => {
+            let a = *a.downcast::<Duration>().unwrap();
+            let b: f64 = b.cast()?;
+            Value::dynamic(a.checked_mul(1.0 / b).ok_or("duration is out of range")?)
+        }
+        (Dyn(a), Dyn(b)) if a.is::<Duration>() && b.is::<Duration>() => {
+            let a = *a.downcast::<Duration>().unwrap();
+            let b = *b.downcast::<Duration>().unwrap();
+            Float(a.ratio(b))
+        }
+
+        (Dyn(a), b @ (Int(_) | Float(_))) if a.is::<Quantity>() => {
+            let b: f64 = b.cast()?;
+            Value::dynamic(a.downcast::<Quantity>().unwrap().scale(1.0 / b))
+        }
+        (a @ (Int(_) | Float(_)), Dyn(b)) if b.is::<Quantity>() => {
+            let a: f64 = a.cast()?;
+            Value::dynamic(b.downcast::<Quantity>().unwrap().recip()?.scale(a))
+        }
+        (Dyn(a), Dyn(b)) if a.is::<Quantity>() && b.is::<Quantity>() => {
+            let a = a.downcast::<Quantity>().unwrap();
+            let b = b.downcast::<Quantity>().unwrap();
+            quantity(a.div(b)?)
+        }
+
         (a, b) => mismatch!("cannot divide {} by {}", a, b),
     })
 }
 
+/// Turn a quantity whose unit cancelled out into a plain float.
+fn quantity(quantity: Quantity) -> Value {
+    if quantity.is_unitless() {
+        Float(quantity.value())
+    } else {
+        Value::dynamic(quantity)
+    }
+}
+
 /// Whether a value is a numeric zero.
 fn is_zero(v: &Value) -> bool {
     match *v {
         Int(v) => v == 0,
         Float(v) => v == 0.0,
+        Decimal(v) => v.is_zero(),
         Length(v) => v.is_zero(),
         Angle(v) => v.is_zero(),
         Ratio(v) => v.is_zero(),
         Relative(v) => v.is_zero(),
         Fraction(v) => v.is_zero(),
+        Dyn(ref v) => {
+            v.downcast::<Duration>().map_or(false, |v| v.is_zero())
+                || v.downcast::<Quantity>().map_or(false, Quantity::is_zero)
+        }
         _ => false,
     }
 }
 
+/// Apply a checked operation to two decimals or a decimal and an integer.
+fn decimal_op(
+    lhs: Value,
+    rhs: Value,
+    op: fn(Decimal, Decimal) -> Option<Decimal>,
+) -> StrResult<Value> {
+    let result = op(lhs.cast()?, rhs.cast()?);
+    Ok(Decimal(result.ok_or("value is too large")?))
+}
+
 /// Try to divide two lengths.
 fn try_div_length(a: Length, b: Length) -> StrResult<f64> {
     a.try_div(b).ok_or_else(|| "cannot divide these two lengths".into())
@@ -339,6 +480,7 @@ pub fn equal(lhs: &Value, rhs: &Value) -> bool {
         (Bool(a), Bool(b)) => a == b,
         (Int(a), Int(b)) => a == b,
         (Float(a), Float(b)) => a == b,
+        (Decimal(a), Decimal(b)) => a == b,
         (Length(a), Length(b)) => a == b,
         (Angle(a), Angle(b)) => a == b,
         (Ratio(a), Ratio(b)) => a == b,
@@ -359,6 +501,8 @@ pub fn equal(lhs: &Value, rhs: &Value) -> bool {
         // Some technically different things should compare equal.
         (&Int(a), &Float(b)) => a as f64 == b,
         (&Float(a), &Int(b)) => a == b as f64,
+        (&Int(a), &Decimal(b)) => Decimal::from(a) == b,
+        (&Decimal(a), &Int(b)) => a == Decimal::from(b),
         (&Length(a), &Relative(b)) => a == b.abs && b.rel.is_zero(),
         (&Ratio(a), &Relative(b)) => a == b.rel && b.abs.is_zero(),
         (&Relative(a), &Length(b)) => a.abs == b && a.rel.is_zero(),
@@ -374,16 +518,36 @@ pub fn compare(lhs: &Value, rhs: &Value) -> StrResult<Ordering> {
         (Bool(a), Bool(b)) => a.cmp(b),
         (Int(a), Int(b)) => a.cmp(b),
         (Float(a), Float(b)) => try_cmp_values(a, b)?,
+        (Decimal(a), Decimal(b)) => a.cmp(b),
         (Length(a), Length(b)) => try_cmp_values(a, b)?,
         (Angle(a), Angle(b)) => a.cmp(b),
         (Ratio(a), Ratio(b)) => a.cmp(b),
         (Relative(a), Relative(b)) => try_cmp_values(a, b)?,
         (Fraction(a), Fraction(b)) => a.cmp(b),
         (Str(a), Str(b)) => a.cmp(b),
+        (Dyn(a), Dyn(b)) => {
+            if let (Some(a), Some(b)) =
+                (a.downcast::<Duration>(), b.downcast::<Duration>())
+            {
+                a.cmp(b)
+            } else if let (Some(a), Some(b)) =
+                (a.downcast::<Datetime>(), b.downcast::<Datetime>())
+            {
+                try_cmp_values(a, b)?
+            } else if let (Some(a), Some(b)) =
+                (a.downcast::<Quantity>(), b.downcast::<Quantity>())
+            {
+                a.compare(b)?
+            } else {
+                mismatch!("cannot compare {} and {}", lhs, rhs);
+            }
+        }
 
         // Some technically different things should be comparable.
         (Int(a), Float(b)) => try_cmp_values(&(*a as f64), b)?,
         (Float(a), Int(b)) => try_cmp_values(a, &(*b as f64))?,
+        (&Int(a), Decimal(b)) => Decimal::from(a).cmp(b),
+        (Decimal(a), &Int(b)) => a.cmp(&Decimal::from(b)),
         (Length(a), Relative(b)) if b.rel.is_zero() => try_cmp_values(a, &b.abs)?,
         (Ratio(a), Relative(b)) if b.abs.is_zero() => a.cmp(&b.rel),
         (Relative(a), Length(b)) if a.rel.is_zero() => try_cmp_values(&a.abs, b)?,
@@ -0,0 +1,604 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display, Formatter, Write};
+use std::hash::{Hash, Hasher};
+
+use ecow::{eco_format, eco_vec, EcoString, EcoVec};
+
+use crate::diag::{bail, StrResult};
+use crate::eval::cast;
+
+/// A number with a physical unit, like `3.5 kN*m`.
+#[derive(Clone, PartialEq)]
+pub struct Quantity {
+    /// The numeric value, in terms of the unit.
+    value: f64,
+    /// The unit the value is given in.
+    unit: Unit,
+}
+
+impl Quantity {
+    /// Create a quantity from a value and a unit like `kN*m` or `m/s^2`.
+    pub fn new(value: f64, unit: &str) -> StrResult<Self> {
+        Ok(Self { value, unit: Unit::parse(unit)? })
+    }
+
+    /// The numeric value, in terms of the unit.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The unit the value is given in.
+    pub fn unit(&self) -> &Unit {
+        &self.unit
+    }
+
+    /// Whether the unit cancelled out completely.
+    pub fn is_unitless(&self) -> bool {
+        self.unit.0.is_empty()
+    }
+
+    /// Whether the quantity is zero.
+    pub fn is_zero(&self) -> bool {
+        self.value == 0.0
+    }
+
+    /// Convert the quantity into another unit of the same dimension.
+    pub fn to(&self, unit: &str) -> StrResult<Self> {
+        let unit = Unit::parse(unit)?;
+        let value = self.value * self.unit.factor_to(&unit)?;
+        Ok(Self { value, unit })
+    }
+
+    /// Negate the quantity.
+    pub fn neg(&self) -> Self {
+        Self { value: -self.value, unit: self.unit.clone() }
+    }
+
+    /// Add a quantity of the same dimension, keeping this quantity's unit.
+    pub fn checked_add(&self, other: &Self) -> StrResult<Self> {
+        let other = other.value * other.unit.factor_to(&self.unit)?;
+        Ok(Self { value: self.value + other, unit: self.unit.clone() })
+    }
+
+    /// Subtract a quantity of the same dimension, keeping this quantity's
+    /// unit.
+    pub fn checked_sub(&self, other: &Self) -> StrResult<Self> {
+        self.checked_add(&other.neg())
+    }
+
+    /// Scale the quantity by a factor.
+    pub fn scale(&self, factor: f64) -> Self {
+        Self {
+            value: self.value * factor,
+            unit: self.unit.clone(),
+        }
+    }
+
+    /// Multiply two quantities, combining their units.
+    pub fn mul(&self, other: &Self) -> StrResult<Self> {
+        Ok(Self {
+            value: self.value * other.value,
+            unit: self.unit.mul(&other.unit)?,
+        })
+    }
+
+    /// Divide a quantity by another one, combining their units.
+    pub fn div(&self, other: &Self) -> StrResult<Self> {
+        self.mul(&other.recip()?)
+    }
+
+    /// The reciprocal of the quantity.
+    pub fn recip(&self) -> StrResult<Self> {
+        Ok(Self { value: 1.0 / self.value, unit: self.unit.recip()? })
+    }
+
+    /// Compare with a quantity of the same dimension.
+    pub fn compare(&self, other: &Self) -> StrResult<Ordering> {
+        let other = other.value * other.unit.factor_to(&self.unit)?;
+        self.value.partial_cmp(&other).ok_or_else(|| {
+            eco_format!("cannot compare {:?} with {:?}", self.value, other)
+        })
+    }
+
+    /// Format the quantity according to SI conventions: Groups of three
+    /// digits and the unit are separated by narrow spaces, very large and
+    /// small numbers are written in exponent notation and unit exponents are
+    /// written as superscripts.
+    pub fn format(&self, decimal_marker: char) -> EcoString {
+        let mut text = format_number(self.value, decimal_marker);
+        let unit = self.unit.format();
+        if !unit.is_empty() {
+            // Degrees of plane angles directly follow the number.
+            if !unit.starts_with('°') {
+                text.push(NARROW_SPACE);
+            }
+            text.push_str(&unit);
+        }
+        text
+    }
+}
+
+impl Debug for Quantity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "quantity({:?}, {:?})", self.value, self.unit)
+    }
+}
+
+impl Hash for Quantity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.to_bits().hash(state);
+        self.unit.hash(state);
+    }
+}
+
+cast! {
+    type Quantity: "quantity",
+}
+
+/// A product of powers of unit symbols, like `kN*m` or `m/s^2`.
+#[derive(Default, Clone, Eq, PartialEq, Hash)]
+pub struct Unit(EcoVec<Factor>);
+
+impl Unit {
+    /// Parse a unit from text like `kN*m` or `kg*m^2/s^2`.
+    ///
+    /// Factors are separated by `*` and divided with `/`, which only applies
+    /// to the factor directly following it. A plain `1` stands for no unit.
+    pub fn parse(text: &str) -> StrResult<Self> {
+        let mut unit = Self::default();
+        let mut rest = text;
+        let mut divide = false;
+        loop {
+            let end = rest.find(['*', '/', '·', '⋅']).unwrap_or(rest.len());
+            let (atom, tail) = rest.split_at(end);
+            let atom = atom.trim();
+            if atom != "1" {
+                let mut factor = Factor::parse(atom)?;
+                if divide {
+                    factor.exponent = negate(factor.exponent)?;
+                }
+                unit = unit.mul(&Self(eco_vec![factor]))?;
+            }
+
+            let Some(op) = tail.chars().next() else { break };
+            divide = op == '/';
+            rest = &tail[op.len_utf8()..];
+        }
+        Ok(unit)
+    }
+
+    /// Combine the factors of two units, adding the exponents of equal
+    /// symbols.
+    pub fn mul(&self, other: &Self) -> StrResult<Self> {
+        let mut factors = self.0.clone();
+        for factor in other.0.iter() {
+            match factors.iter().position(|f| f.same_symbol(factor)) {
+                Some(i) => {
+                    let exponent = &mut factors.make_mut()[i].exponent;
+                    *exponent = exponent
+                        .checked_add(factor.exponent)
+                        .ok_or(EXPONENT_OUT_OF_RANGE)?;
+                }
+                None => factors.push(factor.clone()),
+            }
+        }
+        Ok(Self(factors.into_iter().filter(|f| f.exponent != 0).collect()))
+    }
+
+    /// The reciprocal of the unit.
+    pub fn recip(&self) -> StrResult<Self> {
+        self.0
+            .iter()
+            .map(|f| Ok(Factor { exponent: negate(f.exponent)?, ..f.clone() }))
+            .collect::<StrResult<_>>()
+            .map(Self)
+    }
+
+    /// The factor by which a value in this unit must be multiplied to express
+    /// it in the other unit.
+    pub fn factor_to(&self, other: &Self) -> StrResult<f64> {
+        if self.dims()? != other.dims()? {
+            bail!("cannot convert from {:?} to {:?}", self, other);
+        }
+        Ok(self.scale() / other.scale())
+    }
+
+    /// Format the unit according to SI conventions.
+    pub fn format(&self) -> EcoString {
+        let mut text = EcoString::new();
+        for (i, factor) in self.0.iter().enumerate() {
+            if i > 0 {
+                text.push(NARROW_SPACE);
+            }
+            text.push_str(factor.prefix.map_or("", |i| PREFIXES[i].pretty));
+            text.push_str(BASES[factor.base].pretty);
+            if factor.exponent != 1 {
+                text.push_str(&superscript(factor.exponent));
+            }
+        }
+        text
+    }
+
+    /// The powers of the SI base units the unit is composed of.
+    fn dims(&self) -> StrResult<Dims> {
+        let mut dims = [0; 7];
+        for factor in self.0.iter() {
+            for (dim, base) in dims.iter_mut().zip(BASES[factor.base].dims) {
+                *dim = base
+                    .checked_mul(factor.exponent)
+                    .and_then(|power| dim.checked_add(power))
+                    .ok_or(EXPONENT_OUT_OF_RANGE)?;
+            }
+        }
+        Ok(dims)
+    }
+
+    /// The factor by which a value in this unit must be multiplied to express
+    /// it in SI base units.
+    fn scale(&self) -> f64 {
+        self.0
+            .iter()
+            .map(|factor| {
+                let prefix = factor.prefix.map_or(0, |i| PREFIXES[i].power);
+                let scale = 10f64.powi(prefix) * BASES[factor.base].scale;
+                scale.powi(factor.exponent)
+            })
+            .product()
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut first = true;
+        for factor in self.0.iter().filter(|f| f.exponent > 0) {
+            if !first {
+                f.write_char('*')?;
+            }
+            factor.write(f, factor.exponent.into())?;
+            first = false;
+        }
+
+        if first {
+            f.write_char('1')?;
+        }
+
+        for factor in self.0.iter().filter(|f| f.exponent < 0) {
+            f.write_char('/')?;
+            factor.write(f, -i64::from(factor.exponent))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for Unit {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&self.to_string(), f)
+    }
+}
+
+/// A power of a possibly prefixed unit symbol.
+#[derive(Clone, Eq, PartialEq, Hash)]
+struct Factor {
+    /// The index of the prefix in `PREFIXES`, if any.
+    prefix: Option<usize>,
+    /// The index of the unit in `BASES`.
+    base: usize,
+    /// The power of the prefixed unit.
+    exponent: i32,
+}
+
+impl Factor {
+    /// Parse a factor like `km` or `s^-2`.
+    fn parse(text: &str) -> StrResult<Self> {
+        let (symbol, exponent) = match text.split_once('^') {
+            Some((symbol, exponent)) => {
+                let exponent = exponent.trim().parse::<i32>().map_err(|_| {
+                    eco_format!("invalid exponent in unit: {}", exponent.trim())
+                })?;
+                (symbol.trim(), exponent)
+            }
+            None => (text, 1),
+        };
+
+        if symbol.is_empty() {
+            bail!("missing unit symbol");
+        }
+
+        // Prefer unprefixed units so that, e.g., `min` is not read as a
+        // milli-inch.
+        if let Some(base) = BASES.iter().position(|base| base.symbol == symbol) {
+            return Ok(Self { prefix: None, base, exponent });
+        }
+
+        for (prefix, info) in PREFIXES.iter().enumerate() {
+            let Some(rest) = symbol.strip_prefix(info.symbol) else { continue };
+            if let Some(base) =
+                BASES.iter().position(|base| base.prefixable && base.symbol == rest)
+            {
+                return Ok(Self { prefix: Some(prefix), base, exponent });
+            }
+        }
+
+        bail!("unknown unit: {symbol}")
+    }
+
+    /// Whether the factors have the same prefixed symbol.
+    fn same_symbol(&self, other: &Self) -> bool {
+        self.prefix == other.prefix && self.base == other.base
+    }
+
+    /// Write the symbol with the given exponent in input syntax.
+    fn write(&self, f: &mut Formatter, exponent: i64) -> fmt::Result {
+        f.write_str(self.prefix.map_or("", |i| PREFIXES[i].symbol))?;
+        f.write_str(BASES[self.base].symbol)?;
+        if exponent != 1 {
+            write!(f, "^{exponent}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Powers of metre, kilogram, second, ampere, kelvin, mole and candela.
+type Dims = [i32; 7];
+
+/// A decimal unit prefix.
+struct Prefix {
+    /// The symbol in input syntax.
+    symbol: &'static str,
+    /// The typeset symbol.
+    pretty: &'static str,
+    /// The power of ten the prefix stands for.
+    power: i32,
+}
+
+/// A unit that quantities can be given in.
+struct Base {
+    /// The symbol in input syntax.
+    symbol: &'static str,
+    /// The typeset symbol.
+    pretty: &'static str,
+    /// The value of the unit in SI base units.
+    scale: f64,
+    /// The powers of the SI base units the unit is composed of.
+    dims: Dims,
+    /// Whether the unit may be prefixed.
+    prefixable: bool,
+}
+
+/// The known prefixes. Longer symbols must come first.
+const PREFIXES: &[Prefix] = &[
+    prefix("da", "da", 1),
+    prefix("Q", "Q", 30),
+    prefix("R", "R", 27),
+    prefix("Y", "Y", 24),
+    prefix("Z", "Z", 21),
+    prefix("E", "E", 18),
+    prefix("P", "P", 15),
+    prefix("T", "T", 12),
+    prefix("G", "G", 9),
+    prefix("M", "M", 6),
+    prefix("k", "k", 3),
+    prefix("h", "h", 2),
+    prefix("d", "d", -1),
+    prefix("c", "c", -2),
+    prefix("m", "m", -3),
+    prefix("u", "µ", -6),
+    prefix("µ", "µ", -6),
+    prefix("μ", "µ", -6),
+    prefix("n", "n", -9),
+    prefix("p", "p", -12),
+    prefix("f", "f", -15),
+    prefix("a", "a", -18),
+    prefix("z", "z", -21),
+    prefix("y", "y", -24),
+    prefix("r", "r", -27),
+    prefix("q", "q", -30),
+];
+
+/// The known units.
+const BASES: &[Base] = &[
+    // SI base units.
+    unit("m", "m", 1.0, [1, 0, 0, 0, 0, 0, 0], true),
+    unit("g", "g", 1e-3, [0, 1, 0, 0, 0, 0, 0], true),
+    unit("s", "s", 1.0, [0, 0, 1, 0, 0, 0, 0], true),
+    unit("A", "A", 1.0, [0, 0, 0, 1, 0, 0, 0], true),
+    unit("K", "K", 1.0, [0, 0, 0, 0, 1, 0, 0], true),
+    unit("mol", "mol", 1.0, [0, 0, 0, 0, 0, 1, 0], true),
+    unit("cd", "cd", 1.0, [0, 0, 0, 0, 0, 0, 1], true),
+    // Derived SI units.
+    unit("Hz", "Hz", 1.0, [0, 0, -1, 0, 0, 0, 0], true),
+    unit("N", "N", 1.0, [1, 1, -2, 0, 0, 0, 0], true),
+    unit("Pa", "Pa", 1.0, [-1, 1, -2, 0, 0, 0, 0], true),
+    unit("J", "J", 1.0, [2, 1, -2, 0, 0, 0, 0], true),
+    unit("W", "W", 1.0, [2, 1, -3, 0, 0, 0, 0], true),
+    unit("C", "C", 1.0, [0, 0, 1, 1, 0, 0, 0], true),
+    unit("V", "V", 1.0, [2, 1, -3, -1, 0, 0, 0], true),
+    unit("F", "F", 1.0, [-2, -1, 4, 2, 0, 0, 0], true),
+    unit("Ohm", "Ω", 1.0, [2, 1, -3, -2, 0, 0, 0], true),
+    unit("Ω", "Ω", 1.0, [2, 1, -3, -2, 0, 0, 0], true),
+    unit("S", "S", 1.0, [-2, -1, 3, 2, 0, 0, 0], true),
+    unit("Wb", "Wb", 1.0, [2, 1, -2, -1, 0, 0, 0], true),
+    unit("T", "T", 1.0, [0, 1, -2, -1, 0, 0, 0], true),
+    unit("H", "H", 1.0, [2, 1, -2, -2, 0, 0, 0], true),
+    unit("rad", "rad", 1.0, [0; 7], false),
+    unit("sr", "sr", 1.0, [0; 7], false),
+    // Units accepted for use with the SI.
+    unit("L", "L", 1e-3, [3, 0, 0, 0, 0, 0, 0], true),
+    unit("t", "t", 1e3, [0, 1, 0, 0, 0, 0, 0], false),
+    unit("min", "min", 60.0, [0, 0, 1, 0, 0, 0, 0], false),
+    unit("h", "h", 3600.0, [0, 0, 1, 0, 0, 0, 0], false),
+    unit("d", "d", 86400.0, [0, 0, 1, 0, 0, 0, 0], false),
+    unit("bar", "bar", 1e5, [-1, 1, -2, 0, 0, 0, 0], true),
+    unit("eV", "eV", 1.602176634e-19, [2, 1, -2, 0, 0, 0, 0], true),
+    unit("deg", "°", std::f64::consts::PI / 180.0, [0; 7], false),
+    unit("°", "°", std::f64::consts::PI / 180.0, [0; 7], false),
+    unit("%", "%", 0.01, [0; 7], false),
+];
+
+/// Create a prefix table entry.
+const fn prefix(symbol: &'static str, pretty: &'static str, power: i32) -> Prefix {
+    Prefix { symbol, pretty, power }
+}
+
+/// Create a unit table entry.
+const fn unit(
+    symbol: &'static str,
+    pretty: &'static str,
+    scale: f64,
+    dims: Dims,
+    prefixable: bool,
+) -> Base {
+    Base { symbol, pretty, scale, dims, prefixable }
+}
+
+/// A narrow no-break space, separating digit groups and units.
+const NARROW_SPACE: char = '\u{202F}';
+
+/// The error for unit exponents that don't fit into an `i32`.
+const EXPONENT_OUT_OF_RANGE: &str = "unit exponent is out of range";
+
+/// Negate a unit exponent.
+fn negate(exponent: i32) -> StrResult<i32> {
+    exponent.checked_neg().ok_or_else(|| EXPONENT_OUT_OF_RANGE.into())
+}
+
+/// Format a number with grouped digits and, for very large or small
+/// magnitudes, in exponent notation.
+fn format_number(value: f64, decimal_marker: char) -> EcoString {
+    let magnitude = value.abs();
+    let mut exponent = 0;
+    if magnitude.is_finite() && magnitude != 0.0 {
+        let power = magnitude.log10().floor() as i32;
+        if !(-4..9).contains(&power) {
+            exponent = power;
+        }
+    }
+
+    // Round off floating point noise.
+    let mantissa = magnitude / 10f64.powi(exponent);
+    let mantissa = format!("{mantissa:.12e}").parse::<f64>().unwrap_or(mantissa);
+    let digits = mantissa.to_string();
+    let (int, fract) = digits.split_once('.').unwrap_or((&digits, ""));
+
+    let mut text = EcoString::new();
+    if value.is_sign_negative() && value != 0.0 {
+        text.push('−');
+    }
+
+    // Only group digits when there are more than four of them.
+    let grouped = |text: &mut EcoString, digits: &str, from_end: bool| {
+        let len = digits.len();
+        for (i, c) in digits.chars().enumerate() {
+            let pos = if from_end { len - i } else { i };
+            if len > 4 && i > 0 && pos % 3 == 0 {
+                text.push(NARROW_SPACE);
+            }
+            text.push(c);
+        }
+    };
+
+    grouped(&mut text, int, true);
+    if !fract.is_empty() {
+        text.push(decimal_marker);
+        grouped(&mut text, fract, false);
+    }
+
+    if exponent != 0 {
+        text.push(NARROW_SPACE);
+        text.push('×');
+        text.push(NARROW_SPACE);
+        text.push_str("10");
+        text.push_str(&superscript(exponent));
+    }
+
+    text
+}
+
+/// Write an integer with superscript digits.
+fn superscript(n: i32) -> EcoString {
+    n.to_string()
+        .chars()
+        .map(|c| match c {
+            '-' => '⁻',
+            '0' => '⁰',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '4' => '⁴',
+            '5' => '⁵',
+            '6' => '⁶',
+            '7' => '⁷',
+            '8' => '⁸',
+            '9' => '⁹',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn quantity(value: f64, unit: &str) -> Quantity {
+        Quantity::new(value, unit).unwrap()
+    }
+
+    #[test]
+    fn test_quantity_parse_and_repr() {
+        assert_eq!(format!("{:?}", quantity(3.5, "kN*m")), "quantity(3.5, \"kN*m\")");
+        assert_eq!(format!("{:?}", quantity(1.0, "m/s^2")), "quantity(1.0, \"m/s^2\")");
+        assert_eq!(format!("{:?}", quantity(1.0, "m*m")), "quantity(1.0, \"m^2\")");
+        assert_eq!(format!("{:?}", quantity(2.0, "1/s")), "quantity(2.0, \"1/s\")");
+        assert_eq!(format!("{:?}", quantity(1.0, "m/m")), "quantity(1.0, \"1\")");
+        assert_eq!(format!("{:?}", quantity(1.0, "min")), "quantity(1.0, \"min\")");
+        assert!(Quantity::new(1.0, "xyz").is_err());
+        assert!(Quantity::new(1.0, "m^x").is_err());
+        assert!(Quantity::new(1.0, "kmin").is_err());
+    }
+
+    #[test]
+    fn test_quantity_algebra() {
+        let q = quantity(3.0, "kN").mul(&quantity(2.0, "m")).unwrap();
+        assert_eq!(q, quantity(6.0, "kN*m"));
+        assert_eq!(q.to("J").unwrap().value(), 6000.0);
+        assert_eq!(
+            quantity(1.0, "km").checked_add(&quantity(500.0, "m")).unwrap(),
+            quantity(1.5, "km")
+        );
+        assert!(quantity(1.0, "km").checked_add(&quantity(1.0, "s")).is_err());
+        assert!(quantity(6.0, "m").div(&quantity(2.0, "m")).unwrap().is_unitless());
+        assert_eq!(
+            quantity(1.0, "h").compare(&quantity(59.0, "min")).unwrap(),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_quantity_exponent_overflow() {
+        let error = Some("unit exponent is out of range");
+        let max = quantity(1.0, "m^2147483647");
+        let min = quantity(1.0, "m^-2147483648");
+        assert_eq!(format!("{min:?}"), "quantity(1.0, \"1/m^2147483648\")");
+        assert_eq!(Quantity::new(1.0, "1/m^-2147483648").err().as_deref(), error);
+        assert_eq!(Quantity::new(1.0, "m^2147483647*m").err().as_deref(), error);
+        assert_eq!(max.mul(&quantity(1.0, "m")).err().as_deref(), error);
+        assert_eq!(min.recip().err().as_deref(), error);
+        assert_eq!(quantity(1.0, "N^1500000000").to("N").err().as_deref(), error);
+        assert_eq!(max.div(&max).unwrap().value(), 1.0);
+    }
+
+    #[test]
+    fn test_quantity_format() {
+        assert_eq!(quantity(3.5, "kN*m").format('.'), "3.5\u{202F}kN\u{202F}m");
+        assert_eq!(quantity(9.81, "m/s^2").format(','), "9,81\u{202F}m\u{202F}s⁻²");
+        assert_eq!(quantity(12345.0, "uOhm").format('.'), "12\u{202F}345\u{202F}µΩ");
+        assert_eq!(quantity(-0.1 - 0.2, "1").format('.'), "−0.3");
+        assert_eq!(quantity(90.0, "deg").format('.'), "90°");
+        assert_eq!(
+            quantity(6.022e23, "1/mol").format('.'),
+            "6.022\u{202F}×\u{202F}10²³\u{202F}mol⁻¹"
+        );
+    }
+}
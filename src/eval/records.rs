@@ -0,0 +1,186 @@
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use ecow::{eco_vec, EcoVec};
+
+use super::{cast, Args, Array, Func, Value, Vm};
+use crate::diag::{At, SourceResult, StrResult};
+use crate::syntax::Span;
+use crate::util::{hash128, Buffer};
+
+/// A lazily decoded sequence of records from a data file.
+///
+/// Only the file's raw data is kept in memory. The records are decoded one by
+/// one while they are iterated over, so that huge files never have to be
+/// converted into values all at once.
+#[derive(Clone)]
+pub struct Records {
+    /// The raw data of the file.
+    data: Buffer,
+    /// Decodes the records in the data.
+    decoder: Arc<dyn Decoder>,
+    /// A hash of the decoder and its configuration.
+    hash: u128,
+    /// The adapters that are applied to the decoded records, in order.
+    adapters: EcoVec<Adapter>,
+}
+
+/// Decodes the records in a file's data.
+pub trait Decoder: Debug + Send + Sync + 'static {
+    /// The name of the file format, like `csv`.
+    fn format(&self) -> &'static str;
+
+    /// Decode the records in the data one by one.
+    fn decode<'a>(
+        &'a self,
+        data: &'a [u8],
+    ) -> Box<dyn Iterator<Item = StrResult<Value>> + 'a>;
+}
+
+/// An adapter that is applied to the records while they are iterated over.
+#[derive(Clone, PartialEq, Hash)]
+enum Adapter {
+    /// Skip the first records.
+    Skip(usize),
+    /// Stop after some records.
+    Take(usize),
+    /// Only keep the records for which the function returns `{true}`.
+    Filter(Func),
+}
+
+impl Records {
+    /// Create a new sequence of records.
+    ///
+    /// Records are hashed by the file's data and the decoder's configuration,
+    /// never by the decoded values.
+    pub fn new<D: Decoder + Hash>(data: Buffer, decoder: D) -> Self {
+        let hash = hash128(&decoder);
+        Self {
+            data,
+            decoder: Arc::new(decoder),
+            hash,
+            adapters: eco_vec![],
+        }
+    }
+
+    /// Skip the first records.
+    pub fn skip(&self, count: usize) -> Self {
+        self.adapt(Adapter::Skip(count))
+    }
+
+    /// Stop after a number of records.
+    pub fn take(&self, count: usize) -> Self {
+        self.adapt(Adapter::Take(count))
+    }
+
+    /// Only keep the records for which the function returns `{true}`.
+    pub fn filter(&self, func: Func) -> Self {
+        self.adapt(Adapter::Filter(func))
+    }
+
+    /// Decode all records into an array.
+    pub fn collect(&self, vm: &mut Vm, span: Span) -> SourceResult<Array> {
+        let mut cursor = self.cursor(span);
+        let mut array = Array::new();
+        while let Some(record) = cursor.next(vm)? {
+            array.push(record);
+        }
+        Ok(array)
+    }
+
+    /// Start iterating over the records. Decoding errors are reported at the
+    /// given span.
+    pub fn cursor(&self, span: Span) -> Cursor<'_> {
+        Cursor {
+            records: self.decoder.decode(&self.data),
+            adapters: &self.adapters,
+            counts: vec![0; self.adapters.len()],
+            done: false,
+            span,
+        }
+    }
+
+    /// Add an adapter to a copy of the records.
+    fn adapt(&self, adapter: Adapter) -> Self {
+        let mut records = self.clone();
+        records.adapters.push(adapter);
+        records
+    }
+}
+
+impl Debug for Records {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "records({})", self.decoder.format())
+    }
+}
+
+impl PartialEq for Records {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+            && self.data == other.data
+            && self.adapters == other.adapters
+    }
+}
+
+impl Hash for Records {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.hash.hash(state);
+        self.adapters.hash(state);
+    }
+}
+
+cast! {
+    type Records: "records",
+}
+
+/// Iterates over records while applying their adapters.
+pub struct Cursor<'a> {
+    /// The decoded records.
+    records: Box<dyn Iterator<Item = StrResult<Value>> + 'a>,
+    /// The adapters to apply.
+    adapters: &'a [Adapter],
+    /// How many records each adapter has skipped or taken so far.
+    counts: Vec<usize>,
+    /// Whether a `take` adapter is exhausted.
+    done: bool,
+    /// Where to report decoding errors.
+    span: Span,
+}
+
+impl Cursor<'_> {
+    /// Decode the next record that passes all adapters.
+    pub fn next(&mut self, vm: &mut Vm) -> SourceResult<Option<Value>> {
+        'records: while !self.done {
+            let Some(record) = self.records.next() else { break };
+            let record = record.at(self.span)?;
+
+            for (adapter, count) in self.adapters.iter().zip(&mut self.counts) {
+                match adapter {
+                    Adapter::Skip(n) if *count < *n => {
+                        *count += 1;
+                        continue 'records;
+                    }
+                    Adapter::Skip(_) => {}
+                    Adapter::Take(n) if *count < *n => *count += 1,
+                    Adapter::Take(_) => {
+                        self.done = true;
+                        break 'records;
+                    }
+                    Adapter::Filter(func) => {
+                        let args = Args::new(func.span(), [record.clone()]);
+                        if !func.call_vm(vm, args)?.cast::<bool>().at(func.span())? {
+                            continue 'records;
+                        }
+                    }
+                }
+            }
+
+            return Ok(Some(record));
+        }
+
+        self.done = true;
+        Ok(None)
+    }
+}
@@ -144,7 +144,9 @@ impl Str {
             StrPattern::Str(pat) => {
                 self.0.match_indices(pat.as_str()).next().map(match_to_dict)
             }
-            StrPattern::Regex(re) => re.captures(self).map(captures_to_dict),
+            StrPattern::Regex(re) => {
+                re.captures(self).map(|caps| captures_to_dict(&re, caps))
+            }
         }
     }
 
@@ -160,22 +162,40 @@ impl Str {
                 .collect(),
             StrPattern::Regex(re) => re
                 .captures_iter(self)
-                .map(captures_to_dict)
+                .map(|caps| captures_to_dict(&re, caps))
                 .map(Value::Dict)
                 .collect(),
         }
     }
 
-    /// Split this string at whitespace or a specific pattern.
-    pub fn split(&self, pattern: Option<StrPattern>) -> Array {
+    /// Split this string at whitespace or a specific pattern, at most `count`
+    /// times (beginning from the start). If no count is given, the string is
+    /// split at all occurrences.
+    pub fn split(&self, pattern: Option<StrPattern>, count: Option<usize>) -> Array {
         let s = self.as_str();
+        let parts = count.map_or(usize::MAX, |count| count.saturating_add(1));
         match pattern {
-            None => s.split_whitespace().map(|v| Value::Str(v.into())).collect(),
+            None => {
+                let mut array = Array::new();
+                let mut rest = s.trim_start();
+                while !rest.is_empty() {
+                    // Keep the remainder as-is once all splits are used up.
+                    if array.len() + 1 == parts {
+                        array.push(Value::Str(rest.into()));
+                        break;
+                    }
+
+                    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                    array.push(Value::Str(rest[..end].into()));
+                    rest = rest[end..].trim_start();
+                }
+                array
+            }
             Some(StrPattern::Str(pat)) => {
-                s.split(pat.as_str()).map(|v| Value::Str(v.into())).collect()
+                s.splitn(parts, pat.as_str()).map(|v| Value::Str(v.into())).collect()
             }
             Some(StrPattern::Regex(re)) => {
-                re.split(s).map(|v| Value::Str(v.into())).collect()
+                re.splitn(s, parts).map(|v| Value::Str(v.into())).collect()
             }
         }
     }
@@ -304,7 +324,7 @@ impl Str {
                 for caps in re.captures_iter(self).take(count) {
                     // Extract the entire match over all capture groups.
                     let m = caps.get(0).unwrap();
-                    handle_match(m.start()..m.end(), captures_to_dict(caps))?;
+                    handle_match(m.start()..m.end(), captures_to_dict(re, caps))?;
                 }
             }
         }
@@ -379,20 +399,25 @@ fn match_to_dict((start, text): (usize, &str)) -> Dict {
         "end" => start + text.len(),
         "text" => text,
         "captures" => Array::new(),
+        "named" => Dict::new(),
     }
 }
 
 /// Convert regex captures to a dictionary.
-fn captures_to_dict(cap: regex::Captures) -> Dict {
+fn captures_to_dict(re: &Regex, cap: regex::Captures) -> Dict {
     let m = cap.get(0).expect("missing first match");
+    let text =
+        |m: Option<regex::Match>| m.map_or(Value::None, |m| m.as_str().into_value());
     dict! {
         "start" => m.start(),
         "end" => m.end(),
         "text" => m.as_str(),
-        "captures" =>  cap.iter()
-            .skip(1)
-            .map(|opt| opt.map_or(Value::None, |m| m.as_str().into_value()))
-            .collect::<Array>(),
+        "captures" =>  cap.iter().skip(1).map(text).collect::<Array>(),
+        "named" => re
+            .capture_names()
+            .flatten()
+            .map(|name| (name.into(), text(cap.name(name))))
+            .collect::<Dict>(),
     }
 }
 
@@ -8,8 +8,8 @@ use ecow::eco_format;
 use siphasher::sip128::{Hasher128, SipHasher13};
 
 use super::{
-    cast, format_str, ops, Args, Array, CastInfo, Content, Dict, FromValue, Func,
-    IntoValue, Module, Reflect, Str, Symbol,
+    cast, format_str, ops, Args, Array, CastInfo, Content, Decimal, Dict, FromValue,
+    Func, IntoValue, Module, Quantity, Reflect, Str, Symbol,
 };
 use crate::diag::StrResult;
 use crate::geom::{Abs, Angle, Color, Em, Fr, Length, Ratio, Rel};
@@ -30,6 +30,8 @@ pub enum Value {
     Int(i64),
     /// A floating-point number: `1.2`, `10e-4`.
     Float(f64),
+    /// An exact decimal number: `decimal("1.25")`.
+    Decimal(Decimal),
     /// A length: `12pt`, `3cm`, `1.5em`, `1em - 2pt`.
     Length(Length),
     /// An angle: `1.5rad`, `90deg`.
@@ -95,6 +97,7 @@ impl Value {
             Self::Bool(_) => bool::TYPE_NAME,
             Self::Int(_) => i64::TYPE_NAME,
             Self::Float(_) => f64::TYPE_NAME,
+            Self::Decimal(_) => Decimal::TYPE_NAME,
             Self::Length(_) => Length::TYPE_NAME,
             Self::Angle(_) => Angle::TYPE_NAME,
             Self::Ratio(_) => Ratio::TYPE_NAME,
@@ -152,11 +155,15 @@ impl Value {
             Self::None => Content::empty(),
             Self::Int(v) => item!(text)(eco_format!("{}", v)),
             Self::Float(v) => item!(text)(eco_format!("{}", v)),
+            Self::Decimal(v) => item!(text)(eco_format!("{}", v)),
             Self::Str(v) => item!(text)(v.into()),
             Self::Symbol(v) => item!(text)(v.get().into()),
             Self::Content(v) => v,
             Self::Func(_) => Content::empty(),
             Self::Module(module) => module.content(),
+            Self::Dyn(v) if v.is::<Quantity>() => {
+                item!(quantity)(v.downcast::<Quantity>().unwrap().clone())
+            }
             _ => item!(raw)(self.repr().into(), Some("typc".into()), false),
         }
     }
@@ -178,6 +185,7 @@ impl Debug for Value {
             Self::Bool(v) => Debug::fmt(v, f),
             Self::Int(v) => Debug::fmt(v, f),
             Self::Float(v) => Debug::fmt(v, f),
+            Self::Decimal(v) => Debug::fmt(v, f),
             Self::Length(v) => Debug::fmt(v, f),
             Self::Angle(v) => Debug::fmt(v, f),
             Self::Ratio(v) => Debug::fmt(v, f),
@@ -220,6 +228,7 @@ impl Hash for Value {
             Self::Bool(v) => v.hash(state),
             Self::Int(v) => v.hash(state),
             Self::Float(v) => v.to_bits().hash(state),
+            Self::Decimal(v) => v.hash(state),
             Self::Length(v) => v.hash(state),
             Self::Angle(v) => v.hash(state),
             Self::Ratio(v) => v.hash(state),
@@ -384,6 +393,7 @@ macro_rules! primitive {
 primitive! { bool: "boolean", Bool }
 primitive! { i64: "integer", Int }
 primitive! { f64: "float", Float, Int(v) => v as f64 }
+primitive! { Decimal: "decimal", Decimal, Int(v) => v.into() }
 primitive! { Length: "length", Length }
 primitive! { Angle: "angle", Angle }
 primitive! { Ratio: "ratio", Ratio }
@@ -432,6 +442,7 @@ mod tests {
         test(false, "false");
         test(12i64, "12");
         test(3.24, "3.24");
+        test(Decimal::from(-12), "decimal(\"-12\")");
         test(Abs::pt(5.5), "5.5pt");
         test(Angle::deg(90.0), "90deg");
         test(Ratio::one() / 2.0, "50%");
@@ -0,0 +1,638 @@
+use std::fmt::Write;
+
+use ecow::{eco_format, EcoString};
+
+use super::html::{escape, placed_images, take_image};
+use super::zip::ZipWriter;
+use crate::diag::StrResult;
+use crate::doc::Document;
+use crate::eval::Value;
+use crate::image::{Image, ImageFormat, RasterFormat};
+use crate::model::Content;
+use crate::syntax::Span;
+use crate::util::Buffer;
+
+/// The number of EMUs (the unit of Office Open XML drawings) per pixel at 96
+/// pixels per inch.
+const EMU_PER_PX: f64 = 9525.0;
+
+/// The maximum width of an image in EMUs, which is six inches.
+const MAX_IMAGE_WIDTH: f64 = 5486400.0;
+
+/// Export a document into a Word document. (Experimental)
+///
+/// Like HTML export, this works on the document's content instead of its
+/// layout: Paragraphs, headings, lists, tables, images, raw text and links are
+/// translated into their Office Open XML counterparts, so that the text and
+/// its structure can be edited. Page layout and styling are not carried over.
+/// Images are taken from the laid out pages.
+#[tracing::instrument(skip_all)]
+pub fn docx(document: &Document) -> StrResult<Vec<u8>> {
+    let mut writer = DocxWriter {
+        placed: placed_images(&document.pages),
+        buf: String::new(),
+        par: false,
+        inline: false,
+        style: EcoString::new(),
+        numbering: None,
+        run: Run::default(),
+        list: None,
+        depth: 0,
+        rels: vec![],
+        nums: vec![],
+        images: vec![],
+    };
+    writer.content(&document.content);
+    writer.close_par();
+
+    let mut zip = ZipWriter::default();
+    zip.add("[Content_Types].xml", CONTENT_TYPES.as_bytes(), true);
+    zip.add("_rels/.rels", RELS.as_bytes(), true);
+
+    let mut core = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <cp:coreProperties \
+         xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n",
+    );
+    if let Some(title) = &document.title {
+        core.push_str("<dc:title>");
+        escape(&mut core, title);
+        core.push_str("</dc:title>\n");
+    }
+    if !document.author.is_empty() {
+        core.push_str("<dc:creator>");
+        escape(&mut core, &document.author.join("; "));
+        core.push_str("</dc:creator>\n");
+    }
+    core.push_str("</cp:coreProperties>\n");
+    zip.add("docProps/core.xml", core.as_bytes(), true);
+
+    let mut rels = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n\
+         <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>\n\
+         <Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/numbering\" Target=\"numbering.xml\"/>\n",
+    );
+    for (i, (kind, target, external)) in writer.rels.iter().enumerate() {
+        let _ = write!(
+            rels,
+            "<Relationship Id=\"rId{}\" \
+             Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/{kind}\" \
+             Target=\"",
+            i + 3,
+        );
+        escape(&mut rels, target);
+        rels.push('"');
+        if *external {
+            rels.push_str(" TargetMode=\"External\"");
+        }
+        rels.push_str("/>\n");
+    }
+    rels.push_str("</Relationships>\n");
+    zip.add("word/_rels/document.xml.rels", rels.as_bytes(), true);
+
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <w:document \
+         xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\" \
+         xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" \
+         xmlns:wp=\"http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing\" \
+         xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" \
+         xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">\n\
+         <w:body>\n{}</w:body>\n</w:document>\n",
+        writer.buf,
+    );
+    zip.add("word/document.xml", body.as_bytes(), true);
+    zip.add("word/styles.xml", styles().as_bytes(), true);
+    zip.add("word/numbering.xml", numbering(&writer.nums).as_bytes(), true);
+    for (name, data) in &writer.images {
+        zip.add(&eco_format!("word/{name}"), data, true);
+    }
+
+    Ok(zip.finish())
+}
+
+/// Declares the content types of the package's parts.
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Default Extension="png" ContentType="image/png"/>
+<Default Extension="jpg" ContentType="image/jpeg"/>
+<Default Extension="gif" ContentType="image/gif"/>
+<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+<Override PartName="/word/numbering.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml"/>
+<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
+</Types>
+"#;
+
+/// Points to the main document and its properties.
+const RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
+</Relationships>
+"#;
+
+/// Writes content as WordprocessingML.
+struct DocxWriter {
+    /// The images laid out in the document that are yet to be written.
+    placed: Vec<(Span, Image)>,
+    /// The markup written so far.
+    buf: String,
+    /// Whether a paragraph is open.
+    par: bool,
+    /// Whether paragraphs may not be broken, e.g. in the body of a heading.
+    inline: bool,
+    /// The paragraph style for new paragraphs.
+    style: EcoString,
+    /// The numbering for the next paragraph.
+    numbering: Option<EcoString>,
+    /// The properties for new runs.
+    run: Run,
+    /// The kind and numbering id of the list that is open, if any.
+    list: Option<(&'static str, usize)>,
+    /// How deeply lists are nested.
+    depth: usize,
+    /// The document's relationships, with their kind, target and whether the
+    /// target is external.
+    rels: Vec<(&'static str, EcoString, bool)>,
+    /// The numberings of the lists, with their abstract numbering, level and
+    /// start.
+    nums: Vec<(usize, usize, usize)>,
+    /// The images to package, with their names.
+    images: Vec<(EcoString, Buffer)>,
+}
+
+/// The properties of a run.
+#[derive(Debug, Default, Copy, Clone)]
+struct Run {
+    link: bool,
+    code: bool,
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    underline: bool,
+    script: Option<&'static str>,
+}
+
+impl DocxWriter {
+    /// Write content of any kind.
+    fn content(&mut self, content: &Content) {
+        if let Some(children) = content.to_sequence() {
+            children.for_each(|child| self.content(child));
+            return;
+        }
+
+        if let Some((child, _)) = content.to_styled() {
+            self.content(child);
+            return;
+        }
+
+        // Consecutive list items form one list, like in HTML export.
+        let name = content.func().name();
+        if !matches!(name, "listitem" | "enumitem" | "termitem" | "space" | "parbreak") {
+            self.list = None;
+        }
+
+        match name {
+            "text" => self.text(&content.expect_field::<EcoString>("text")),
+            "space" => {
+                if self.par {
+                    self.text(" ");
+                }
+            }
+            "linebreak" => {
+                self.open_par();
+                self.buf.push_str("<w:r><w:br/></w:r>");
+            }
+            "parbreak" => {
+                if self.inline {
+                    self.text(" ");
+                } else {
+                    self.close_par();
+                }
+            }
+            "smartquote" => {
+                let double = content.cast_field("double").unwrap_or(true);
+                self.text(if double { "\"" } else { "'" });
+            }
+            "strong" => self.styled(content, |run| run.bold = true),
+            "emph" => self.styled(content, |run| run.italic = true),
+            "underline" => self.styled(content, |run| run.underline = true),
+            "strike" => self.styled(content, |run| run.strike = true),
+            "sub" => self.styled(content, |run| run.script = Some("subscript")),
+            "super" => self.styled(content, |run| run.script = Some("superscript")),
+            "link" => {
+                let Some(Value::Str(url)) = content.field("dest") else {
+                    self.body(content);
+                    return;
+                };
+
+                self.open_par();
+                let id = self.rel("hyperlink", url.as_str().into(), true);
+                let _ = write!(self.buf, "<w:hyperlink r:id=\"{id}\">");
+                let inline = std::mem::replace(&mut self.inline, true);
+                self.styled(content, |run| run.link = true);
+                self.inline = inline;
+                self.buf.push_str("</w:hyperlink>");
+            }
+            "raw" => {
+                let text = content.expect_field::<EcoString>("text");
+                if content.cast_field("block").unwrap_or(false) && !self.inline {
+                    self.close_par();
+                    let style = self.set_style("SourceCode");
+                    for line in text.lines() {
+                        self.open_par();
+                        self.text(line);
+                        self.close_par();
+                    }
+                    self.style = style;
+                } else {
+                    let run = self.run;
+                    self.run.code = true;
+                    self.text(&text);
+                    self.run = run;
+                }
+            }
+            "equation" => self.text(&content.plain_text()),
+            "image" => self.image(content),
+            "heading" if !self.inline => {
+                self.close_par();
+                let level = content.cast_field::<usize>("level").unwrap_or(1).clamp(1, 6);
+                let style = self.set_style(&eco_format!("Heading{level}"));
+                let inline = std::mem::replace(&mut self.inline, true);
+                self.open_par();
+                self.body(content);
+                self.close_par();
+                self.inline = inline;
+                self.style = style;
+            }
+            "listitem" | "enumitem" | "termitem" if !self.inline => self.item(content),
+            "list" | "enum" | "terms" if !self.inline => {
+                let children = content.cast_field::<Vec<Content>>("children");
+                for child in children.unwrap_or_default() {
+                    self.item(&child);
+                }
+                self.list = None;
+            }
+            "table" if !self.inline => self.table(content),
+            "figure" if !self.inline => {
+                self.close_par();
+                if let Some(body) = content.cast_field::<Content>("body") {
+                    self.content(&body);
+                    self.close_par();
+                }
+                if let Some(caption) = content.cast_field::<Content>("caption") {
+                    let style = self.set_style("Caption");
+                    self.open_par();
+                    self.content(&caption);
+                    self.close_par();
+                    self.style = style;
+                }
+            }
+            _ => {
+                if content.has("body") {
+                    self.body(content);
+                } else if let Some(children) =
+                    content.cast_field::<Vec<Content>>("children")
+                {
+                    children.iter().for_each(|child| self.content(child));
+                }
+            }
+        }
+    }
+
+    /// Write the body of an element.
+    fn body(&mut self, content: &Content) {
+        if let Some(body) = content.cast_field::<Content>("body") {
+            self.content(&body);
+        }
+    }
+
+    /// Write an element's body with changed run properties.
+    fn styled(&mut self, content: &Content, f: impl FnOnce(&mut Run)) {
+        let run = self.run;
+        f(&mut self.run);
+        self.body(content);
+        self.run = run;
+    }
+
+    /// Write an item of a bullet list, numbered list or term list.
+    fn item(&mut self, item: &Content) {
+        let kind = match item.func().name() {
+            "listitem" => "ul",
+            "enumitem" => "ol",
+            "termitem" => "dl",
+            _ => return self.content(item),
+        };
+
+        self.close_par();
+        let id = match self.list {
+            Some((open, id)) if open == kind => id,
+            _ if kind == "dl" => 0,
+            _ => {
+                let start = item.cast_field::<usize>("number").unwrap_or(1);
+                self.nums.push((usize::from(kind == "ol"), self.depth, start));
+                self.nums.len()
+            }
+        };
+        self.list = Some((kind, id));
+
+        let style = self.set_style("ListParagraph");
+        if kind == "dl" {
+            self.open_par();
+            let run = self.run;
+            self.run.bold = true;
+            self.content(&item.cast_field::<Content>("term").unwrap_or_default());
+            self.run = run;
+            self.text(" ");
+            self.content(&item.cast_field::<Content>("description").unwrap_or_default());
+        } else {
+            self.numbering = Some(eco_format!(
+                "<w:numPr><w:ilvl w:val=\"{}\"/><w:numId w:val=\"{id}\"/></w:numPr>",
+                self.depth,
+            ));
+            let list = self.list.take();
+            self.depth += 1;
+            self.content(&item.cast_field::<Content>("body").unwrap_or_default());
+            self.depth -= 1;
+            self.list = list;
+
+            // An empty item still gets its bullet or number.
+            if self.numbering.is_some() {
+                self.open_par();
+            }
+        }
+        self.close_par();
+        self.style = style;
+    }
+
+    /// Write a table, with its cells filling the columns row by row.
+    fn table(&mut self, table: &Content) {
+        self.close_par();
+        let columns = match table.field("columns") {
+            Some(Value::Array(columns)) => columns.len().max(1),
+            _ => 1,
+        };
+
+        self.buf.push_str(
+            "<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/>\
+             <w:tblW w:w=\"0\" w:type=\"auto\"/></w:tblPr><w:tblGrid>",
+        );
+        for _ in 0..columns {
+            self.buf.push_str("<w:gridCol/>");
+        }
+        self.buf.push_str("</w:tblGrid>\n");
+
+        let style = std::mem::take(&mut self.style);
+        let cells = table.cast_field::<Vec<Content>>("children").unwrap_or_default();
+        for row in cells.chunks(columns) {
+            self.buf.push_str("<w:tr>");
+            for cell in row {
+                self.buf.push_str("<w:tc>");
+                let len = self.buf.len();
+                self.content(cell);
+                self.close_par();
+                self.list = None;
+
+                // A cell must contain at least one paragraph.
+                if self.buf.len() == len {
+                    self.buf.push_str("<w:p/>");
+                }
+                self.buf.push_str("</w:tc>");
+            }
+
+            // Fill up the last row.
+            for _ in row.len()..columns {
+                self.buf.push_str("<w:tc><w:p/></w:tc>");
+            }
+            self.buf.push_str("</w:tr>\n");
+        }
+        self.buf.push_str("</w:tbl>\n");
+        self.style = style;
+    }
+
+    /// Write an inline image.
+    ///
+    /// The image is taken from the layout instead of being read again. An
+    /// image that wasn't laid out is left out.
+    fn image(&mut self, content: &Content) {
+        let alt = content.cast_field::<EcoString>("alt").unwrap_or_default();
+        let Some(image) = take_image(&mut self.placed, content.span()) else { return };
+
+        // Vector images aren't supported by all word processors, so they are
+        // replaced by their alternative text.
+        let ext = match image.format() {
+            ImageFormat::Raster(RasterFormat::Png) => "png",
+            ImageFormat::Raster(RasterFormat::Jpg) => "jpg",
+            ImageFormat::Raster(RasterFormat::Gif) => "gif",
+            ImageFormat::Vector(_) => return self.text(&alt),
+        };
+
+        let width = image.width() as f64 * EMU_PER_PX;
+        let height = image.height() as f64 * EMU_PER_PX;
+        let scale = (MAX_IMAGE_WIDTH / width).min(1.0);
+        let (cx, cy) = ((width * scale) as u64, (height * scale) as u64);
+
+        let n = self.images.len() + 1;
+        let name = eco_format!("media/image{n}.{ext}");
+        let id = self.rel("image", name.clone(), false);
+        self.images.push((name, image.data().clone()));
+
+        self.open_par();
+        let _ = write!(
+            self.buf,
+            "<w:r><w:drawing><wp:inline><wp:extent cx=\"{cx}\" cy=\"{cy}\"/>\
+             <wp:docPr id=\"{n}\" name=\"Image {n}\" descr=\""
+        );
+        escape(&mut self.buf, &alt);
+        let _ = write!(
+            self.buf,
+            "\"/><a:graphic><a:graphicData \
+             uri=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">\
+             <pic:pic><pic:nvPicPr><pic:cNvPr id=\"{n}\" name=\"Image {n}\"/>\
+             <pic:cNvPicPr/></pic:nvPicPr><pic:blipFill><a:blip r:embed=\"{id}\"/>\
+             <a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr><a:xfrm>\
+             <a:off x=\"0\" y=\"0\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm>\
+             <a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr></pic:pic>\
+             </a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"
+        );
+    }
+
+    /// Write a run of text.
+    fn text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        self.open_par();
+        self.buf.push_str("<w:r>");
+
+        // The properties must be in the order given by the schema.
+        let run = self.run;
+        let mut props = String::new();
+        if run.link {
+            props.push_str("<w:rStyle w:val=\"Hyperlink\"/>");
+        }
+        if run.code {
+            props.push_str("<w:rFonts w:ascii=\"Courier New\" w:hAnsi=\"Courier New\"/>");
+        }
+        if run.bold {
+            props.push_str("<w:b/>");
+        }
+        if run.italic {
+            props.push_str("<w:i/>");
+        }
+        if run.strike {
+            props.push_str("<w:strike/>");
+        }
+        if run.underline {
+            props.push_str("<w:u w:val=\"single\"/>");
+        }
+        if let Some(script) = run.script {
+            let _ = write!(props, "<w:vertAlign w:val=\"{script}\"/>");
+        }
+        if !props.is_empty() {
+            let _ = write!(self.buf, "<w:rPr>{props}</w:rPr>");
+        }
+
+        self.buf.push_str("<w:t xml:space=\"preserve\">");
+        escape(&mut self.buf, text);
+        self.buf.push_str("</w:t></w:r>");
+    }
+
+    /// Add a relationship and return its id.
+    fn rel(
+        &mut self,
+        kind: &'static str,
+        target: EcoString,
+        external: bool,
+    ) -> EcoString {
+        self.rels.push((kind, target, external));
+
+        // The first two ids belong to the styles and the numbering.
+        eco_format!("rId{}", self.rels.len() + 2)
+    }
+
+    /// Set the paragraph style for new paragraphs, returning the previous one.
+    fn set_style(&mut self, style: &str) -> EcoString {
+        std::mem::replace(&mut self.style, style.into())
+    }
+
+    /// Open a paragraph if none is open.
+    fn open_par(&mut self) {
+        if self.par {
+            return;
+        }
+
+        self.buf.push_str("<w:p>");
+        let numbering = self.numbering.take();
+        if !self.style.is_empty() || numbering.is_some() {
+            self.buf.push_str("<w:pPr>");
+            if !self.style.is_empty() {
+                let _ = write!(self.buf, "<w:pStyle w:val=\"{}\"/>", self.style);
+            }
+            self.buf.push_str(numbering.as_deref().unwrap_or_default());
+            self.buf.push_str("</w:pPr>");
+        }
+        self.par = true;
+    }
+
+    /// Close the open paragraph, if any.
+    fn close_par(&mut self) {
+        if self.par {
+            self.buf.push_str("</w:p>\n");
+            self.par = false;
+        }
+    }
+}
+
+/// The styles referenced by the document.
+fn styles() -> String {
+    let mut buf = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:pPr><w:spacing w:after="120"/></w:pPr></w:style>
+"#,
+    );
+
+    for level in 1..=6 {
+        let size = 32 - 4 * level.min(4);
+        let _ = writeln!(
+            buf,
+            r#"<w:style w:type="paragraph" w:styleId="Heading{level}"><w:name w:val="heading {level}"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="240"/><w:outlineLvl w:val="{}"/></w:pPr><w:rPr><w:b/><w:sz w:val="{size}"/></w:rPr></w:style>"#,
+            level - 1,
+        );
+    }
+
+    buf.push_str(
+        r#"<w:style w:type="paragraph" w:styleId="ListParagraph"><w:name w:val="List Paragraph"/><w:basedOn w:val="Normal"/><w:pPr><w:ind w:left="720"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="SourceCode"><w:name w:val="Source Code"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="0"/></w:pPr><w:rPr><w:rFonts w:ascii="Courier New" w:hAnsi="Courier New"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Caption"><w:name w:val="caption"/><w:basedOn w:val="Normal"/><w:rPr><w:i/></w:rPr></w:style>
+<w:style w:type="character" w:styleId="Hyperlink"><w:name w:val="Hyperlink"/><w:rPr><w:color w:val="0563C1"/><w:u w:val="single"/></w:rPr></w:style>
+<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4"/><w:left w:val="single" w:sz="4"/><w:bottom w:val="single" w:sz="4"/><w:right w:val="single" w:sz="4"/><w:insideH w:val="single" w:sz="4"/><w:insideV w:val="single" w:sz="4"/></w:tblBorders></w:tblPr></w:style>
+</w:styles>
+"#,
+    );
+    buf
+}
+
+/// The numbering definitions for the lists.
+///
+/// Bullet lists use the first abstract numbering and numbered lists the
+/// second. Each list gets its own numbering, so that numbered lists restart.
+fn numbering(nums: &[(usize, usize, usize)]) -> String {
+    let mut buf = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <w:numbering xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">\n",
+    );
+
+    for id in 0..2 {
+        let _ = write!(buf, "<w:abstractNum w:abstractNumId=\"{id}\">");
+        for level in 0..9 {
+            let (format, text) = if id == 0 {
+                ("bullet", EcoString::from(["•", "◦", "▪"][level % 3]))
+            } else {
+                ("decimal", eco_format!("%{}.", level + 1))
+            };
+            let _ = write!(
+                buf,
+                "<w:lvl w:ilvl=\"{level}\"><w:start w:val=\"1\"/>\
+                 <w:numFmt w:val=\"{format}\"/><w:lvlText w:val=\"{text}\"/>\
+                 <w:lvlJc w:val=\"left\"/><w:pPr><w:ind w:left=\"{}\" w:hanging=\"360\"/>\
+                 </w:pPr></w:lvl>",
+                720 * (level + 1),
+            );
+        }
+        buf.push_str("</w:abstractNum>\n");
+    }
+
+    for (i, &(abstract_id, level, start)) in nums.iter().enumerate() {
+        let _ = writeln!(
+            buf,
+            "<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"{abstract_id}\"/>\
+             <w:lvlOverride w:ilvl=\"{level}\"><w:startOverride w:val=\"{start}\"/>\
+             </w:lvlOverride></w:num>",
+            i + 1,
+        );
+    }
+
+    buf.push_str("</w:numbering>\n");
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docx_numbering_restarts() {
+        let xml = numbering(&[(1, 0, 1), (1, 0, 3)]);
+        assert!(xml.contains("<w:num w:numId=\"1\"><w:abstractNumId w:val=\"1\"/>"));
+        assert!(xml.contains("<w:num w:numId=\"2\">"));
+        assert!(xml.contains("<w:startOverride w:val=\"3\"/>"));
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use ecow::{eco_format, EcoString};
+use ttf_parser::Tag;
+
+use super::html::{escape, image_name, placed_images, HtmlWriter};
+use super::zip::ZipWriter;
+use crate::diag::StrResult;
+use crate::doc::{Document, Frame, FrameItem, Lang};
+use crate::font::{Font, FontStyle};
+use crate::util::hash128;
+
+/// Export a document into an EPUB 3 e-book. (Experimental)
+///
+/// The document's content is converted like in HTML export,
+/// with a new chapter starting at each top-level heading. The headings make
+/// up the e-book's table of contents. The images are taken from the laid out
+/// pages and the fonts used in the document are embedded, subsetted to the
+/// glyphs the document needs.
+#[tracing::instrument(skip_all)]
+pub fn epub(document: &Document) -> StrResult<Vec<u8>> {
+    let mut writer = HtmlWriter {
+        images: Some(vec![]),
+        placed: placed_images(&document.pages),
+        chapters: Some(vec![]),
+        ..Default::default()
+    };
+    writer.flow(&document.content);
+
+    let mut chapters = writer.chapters.take().unwrap_or_default();
+    if !writer.buf.is_empty() || chapters.is_empty() {
+        chapters.push(std::mem::take(&mut writer.buf));
+    }
+
+    let mut fonts = vec![];
+    let mut lang = None;
+    for page in &document.pages {
+        collect(page, &mut fonts, &mut lang);
+    }
+
+    let title = document.title.clone().unwrap_or_else(|| "Untitled".into());
+    let lang = lang.unwrap_or(Lang::ENGLISH);
+    let lang = lang.as_str();
+    let mut zip = ZipWriter::default();
+
+    // The mimetype must come first and stay uncompressed, so that the file
+    // can be identified by its first bytes.
+    zip.add("mimetype", b"application/epub+zip", false);
+    zip.add("META-INF/container.xml", CONTAINER.as_bytes(), true);
+
+    // The package document lists all files and the reading order.
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    for i in 0..chapters.len() {
+        let _ = writeln!(
+            manifest,
+            r#"    <item id="chapter-{0}" href="chapter-{0}.xhtml" media-type="application/xhtml+xml"/>"#,
+            i + 1,
+        );
+        let _ = writeln!(spine, r#"    <itemref idref="chapter-{}"/>"#, i + 1);
+    }
+
+    let mut css = String::new();
+    for (i, (font, glyphs)) in fonts.iter().enumerate() {
+        let name = font_name(i, font);
+        let media = if name.ends_with(".otf") { "font/otf" } else { "font/ttf" };
+        let _ = writeln!(
+            manifest,
+            r#"    <item id="font-{}" href="{name}" media-type="{media}"/>"#,
+            i + 1,
+        );
+        let glyphs: Vec<u16> = glyphs.iter().copied().collect();
+        zip.add(&eco_format!("EPUB/{name}"), &subset_font(font, &glyphs), true);
+
+        let info = font.info();
+        let style = match info.variant.style {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        };
+        let _ = writeln!(
+            css,
+            "@font-face {{ font-family: \"{}\"; font-weight: {}; font-style: {style}; \
+             src: url(\"{name}\"); }}",
+            info.family,
+            info.variant.weight.to_number(),
+        );
+    }
+    if let Some((font, _)) = fonts.first() {
+        let _ = writeln!(css, "body {{ font-family: \"{}\"; }}", font.info().family);
+    }
+
+    for (i, image) in writer.images.take().unwrap_or_default().iter().enumerate() {
+        let name = image_name(i, image);
+        let media = match name.rsplit('.').next() {
+            Some("jpg" | "jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("svg") => "image/svg+xml",
+            _ => "image/png",
+        };
+        let _ = writeln!(
+            manifest,
+            r#"    <item id="image-{}" href="{name}" media-type="{media}"/>"#,
+            i + 1,
+        );
+        zip.add(&eco_format!("EPUB/{name}"), image.data(), true);
+    }
+
+    let mut metadata = String::new();
+    let _ = writeln!(
+        metadata,
+        r#"    <dc:identifier id="id">urn:typst:{:032x}</dc:identifier>"#,
+        hash128(&document.content),
+    );
+    metadata.push_str("    <dc:title>");
+    escape(&mut metadata, &title);
+    metadata.push_str("</dc:title>\n");
+    let _ = writeln!(metadata, "    <dc:language>{lang}</dc:language>");
+    for author in &document.author {
+        metadata.push_str("    <dc:creator>");
+        escape(&mut metadata, author);
+        metadata.push_str("</dc:creator>\n");
+    }
+    let modified = document
+        .date
+        .and_then(|date| {
+            Some(eco_format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+                date.year()?,
+                date.month()?,
+                date.day()?,
+                date.hour().unwrap_or(0),
+                date.minute().unwrap_or(0),
+                date.second().unwrap_or(0),
+            ))
+        })
+        .unwrap_or_else(|| "1970-01-01T00:00:00Z".into());
+    let _ =
+        writeln!(metadata, r#"    <meta property="dcterms:modified">{modified}</meta>"#);
+
+    let package = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+{metadata}  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="style" href="style.css" media-type="text/css"/>
+{manifest}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+"#
+    );
+    zip.add("EPUB/package.opf", package.as_bytes(), true);
+    zip.add("EPUB/style.css", css.as_bytes(), true);
+
+    // The navigation document nests the headings by their level.
+    let mut nav = String::new();
+    let mut depth = 0;
+    for heading in &writer.headings {
+        let level = heading.level.min(depth + 1);
+        if level > depth {
+            nav.push_str("<ol>\n");
+            depth += 1;
+        } else {
+            nav.push_str("</li>\n");
+            while depth > level {
+                nav.push_str("</ol>\n</li>\n");
+                depth -= 1;
+            }
+        }
+        let _ = write!(
+            nav,
+            "<li><a href=\"chapter-{}.xhtml#{}\">",
+            heading.chapter + 1,
+            heading.id
+        );
+        escape(&mut nav, &heading.title);
+        nav.push_str("</a>");
+    }
+    while depth > 0 {
+        nav.push_str("</li>\n</ol>\n");
+        depth -= 1;
+    }
+    if writer.headings.is_empty() {
+        nav.push_str("<ol>\n<li><a href=\"chapter-1.xhtml\">");
+        escape(&mut nav, &title);
+        nav.push_str("</a></li>\n</ol>\n");
+    }
+
+    let nav = format!("<nav epub:type=\"toc\" id=\"toc\">\n{nav}</nav>\n");
+    zip.add("EPUB/nav.xhtml", xhtml(&title, lang, &nav).as_bytes(), true);
+    for (i, chapter) in chapters.iter().enumerate() {
+        let path = eco_format!("EPUB/chapter-{}.xhtml", i + 1);
+        zip.add(&path, xhtml(&title, lang, chapter).as_bytes(), true);
+    }
+
+    Ok(zip.finish())
+}
+
+/// The container file pointing to the package document.
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="EPUB/package.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// Wrap a body into an XHTML document.
+fn xhtml(title: &str, lang: &str, body: &str) -> String {
+    let mut buf = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    buf.push_str("<!DOCTYPE html>\n");
+    let _ = writeln!(
+        buf,
+        "<html xmlns=\"http://www.w3.org/1999/xhtml\" \
+         xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{lang}\" xml:lang=\"{lang}\">"
+    );
+    buf.push_str("<head>\n<title>");
+    escape(&mut buf, title);
+    buf.push_str("</title>\n");
+    buf.push_str("<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\" />\n");
+    buf.push_str("</head>\n<body>\n");
+    buf.push_str(body);
+    buf.push_str("</body>\n</html>\n");
+    buf
+}
+
+/// Collect the fonts used in a frame, the glyphs used from them and the
+/// language of the frame's first text.
+///
+/// Besides the laid out glyphs, the glyphs that the font maps the text's
+/// characters to are kept, as reading systems lay the text out anew.
+fn collect(
+    frame: &Frame,
+    fonts: &mut Vec<(Font, BTreeSet<u16>)>,
+    lang: &mut Option<Lang>,
+) {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => collect(&group.frame, fonts, lang),
+            FrameItem::Text(text) => {
+                if lang.is_none() {
+                    *lang = Some(text.lang);
+                }
+
+                let index = match fonts.iter().position(|(font, _)| *font == text.font) {
+                    Some(index) => index,
+                    None => {
+                        fonts.push((text.font.clone(), BTreeSet::new()));
+                        fonts.len() - 1
+                    }
+                };
+
+                let (font, glyphs) = &mut fonts[index];
+                glyphs.extend(text.glyphs.iter().map(|glyph| glyph.id));
+                glyphs.extend(
+                    text.text
+                        .chars()
+                        .filter_map(|c| font.ttf().glyph_index(c))
+                        .map(|id| id.0),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Subset a font to the given glyphs.
+///
+/// The subsetter keeps the glyph ids, but only the tables needed to embed the
+/// font into a PDF. The tables with which reading systems map text to glyphs
+/// and name the font are therefore carried over from the original font.
+fn subset_font(font: &Font, glyphs: &[u16]) -> Vec<u8> {
+    let profile = subsetter::Profile::pdf(glyphs);
+    let Ok(subsetted) = subsetter::subset(font.data(), font.index(), profile) else {
+        return font.data().to_vec();
+    };
+
+    let Some(mut tables) = read_tables(&subsetted) else { return subsetted };
+    let original = font.ttf().raw_face();
+    for tag in [b"cmap", b"name", b"OS/2", b"post"] {
+        if !tables.iter().any(|(other, _)| other == tag) {
+            if let Some(data) = original.table(Tag::from_bytes(tag)) {
+                tables.push((*tag, data));
+            }
+        }
+    }
+
+    write_tables(&subsetted[..4], tables)
+}
+
+/// Read the tables of an OpenType font.
+fn read_tables(data: &[u8]) -> Option<Vec<([u8; 4], &[u8])>> {
+    let u32_at =
+        |i: usize| Some(u32::from_be_bytes(data.get(i..i + 4)?.try_into().ok()?));
+    let count = u16::from_be_bytes(data.get(4..6)?.try_into().ok()?);
+    (0..usize::from(count))
+        .map(|i| {
+            let record = 12 + 16 * i;
+            let tag = data.get(record..record + 4)?.try_into().ok()?;
+            let offset = u32_at(record + 8)? as usize;
+            let length = u32_at(record + 12)? as usize;
+            Some((tag, data.get(offset..offset.checked_add(length)?)?))
+        })
+        .collect()
+}
+
+/// Write an OpenType font with the given version and tables.
+fn write_tables(version: &[u8], mut tables: Vec<([u8; 4], &[u8])>) -> Vec<u8> {
+    tables.sort_by_key(|&(tag, _)| tag);
+
+    // The search parameters are derived from the largest power of two that
+    // is at most the number of tables.
+    let count = tables.len() as u16;
+    let log = 15 - count.max(1).leading_zeros() as u16;
+    let range = 16 << log;
+
+    let mut buf = version.to_vec();
+    for v in [count, range, log, count * 16 - range] {
+        buf.extend(v.to_be_bytes());
+    }
+
+    let mut offset = 12 + 16 * tables.len();
+    for &(tag, data) in &tables {
+        let checksum = data.chunks(4).fold(0u32, |sum, chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            sum.wrapping_add(u32::from_be_bytes(word))
+        });
+        buf.extend(tag);
+        buf.extend(checksum.to_be_bytes());
+        buf.extend((offset as u32).to_be_bytes());
+        buf.extend((data.len() as u32).to_be_bytes());
+        offset += (data.len() + 3) / 4 * 4;
+    }
+
+    // Each table starts at a multiple of four bytes.
+    for (_, data) in tables {
+        buf.extend(data);
+        buf.resize((buf.len() + 3) / 4 * 4, 0);
+    }
+
+    buf
+}
+
+/// The name under which a font is packaged.
+fn font_name(index: usize, font: &Font) -> EcoString {
+    let face = font.ttf().raw_face();
+    let cff = face.table(Tag::from_bytes(b"CFF ")).is_some()
+        || face.table(Tag::from_bytes(b"CFF2")).is_some();
+    eco_format!("fonts/{}.{}", index + 1, if cff { "otf" } else { "ttf" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epub_mimetype_first() {
+        let epub = epub(&Document::default()).unwrap();
+        assert_eq!(&epub[..4], b"PK\x03\x04");
+        assert_eq!(&epub[30..38], b"mimetype");
+        assert_eq!(&epub[38..58], b"application/epub+zip");
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt::Write;
+
+use ecow::{eco_format, EcoString};
+
+use crate::doc::{Document, Frame, FrameItem};
+use crate::eval::Value;
+use crate::image::{Image, ImageFormat, RasterFormat, VectorFormat};
+use crate::model::Content;
+use crate::syntax::Span;
+
+/// Export a document into HTML. (Experimental)
+///
+/// Unlike the other exporters, this one works on the document's content
+/// instead of its layout: Headings, paragraphs, lists, tables, images, raw
+/// text and links are mapped to their semantic counterparts. Show rules and
+/// styling are not applied and elements without an HTML counterpart are
+/// reduced to their body. Images are referenced by their path.
+#[tracing::instrument(skip_all)]
+pub fn html(document: &Document) -> String {
+    let mut writer = HtmlWriter::default();
+    writer.flow(&document.content);
+
+    let mut buf = String::from("<!DOCTYPE html>\n<html>\n<head>\n");
+    buf.push_str("<meta charset=\"utf-8\" />\n");
+    if let Some(title) = &document.title {
+        buf.push_str("<title>");
+        escape(&mut buf, title);
+        buf.push_str("</title>\n");
+    }
+    buf.push_str("</head>\n<body>\n");
+    buf.push_str(&writer.buf);
+    buf.push_str("</body>\n</html>\n");
+    buf
+}
+
+/// Writes content as HTML.
+///
+/// The markup is also well-formed XML, so that it can be packaged as XHTML.
+#[derive(Default)]
+pub(super) struct HtmlWriter {
+    /// The HTML written so far.
+    pub buf: String,
+    /// The headings written so far.
+    pub headings: Vec<Heading>,
+    /// The images written so far. If this is `Some`, images are taken from
+    /// `placed` and referenced by their index instead of their path.
+    pub images: Option<Vec<Image>>,
+    /// The images laid out in the document that are yet to be written.
+    pub placed: Vec<(Span, Image)>,
+    /// The finished chapters. If this is `Some`, a new chapter starts at each
+    /// top-level heading of the first level.
+    pub chapters: Option<Vec<String>>,
+    /// How deeply nested the content being written is.
+    depth: usize,
+    /// Whether a paragraph is open.
+    par: bool,
+    /// The tag of the list that is open, if any.
+    list: Option<&'static str>,
+    /// Whether only inline content may be written.
+    inline: bool,
+}
+
+/// A heading written by an [`HtmlWriter`].
+pub(super) struct Heading {
+    /// The level of the heading.
+    pub level: usize,
+    /// The heading's id, by which it can be linked to.
+    pub id: EcoString,
+    /// The heading's plain text.
+    pub title: EcoString,
+    /// The index of the chapter the heading is in.
+    pub chapter: usize,
+}
+
+impl HtmlWriter {
+    /// Write content that consists of blocks, e.g. the body of a list item.
+    pub fn flow(&mut self, content: &Content) {
+        let par = std::mem::take(&mut self.par);
+        let list = self.list.take();
+        let inline = std::mem::take(&mut self.inline);
+        self.depth += 1;
+        self.content(content);
+        self.close();
+        self.depth -= 1;
+        self.par = par;
+        self.list = list;
+        self.inline = inline;
+    }
+
+    /// Write content that must stay inline, e.g. the body of a heading.
+    fn inline(&mut self, content: &Content) {
+        let inline = std::mem::replace(&mut self.inline, true);
+        self.content(content);
+        self.inline = inline;
+    }
+
+    /// Write content of any kind.
+    fn content(&mut self, content: &Content) {
+        if let Some(children) = content.to_sequence() {
+            children.for_each(|child| self.content(child));
+            return;
+        }
+
+        if let Some((child, _)) = content.to_styled() {
+            self.content(child);
+            return;
+        }
+
+        // Consecutive list items form one list, even if there are spaces or
+        // paragraph breaks between them.
+        let name = content.func().name();
+        if !matches!(name, "listitem" | "enumitem" | "termitem" | "space" | "parbreak") {
+            self.close_list();
+        }
+
+        match name {
+            "text" => {
+                self.open_par();
+                self.text(&content.expect_field::<EcoString>("text"));
+            }
+            "space" => {
+                if self.par || self.inline {
+                    self.buf.push(' ');
+                }
+            }
+            "linebreak" => {
+                self.open_par();
+                self.buf.push_str("<br />");
+            }
+            "parbreak" => {
+                if self.inline {
+                    self.buf.push(' ');
+                } else {
+                    self.close_par();
+                }
+            }
+            "smartquote" => {
+                self.open_par();
+                let double = content.cast_field("double").unwrap_or(true);
+                self.text(if double { "\"" } else { "'" });
+            }
+            "strong" => self.tagged("strong", content),
+            "emph" => self.tagged("em", content),
+            "sub" => self.tagged("sub", content),
+            "super" => self.tagged("sup", content),
+            "underline" => self.tagged("u", content),
+            "strike" => self.tagged("s", content),
+            "link" => {
+                self.open_par();
+                match content.field("dest") {
+                    Some(Value::Str(url)) => {
+                        self.buf.push_str("<a href=\"");
+                        self.text(&url);
+                        self.buf.push_str("\">");
+                    }
+                    _ => self.buf.push_str("<a>"),
+                }
+                self.body(content);
+                self.buf.push_str("</a>");
+            }
+            "raw" => {
+                let text = content.expect_field::<EcoString>("text");
+                if content.cast_field("block").unwrap_or(false) && !self.inline {
+                    self.close_par();
+                    self.buf.push_str("<pre><code");
+                    if let Some(lang) = content.cast_field::<EcoString>("lang") {
+                        self.buf.push_str(" class=\"language-");
+                        self.text(&lang);
+                        self.buf.push('"');
+                    }
+                    self.buf.push('>');
+                    self.text(&text);
+                    self.buf.push_str("</code></pre>\n");
+                } else {
+                    self.open_par();
+                    self.buf.push_str("<code>");
+                    self.text(&text);
+                    self.buf.push_str("</code>");
+                }
+            }
+            "equation" => {
+                let tag = if content.cast_field("block").unwrap_or(false) && !self.inline
+                {
+                    self.close_par();
+                    "div"
+                } else {
+                    self.open_par();
+                    "span"
+                };
+                let _ = write!(self.buf, "<{tag} class=\"math\">");
+                self.text(&content.plain_text());
+                let _ = write!(self.buf, "</{tag}>");
+                if tag == "div" {
+                    self.buf.push('\n');
+                }
+            }
+            "image" => self.image(content),
+            "heading" if !self.inline => {
+                self.close_par();
+                let level = content.cast_field::<usize>("level").unwrap_or(1).clamp(1, 6);
+                if let Some(chapters) = &mut self.chapters {
+                    if level == 1 && self.depth == 1 && !self.buf.is_empty() {
+                        chapters.push(std::mem::take(&mut self.buf));
+                    }
+                }
+
+                let id = eco_format!("heading-{}", self.headings.len() + 1);
+                let title = content
+                    .cast_field::<Content>("body")
+                    .map(|body| body.plain_text())
+                    .unwrap_or_default();
+                let chapter = self.chapters.as_ref().map_or(0, Vec::len);
+                let _ = write!(self.buf, "<h{level} id=\"{id}\">");
+                self.headings.push(Heading { level, id, title, chapter });
+                self.body(content);
+                let _ = writeln!(self.buf, "</h{level}>");
+            }
+            "listitem" | "enumitem" | "termitem" if !self.inline => self.item(content),
+            "list" | "enum" | "terms" if !self.inline => {
+                let children = content.cast_field::<Vec<Content>>("children");
+                for child in children.unwrap_or_default() {
+                    self.item(&child);
+                }
+                self.close_list();
+            }
+            "table" if !self.inline => self.table(content),
+            "figure" if !self.inline => {
+                self.close_par();
+                self.buf.push_str("<figure>\n");
+                if let Some(body) = content.cast_field::<Content>("body") {
+                    self.flow(&body);
+                }
+                if let Some(caption) = content.cast_field::<Content>("caption") {
+                    self.buf.push_str("<figcaption>");
+                    self.inline(&caption);
+                    self.buf.push_str("</figcaption>\n");
+                }
+                self.buf.push_str("</figure>\n");
+            }
+            _ => {
+                if content.has("body") {
+                    self.body(content);
+                } else if let Some(children) =
+                    content.cast_field::<Vec<Content>>("children")
+                {
+                    children.iter().for_each(|child| self.content(child));
+                }
+            }
+        }
+    }
+
+    /// Write the body of an element.
+    fn body(&mut self, content: &Content) {
+        if let Some(body) = content.cast_field::<Content>("body") {
+            if self.inline || matches!(content.func().name(), "heading" | "link") {
+                self.inline(&body);
+            } else {
+                self.content(&body);
+            }
+        }
+    }
+
+    /// Write an element's body wrapped in an inline tag.
+    fn tagged(&mut self, tag: &str, content: &Content) {
+        self.open_par();
+        let _ = write!(self.buf, "<{tag}>");
+        self.inline(&content.cast_field::<Content>("body").unwrap_or_default());
+        let _ = write!(self.buf, "</{tag}>");
+    }
+
+    /// Write an item of a bullet list, numbered list or term list.
+    fn item(&mut self, item: &Content) {
+        let tag = match item.func().name() {
+            "listitem" => "ul",
+            "enumitem" => "ol",
+            "termitem" => "dl",
+            _ => return self.content(item),
+        };
+
+        self.close_par();
+        if self.list != Some(tag) {
+            self.close_list();
+            let _ = writeln!(self.buf, "<{tag}>");
+            self.list = Some(tag);
+        }
+
+        if tag == "dl" {
+            self.buf.push_str("<dt>");
+            self.inline(&item.cast_field::<Content>("term").unwrap_or_default());
+            self.buf.push_str("</dt>\n<dd>");
+            self.flow(&item.cast_field::<Content>("description").unwrap_or_default());
+            self.buf.push_str("</dd>\n");
+        } else {
+            match item.cast_field::<usize>("number") {
+                Some(number) => {
+                    let _ = write!(self.buf, "<li value=\"{number}\">");
+                }
+                None => self.buf.push_str("<li>"),
+            }
+            self.flow(&item.cast_field::<Content>("body").unwrap_or_default());
+            self.buf.push_str("</li>\n");
+        }
+    }
+
+    /// Write a table, with its cells filling the columns row by row.
+    fn table(&mut self, table: &Content) {
+        self.close_par();
+        let columns = match table.field("columns") {
+            Some(Value::Array(columns)) => columns.len().max(1),
+            _ => 1,
+        };
+
+        self.buf.push_str("<table>\n");
+        let cells = table.cast_field::<Vec<Content>>("children").unwrap_or_default();
+        for row in cells.chunks(columns) {
+            self.buf.push_str("<tr>");
+            for cell in row {
+                self.buf.push_str("<td>");
+                self.flow(cell);
+                self.buf.push_str("</td>");
+            }
+            self.buf.push_str("</tr>\n");
+        }
+        self.buf.push_str("</table>\n");
+    }
+
+    /// Open a paragraph if none is open and block content is allowed.
+    fn open_par(&mut self) {
+        if !self.inline && !self.par {
+            self.buf.push_str("<p>");
+            self.par = true;
+        }
+    }
+
+    /// Close the open paragraph, if any.
+    fn close_par(&mut self) {
+        if self.par {
+            self.buf.push_str("</p>\n");
+            self.par = false;
+        }
+    }
+
+    /// Close the open list, if any.
+    fn close_list(&mut self) {
+        if let Some(tag) = self.list.take() {
+            let _ = writeln!(self.buf, "</{tag}>");
+        }
+    }
+
+    /// Close the open paragraph and list.
+    fn close(&mut self) {
+        self.close_par();
+        self.close_list();
+    }
+
+    /// Write an image.
+    ///
+    /// When packaging images, they are taken from the layout instead of being
+    /// read again. An image that wasn't laid out is left out.
+    fn image(&mut self, content: &Content) {
+        let src = match &mut self.images {
+            Some(images) => {
+                let Some(image) = take_image(&mut self.placed, content.span()) else {
+                    return;
+                };
+                let name = image_name(images.len(), &image);
+                images.push(image);
+                name
+            }
+            None => content.expect_field::<EcoString>("path"),
+        };
+
+        self.open_par();
+        self.buf.push_str("<img src=\"");
+        self.text(&src);
+        self.buf.push_str("\" alt=\"");
+        if let Some(alt) = content.cast_field::<EcoString>("alt") {
+            self.text(&alt);
+        }
+        self.buf.push_str("\" />");
+    }
+
+    /// Write escaped text.
+    fn text(&mut self, text: &str) {
+        escape(&mut self.buf, text);
+    }
+}
+
+/// The images laid out in some frames, in layout order and together with the
+/// span of the element that placed them.
+pub(super) fn placed_images(frames: &[Frame]) -> Vec<(Span, Image)> {
+    fn collect(frame: &Frame, images: &mut Vec<(Span, Image)>) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => collect(&group.frame, images),
+                FrameItem::Image(image, _, span) => images.push((*span, image.clone())),
+                _ => {}
+            }
+        }
+    }
+
+    let mut images = vec![];
+    for frame in frames {
+        collect(frame, &mut images);
+    }
+    images
+}
+
+/// Take the first of the placed images that stems from the element with the
+/// given span.
+///
+/// Elements created by the same piece of code share a span. Their images are
+/// assumed to be laid out in the order in which they appear in the content.
+pub(super) fn take_image(placed: &mut Vec<(Span, Image)>, span: Span) -> Option<Image> {
+    let index = placed.iter().position(|&(other, _)| other == span)?;
+    Some(placed.remove(index).1)
+}
+
+/// The name under which an image is packaged, given its index.
+pub(super) fn image_name(index: usize, image: &Image) -> EcoString {
+    let ext = match image.format() {
+        ImageFormat::Raster(RasterFormat::Png) => "png",
+        ImageFormat::Raster(RasterFormat::Jpg) => "jpg",
+        ImageFormat::Raster(RasterFormat::Gif) => "gif",
+        ImageFormat::Vector(VectorFormat::Svg) => "svg",
+    };
+    eco_format!("images/{}.{ext}", index + 1)
+}
+
+/// Write text, escaping characters with a special meaning in HTML and XML.
+pub(super) fn escape(buf: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '"' => buf.push_str("&quot;"),
+            '\u{a0}' => buf.push_str("&#160;"),
+            c => buf.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_escape() {
+        let mut buf = String::new();
+        escape(&mut buf, "<a href=\"x\">&</a>");
+        assert_eq!(buf, "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+}
@@ -0,0 +1,198 @@
+use std::num::NonZeroUsize;
+
+use crate::doc::{Frame, FrameItem};
+use crate::geom::{Abs, Geometry, Numeric, Point, Ratio, Size, Stroke, Transform};
+use crate::syntax::Span;
+
+/// Impose pages for saddle-stitch binding.
+///
+/// Places the pages two-up onto sheets that are printed on both sides, folded
+/// in the middle and stapled along the fold. The pages are padded with blank
+/// pages to a multiple of four. Each side of a sheet is returned as one frame,
+/// with the front and back of a sheet following each other.
+#[tracing::instrument(skip_all)]
+pub fn booklet(pages: &[Frame]) -> Vec<Frame> {
+    if pages.is_empty() {
+        return vec![];
+    }
+
+    let n = (pages.len() + 3) / 4 * 4;
+    let slot = slot_size(pages);
+    (0..n / 2)
+        .map(|i| {
+            // The outermost sheet carries the first and the last page and
+            // the left and right pages swap from one side to the next.
+            let (left, right) = if i % 2 == 0 { (n - 1 - i, i) } else { (i, n - 1 - i) };
+            let mut sheet = Frame::new(Size::new(2.0 * slot.x, slot.y));
+            for (k, index) in [left, right].into_iter().enumerate() {
+                if let Some(page) = pages.get(index) {
+                    let origin = Point::with_x(k as f64 * slot.x);
+                    sheet.push_frame(origin + centered(page.size(), slot), page.clone());
+                }
+            }
+            sheet
+        })
+        .collect()
+}
+
+/// Shrink multiple pages onto each sheet.
+///
+/// The sheets have the size of the largest page and are divided into a grid
+/// with the given number of columns and rows. The pages fill the grid row by
+/// row and are scaled down uniformly to fit into its cells.
+#[tracing::instrument(skip_all)]
+pub fn nup(pages: &[Frame], columns: NonZeroUsize, rows: NonZeroUsize) -> Vec<Frame> {
+    let slot = slot_size(pages);
+    let (columns, rows) = (columns.get(), rows.get());
+    let cell = Size::new(slot.x / columns as f64, slot.y / rows as f64);
+    let scale = (1.0 / columns as f64).min(1.0 / rows as f64);
+    pages
+        .chunks(columns * rows)
+        .map(|chunk| {
+            let mut sheet = Frame::new(slot);
+            for (i, page) in chunk.iter().enumerate() {
+                let origin = Point::new(
+                    (i % columns) as f64 * cell.x,
+                    (i / columns) as f64 * cell.y,
+                );
+                let mut page = page.clone();
+                let size = page.size() * scale;
+                page.transform(Transform::scale(Ratio::new(scale), Ratio::new(scale)));
+                page.set_size(size);
+                sheet.push_frame(origin + centered(size, cell), page);
+            }
+            sheet
+        })
+        .collect()
+}
+
+/// Split pages that are larger than a sheet across multiple sheets.
+///
+/// Neighbouring sheets share a strip of the given width, which is marked at
+/// the sheets' edges to make aligning them easier. The sheets of a page
+/// follow each other row by row.
+#[tracing::instrument(skip_all)]
+pub fn tile(pages: &[Frame], sheet: Size, overlap: Abs) -> Vec<Frame> {
+    let step = sheet - Size::splat(overlap);
+    let count = |extent: Abs, sheet: Abs, step: Abs| {
+        if extent <= sheet || step <= Abs::zero() {
+            1
+        } else {
+            ((extent - overlap) / step).ceil() as usize
+        }
+    };
+
+    let stroke = Stroke { thickness: Abs::pt(0.25), ..Stroke::default() };
+    let mut sheets = vec![];
+    for page in pages {
+        let columns = count(page.width(), sheet.x, step.x);
+        let rows = count(page.height(), sheet.y, step.y);
+        for y in 0..rows {
+            for x in 0..columns {
+                let mut tile = Frame::new(sheet);
+                let offset = Point::new(x as f64 * step.x, y as f64 * step.y);
+                tile.push_frame(-offset, page.clone());
+                tile.clip();
+
+                // Mark the strips shared with the neighbouring sheets.
+                let mut marks = vec![];
+                if x > 0 {
+                    marks.push((Point::with_x(overlap), Point::with_y(sheet.y)));
+                }
+                if x + 1 < columns {
+                    marks.push((Point::with_x(step.x), Point::with_y(sheet.y)));
+                }
+                if y > 0 {
+                    marks.push((Point::with_y(overlap), Point::with_x(sheet.x)));
+                }
+                if y + 1 < rows {
+                    marks.push((Point::with_y(step.y), Point::with_x(sheet.x)));
+                }
+
+                for (pos, delta) in marks {
+                    let shape = Geometry::Line(delta).stroked(stroke.clone());
+                    tile.push(pos, FrameItem::Shape(shape, Span::detached()));
+                }
+
+                sheets.push(tile);
+            }
+        }
+    }
+
+    sheets
+}
+
+/// The size that fits all of the pages.
+fn slot_size(pages: &[Frame]) -> Size {
+    pages.iter().fold(Size::zero(), |size, page| size.max(page.size()))
+}
+
+/// The offset that centers an item of the given size in a slot.
+fn centered(size: Size, slot: Size) -> Point {
+    ((slot - size) / 2.0).to_point()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::{FrameItem, Meta};
+    use crate::eval::Value;
+    use crate::geom::Abs;
+
+    #[test]
+    fn test_booklet_order() {
+        // Mark each page with its number so that we can identify it.
+        let size = Size::splat(Abs::pt(10.0));
+        let pages: Vec<Frame> = (1..=6)
+            .map(|i| {
+                let mut frame = Frame::new(size);
+                let meta = Meta::PageNumbering(Value::Int(i));
+                frame.push(Point::zero(), FrameItem::Meta(meta, Size::zero()));
+                frame
+            })
+            .collect();
+
+        let sheets = booklet(&pages);
+        let order: Vec<Vec<(f64, i64)>> = sheets
+            .iter()
+            .map(|sheet| {
+                sheet
+                    .items()
+                    .map(|(pos, item)| match item {
+                        FrameItem::Meta(Meta::PageNumbering(Value::Int(i)), _) => {
+                            (pos.x.to_pt(), *i)
+                        }
+                        _ => panic!("expected page marker"),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Pages 7 and 8 are blank.
+        assert_eq!(
+            order,
+            [
+                vec![(10.0, 1)],
+                vec![(0.0, 2)],
+                vec![(0.0, 6), (10.0, 3)],
+                vec![(0.0, 4), (10.0, 5)],
+            ]
+        );
+        assert_eq!(sheets[0].size(), Size::new(Abs::pt(20.0), Abs::pt(10.0)));
+    }
+
+    #[test]
+    fn test_tile_count() {
+        let sheet = Size::splat(Abs::pt(100.0));
+        let overlap = Abs::pt(10.0);
+        let tiles = |w, h| {
+            let page = Frame::new(Size::new(Abs::pt(w), Abs::pt(h)));
+            tile(&[page], sheet, overlap).len()
+        };
+
+        assert_eq!(tiles(80.0, 100.0), 1);
+        assert_eq!(tiles(190.0, 100.0), 2);
+        assert_eq!(tiles(191.0, 100.0), 3);
+        assert_eq!(tiles(190.0, 190.0), 4);
+    }
+}
@@ -1,7 +1,18 @@
 //! Exporting into external formats.
 
+mod docx;
+mod epub;
+mod html;
+mod impose;
 mod pdf;
 mod render;
+mod text;
+mod zip;
 
-pub use self::pdf::pdf;
+pub use self::docx::docx;
+pub use self::epub::epub;
+pub use self::html::html;
+pub use self::impose::{booklet, nup, tile};
+pub use self::pdf::{pdf, pdf_with_destinations, pdf_with_standard, PdfStandard};
 pub use self::render::render;
+pub use self::text::text;
@@ -4,6 +4,7 @@ mod font;
 mod image;
 mod outline;
 mod page;
+mod pdfa;
 
 use std::cmp::Eq;
 use std::collections::{BTreeMap, HashMap};
@@ -11,11 +12,13 @@ use std::hash::Hash;
 
 use ecow::EcoString;
 use pdf_writer::types::Direction;
-use pdf_writer::{Finish, Name, PdfWriter, Ref, TextStr};
-use xmp_writer::{LangId, RenditionClass, XmpWriter};
+use pdf_writer::{Finish, Name, Null, PdfWriter, Ref, Str, TextStr};
+use xmp_writer::{DateTime, LangId, RenditionClass, Timezone, XmpWriter};
 
-use self::page::Page;
-use crate::doc::{Document, Lang};
+use self::page::{ExtGState, Page};
+use crate::diag::StrResult;
+use crate::doc::{Document, Lang, Position};
+use crate::eval::Datetime;
 use crate::font::Font;
 use crate::geom::{Abs, Dir, Em};
 use crate::image::Image;
@@ -26,10 +29,59 @@ use crate::model::Introspector;
 /// Returns the raw bytes making up the PDF file.
 #[tracing::instrument(skip_all)]
 pub fn pdf(document: &Document) -> Vec<u8> {
-    let mut ctx = PdfContext::new(document);
+    export(document, None, &[])
+}
+
+/// Export a document into a PDF file that conforms to a PDF standard.
+///
+/// Fails if the document uses constructs that the standard does not allow.
+#[tracing::instrument(skip_all)]
+pub fn pdf_with_standard(
+    document: &Document,
+    standard: PdfStandard,
+) -> StrResult<Vec<u8>> {
+    pdfa::check(document, standard)?;
+    Ok(export(document, Some(standard), &[]))
+}
+
+/// Export a document into a PDF file with additional named destinations.
+///
+/// Viewers can jump to a named destination by its name, for example when
+/// opening the file. If a standard is given, this fails like
+/// [`pdf_with_standard`].
+#[tracing::instrument(skip_all)]
+pub fn pdf_with_destinations(
+    document: &Document,
+    standard: Option<PdfStandard>,
+    destinations: &[(EcoString, Position)],
+) -> StrResult<Vec<u8>> {
+    match standard {
+        None => Ok(export(document, None, destinations)),
+        Some(standard) => {
+            pdfa::check(document, standard)?;
+            Ok(export(document, Some(standard), destinations))
+        }
+    }
+}
+
+/// A standard that an exported PDF file can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PdfStandard {
+    /// PDF/A-2b, for long-term archival.
+    A2b,
+}
+
+/// Export a document, optionally conforming to a standard.
+fn export(
+    document: &Document,
+    standard: Option<PdfStandard>,
+    destinations: &[(EcoString, Position)],
+) -> Vec<u8> {
+    let mut ctx = PdfContext::new(document, standard, destinations);
     page::construct_pages(&mut ctx, &document.pages);
     font::write_fonts(&mut ctx);
     image::write_images(&mut ctx);
+    page::write_ext_graphics_states(&mut ctx);
     page::write_page_tree(&mut ctx);
     write_catalog(&mut ctx);
     ctx.writer.finish()
@@ -42,6 +94,8 @@ const D65_GRAY: Name<'static> = Name(b"d65gray");
 /// Context for exporting a whole PDF document.
 pub struct PdfContext<'a> {
     document: &'a Document,
+    standard: Option<PdfStandard>,
+    destinations: &'a [(EcoString, Position)],
     introspector: Introspector,
     writer: PdfWriter,
     pages: Vec<Page>,
@@ -50,9 +104,11 @@ pub struct PdfContext<'a> {
     page_tree_ref: Ref,
     font_refs: Vec<Ref>,
     image_refs: Vec<Ref>,
+    ext_gs_refs: Vec<Ref>,
     page_refs: Vec<Ref>,
     font_map: Remapper<Font>,
     image_map: Remapper<Image>,
+    ext_gs_map: Remapper<ExtGState>,
     /// For each font a mapping from used glyphs to their text representation.
     /// May contain multiple chars in case of ligatures or similar things. The
     /// same glyph can have a different text representation within one document,
@@ -64,11 +120,17 @@ pub struct PdfContext<'a> {
 }
 
 impl<'a> PdfContext<'a> {
-    fn new(document: &'a Document) -> Self {
+    fn new(
+        document: &'a Document,
+        standard: Option<PdfStandard>,
+        destinations: &'a [(EcoString, Position)],
+    ) -> Self {
         let mut alloc = Ref::new(1);
         let page_tree_ref = alloc.bump();
         Self {
             document,
+            standard,
+            destinations,
             introspector: Introspector::new(&document.pages),
             writer: PdfWriter::new(),
             pages: vec![],
@@ -78,8 +140,10 @@ impl<'a> PdfContext<'a> {
             page_refs: vec![],
             font_refs: vec![],
             image_refs: vec![],
+            ext_gs_refs: vec![],
             font_map: Remapper::new(),
             image_map: Remapper::new(),
+            ext_gs_map: Remapper::new(),
             glyph_sets: HashMap::new(),
             languages: HashMap::new(),
         }
@@ -117,6 +181,21 @@ fn write_catalog(ctx: &mut PdfContext) {
         info.author(TextStr(&authors.join(", ")));
         xmp.creator(authors.iter().map(|s| s.as_str()));
     }
+
+    let keywords = &ctx.document.keywords;
+    if !keywords.is_empty() {
+        let joined = keywords.join(", ");
+        info.keywords(TextStr(&joined));
+        xmp.pdf_keywords(&joined);
+    }
+
+    if let Some((pdf_date, xmp_date)) = ctx.document.date.and_then(convert_date) {
+        info.creation_date(pdf_date);
+        info.modified_date(pdf_date);
+        xmp.create_date(xmp_date);
+        xmp.modify_date(xmp_date);
+    }
+
     info.creator(TextStr("Typst"));
     info.finish();
     xmp.creator_tool("Typst");
@@ -126,26 +205,103 @@ fn write_catalog(ctx: &mut PdfContext) {
     xmp.rendition_class(RenditionClass::Proof);
     xmp.pdf_version("1.7");
 
-    let xmp_buf = xmp.finish(None);
+    let mut xmp_buf = xmp.finish(None);
+    if let Some(standard) = ctx.standard {
+        xmp_buf = pdfa::identify(&xmp_buf, standard);
+    }
+
     let meta_ref = ctx.alloc.bump();
     let mut meta_stream = ctx.writer.stream(meta_ref, xmp_buf.as_bytes());
     meta_stream.pair(Name(b"Type"), Name(b"Metadata"));
     meta_stream.pair(Name(b"Subtype"), Name(b"XML"));
     meta_stream.finish();
 
+    // Write the color profile for the output intent.
+    let icc_ref = ctx.standard.map(|_| pdfa::write_srgb_profile(ctx));
+
+    // Write the named destinations.
+    let destinations = write_destinations(ctx);
+
     // Write the document catalog.
     let mut catalog = ctx.writer.catalog(ctx.alloc.bump());
     catalog.pages(ctx.page_tree_ref);
     catalog.viewer_preferences().direction(dir);
     catalog.pair(Name(b"Metadata"), meta_ref);
 
+    if let Some(icc_ref) = icc_ref {
+        pdfa::write_output_intent(&mut catalog, icc_ref);
+    }
+
     if let Some(outline_root_id) = outline_root_id {
         catalog.outlines(outline_root_id);
     }
 
+    if !destinations.is_empty() {
+        let mut names = catalog.insert(Name(b"Names")).dict();
+        let mut dests = names.insert(Name(b"Dests")).dict();
+        let mut array = dests.insert(Name(b"Names")).array();
+        for (name, id) in destinations {
+            array.item(Str(name.as_bytes()));
+            array.item(id);
+        }
+    }
+
     if let Some(lang) = lang {
         catalog.lang(TextStr(lang.as_str()));
     }
+
+    catalog.finish();
+    if ctx.standard.is_some() {
+        pdfa::write_file_id(ctx);
+    }
+}
+
+/// Write the named destinations as indirect objects.
+///
+/// Returns their names and ids, sorted by name as the name tree requires.
+/// Destinations on pages that don't exist are skipped.
+fn write_destinations(ctx: &mut PdfContext) -> Vec<(EcoString, Ref)> {
+    let mut destinations = vec![];
+    for (name, pos) in ctx.destinations {
+        let index = pos.page.get() - 1;
+        let Some(&height) = ctx.page_heights.get(index) else { continue };
+        let id = ctx.alloc.bump();
+        ctx.writer
+            .indirect(id)
+            .array()
+            .item(ctx.page_refs[index])
+            .item(Name(b"XYZ"))
+            .item(pos.point.x.to_f32())
+            .item(height - pos.point.y.to_f32())
+            .item(Null);
+        destinations.push((name.clone(), id));
+    }
+
+    destinations.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+    destinations.dedup_by(|(a, _), (b, _)| a == b);
+    destinations
+}
+
+/// Convert a UTC datetime into a PDF and an XMP date.
+///
+/// Returns `None` if the datetime has no date part.
+fn convert_date(datetime: Datetime) -> Option<(pdf_writer::Date, DateTime)> {
+    let year = datetime.year()?.try_into().ok()?;
+    let (month, day) = (datetime.month()?, datetime.day()?);
+    let mut pdf_date = pdf_writer::Date::new(year).month(month).day(day);
+    let mut xmp_date = DateTime::date(year, month, day);
+
+    if let (Some(hour), Some(minute), Some(second)) =
+        (datetime.hour(), datetime.minute(), datetime.second())
+    {
+        pdf_date = pdf_date.hour(hour).minute(minute).second(second).utc_offset_hour(0);
+        xmp_date.hour = Some(hour);
+        xmp_date.minute = Some(minute);
+        xmp_date.second = Some(second);
+        xmp_date.timezone = Some(Timezone::Utc);
+    }
+
+    Some((pdf_date, xmp_date))
 }
 
 /// Compress data with the DEFLATE algorithm.
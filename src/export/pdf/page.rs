@@ -1,12 +1,13 @@
 use ecow::eco_format;
 use pdf_writer::types::{
-    ActionType, AnnotationType, ColorSpaceOperand, LineCapStyle, LineJoinStyle,
+    ActionType, AnnotationFlags, AnnotationType, ColorSpaceOperand, LineCapStyle,
+    LineJoinStyle,
 };
 use pdf_writer::writers::ColorSpace;
 use pdf_writer::{Content, Filter, Finish, Name, Rect, Ref, Str};
 
 use super::{deflate, AbsExt, EmExt, PdfContext, RefExt, D65_GRAY, SRGB};
-use crate::doc::{Destination, Frame, FrameItem, GroupItem, Meta, TextItem};
+use crate::doc::{Destination, Frame, FrameItem, GroupItem, Meta, PageBox, TextItem};
 use crate::font::Font;
 use crate::geom::{
     self, Abs, Color, Em, Geometry, LineCap, LineJoin, Numeric, Paint, Point, Ratio,
@@ -55,11 +56,31 @@ pub fn construct_page(ctx: &mut PdfContext, frame: &Frame) {
     // Encode the page into the content stream.
     write_frame(&mut ctx, frame);
 
+    // Determine the print production boxes.
+    let mut bleed_box = None;
+    let mut trim_box = None;
+    for (pos, item) in frame.items() {
+        if let FrameItem::Meta(Meta::PageBox(kind), area) = item {
+            let rect = Rect::new(
+                pos.x.to_f32(),
+                (size.y - pos.y - area.y).to_f32(),
+                (pos.x + area.x).to_f32(),
+                (size.y - pos.y).to_f32(),
+            );
+            match kind {
+                PageBox::Bleed => bleed_box = Some(rect),
+                PageBox::Trim => trim_box = Some(rect),
+            }
+        }
+    }
+
     let page = Page {
         size,
         content: ctx.content,
         id: ctx.page_ref,
         links: ctx.links,
+        bleed_box,
+        trim_box,
     };
 
     ctx.parent.pages.push(page);
@@ -98,10 +119,31 @@ pub fn write_page_tree(ctx: &mut PdfContext) {
     }
 
     images.finish();
+
+    let mut ext_gs_states = resources.ext_g_states();
+    for (gs_ref, gs) in ctx.ext_gs_map.pdf_indices(&ctx.ext_gs_refs) {
+        let name = eco_format!("Gs{}", gs);
+        ext_gs_states.pair(Name(name.as_bytes()), gs_ref);
+    }
+
+    ext_gs_states.finish();
     resources.finish();
     pages.finish();
 }
 
+/// Write the external graphics states that control transparency.
+#[tracing::instrument(skip_all)]
+pub fn write_ext_graphics_states(ctx: &mut PdfContext) {
+    for gs in ctx.ext_gs_map.items() {
+        let gs_ref = ctx.alloc.bump();
+        ctx.ext_gs_refs.push(gs_ref);
+        ctx.writer
+            .ext_graphics(gs_ref)
+            .non_stroking_alpha(gs.fill_alpha as f32 / 255.0)
+            .stroking_alpha(gs.stroke_alpha as f32 / 255.0);
+    }
+}
+
 /// Write a page tree node.
 #[tracing::instrument(skip_all)]
 fn write_page(ctx: &mut PdfContext, page: Page) {
@@ -113,6 +155,12 @@ fn write_page(ctx: &mut PdfContext, page: Page) {
     let w = page.size.x.to_f32();
     let h = page.size.y.to_f32();
     page_writer.media_box(Rect::new(0.0, 0.0, w, h));
+    if let Some(rect) = page.bleed_box {
+        page_writer.bleed_box(rect);
+    }
+    if let Some(rect) = page.trim_box {
+        page_writer.trim_box(rect);
+    }
     page_writer.contents(content_id);
 
     let mut annotations = page_writer.annotations();
@@ -120,6 +168,7 @@ fn write_page(ctx: &mut PdfContext, page: Page) {
         let mut annotation = annotations.push();
         annotation.subtype(AnnotationType::Link).rect(rect);
         annotation.border(0.0, 0.0, 0.0, None);
+        annotation.flags(AnnotationFlags::PRINT);
 
         let pos = match dest {
             Destination::Url(uri) => {
@@ -163,6 +212,10 @@ pub struct Page {
     pub content: Content,
     /// Links in the PDF coordinate system.
     pub links: Vec<(Destination, Rect)>,
+    /// The bleed box in the PDF coordinate system, if any.
+    pub bleed_box: Option<Rect>,
+    /// The trim box in the PDF coordinate system, if any.
+    pub trim_box: Option<Rect>,
 }
 
 /// An exporter for the contents of a single PDF page.
@@ -186,6 +239,18 @@ struct State {
     fill_space: Option<Name<'static>>,
     stroke: Option<Stroke>,
     stroke_space: Option<Name<'static>>,
+    /// The combined opacity of the enclosing groups, if any is translucent.
+    opacity: Option<Ratio>,
+    ext_gs: Option<ExtGState>,
+}
+
+/// An external graphics state that sets the alpha of fills and strokes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ExtGState {
+    /// The alpha of fills, including text and images.
+    pub fill_alpha: u8,
+    /// The alpha of strokes.
+    pub stroke_alpha: u8,
 }
 
 impl PageContext<'_, '_> {
@@ -212,6 +277,27 @@ impl PageContext<'_, '_> {
         ]);
     }
 
+    fn set_opacities(&mut self, fill: Option<&Paint>, stroke: Option<&Stroke>) {
+        let opacity = self.state.opacity.map_or(1.0, Ratio::get);
+        let alpha = |paint: Option<&Paint>| {
+            let Some(Paint::Solid(color)) = paint else { return u8::MAX };
+            (color.alpha() as f64 * opacity).round() as u8
+        };
+
+        let gs = ExtGState {
+            fill_alpha: alpha(fill),
+            stroke_alpha: alpha(stroke.map(|s| &s.paint)),
+        };
+
+        let opaque = gs.fill_alpha == u8::MAX && gs.stroke_alpha == u8::MAX;
+        if self.state.ext_gs != Some(gs) && !(opaque && self.state.ext_gs.is_none()) {
+            self.parent.ext_gs_map.insert(gs);
+            let name = eco_format!("Gs{}", self.parent.ext_gs_map.map(gs));
+            self.content.set_parameters(Name(name.as_bytes()));
+            self.state.ext_gs = Some(gs);
+        }
+    }
+
     fn set_font(&mut self, font: &Font, size: Abs) {
         if self.state.font.as_ref().map(|(f, s)| (f, *s)) != Some((font, size)) {
             self.parent.font_map.insert(font.clone());
@@ -333,6 +419,7 @@ fn write_frame(ctx: &mut PageContext, frame: &Frame) {
                 Meta::Elem(_) => {}
                 Meta::Hide => {}
                 Meta::PageNumbering(_) => {}
+                Meta::PageBox(_) => {}
             },
         }
     }
@@ -345,6 +432,11 @@ fn write_group(ctx: &mut PageContext, pos: Point, group: &GroupItem) {
     ctx.save_state();
     ctx.transform(translation.pre_concat(group.transform));
 
+    if !group.opacity.is_one() {
+        let opacity = ctx.state.opacity.unwrap_or(Ratio::one()) * group.opacity.get();
+        ctx.state.opacity = Some(opacity);
+    }
+
     if group.clips {
         let size = group.frame.size();
         let w = size.x.to_f32();
@@ -372,11 +464,14 @@ fn write_text(ctx: &mut PageContext, x: f32, y: f32, text: &TextItem) {
     }
 
     ctx.set_fill(&text.fill);
+    ctx.set_opacities(Some(&text.fill), None);
     ctx.set_font(&text.font, text.size);
     ctx.content.begin_text();
 
-    // Positiosn the text.
-    ctx.content.set_text_matrix([1.0, 0.0, 0.0, -1.0, x, y]);
+    // Position the text. The horizontal scale also applies to the glyph
+    // displacements, so we need to undo it for the adjustments.
+    let x_scale = text.x_scale.get();
+    ctx.content.set_text_matrix([x_scale as f32, 0.0, 0.0, -1.0, x, y]);
 
     let mut positioned = ctx.content.show_positioned();
     let mut items = positioned.items();
@@ -385,7 +480,7 @@ fn write_text(ctx: &mut PageContext, x: f32, y: f32, text: &TextItem) {
 
     // Write the glyphs with kerning adjustments.
     for glyph in &text.glyphs {
-        adjustment += glyph.x_offset;
+        adjustment += glyph.x_offset / x_scale;
 
         if !adjustment.is_zero() {
             if !encoded.is_empty() {
@@ -401,10 +496,10 @@ fn write_text(ctx: &mut PageContext, x: f32, y: f32, text: &TextItem) {
         encoded.push((glyph.id & 0xff) as u8);
 
         if let Some(advance) = text.font.advance(glyph.id) {
-            adjustment += glyph.x_advance - advance;
+            adjustment += glyph.x_advance / x_scale - advance;
         }
 
-        adjustment -= glyph.x_offset;
+        adjustment -= glyph.x_offset / x_scale;
     }
 
     if !encoded.is_empty() {
@@ -438,6 +533,8 @@ fn write_shape(ctx: &mut PageContext, x: f32, y: f32, shape: &Shape) {
         ctx.set_stroke(stroke);
     }
 
+    ctx.set_opacities(shape.fill.as_ref(), stroke);
+
     match shape.geometry {
         Geometry::Line(target) => {
             let dx = target.x.to_f32();
@@ -494,6 +591,7 @@ fn write_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size)
     let name = eco_format!("Im{}", ctx.parent.image_map.map(image.clone()));
     let w = size.x.to_f32();
     let h = size.y.to_f32();
+    ctx.set_opacities(Some(&Paint::Solid(Color::BLACK)), None);
     ctx.content.save_state();
     ctx.content.transform([w, 0.0, 0.0, -h, x, y + h]);
 
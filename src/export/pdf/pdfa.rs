@@ -0,0 +1,282 @@
+use pdf_writer::writers::Catalog;
+use pdf_writer::{Filter, Name, Ref, TextStr};
+
+use super::{deflate, PdfContext, PdfStandard, RefExt};
+use crate::diag::{bail, StrResult};
+use crate::doc::{Document, Frame, FrameItem};
+use crate::geom::{Color, Paint};
+use crate::util::hash128;
+
+impl PdfStandard {
+    /// The name of the standard.
+    fn name(self) -> &'static str {
+        match self {
+            Self::A2b => "PDF/A-2b",
+        }
+    }
+
+    /// The part and conformance level as recorded in the XMP metadata.
+    fn identification(self) -> (&'static str, &'static str) {
+        match self {
+            Self::A2b => ("2", "B"),
+        }
+    }
+}
+
+/// Check that the document only uses constructs the standard allows.
+///
+/// The output intent is an sRGB profile, so device-dependent CMYK colors
+/// cannot be represented.
+pub fn check(document: &Document, standard: PdfStandard) -> StrResult<()> {
+    for (i, frame) in document.pages.iter().enumerate() {
+        if uses_cmyk(frame) {
+            bail!(
+                "{} does not allow CMYK colors (used on page {}), \
+                 use RGB or luma colors instead",
+                standard.name(),
+                i + 1,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether any item in the frame is painted with a CMYK color.
+fn uses_cmyk(frame: &Frame) -> bool {
+    let is_cmyk = |paint: &Paint| matches!(paint, Paint::Solid(Color::Cmyk(_)));
+    frame.items().any(|(_, item)| match item {
+        FrameItem::Group(group) => uses_cmyk(&group.frame),
+        FrameItem::Text(text) => is_cmyk(&text.fill),
+        FrameItem::Shape(shape, _) => {
+            shape.fill.as_ref().map_or(false, is_cmyk)
+                || shape.stroke.as_ref().map_or(false, |stroke| is_cmyk(&stroke.paint))
+        }
+        FrameItem::Image(..) | FrameItem::Meta(..) => false,
+    })
+}
+
+/// Add the PDF/A identification schema to XMP metadata.
+pub fn identify(xmp: &str, standard: PdfStandard) -> String {
+    let (part, conformance) = standard.identification();
+    let description = format!(
+        "<rdf:Description rdf:about=\"\" \
+         xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\
+         <pdfaid:part>{part}</pdfaid:part>\
+         <pdfaid:conformance>{conformance}</pdfaid:conformance>\
+         </rdf:Description>"
+    );
+
+    let end = xmp.rfind("</rdf:RDF>").unwrap_or(xmp.len());
+    let mut buf = String::with_capacity(xmp.len() + description.len());
+    buf.push_str(&xmp[..end]);
+    buf.push_str(&description);
+    buf.push_str(&xmp[end..]);
+    buf
+}
+
+/// Write the sRGB color profile that serves as the output intent.
+pub fn write_srgb_profile(ctx: &mut PdfContext) -> Ref {
+    let icc_ref = ctx.alloc.bump();
+    let compressed = deflate(&srgb_profile());
+    let mut stream = ctx.writer.icc_profile(icc_ref, &compressed);
+    stream.filter(Filter::FlateDecode);
+    stream.n(3);
+    icc_ref
+}
+
+/// Declare that the document is intended for display in sRGB.
+pub fn write_output_intent(catalog: &mut Catalog, icc_ref: Ref) {
+    let mut intents = catalog.insert(Name(b"OutputIntents")).array();
+    let mut intent = intents.push().dict();
+    intent.pair(Name(b"Type"), Name(b"OutputIntent"));
+    intent.pair(Name(b"S"), Name(b"GTS_PDFA1"));
+    intent.pair(Name(b"OutputConditionIdentifier"), TextStr("sRGB"));
+    intent.pair(Name(b"RegistryName"), TextStr("http://www.color.org"));
+    intent.pair(Name(b"Info"), TextStr("sRGB IEC61966-2.1"));
+    intent.pair(Name(b"DestOutputProfile"), icc_ref);
+}
+
+/// Write a file identifier into the trailer.
+///
+/// The identifier is derived from the document so that exporting the same
+/// document twice yields the same bytes.
+pub fn write_file_id(ctx: &mut PdfContext) {
+    let id = hash128(&(ctx.document, ctx.destinations)).to_be_bytes().to_vec();
+    ctx.writer.set_file_id((id.clone(), id));
+}
+
+/// Build a minimal ICC version 2 profile for the sRGB color space.
+///
+/// The primaries are chromatically adapted to the D50 profile connection
+/// space, as required by the ICC specification.
+fn srgb_profile() -> Vec<u8> {
+    const HEADER: usize = 128;
+
+    // The tag data, each padded to a multiple of four bytes.
+    let desc = text_description("sRGB IEC61966-2.1");
+    let cprt = text("No copyright, use freely");
+    let wtpt = xyz([0.9642, 1.0, 0.8249]);
+    let r = xyz([0.4360747, 0.2225045, 0.0139322]);
+    let g = xyz([0.3850649, 0.7168786, 0.0971045]);
+    let b = xyz([0.1430804, 0.0606169, 0.7141733]);
+    let trc = curve();
+
+    // The three tone reproduction curves share their data.
+    let data: [(&[u8; 4], &[u8]); 6] = [
+        (b"desc", &desc),
+        (b"cprt", &cprt),
+        (b"wtpt", &wtpt),
+        (b"rXYZ", &r),
+        (b"gXYZ", &g),
+        (b"bXYZ", &b),
+    ];
+
+    let count = data.len() + 3;
+    let mut offset = HEADER + 4 + 12 * count;
+    let mut table = Vec::new();
+    let mut body = Vec::new();
+    for (sig, bytes) in data {
+        table.push((*sig, offset, bytes.len()));
+        body.extend_from_slice(bytes);
+        offset += bytes.len();
+    }
+    for sig in [b"rTRC", b"gTRC", b"bTRC"] {
+        table.push((*sig, offset, trc.len()));
+    }
+    body.extend_from_slice(&trc);
+    let size = offset + trc.len();
+
+    let mut buf = Vec::with_capacity(size);
+    buf.extend_from_slice(&(size as u32).to_be_bytes());
+    buf.extend_from_slice(&[0; 4]);
+    buf.extend_from_slice(&0x02100000_u32.to_be_bytes());
+    buf.extend_from_slice(b"mntrRGB XYZ ");
+    for part in [2023_u16, 1, 1, 0, 0, 0] {
+        buf.extend_from_slice(&part.to_be_bytes());
+    }
+    buf.extend_from_slice(b"acsp");
+    buf.extend_from_slice(&[0; 24]);
+    // Perceptual rendering intent.
+    buf.extend_from_slice(&0_u32.to_be_bytes());
+    for v in [0.9642, 1.0, 0.8249] {
+        buf.extend_from_slice(&s15_fixed16(v));
+    }
+    buf.resize(HEADER, 0);
+
+    buf.extend_from_slice(&(count as u32).to_be_bytes());
+    for (sig, offset, len) in table {
+        buf.extend_from_slice(&sig);
+        buf.extend_from_slice(&(offset as u32).to_be_bytes());
+        buf.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+
+    buf.extend_from_slice(&body);
+    buf
+}
+
+/// Encode a `textDescriptionType` tag.
+fn text_description(text: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"desc\0\0\0\0");
+    buf.extend_from_slice(&(text.len() as u32 + 1).to_be_bytes());
+    buf.extend_from_slice(text.as_bytes());
+    buf.push(0);
+    // Empty Unicode and ScriptCode descriptions.
+    buf.extend_from_slice(&[0; 8]);
+    buf.extend_from_slice(&[0; 3]);
+    buf.extend_from_slice(&[0; 67]);
+    pad(buf)
+}
+
+/// Encode a `textType` tag.
+fn text(text: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"text\0\0\0\0");
+    buf.extend_from_slice(text.as_bytes());
+    buf.push(0);
+    pad(buf)
+}
+
+/// Encode an `XYZType` tag.
+fn xyz(values: [f64; 3]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"XYZ \0\0\0\0");
+    for v in values {
+        buf.extend_from_slice(&s15_fixed16(v));
+    }
+    buf
+}
+
+/// Encode the sRGB transfer function as a sampled `curveType` tag.
+fn curve() -> Vec<u8> {
+    const SAMPLES: u32 = 1024;
+
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"curv\0\0\0\0");
+    buf.extend_from_slice(&SAMPLES.to_be_bytes());
+    for i in 0..SAMPLES {
+        let v = i as f64 / (SAMPLES - 1) as f64;
+        let linear =
+            if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) };
+        buf.extend_from_slice(&((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+    buf
+}
+
+/// Encode a number in the ICC's signed 15.16 fixed-point format.
+fn s15_fixed16(v: f64) -> [u8; 4] {
+    ((v * 65536.0).round() as i32).to_be_bytes()
+}
+
+/// Pad tag data to a multiple of four bytes.
+fn pad(mut buf: Vec<u8>) -> Vec<u8> {
+    buf.resize((buf.len() + 3) / 4 * 4, 0);
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::pdf::{pdf, pdf_with_standard};
+    use crate::geom::{Abs, CmykColor, Geometry, Point, Size};
+    use crate::syntax::Span;
+
+    /// A document with a single empty page.
+    fn document() -> Document {
+        let frame = Frame::new(Size::splat(Abs::pt(10.0)));
+        Document { pages: vec![frame], ..Default::default() }
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|window| window == needle)
+    }
+
+    #[test]
+    fn test_pdfa_2b() {
+        let bytes = pdf_with_standard(&document(), PdfStandard::A2b).unwrap();
+        assert!(contains(&bytes, b"/OutputIntents"));
+        assert!(contains(&bytes, b"/S /GTS_PDFA1"));
+        assert!(contains(&bytes, b"<pdfaid:part>2</pdfaid:part>"));
+        assert!(contains(&bytes, b"<pdfaid:conformance>B</pdfaid:conformance>"));
+        assert!(contains(&bytes, b"/ID ["));
+        assert_eq!(bytes, pdf_with_standard(&document(), PdfStandard::A2b).unwrap());
+    }
+
+    #[test]
+    fn test_pdf_without_standard() {
+        let bytes = pdf(&document());
+        assert!(!contains(&bytes, b"/OutputIntents"));
+        assert!(!contains(&bytes, b"pdfaid"));
+        assert!(!contains(&bytes, b"/ID ["));
+    }
+
+    #[test]
+    fn test_pdfa_rejects_cmyk() {
+        let mut document = document();
+        let fill = Paint::Solid(CmykColor::new(0, 0, 0, 255).into());
+        let shape = Geometry::Rect(Size::splat(Abs::pt(1.0))).filled(fill);
+        let item = FrameItem::Shape(shape, Span::detached());
+        document.pages[0].push(Point::zero(), item);
+        assert!(pdf_with_standard(&document, PdfStandard::A2b).is_err());
+    }
+}
@@ -64,6 +64,7 @@ fn render_frame(
                 Meta::Link(_) => {}
                 Meta::Elem(_) => {}
                 Meta::PageNumbering(_) => {}
+                Meta::PageBox(_) => {}
                 Meta::Hide => {}
             },
         }
@@ -120,7 +121,25 @@ fn render_group(
         }
     }
 
-    render_frame(canvas, ts, mask, &group.frame);
+    if group.opacity.is_one() {
+        render_frame(canvas, ts, mask, &group.frame);
+        return;
+    }
+
+    // Render translucent groups into a separate layer that is then composited
+    // onto the canvas as a whole.
+    let Some(mut layer) = sk::Pixmap::new(canvas.width(), canvas.height()) else {
+        return;
+    };
+
+    render_frame(&mut layer, ts, mask, &group.frame);
+
+    let paint = sk::PixmapPaint {
+        opacity: group.opacity.get() as f32,
+        ..Default::default()
+    };
+
+    canvas.draw_pixmap(0, 0, layer.as_ref(), &paint, sk::Transform::identity(), None);
 }
 
 /// Render a text run into the canvas.
@@ -130,11 +149,12 @@ fn render_text(
     mask: Option<&sk::Mask>,
     text: &TextItem,
 ) {
+    let x_scale = text.x_scale.get() as f32;
     let mut x = 0.0;
     for glyph in &text.glyphs {
         let id = GlyphId(glyph.id);
         let offset = x + glyph.x_offset.at(text.size).to_f32();
-        let ts = ts.pre_translate(offset, 0.0);
+        let ts = ts.pre_translate(offset, 0.0).pre_scale(x_scale, 1.0);
 
         render_svg_glyph(canvas, ts, mask, text, id)
             .or_else(|| render_bitmap_glyph(canvas, ts, mask, text, id))
@@ -316,7 +336,8 @@ fn render_outline_glyph(
         let mut pixmap = sk::Pixmap::new(mw + 2, mh + 2)?;
         for x in 0..mw {
             for y in 0..mh {
-                let alpha = bitmap.coverage[(y * mw + x) as usize];
+                let cov = bitmap.coverage[(y * mw + x) as usize];
+                let alpha = (cov as u32 * c.a as u32 / 255) as u8;
                 let color = sk::ColorU8::from_rgba(c.r, c.g, c.b, alpha).premultiply();
                 pixmap.pixels_mut()[((y + 1) * (mw + 2) + (x + 1)) as usize] = color;
             }
@@ -350,7 +371,7 @@ fn render_outline_glyph(
         // Premultiply the text color.
         let Paint::Solid(color) = text.fill;
         let c = color.to_rgba();
-        let color = sk::ColorU8::from_rgba(c.r, c.g, c.b, 255).premultiply().get();
+        let color = sk::ColorU8::from_rgba(c.r, c.g, c.b, c.a).premultiply().get();
 
         // Blend the glyph bitmap with the existing pixels on the canvas.
         let pixels = bytemuck::cast_slice_mut::<u8, u32>(canvas.data_mut());
@@ -363,7 +384,7 @@ fn render_outline_glyph(
                 }
 
                 let pi = (y * cw + x) as usize;
-                if cov == 255 {
+                if cov == 255 && c.a == 255 {
                     pixels[pi] = color;
                     continue;
                 }
@@ -0,0 +1,207 @@
+use crate::doc::{Document, Frame, FrameItem, TextItem};
+use crate::geom::{Abs, Point, Transform};
+
+/// Export a document into plain text.
+///
+/// The text is extracted from the laid out pages in the order in which it was
+/// laid out. Lines end with a newline and paragraphs are separated by an
+/// empty line. Pages are separated by the given separator.
+///
+/// If `soft_hyphens` is true, hyphens that were inserted at line breaks are
+/// written as soft hyphens and soft hyphens in the text are kept. Otherwise,
+/// inserted hyphens are written as regular hyphens and soft hyphens are
+/// dropped.
+#[tracing::instrument(skip_all)]
+pub fn text(document: &Document, separator: &str, soft_hyphens: bool) -> String {
+    let mut writer = TextWriter { buf: String::new(), last: None, soft_hyphens };
+    for (i, page) in document.pages.iter().enumerate() {
+        if i > 0 {
+            writer.buf.push_str(separator);
+        }
+
+        writer.last = None;
+        writer.frame(page, Transform::identity());
+        if !writer.buf.is_empty() && !writer.buf.ends_with('\n') {
+            writer.buf.push('\n');
+        }
+    }
+
+    writer.buf
+}
+
+/// Collects the text of frames.
+struct TextWriter {
+    /// The text written so far.
+    buf: String,
+    /// The end of the previous text run on the page and its font size.
+    last: Option<(Point, Abs)>,
+    /// Whether to write soft hyphens.
+    soft_hyphens: bool,
+}
+
+impl TextWriter {
+    /// Write the text in a frame.
+    fn frame(&mut self, frame: &Frame, ts: Transform) {
+        for (pos, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    let ts = ts
+                        .pre_concat(Transform::translate(pos.x, pos.y))
+                        .pre_concat(group.transform);
+                    self.frame(&group.frame, ts);
+                }
+                FrameItem::Text(text) => self.text(pos.transform(ts), text),
+                _ => {}
+            }
+        }
+    }
+
+    /// Write a text run whose baseline starts at the given position.
+    fn text(&mut self, pos: Point, text: &TextItem) {
+        // Separate the run from the previous one depending on how far apart
+        // they are: Runs on different lines are separated by a newline and
+        // runs that are far apart vertically by an empty line.
+        if let Some((end, size)) = self.last {
+            let dy = pos.y - end.y;
+            if dy > 2.0 * size {
+                self.buf.push_str("\n\n");
+            } else if dy.abs() > 0.5 * size || pos.x < end.x - 0.5 * size {
+                self.buf.push('\n');
+            } else if pos.x - end.x > 0.15 * size && !self.buf.ends_with(' ') {
+                self.buf.push(' ');
+            }
+        }
+
+        if self.soft_hyphens {
+            self.buf.push_str(&text.text);
+        } else {
+            self.buf.extend(text.text.chars().filter(|&c| c != '\u{ad}'));
+        }
+
+        // A hyphen that was inserted at a line break doesn't correspond to
+        // any of the run's text.
+        if text.glyphs.last().map_or(false, |glyph| glyph.range.is_empty()) {
+            self.buf.push(if self.soft_hyphens { '\u{ad}' } else { '-' });
+        }
+
+        self.last = Some((pos + Point::with_x(text.width()), text.size));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::{Glyph, GroupItem, Lang};
+    use crate::font::Font;
+    use crate::geom::{Color, Em, Ratio, Size};
+    use crate::syntax::Span;
+    use crate::util::Buffer;
+
+    /// A run of text in ten point type whose glyphs are each half an em wide.
+    /// If `hyphen` is true, the run ends with a hyphen that was inserted at a
+    /// line break.
+    fn run(text: &str, hyphen: bool) -> FrameItem {
+        let font = Font::new(
+            Buffer::from_static(include_bytes!("../../assets/fonts/LinLibertine_R.ttf")),
+            0,
+        )
+        .unwrap();
+
+        let glyph = |range: std::ops::Range<usize>| Glyph {
+            id: 0,
+            x_advance: Em::new(0.5),
+            x_offset: Em::zero(),
+            range: range.start as u16..range.end as u16,
+            span: (Span::detached(), 0),
+        };
+
+        let mut glyphs: Vec<_> =
+            text.char_indices().map(|(i, c)| glyph(i..i + c.len_utf8())).collect();
+        if hyphen {
+            glyphs.push(glyph(text.len()..text.len()));
+        }
+
+        FrameItem::Text(TextItem {
+            font,
+            size: Abs::pt(10.0),
+            fill: Color::BLACK.into(),
+            lang: Lang::ENGLISH,
+            text: text.into(),
+            x_scale: Ratio::one(),
+            glyphs,
+        })
+    }
+
+    /// A page with runs of text at the given baseline positions.
+    fn page(runs: &[(f64, f64, &str)]) -> Frame {
+        let mut frame = Frame::new(Size::splat(Abs::pt(100.0)));
+        for &(x, y, text) in runs {
+            frame.push(Point::new(Abs::pt(x), Abs::pt(y)), run(text, false));
+        }
+        frame
+    }
+
+    /// Export pages without soft hyphens.
+    fn export(pages: Vec<Frame>) -> String {
+        text(&Document { pages, ..Default::default() }, "\u{c}", false)
+    }
+
+    #[test]
+    fn test_text_words_and_lines() {
+        // Adjacent runs are joined, runs with a gap are separated by a space
+        // and runs on the next line by a newline.
+        let lines = page(&[
+            (0.0, 10.0, "Hel"),
+            (15.0, 10.0, "lo"),
+            (30.0, 10.0, "big"),
+            (0.0, 22.0, "world"),
+        ]);
+        assert_eq!(export(vec![lines]), "Hello big\nworld\n");
+
+        // A run that starts left of the previous one's end is on a new line,
+        // even if the baselines are close.
+        let wrapped = page(&[(50.0, 10.0, "end"), (0.0, 11.0, "start")]);
+        assert_eq!(export(vec![wrapped]), "end\nstart\n");
+    }
+
+    #[test]
+    fn test_text_paragraphs() {
+        let mut frame = page(&[(0.0, 10.0, "First"), (0.0, 22.0, "line")]);
+
+        // Runs in transformed groups are placed where they end up on the
+        // page.
+        let mut inner = Frame::new(Size::splat(Abs::pt(50.0)));
+        inner.push(Point::with_y(Abs::pt(10.0)), run("Second", false));
+        let group = GroupItem {
+            transform: Transform::translate(Abs::zero(), Abs::pt(40.0)),
+            ..GroupItem::new(inner)
+        };
+        frame.push(Point::zero(), FrameItem::Group(group));
+
+        assert_eq!(export(vec![frame]), "First\nline\n\nSecond\n");
+    }
+
+    #[test]
+    fn test_text_pages() {
+        let pages =
+            vec![page(&[(0.0, 10.0, "One")]), page(&[]), page(&[(0.0, 90.0, "Two")])];
+
+        // Each page's text ends with a newline, so a blank page is an empty
+        // line.
+        assert_eq!(export(pages), "One\n\u{c}\n\u{c}Two\n");
+        assert_eq!(export(vec![]), "");
+    }
+
+    #[test]
+    fn test_text_soft_hyphens() {
+        let mut frame = Frame::new(Size::splat(Abs::pt(100.0)));
+        frame.push(Point::new(Abs::zero(), Abs::pt(10.0)), run("hy", true));
+        frame.push(Point::new(Abs::zero(), Abs::pt(22.0)), run("phen a\u{ad}b", false));
+        let document = Document { pages: vec![frame], ..Default::default() };
+
+        // Inserted hyphens become soft hyphens or regular ones and soft
+        // hyphens in the text are kept or dropped.
+        assert_eq!(text(&document, "", true), "hy\u{ad}\nphen a\u{ad}b\n");
+        assert_eq!(text(&document, "", false), "hy-\nphen ab\n");
+    }
+}
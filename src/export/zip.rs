@@ -0,0 +1,65 @@
+/// Writes a ZIP archive.
+#[derive(Default)]
+pub(super) struct ZipWriter {
+    /// The archive written so far.
+    buf: Vec<u8>,
+    /// The central directory, which is written at the end.
+    directory: Vec<u8>,
+    /// The number of files in the archive.
+    count: u16,
+}
+
+impl ZipWriter {
+    /// Add a file to the archive, optionally compressing it.
+    pub fn add(&mut self, name: &str, data: &[u8], compress: bool) {
+        const COMPRESSION_LEVEL: u8 = 6;
+        let compressed;
+        let (method, stored) = if compress {
+            compressed = miniz_oxide::deflate::compress_to_vec(data, COMPRESSION_LEVEL);
+            (8u16, compressed.as_slice())
+        } else {
+            (0u16, data)
+        };
+
+        // The modification time is fixed to keep the archive reproducible.
+        let offset = self.buf.len() as u32;
+        let mut header = vec![];
+        header.extend(20u16.to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(method.to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(0x21u16.to_le_bytes());
+        header.extend(crc32fast::hash(data).to_le_bytes());
+        header.extend((stored.len() as u32).to_le_bytes());
+        header.extend((data.len() as u32).to_le_bytes());
+        header.extend((name.len() as u16).to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+
+        self.buf.extend(0x04034b50u32.to_le_bytes());
+        self.buf.extend(&header);
+        self.buf.extend(name.as_bytes());
+        self.buf.extend(stored);
+
+        self.directory.extend(0x02014b50u32.to_le_bytes());
+        self.directory.extend(20u16.to_le_bytes());
+        self.directory.extend(&header);
+        self.directory.extend([0; 10]);
+        self.directory.extend(offset.to_le_bytes());
+        self.directory.extend(name.as_bytes());
+        self.count += 1;
+    }
+
+    /// Write the central directory and return the archive.
+    pub fn finish(mut self) -> Vec<u8> {
+        let offset = self.buf.len() as u32;
+        self.buf.extend(&self.directory);
+        self.buf.extend(0x06054b50u32.to_le_bytes());
+        self.buf.extend([0; 4]);
+        self.buf.extend(self.count.to_le_bytes());
+        self.buf.extend(self.count.to_le_bytes());
+        self.buf.extend((self.directory.len() as u32).to_le_bytes());
+        self.buf.extend(offset.to_le_bytes());
+        self.buf.extend(0u16.to_le_bytes());
+        self.buf
+    }
+}
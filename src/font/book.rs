@@ -77,6 +77,28 @@ impl FontBook {
             .copied()
     }
 
+    /// The names of the known families that are closest to the given
+    /// `family`, ordered by similarity.
+    ///
+    /// The `family` should be all lowercase. Returns at most `max` names and
+    /// only those that are reasonably similar.
+    pub fn similar(&self, family: &str, max: usize) -> Vec<&str> {
+        let threshold = (family.chars().count() / 3).max(2);
+        let mut candidates: Vec<_> = self
+            .families
+            .iter()
+            .map(|(key, ids)| (edit_distance(family, key), ids[0]))
+            .filter(|&(distance, _)| distance <= threshold)
+            .collect();
+
+        candidates.sort_by_key(|&(distance, _)| distance);
+        candidates
+            .into_iter()
+            .take(max)
+            .map(|(_, id)| self.infos[id].family.as_str())
+            .collect()
+    }
+
     /// Try to find and load a fallback font that
     /// - is as close as possible to the font `like` (if any)
     /// - is as close as possible to the given `variant`
@@ -195,6 +217,23 @@ impl FontInfo {
         })
     }
 
+    /// Compute metadata for all fonts in the given data, together with the
+    /// index of the face they belong to.
+    ///
+    /// Unlike with [`iter`](Self::iter), the indices stay correct if some of
+    /// the faces in a collection can't be parsed.
+    ///
+    /// Named instances of variable fonts are not yielded separately: Fonts are
+    /// always used at their default coordinates, so an instance would be
+    /// selected for its variant, but rendered like the default instance.
+    pub fn iter_indexed(data: &[u8]) -> impl Iterator<Item = (u32, FontInfo)> + '_ {
+        let count = ttf_parser::fonts_in_collection(data).unwrap_or(1);
+        (0..count).filter_map(move |index| {
+            let ttf = ttf_parser::Face::parse(data, index).ok()?;
+            Some((index, Self::from_ttf(&ttf)?))
+        })
+    }
+
     /// Compute metadata for a single ttf-parser face.
     pub(super) fn from_ttf(ttf: &ttf_parser::Face) -> Option<Self> {
         // We cannot use Name ID 16 "Typographic Family", because for some
@@ -490,6 +529,22 @@ impl Coverage {
     }
 }
 
+/// The Levenshtein distance between two strings, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -512,6 +567,16 @@ mod tests {
         assert_eq!(typographic_family("Font Ultra Bold"), "Font");
     }
 
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("new computer modern", "new computer modren"), 2);
+        assert_eq!(edit_distance("libertine", "linux libertine"), 6);
+    }
+
     #[test]
     fn test_coverage() {
         #[track_caller]
@@ -42,6 +42,15 @@ impl Color {
         }
     }
 
+    /// The alpha component of this color. Only RGBA colors can be
+    /// translucent.
+    pub fn alpha(self) -> u8 {
+        match self {
+            Self::Rgba(rgba) => rgba.a,
+            Self::Luma(_) | Self::Cmyk(_) => u8::MAX,
+        }
+    }
+
     /// Lighten this color by the given factor.
     pub fn lighten(self, factor: Ratio) -> Self {
         match self {
@@ -68,6 +77,52 @@ impl Color {
             Self::Cmyk(cmyk) => Self::Cmyk(cmyk.negate()),
         }
     }
+
+    /// Mix this color with another one.
+    ///
+    /// A ratio of zero yields this color and a ratio of one the other color.
+    /// Two CMYK colors are mixed component-wise, all other colors are mixed
+    /// in the Oklab color space. Mixing two grayscale colors yields a
+    /// grayscale color.
+    pub fn mix(self, other: Self, ratio: Ratio) -> Self {
+        match (self, other) {
+            (Self::Luma(a), Self::Luma(b)) => {
+                Self::Luma(LumaColor(a.to_rgba().mix(b.to_rgba(), ratio).r))
+            }
+            (Self::Cmyk(a), Self::Cmyk(b)) => Self::Cmyk(a.mix(b, ratio)),
+            (a, b) => Self::Rgba(a.to_rgba().mix(b.to_rgba(), ratio)),
+        }
+    }
+
+    /// Increase the saturation of this color by the given factor.
+    ///
+    /// Grayscale colors are not affected. CMYK colors are converted to RGBA.
+    pub fn saturate(self, factor: Ratio) -> Self {
+        match self {
+            Self::Luma(luma) => Self::Luma(luma),
+            _ => Self::Rgba(self.to_rgba().saturate(factor)),
+        }
+    }
+
+    /// Decrease the saturation of this color by the given factor.
+    ///
+    /// Grayscale colors are not affected. CMYK colors are converted to RGBA.
+    pub fn desaturate(self, factor: Ratio) -> Self {
+        match self {
+            Self::Luma(luma) => Self::Luma(luma),
+            _ => Self::Rgba(self.to_rgba().desaturate(factor)),
+        }
+    }
+
+    /// Rotate the hue of this color by the given angle.
+    ///
+    /// Grayscale colors are not affected. CMYK colors are converted to RGBA.
+    pub fn rotate(self, angle: Angle) -> Self {
+        match self {
+            Self::Luma(luma) => Self::Luma(luma),
+            _ => Self::Rgba(self.to_rgba().rotate(angle)),
+        }
+    }
 }
 
 impl Debug for Color {
@@ -192,6 +247,126 @@ impl RgbaColor {
             a: self.a,
         }
     }
+
+    /// Construct a color from its hue in degrees and its saturation and
+    /// lightness between zero and one.
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64, alpha: u8) -> Self {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        let m = lightness - c / 2.0;
+        let f = |v: f64| round_u8(255.0 * (v + m).clamp(0.0, 1.0));
+        Self::new(f(r), f(g), f(b), alpha)
+    }
+
+    /// Convert this color to its hue in degrees and its saturation and
+    /// lightness between zero and one.
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|v| v as f64 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+        let delta = max - min;
+        if delta == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+        let hue = if max == r {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+
+        (hue * 60.0, saturation.min(1.0), lightness)
+    }
+
+    /// Construct a color from its coordinates in the Oklab color space.
+    ///
+    /// Colors outside of the sRGB gamut are mapped into it by reducing their
+    /// chroma while keeping their lightness and hue.
+    pub fn from_oklab(lightness: f64, a: f64, b: f64, alpha: u8) -> Self {
+        let lightness = lightness.clamp(0.0, 1.0);
+        let mut rgb = oklab_to_linear_srgb(lightness, a, b);
+        if !in_gamut(rgb) {
+            // Find the largest chroma that is still in gamut.
+            let (mut lo, mut hi) = (0.0, 1.0);
+            for _ in 0..24 {
+                let mid = (lo + hi) / 2.0;
+                if in_gamut(oklab_to_linear_srgb(lightness, a * mid, b * mid)) {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            rgb = oklab_to_linear_srgb(lightness, a * lo, b * lo);
+        }
+
+        let [r, g, b] = rgb.map(|v| round_u8(255.0 * linear_to_gamma(v.clamp(0.0, 1.0))));
+        Self::new(r, g, b, alpha)
+    }
+
+    /// Convert this color to its lightness and its `a` and `b` coordinates in
+    /// the Oklab color space.
+    pub fn to_oklab(self) -> (f64, f64, f64) {
+        let [r, g, b] =
+            [self.r, self.g, self.b].map(|v| gamma_to_linear(v as f64 / 255.0));
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        (
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        )
+    }
+
+    /// Mix this color with another one in the Oklab color space.
+    ///
+    /// The alpha channels are mixed linearly.
+    pub fn mix(self, other: Self, ratio: Ratio) -> Self {
+        let t = ratio.get();
+        let lerp = |x: f64, y: f64| x + (y - x) * t;
+        let (l1, a1, b1) = self.to_oklab();
+        let (l2, a2, b2) = other.to_oklab();
+        let alpha = round_u8(lerp(self.a as f64, other.a as f64));
+        Self::from_oklab(lerp(l1, l2), lerp(a1, a2), lerp(b1, b2), alpha)
+    }
+
+    /// Increase the saturation of this color by a factor.
+    ///
+    /// The alpha channel is not affected.
+    pub fn saturate(self, factor: Ratio) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h, s + (1.0 - s) * factor.get(), l, self.a)
+    }
+
+    /// Decrease the saturation of this color by a factor.
+    ///
+    /// The alpha channel is not affected.
+    pub fn desaturate(self, factor: Ratio) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h, s - s * factor.get(), l, self.a)
+    }
+
+    /// Rotate the hue of this color by an angle.
+    ///
+    /// The alpha channel is not affected.
+    pub fn rotate(self, angle: Angle) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h + angle.to_deg(), s, l, self.a)
+    }
 }
 
 impl FromStr for RgbaColor {
@@ -325,6 +500,17 @@ impl CmykColor {
             k: self.k,
         }
     }
+
+    /// Mix this color with another one component-wise.
+    pub fn mix(self, other: Self, ratio: Ratio) -> Self {
+        let mix = |x: u8, y: u8| round_u8(x as f64 + (y as f64 - x as f64) * ratio.get());
+        Self {
+            c: mix(self.c, other.c),
+            m: mix(self.m, other.m),
+            y: mix(self.y, other.y),
+            k: mix(self.k, other.k),
+        }
+    }
 }
 
 impl Debug for CmykColor {
@@ -352,6 +538,43 @@ fn round_u8(value: f64) -> u8 {
     value.round() as u8
 }
 
+/// Convert a gamma-encoded sRGB component to linear light.
+fn gamma_to_linear(v: f64) -> f64 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear light component to gamma-encoded sRGB.
+fn linear_to_gamma(v: f64) -> f64 {
+    if v <= 0.0031308 {
+        12.92 * v
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Convert Oklab coordinates to linear sRGB components, which may lie
+/// outside of the gamut.
+fn oklab_to_linear_srgb(lightness: f64, a: f64, b: f64) -> [f64; 3] {
+    let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ]
+}
+
+/// Whether linear sRGB components lie inside of the gamut, allowing for
+/// rounding errors.
+fn in_gamut(rgb: [f64; 3]) -> bool {
+    rgb.iter().all(|v| (-1e-6..=1.0 + 1e-6).contains(v))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -383,4 +606,26 @@ mod tests {
         test("hmmm", "color string contains non-hexadecimal letters");
         test("14B2AH", "color string contains non-hexadecimal letters");
     }
+
+    #[test]
+    fn test_color_space_round_trips() {
+        for color in [Color::RED, Color::NAVY, Color::OLIVE, Color::SILVER] {
+            let rgba = color.to_rgba();
+            let (l, a, b) = rgba.to_oklab();
+            assert_eq!(RgbaColor::from_oklab(l, a, b, rgba.a), rgba);
+            let (h, s, l) = rgba.to_hsl();
+            assert_eq!(RgbaColor::from_hsl(h, s, l, rgba.a), rgba);
+        }
+    }
+
+    #[test]
+    fn test_oklab_gamut_mapping() {
+        let color = RgbaColor::from_oklab(0.7, 0.4, 0.4, 255);
+        let (l, ..) = color.to_oklab();
+        assert!((l - 0.7).abs() < 0.01);
+        assert_eq!(
+            RgbaColor::from_oklab(1.5, 0.0, 0.0, 255),
+            RgbaColor::new(255, 255, 255, 255)
+        );
+    }
 }
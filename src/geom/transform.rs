@@ -47,6 +47,18 @@ impl Transform {
         }
     }
 
+    /// A skew transform.
+    ///
+    /// The first angle slants the vertical axis towards the horizontal one and
+    /// the second angle slants the horizontal axis towards the vertical one.
+    pub fn skew(x: Angle, y: Angle) -> Self {
+        Self {
+            kx: Ratio::new(x.tan()),
+            ky: Ratio::new(y.tan()),
+            ..Self::identity()
+        }
+    }
+
     /// Whether this is the identity transformation.
     pub fn is_identity(self) -> bool {
         self == Self::identity()
@@ -6,7 +6,7 @@ use ecow::EcoString;
 use crate::doc::Frame;
 use crate::eval::{eval, Module, Route, Tracer, Value};
 use crate::model::{Introspector, Label};
-use crate::syntax::{ast, LinkedNode, Source, SyntaxKind};
+use crate::syntax::{ast, LinkedNode, Source, SourceId, SyntaxKind};
 use crate::util::{AccessMode, PathExt};
 use crate::World;
 
@@ -66,6 +66,19 @@ pub fn analyze_import(
     source: &Source,
     path: &str,
 ) -> Option<Module> {
+    let route = Route::default();
+    let mut tracer = Tracer::default();
+    let id = resolve_import(world, source, path)?;
+    let source = world.source(id);
+    eval(world.track(), route.track(), tracer.track_mut(), source).ok()
+}
+
+/// Try to resolve the path of an import in the current source file.
+pub fn resolve_import(
+    world: &(dyn World + 'static),
+    source: &Source,
+    path: &str,
+) -> Option<SourceId> {
     let full: PathBuf = if let Some(path) = path.strip_prefix('/') {
         world.root(AccessMode::R).ok()?.join(path).normalize()
     } else if let Some(dir) = source.path().parent() {
@@ -73,11 +86,7 @@ pub fn analyze_import(
     } else {
         path.into()
     };
-    let route = Route::default();
-    let mut tracer = Tracer::default();
-    let id = world.resolve(&full).ok()?;
-    let source = world.source(id);
-    eval(world.track(), route.track(), tracer.track_mut(), source).ok()
+    world.resolve(&full).ok()
 }
 
 /// Find all labels and details for them.
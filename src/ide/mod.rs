@@ -12,6 +12,8 @@ pub use self::highlight::{highlight, highlight_html, Tag};
 pub use self::jump::{jump_from_click, jump_from_cursor, Jump};
 pub use self::tooltip::{tooltip, Tooltip};
 
+pub(crate) use self::analyze::resolve_import;
+
 use std::fmt::Write;
 
 use ecow::{eco_format, EcoString};
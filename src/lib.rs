@@ -43,6 +43,7 @@ pub mod util;
 pub mod diag;
 #[macro_use]
 pub mod eval;
+pub mod analyze;
 pub mod doc;
 pub mod export;
 pub mod font;
@@ -51,23 +52,26 @@ pub mod ide;
 pub mod image;
 pub mod model;
 pub mod syntax;
+pub mod world;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use comemo::{Prehashed, Track, TrackedMut};
+use ecow::{eco_format, EcoString};
 
-use crate::diag::{FileError, FileResult, SourceResult};
+use crate::diag::{FileError, FileResult, SourceResult, StrResult};
 use crate::doc::Document;
 use crate::eval::{Datetime, Library, Route, Tracer};
 use crate::font::{Font, FontBook};
-use crate::syntax::{Source, SourceId};
-use crate::util::{AccessMode, Buffer};
+use crate::syntax::{Source, SourceId, Span};
+use crate::util::{AccessMode, Buffer, PackageSpec, WriteMode};
 
 /// Compile a source file into a fully layouted document.
-#[tracing::instrument(skip(world))]
-pub fn compile(world: &dyn World) -> SourceResult<Document> {
+///
+/// Warnings produced during compilation are collected in the `tracer`.
+#[tracing::instrument(skip(world, tracer))]
+pub fn compile(world: &dyn World, tracer: &mut Tracer) -> SourceResult<Document> {
     let route = Route::default();
-    let mut tracer = Tracer::default();
 
     // Call `track` just once to keep comemo's ID stable.
     let world = world.track();
@@ -83,7 +87,10 @@ pub fn compile(world: &dyn World) -> SourceResult<Document> {
     )?;
 
     // Typeset the module's contents.
-    model::typeset(world, tracer, &module.content())
+    let content = module.content();
+    let mut document = model::typeset(world, tracer, &content)?;
+    document.content = content;
+    Ok(document)
 }
 
 /// The environment in which typesetting occurs.
@@ -110,6 +117,14 @@ pub trait World {
     /// Access a source file by id.
     fn source(&self, id: SourceId) -> &Source;
 
+    /// Locate the entrypoint of a package.
+    ///
+    /// Relative paths in the package are resolved from the entrypoint.
+    /// There are no packages by default.
+    fn package(&self, spec: &PackageSpec) -> StrResult<PathBuf> {
+        Err(eco_format!("package not found ({spec})"))
+    }
+
     /// Metadata about all known fonts.
     fn book(&self) -> &Prehashed<FontBook>;
 
@@ -119,13 +134,58 @@ pub trait World {
     /// Try to access (read) a file at a path.
     fn read(&self, path: &Path) -> FileResult<Buffer>;
 
-    /// Write or append data to a file at a path.
-    /// From is a unique identifier (a hash), and does not indicate any kind of order.
-    fn write(&self, path: &Path, from: u128, what: Vec<u8>) -> FileResult<()>;
+    /// Add a chunk of data to the file at a path.
+    ///
+    /// The file is written once compilation is over. Its chunks are ordered
+    /// by their `order`, and chunks with the same `order` are kept in the
+    /// order in which they were written. A chunk without an `order` must be
+    /// the file's only chunk, like a serialized document that would be
+    /// corrupted by further data. Writing such a chunk again replaces the
+    /// earlier one, so that a call that is repeated when the document is laid
+    /// out again doesn't fail.
+    ///
+    /// The `mode` decides what happens if the file already exists. All chunks
+    /// of a file must be written with the same mode. The `span` identifies the
+    /// call that wrote the chunk.
+    fn write(
+        &self,
+        path: &Path,
+        order: Option<i64>,
+        mode: WriteMode,
+        what: Vec<u8>,
+        span: Span,
+    ) -> FileResult<()>;
 
     /// Get the current date.
     ///
     /// If no offset is specified, the local date should be chosen. Otherwise,
     /// the UTC date should be chosen with the corresponding offset in hours.
     fn today(&self, offset: Option<i64>) -> Option<Datetime>;
+
+    /// Read the value of an environment variable.
+    ///
+    /// Returns `None` if the variable is not set. Fails if access to the
+    /// variable is not allowed, which is the default.
+    fn env(&self, name: &str) -> StrResult<Option<EcoString>> {
+        Err(eco_format!("access to environment variable {name} is not allowed"))
+    }
+
+    /// Run an external program with arguments and return its standard
+    /// output.
+    ///
+    /// Fails if the program can't be run, exits unsuccessfully or if running
+    /// programs is not allowed, which is the default.
+    fn exec(&self, program: &str, args: &[EcoString]) -> StrResult<EcoString> {
+        let _ = args;
+        Err(eco_format!("running external programs like {program} is not allowed"))
+    }
+
+    /// Fetch a remote resource by its URL.
+    ///
+    /// Fails if the resource can't be fetched or if network access is not
+    /// allowed, which is the default.
+    fn fetch(&self, url: &str) -> StrResult<Buffer> {
+        let _ = url;
+        Err("network access is not allowed".into())
+    }
 }
@@ -12,6 +12,7 @@ use super::{
 ///
 /// The high-level API for this function is
 /// [`Source::edit`](super::Source::edit).
+#[tracing::instrument(skip_all)]
 pub fn reparse(
     root: &mut SyntaxNode,
     text: &str,
@@ -3,8 +3,10 @@
 pub mod fat;
 
 mod buffer;
+mod package;
 
 pub use buffer::Buffer;
+pub use package::PackageSpec;
 
 use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::Hash;
@@ -15,6 +17,7 @@ use std::sync::Arc;
 use siphasher::sip128::{Hasher128, SipHasher13};
 
 use crate::diag::{FileError, FileResult};
+use crate::eval::Cast;
 
 /// Turn a closure into a struct implementing [`Debug`].
 pub fn debug<F>(f: F) -> impl Debug
@@ -304,3 +307,15 @@ impl Display for AccessMode {
         }
     }
 }
+
+/// How data written during compilation interacts with an existing file.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum WriteMode {
+    /// Replace the existing file.
+    #[default]
+    Overwrite,
+    /// Add the data to the end of the existing file.
+    Append,
+    /// Fail if the file already exists.
+    ErrorIfExists,
+}
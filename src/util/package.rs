@@ -0,0 +1,59 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use ecow::{eco_format, EcoString};
+
+use crate::syntax::is_ident;
+
+/// Identifies a package, e.g. `@local/mypkg:0.1.0`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PackageSpec {
+    /// The namespace the package lives in.
+    pub namespace: EcoString,
+    /// The name of the package within its namespace.
+    pub name: EcoString,
+    /// The package's version, e.g. `0.1.0`.
+    pub version: EcoString,
+}
+
+impl FromStr for PackageSpec {
+    type Err = EcoString;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(rest) = s.strip_prefix('@') else {
+            return Err("package specification must start with @".into());
+        };
+
+        let (namespace, rest) =
+            rest.split_once('/').ok_or("package specification is missing name")?;
+        let (name, version) = rest
+            .split_once(':')
+            .ok_or("package specification is missing version")?;
+
+        for (part, what) in [(namespace, "namespace"), (name, "name")] {
+            if !is_ident(part) {
+                return Err(eco_format!("`{part}` is not a valid package {what}"));
+            }
+        }
+
+        let parts: Vec<_> = version.split('.').collect();
+        if parts.len() != 3 || parts.iter().any(|part| part.parse::<u32>().is_err()) {
+            return Err(eco_format!(
+                "`{version}` is not a valid package version \
+                 (expected major.minor.patch)"
+            ));
+        }
+
+        Ok(Self {
+            namespace: namespace.into(),
+            name: name.into(),
+            version: version.into(),
+        })
+    }
+}
+
+impl Display for PackageSpec {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "@{}/{}:{}", self.namespace, self.name, self.version)
+    }
+}
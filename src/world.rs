@@ -0,0 +1,212 @@
+//! Worlds that don't depend on the file system.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use comemo::Prehashed;
+
+use crate::diag::{FileError, FileResult, StrResult};
+use crate::eval::{Datetime, Library};
+use crate::font::{Font, FontBook};
+use crate::syntax::{Source, SourceId, Span};
+use crate::util::{Access, AccessMode, Buffer, PathExt, WriteMode};
+use crate::World;
+
+/// The virtual directory that holds the sources and files of a [`MemoryWorld`].
+const ROOT: &str = "/root";
+
+/// The virtual directory that a [`MemoryWorld`] collects writes in.
+const DEST: &str = "/dest";
+
+/// A world whose sources, files and fonts are all held in memory.
+///
+/// This is useful for applications and WASM hosts that embed the compiler
+/// and cannot or don't want to access the file system. Paths are relative to
+/// the world's root, e.g. `chapters/intro.typ`. Everything written during
+/// compilation is collected and can be retrieved with
+/// [`take_writes`](Self::take_writes).
+pub struct MemoryWorld {
+    library: Prehashed<Library>,
+    book: Prehashed<FontBook>,
+    fonts: Vec<Font>,
+    sources: Vec<Source>,
+    ids: HashMap<PathBuf, SourceId>,
+    files: HashMap<PathBuf, Buffer>,
+    main: SourceId,
+    today: Option<Datetime>,
+    writes: RefCell<BTreeMap<PathBuf, Written>>,
+}
+
+/// The chunks written to a single file, ordered and replaced like in
+/// [`World::write`].
+#[derive(Default)]
+struct Written {
+    mode: WriteMode,
+    chunks: BTreeMap<i64, Vec<Vec<u8>>>,
+    exclusive: bool,
+}
+
+impl MemoryWorld {
+    /// Create a new world from in-memory sources, files and fonts.
+    ///
+    /// Fails if `main` is not among the `sources`.
+    pub fn new(
+        library: Library,
+        main: impl AsRef<Path>,
+        sources: HashMap<PathBuf, String>,
+        files: HashMap<PathBuf, Buffer>,
+        fonts: Vec<Font>,
+    ) -> StrResult<Self> {
+        let mut world = Self {
+            library: Prehashed::new(library),
+            book: Prehashed::new(FontBook::from_fonts(&fonts)),
+            fonts,
+            sources: vec![],
+            ids: HashMap::new(),
+            files: files
+                .into_iter()
+                .map(|(path, data)| (virtualize(&path), data))
+                .collect(),
+            main: SourceId::detached(),
+            today: None,
+            writes: RefCell::default(),
+        };
+
+        for (path, text) in sources {
+            let path = virtualize(&path);
+            let id = SourceId::from_u16(world.sources.len() as u16);
+            world.sources.push(Source::new(id, &path, text));
+            world.ids.insert(path, id);
+        }
+
+        let main = main.as_ref();
+        world.main = *world
+            .ids
+            .get(&virtualize(main))
+            .ok_or_else(|| format!("main source {} does not exist", main.display()))?;
+
+        Ok(world)
+    }
+
+    /// The path of a source relative to the world's root.
+    pub fn path(&self, id: SourceId) -> &Path {
+        let path = self.source(id).path();
+        path.strip_prefix(ROOT).unwrap_or(path)
+    }
+
+    /// Set the date that the world reports as today.
+    pub fn set_today(&mut self, today: Option<Datetime>) {
+        self.today = today;
+    }
+
+    /// Take everything written during compilation, by path relative to the
+    /// destination directory.
+    ///
+    /// The destination directory starts out empty, so the write modes don't
+    /// make a difference: Appending yields the same data as overwriting and
+    /// creating a new file never fails.
+    pub fn take_writes(&self) -> BTreeMap<PathBuf, Vec<u8>> {
+        std::mem::take(&mut *self.writes.borrow_mut())
+            .into_iter()
+            .map(|(path, written)| {
+                let path = path.strip_prefix(DEST).map_or(path.clone(), Into::into);
+                let data = written.chunks.into_values().flatten().flatten().collect();
+                (path, data)
+            })
+            .collect()
+    }
+}
+
+impl World for MemoryWorld {
+    fn root(&self, mode: AccessMode) -> FileResult<&Path> {
+        match mode {
+            Access::Read(_) => Ok(Path::new(ROOT)),
+            Access::Write(_) => Ok(Path::new(DEST)),
+        }
+    }
+
+    fn library(&self) -> &Prehashed<Library> {
+        &self.library
+    }
+
+    fn main(&self) -> &Source {
+        self.source(self.main)
+    }
+
+    fn resolve(&self, path: &Path) -> FileResult<SourceId> {
+        let path = path.normalize();
+        self.ids.get(&path).copied().ok_or(FileError::NotFound(path))
+    }
+
+    fn source(&self, id: SourceId) -> &Source {
+        &self.sources[id.as_u16() as usize]
+    }
+
+    fn book(&self) -> &Prehashed<FontBook> {
+        &self.book
+    }
+
+    fn font(&self, id: usize) -> Option<Font> {
+        self.fonts.get(id).cloned()
+    }
+
+    fn read(&self, path: &Path) -> FileResult<Buffer> {
+        let path = path.normalize();
+        if let Some(data) = self.files.get(&path) {
+            return Ok(data.clone());
+        }
+
+        // Sources can also be read as plain files.
+        match self.ids.get(&path) {
+            Some(&id) => Ok(self.source(id).text().as_bytes().into()),
+            None => Err(FileError::NotFound(path)),
+        }
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        order: Option<i64>,
+        mode: WriteMode,
+        what: Vec<u8>,
+        _: Span,
+    ) -> FileResult<()> {
+        let path = path.normalize();
+        if !path.starts_with(DEST) {
+            return Err(FileError::OutsideDest(path));
+        }
+
+        let mut writes = self.writes.borrow_mut();
+        let written = writes.entry(path).or_default();
+        if written.exclusive && order.is_none() {
+            *written = Written::default();
+        }
+
+        if written.chunks.is_empty() {
+            written.mode = mode;
+        } else if written.exclusive || order.is_none() {
+            return Err(FileError::WrittenTwice);
+        } else if written.mode != mode {
+            return Err(FileError::ConflictingModes);
+        }
+
+        written.exclusive = order.is_none();
+        written
+            .chunks
+            .entry(order.unwrap_or_default())
+            .or_default()
+            .push(what);
+        Ok(())
+    }
+
+    fn today(&self, _: Option<i64>) -> Option<Datetime> {
+        self.today
+    }
+}
+
+/// Place a path relative to the world's root in the virtual root directory.
+fn virtualize(path: &Path) -> PathBuf {
+    let relative = path.strip_prefix("/").unwrap_or(path);
+    Path::new(ROOT).join(relative).normalize()
+}
@@ -6,9 +6,8 @@ use typst::diag::{FileError, FileResult};
 use typst::eval::{Datetime, Library};
 use typst::font::{Font, FontBook};
 use typst::geom::Color;
-use typst::model::Location;
-use typst::syntax::{Source, SourceId};
-use typst::util::Buffer;
+use typst::syntax::{Source, SourceId, Span};
+use typst::util::{Buffer, WriteMode};
 use typst::World;
 use unscanny::Scanner;
 
@@ -86,12 +85,14 @@ fn bench_typeset(iai: &mut Iai) {
 
 fn bench_compile(iai: &mut Iai) {
     let world = BenchWorld::new();
-    iai.run(|| typst::compile(&world));
+    let mut tracer = typst::eval::Tracer::default();
+    iai.run(|| typst::compile(&world, &mut tracer));
 }
 
 fn bench_render(iai: &mut Iai) {
     let world = BenchWorld::new();
-    let document = typst::compile(&world).unwrap();
+    let mut tracer = typst::eval::Tracer::default();
+    let document = typst::compile(&world, &mut tracer).unwrap();
     iai.run(|| typst::export::render(&document.pages[0], 1.0, Color::WHITE))
 }
 
@@ -149,8 +150,15 @@ impl World for BenchWorld {
         Err(FileError::NotFound(path.into()))
     }
 
-    fn write(&self, _: &Path, _: Location, _: Vec<u8>) -> FileResult<()> {
-        todo!()
+    fn write(
+        &self,
+        _: &Path,
+        _: Option<i64>,
+        _: WriteMode,
+        _: Vec<u8>,
+        _: Span,
+    ) -> FileResult<()> {
+        Err(FileError::Disabled)
     }
 
     fn today(&self, _: Option<i64>) -> Option<Datetime> {
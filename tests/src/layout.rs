@@ -0,0 +1,530 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use typst::doc::{Document, Frame, FrameItem, Meta, PageBox, TextItem};
+use typst::eval::Tracer;
+use typst::font::Font;
+use typst::geom::{Abs, Geometry, Point, Ratio, Size, Transform};
+use typst::util::Buffer;
+use typst::world::MemoryWorld;
+
+/// Compile a document from a main source.
+fn compile(text: &str) -> Document {
+    compile_with(text, &[])
+}
+
+/// Compile a document from a main source and additional files.
+fn compile_with(text: &str, files: &[(&str, &[u8])]) -> Document {
+    let sources = HashMap::from([(PathBuf::from("main.typ"), text.to_string())]);
+    let files = files
+        .iter()
+        .map(|&(path, data)| (PathBuf::from(path), Buffer::from(data.to_vec())))
+        .collect();
+    let fonts = Font::iter(Buffer::from_static(include_bytes!(
+        "../../assets/fonts/LinLibertine_R.ttf"
+    )))
+    .collect();
+    let world =
+        MemoryWorld::new(typst_library::build(), "main.typ", sources, files, fonts)
+            .unwrap();
+    typst::compile(&world, &mut Tracer::default()).unwrap()
+}
+
+/// Collect the items of a frame and of its groups with their positions on the
+/// frame.
+fn items(frame: &Frame) -> Vec<(Point, &FrameItem)> {
+    fn collect<'a>(
+        frame: &'a Frame,
+        ts: Transform,
+        items: &mut Vec<(Point, &'a FrameItem)>,
+    ) {
+        for (pos, item) in frame.items() {
+            if let FrameItem::Group(group) = item {
+                let ts = ts
+                    .pre_concat(Transform::translate(pos.x, pos.y))
+                    .pre_concat(group.transform);
+                collect(&group.frame, ts, items);
+            }
+            items.push((pos.transform(ts), item));
+        }
+    }
+
+    let mut items = vec![];
+    collect(frame, Transform::identity(), &mut items);
+    items
+}
+
+/// The baseline positions of the text runs in a frame.
+fn texts(frame: &Frame) -> Vec<(Point, &TextItem)> {
+    items(frame)
+        .into_iter()
+        .filter_map(|(pos, item)| match item {
+            FrameItem::Text(text) => Some((pos, text)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The baseline position of the first text run with the given text.
+fn text_pos(frame: &Frame, text: &str) -> Point {
+    texts(frame)
+        .into_iter()
+        .find(|(_, t)| t.text == text)
+        .unwrap_or_else(|| panic!("no text run `{text}`"))
+        .0
+}
+
+/// The opacities of the faded groups in a frame together with their frames,
+/// innermost first.
+fn faded(frame: &Frame) -> Vec<(Ratio, &Frame)> {
+    items(frame)
+        .into_iter()
+        .filter_map(|(_, item)| match item {
+            FrameItem::Group(group) if !group.opacity.is_one() => {
+                Some((group.opacity, &group.frame))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The transformations of the transformed groups in a frame, innermost first.
+fn transforms(frame: &Frame) -> Vec<Transform> {
+    items(frame)
+        .into_iter()
+        .filter_map(|(_, item)| match item {
+            FrameItem::Group(group) if !group.transform.is_identity() => {
+                Some(group.transform)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The start and end points of the lines in a frame.
+fn lines(frame: &Frame) -> Vec<(Point, Point)> {
+    items(frame)
+        .into_iter()
+        .filter_map(|(pos, item)| match item {
+            FrameItem::Shape(shape, _) => match shape.geometry {
+                Geometry::Line(end) => Some((pos, pos + end)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// The position and size of a print production box in a frame.
+fn page_box(frame: &Frame, kind: PageBox) -> (Point, Size) {
+    items(frame)
+        .into_iter()
+        .find_map(|(pos, item)| match item {
+            FrameItem::Meta(Meta::PageBox(k), size) if *k == kind => Some((pos, *size)),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no {kind:?} box"))
+}
+
+/// Whether two sizes are approximately equal.
+fn size_approx_eq(a: Size, b: Size) -> bool {
+    a.x.approx_eq(b.x) && a.y.approx_eq(b.y)
+}
+
+/// The positions and lengths of the vertical lines in a frame.
+fn vertical_lines(frame: &Frame) -> Vec<(Point, Abs)> {
+    items(frame)
+        .into_iter()
+        .filter_map(|(pos, item)| match item {
+            FrameItem::Shape(shape, _) => match shape.geometry {
+                Geometry::Line(end) if end.x.approx_eq(Abs::zero()) => Some((pos, end.y)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_columns_rules_sit_between_columns() {
+    let document = compile(
+        "#set page(width: 120pt, height: 80pt, margin: 0pt)\n\
+         #columns(3, gutter: 6pt, rule: 1pt)[A #colbreak() B #colbreak() C]",
+    );
+
+    let lines = vertical_lines(&document.pages[0]);
+    assert_eq!(
+        lines,
+        [
+            (Point::new(Abs::pt(39.0), Abs::zero()), Abs::pt(80.0)),
+            (Point::new(Abs::pt(81.0), Abs::zero()), Abs::pt(80.0)),
+        ]
+    );
+}
+
+#[test]
+fn test_columns_rules_reach_as_far_as_the_columns() {
+    let document = compile(
+        "#set page(width: 120pt, height: auto, margin: 0pt)\n\
+         #columns(2, gutter: 6pt, rule: 1pt)[\
+           #block(height: 20pt) #colbreak() #block(height: 30pt)\
+         ]",
+    );
+
+    let lines = vertical_lines(&document.pages[0]);
+    assert_eq!(lines, [(Point::new(Abs::pt(60.0), Abs::zero()), Abs::pt(30.0))]);
+}
+
+#[test]
+fn test_columns_are_mirrored_in_rtl() {
+    let rule = |dir: &str| {
+        let document = compile(&format!(
+            "#set page(width: 120pt, height: 80pt, margin: 0pt)\n\
+             #set text(dir: {dir})\n\
+             #columns((1fr, 2fr), gutter: 6pt, rule: 1pt)[A #colbreak() B]"
+        ));
+        vertical_lines(&document.pages[0])[0].0.x
+    };
+
+    assert_eq!(rule("ltr"), Abs::pt(41.0));
+    assert_eq!(rule("rtl"), Abs::pt(79.0));
+}
+
+#[test]
+fn test_opacity_fades_shapes_and_text() {
+    let document = compile(
+        "#rect(fill: blue, opacity: 50%)[\
+           #text(opacity: 40%)[Faded] Opaque\
+         ]",
+    );
+
+    let groups = faded(&document.pages[0]);
+    assert_eq!(groups.len(), 2);
+
+    // The text is faded on its own and only contains the faded word.
+    let (opacity, text) = groups[0];
+    assert_eq!(opacity, Ratio::new(0.4));
+    assert!(matches!(text.items().next(), Some((_, FrameItem::Text(_)))));
+    assert_eq!(text.items().count(), 1);
+
+    // The rectangle is faded together with its fill and content.
+    let (opacity, rect) = groups[1];
+    assert_eq!(opacity, Ratio::new(0.5));
+    assert!(matches!(rect.items().next(), Some((_, FrameItem::Shape(..)))));
+}
+
+#[test]
+fn test_opacity_is_clamped() {
+    let document = compile(
+        "#square(fill: red, opacity: 150%)\n\
+         #square(fill: red, opacity: 100%)\n\
+         #square(fill: red, opacity: -20%)",
+    );
+
+    let groups = faded(&document.pages[0]);
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].0, Ratio::zero());
+}
+
+#[test]
+fn test_skew_slants_around_origin() {
+    let document = compile(
+        "#set page(margin: 0pt)\n\
+         #box(skew(ax: 45deg, origin: bottom + left, rect(width: 20pt, height: 20pt)))",
+    );
+
+    // The bottom edge stays in place while the top edge moves by the height
+    // of the rectangle.
+    let ts = transforms(&document.pages[0]);
+    assert_eq!(ts.len(), 1);
+    assert!((ts[0].kx.get() - 1.0).abs() < 1e-9);
+    assert!(ts[0].ky.is_zero());
+    assert!(ts[0].tx.approx_eq(Abs::pt(-20.0)));
+    assert!(ts[0].ty.approx_eq(Abs::zero()));
+}
+
+#[test]
+fn test_skew_vertically() {
+    let document = compile(
+        "#set page(margin: 0pt)\n\
+         #box(skew(ay: 20deg, rect(width: 20pt, height: 10pt)))",
+    );
+
+    // The horizontal edges are slanted around the center of the rectangle.
+    let ts = transforms(&document.pages[0]);
+    let tan = 20f64.to_radians().tan();
+    assert_eq!(ts.len(), 1);
+    assert!(ts[0].kx.is_zero());
+    assert!((ts[0].ky.get() - tan).abs() < 1e-9);
+    assert!(ts[0].tx.approx_eq(Abs::zero()));
+    assert!(ts[0].ty.approx_eq(Abs::pt(-10.0 * tan)));
+}
+
+#[test]
+fn test_par_numbers_sit_in_the_gutter() {
+    let document = compile(
+        "#set page(width: 120pt, margin: (left: 30pt, rest: 10pt))\n\
+         #set par(numbering: \"1\")\n\
+         First\n\n\
+         Second",
+    );
+
+    // The numbers are one em before the lines and share their baselines.
+    let page = &document.pages[0];
+    for (number, line) in [("1", "First"), ("2", "Second")] {
+        let number = text_pos(page, number);
+        let line = text_pos(page, line);
+        assert!(line.x.approx_eq(Abs::pt(30.0)));
+        assert!(number.x > Abs::zero() && number.x < Abs::pt(19.0));
+        assert!(number.y.approx_eq(line.y));
+    }
+}
+
+#[test]
+fn test_par_numbers_are_mirrored_in_rtl() {
+    let document = compile(
+        "#set page(width: 120pt, margin: 10pt)\n\
+         #set text(dir: rtl)\n\
+         #set par(numbering: \"1\")\n\
+         Line",
+    );
+
+    let page = &document.pages[0];
+    assert!(text_pos(page, "1").x.approx_eq(Abs::pt(121.0)));
+    assert!(text_pos(page, "1").y.approx_eq(text_pos(page, "Line").y));
+}
+
+#[test]
+fn test_raw_numbers_precede_the_lines() {
+    let document = compile(
+        "#set page(width: 120pt, margin: 0pt)\n\
+         #set raw(numbering: \"1\")\n\
+         ```\nab\ncd\n```\n\
+         Inline `ef` is not numbered.",
+    );
+
+    let page = &document.pages[0];
+    let first = text_pos(page, "ab");
+    let second = text_pos(page, "cd");
+    assert!(second.y > first.y);
+    for (number, line) in [("1", first), ("2", second)] {
+        let number = text_pos(page, number);
+        assert!(number.x >= Abs::zero() && number.x < line.x);
+        assert!(number.y.approx_eq(line.y));
+    }
+
+    // Only the lines of the block are numbered.
+    let numbers: Vec<_> = texts(page)
+        .into_iter()
+        .map(|(_, t)| t.text.as_str())
+        .filter(|t| t.chars().all(|c| c.is_ascii_digit()))
+        .collect();
+    assert_eq!(numbers, ["1", "2"]);
+}
+
+#[test]
+fn test_hyphenate_with_custom_patterns() {
+    let hyphenate = |patterns: &[u8]| {
+        let document = compile_with(
+            "#set page(width: 60pt)\n\
+             #set text(hyphenate: true, hyphenation-patterns: \"/patterns.tex\")\n\
+             Hyphenation of a table.",
+            &[("/patterns.tex", patterns)],
+        );
+        typst::export::text(&document, "", false)
+    };
+
+    // The patterns only allow "hy-phen-ation" and the exception "ta-ble".
+    let text = hyphenate(include_bytes!("../../assets/files/hyph-liang.tex"));
+    let breaks: Vec<_> = text
+        .split_inclusive('\n')
+        .filter_map(|line| line.strip_suffix("-\n"))
+        .map(|line| line.rsplit(' ').next().unwrap())
+        .collect();
+    assert!(!breaks.is_empty());
+    assert!(breaks
+        .iter()
+        .all(|fragment| ["Hy", "Hyphen", "ta"].contains(fragment)));
+    assert_eq!(
+        text.replace("-\n", "").split_whitespace().collect::<Vec<_>>(),
+        ["Hyphenation", "of", "a", "table."],
+    );
+
+    // Patterns that never match prevent all hyphenation.
+    let text = hyphenate(b"\\patterns{ x1y }");
+    assert!(!text.contains('-'));
+}
+
+#[test]
+fn test_microtype_protrudes_opening_quotes() {
+    let start = |microtype: bool, body: &str| {
+        let document = compile(&format!(
+            "#set page(width: 100pt, margin: 10pt)\n\
+             #set par(microtype: {microtype})\n\
+             {body}"
+        ));
+        texts(&document.pages[0])[0].0.x
+    };
+
+    // Half of the quote hangs into the margin.
+    let hanging = start(true, "\"Quoted\"");
+    assert!(hanging < Abs::pt(10.0) && hanging > Abs::pt(7.0));
+    assert!(start(false, "\"Quoted\"").approx_eq(Abs::pt(10.0)));
+    assert!(start(true, "Plain").approx_eq(Abs::pt(10.0)));
+}
+
+#[test]
+fn test_microtype_expands_glyphs_when_justifying() {
+    let scales = |microtype: bool| {
+        let document = compile(&format!(
+            "#set page(width: 120pt)\n\
+             #set par(justify: true, microtype: {microtype})\n\
+             #lorem(30)"
+        ));
+        texts(&document.pages[0])
+            .into_iter()
+            .map(|(_, text)| text.x_scale.get())
+            .collect::<Vec<_>>()
+    };
+
+    // The glyphs are scaled by at most two percent.
+    let expanded = scales(true);
+    assert!(expanded.iter().any(|&scale| scale != 1.0));
+    assert!(expanded.iter().all(|&scale| (scale - 1.0).abs() <= 0.02 + 1e-9));
+    assert!(scales(false).iter().all(|&scale| scale == 1.0));
+}
+
+#[test]
+fn test_index_lists_terms_with_their_pages() {
+    let lines = |text: &str| {
+        let document = compile(text);
+        let text = typst::export::text(&document, "\u{c}", false);
+        let last = text.rsplit('\u{c}').next().unwrap();
+        last.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+
+    // Consecutive pages are collapsed and subterms are listed below their
+    // term.
+    let index = lines(
+        "#set page(height: 120pt)\n\
+         Apples#index(\"Apple\") and pears#index(\"pear\").\n\
+         #pagebreak()\n\
+         More apples#index(\"Apple\") and\n\
+         colors#index(\"Color\", \"Red\")#index(\"Color\", \"Green\").\n\
+         #pagebreak()\n\
+         Apples#index(\"Apple\") again.\n\
+         #pagebreak()\n\
+         Bananas#index(\"Banana\") are red#index(\"Color\", \"Red\").\n\
+         #set page(height: auto)\n\
+         #make-index()",
+    );
+    assert_eq!(
+        index,
+        [
+            "Index",
+            "A",
+            "Apple, 1–3",
+            "B",
+            "Banana, 4",
+            "C",
+            "Color",
+            "Green, 2",
+            "Red, 2, 4",
+            "P",
+            "pear, 1",
+        ]
+    );
+
+    // Empty terms are skipped.
+    let index = lines(
+        "#make-index(title: [Terms], letters: false)\n\
+         #index(\"zebra\") #index(\"Aardvark\") #index(\"\")",
+    );
+    assert_eq!(index, ["Terms", "Aardvark, 1", "zebra, 1"]);
+}
+
+#[test]
+fn test_page_bleed_extends_the_fill() {
+    let document = compile(
+        "#set page(width: 80pt, height: 60pt, bleed: 3mm, fill: aqua)\n\
+         Printed edge to edge.",
+    );
+
+    let page = &document.pages[0];
+    let bleed = Abs::mm(3.0);
+    let size = Size::new(Abs::pt(80.0), Abs::pt(60.0));
+    let bled = size + Size::splat(2.0 * bleed);
+    assert!(size_approx_eq(page.size(), bled));
+
+    // The fill covers the bleed, while the trimmed page is inset by it.
+    let Some((pos, FrameItem::Shape(fill, _))) = page.items().next() else {
+        panic!("page has no fill");
+    };
+    assert_eq!(*pos, Point::zero());
+    assert_eq!(fill.geometry, Geometry::Rect(bled));
+    assert_eq!(page_box(page, PageBox::Bleed), (Point::zero(), bled));
+    let (pos, trim) = page_box(page, PageBox::Trim);
+    assert_eq!(pos, Point::splat(bleed));
+    assert!(size_approx_eq(trim, size));
+    assert!(texts(page)[0].0.x > bleed);
+    assert!(lines(page).is_empty());
+}
+
+#[test]
+fn test_page_crop_marks_sit_outside_of_the_bleed() {
+    let document = compile(
+        "#set page(width: 80pt, height: 60pt, bleed: 3mm, marks: (crop: true))\n\
+         Ready for print.",
+    );
+
+    let page = &document.pages[0];
+    let bleed = Abs::mm(3.0);
+    let slug = Abs::mm(10.0);
+    let size = Size::new(Abs::pt(80.0), Abs::pt(60.0));
+    assert!(size_approx_eq(page.size(), size + Size::splat(2.0 * (bleed + slug))));
+
+    let (pos, trim) = page_box(page, PageBox::Trim);
+    assert_eq!(pos, Point::splat(bleed + slug));
+    let (pos, bled) = page_box(page, PageBox::Bleed);
+    assert_eq!(pos, Point::splat(slug));
+
+    // Each corner has a horizontal and a vertical mark in line with the trim
+    // box's edges that doesn't reach into the bleed box.
+    let edges_x = [pos.x + bleed, pos.x + bleed + trim.x];
+    let edges_y = [pos.y + bleed, pos.y + bleed + trim.y];
+    let outside = |a: Abs, b: Abs, start: Abs, len: Abs| {
+        a.max(b) <= start || a.min(b) >= start + len
+    };
+
+    let marks = lines(page);
+    assert_eq!(marks.len(), 8);
+    for (start, end) in marks {
+        if start.y.approx_eq(end.y) {
+            assert!(edges_y.iter().any(|y| y.approx_eq(start.y)));
+            assert!(outside(start.x, end.x, pos.x, bled.x));
+        } else {
+            assert!(start.x.approx_eq(end.x));
+            assert!(edges_x.iter().any(|x| x.approx_eq(start.x)));
+            assert!(outside(start.y, end.y, pos.y, bled.y));
+        }
+    }
+}
+
+#[test]
+fn test_page_without_marks_or_bleed() {
+    let document = compile(
+        "#set page(width: 80pt, height: 60pt, marks: (crop: true))\n\
+         #set page(marks: none)\n\
+         No marks.",
+    );
+
+    let page = &document.pages[0];
+    assert_eq!(page.size(), Size::new(Abs::pt(80.0), Abs::pt(60.0)));
+    assert!(lines(page).is_empty());
+    assert!(page
+        .items()
+        .all(|(_, item)| !matches!(item, FrameItem::Meta(Meta::PageBox(_), _))));
+}
@@ -1,7 +1,7 @@
 #![allow(clippy::comparison_chain)]
 
 use std::cell::{RefCell, RefMut};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::OsStr;
 use std::fmt::Write as FmtWrite;
 use std::fs;
@@ -12,6 +12,7 @@ use std::{env, io};
 
 use clap::Parser;
 use comemo::{Prehashed, Track};
+use ecow::{eco_format, EcoString};
 use elsa::FrozenVec;
 use oxipng::{InFile, Options, OutFile};
 use rayon::iter::{ParallelBridge, ParallelIterator};
@@ -20,14 +21,13 @@ use tiny_skia as sk;
 use unscanny::Scanner;
 use walkdir::WalkDir;
 
-use typst::diag::{bail, FileError, FileResult, StrResult};
+use typst::diag::{bail, FileError, FileResult, SourceError, StrResult};
 use typst::doc::{Document, Frame, FrameItem, Meta};
 use typst::eval::{func, Datetime, Library, NoneValue, Value};
 use typst::font::{Font, FontBook};
 use typst::geom::{Abs, Color, RgbaColor, Smart};
-use typst::model::Location;
 use typst::syntax::{Source, SourceId, Span, SyntaxNode};
-use typst::util::{AccessMode, Buffer, PathExt};
+use typst::util::{AccessMode, Buffer, PackageSpec, PathExt, WriteMode};
 use typst::World;
 use typst_library::layout::{Margin, PageElem};
 use typst_library::text::{TextElem, TextSize};
@@ -38,6 +38,8 @@ const PNG_DIR: &str = "png";
 const PDF_DIR: &str = "pdf";
 const FONT_DIR: &str = "../assets/fonts";
 const FILE_DIR: &str = "../assets/files";
+const DEST_DIR: &str = "../assets/dest";
+const PACKAGE_DIR: &str = "../assets/packages";
 
 #[derive(Debug, Clone, Parser)]
 #[clap(name = "typst-test", author)]
@@ -205,6 +207,7 @@ struct TestWorld {
     paths: RefCell<HashMap<PathBuf, PathSlot>>,
     sources: FrozenVec<Box<Source>>,
     main: SourceId,
+    writes: RefCell<BTreeMap<PathBuf, Written>>,
 }
 
 impl Clone for TestWorld {
@@ -217,6 +220,7 @@ impl Clone for TestWorld {
             paths: self.paths.clone(),
             sources: FrozenVec::from_iter(self.sources.iter().cloned().map(Box::new)),
             main: self.main,
+            writes: self.writes.clone(),
         }
     }
 }
@@ -227,6 +231,35 @@ struct PathSlot {
     buffer: OnceCell<FileResult<Buffer>>,
 }
 
+/// The chunks written to a single file during the compilation of a subtest.
+#[derive(Clone)]
+struct Written {
+    mode: WriteMode,
+    chunks: BTreeMap<i64, Vec<Vec<u8>>>,
+    exclusive: bool,
+    span: Span,
+}
+
+impl Written {
+    /// The contents of the file at the path once the chunks are written to it.
+    ///
+    /// The mode is applied to the file that exists there on disk, if any.
+    fn contents(&self, path: &Path) -> FileResult<Vec<u8>> {
+        let data = self.chunks.values().flatten().flatten().copied();
+        let existing = fs::read(path).ok();
+        Ok(match self.mode {
+            WriteMode::Overwrite => data.collect(),
+            WriteMode::Append => {
+                existing.unwrap_or_default().into_iter().chain(data).collect()
+            }
+            WriteMode::ErrorIfExists if existing.is_some() => {
+                return Err(FileError::AlreadyExists(suffix(path, DEST_DIR)));
+            }
+            WriteMode::ErrorIfExists => data.collect(),
+        })
+    }
+}
+
 impl TestWorld {
     fn new(print: PrintConfig) -> Self {
         // Search for fonts.
@@ -249,14 +282,14 @@ impl TestWorld {
             paths: RefCell::default(),
             sources: FrozenVec::new(),
             main: SourceId::detached(),
+            writes: RefCell::default(),
         }
     }
 }
 
 impl World for TestWorld {
     fn root(&self, mode: AccessMode) -> FileResult<&Path> {
-        mode.as_read()
-            .map_or(Err(FileError::Disabled), |_| Ok(Path::new(FILE_DIR)))
+        Ok(Path::new(if mode.as_read().is_some() { FILE_DIR } else { DEST_DIR }))
     }
 
     fn library(&self) -> &Prehashed<Library> {
@@ -282,6 +315,28 @@ impl World for TestWorld {
         &self.sources[id.as_u16() as usize]
     }
 
+    fn package(&self, spec: &PackageSpec) -> StrResult<PathBuf> {
+        let dir = Path::new(PACKAGE_DIR)
+            .join(spec.namespace.as_str())
+            .join(spec.name.as_str())
+            .join(spec.version.as_str());
+        if !dir.is_dir() {
+            bail!("package not found ({spec})");
+        }
+
+        // Like the CLI, respect the entrypoint from the package's manifest.
+        let entrypoint = fs::read_to_string(dir.join("typst.toml"))
+            .ok()
+            .and_then(|text| text.parse::<toml::Table>().ok())
+            .and_then(|manifest| {
+                let entrypoint = manifest.get("package")?.get("entrypoint")?;
+                Some(entrypoint.as_str()?.to_string())
+            })
+            .unwrap_or_else(|| "lib.typ".into());
+
+        Ok(dir.join(entrypoint))
+    }
+
     fn book(&self) -> &Prehashed<FontBook> {
         &self.book
     }
@@ -297,13 +352,59 @@ impl World for TestWorld {
             .clone()
     }
 
-    fn write(&self, _path: &Path, _: Location, _: Vec<u8>) -> FileResult<()> {
-        todo!()
+    fn write(
+        &self,
+        path: &Path,
+        order: Option<i64>,
+        mode: WriteMode,
+        what: Vec<u8>,
+        span: Span,
+    ) -> FileResult<()> {
+        let mut writes = self.writes.borrow_mut();
+        let written = writes.entry(path.normalize()).or_insert_with(|| Written {
+            mode,
+            chunks: BTreeMap::new(),
+            exclusive: false,
+            span,
+        });
+
+        if written.exclusive && order.is_none() {
+            written.chunks.clear();
+            written.span = span;
+        }
+
+        if written.chunks.is_empty() {
+            written.mode = mode;
+        } else if written.exclusive || order.is_none() {
+            return Err(FileError::WrittenTwice);
+        } else if written.mode != mode {
+            return Err(FileError::ConflictingModes);
+        }
+
+        written.exclusive = order.is_none();
+        written
+            .chunks
+            .entry(order.unwrap_or_default())
+            .or_default()
+            .push(what);
+        Ok(())
     }
 
     fn today(&self, _: Option<i64>) -> Option<Datetime> {
         Some(Datetime::from_ymd(1970, 1, 1).unwrap())
     }
+
+    fn exec(&self, program: &str, args: &[EcoString]) -> StrResult<EcoString> {
+        // Only `echo` may run. It is emulated so that the tests don't depend
+        // on the programs installed on the system.
+        match program {
+            "echo" => {
+                let args: Vec<_> = args.iter().map(EcoString::as_str).collect();
+                Ok(eco_format!("{}\n", args.join(" ")))
+            }
+            _ => bail!("running external programs like {program} is not allowed"),
+        }
+    }
 }
 
 impl TestWorld {
@@ -335,15 +436,34 @@ impl TestWorld {
         self.sources.push(Box::new(source));
         id
     }
+
+    /// Take the files written during compilation, by their path in the
+    /// destination directory, along with their contents or the error that
+    /// prevents writing them.
+    fn take_writes(&self) -> Vec<(PathBuf, Result<Vec<u8>, SourceError>)> {
+        std::mem::take(&mut *self.writes.borrow_mut())
+            .into_iter()
+            .map(|(path, written)| {
+                let contents = written
+                    .contents(&path)
+                    .map_err(|err| SourceError::new(written.span, err.to_string()));
+                (suffix(&path, DEST_DIR), contents)
+            })
+            .collect()
+    }
 }
 
-/// Read as file.
-fn read(path: &Path) -> FileResult<Vec<u8>> {
-    let suffix = path
-        .strip_prefix(FILE_DIR)
+/// The path of a file relative to one of the test directories, with a
+/// leading slash.
+fn suffix(path: &Path, dir: &str) -> PathBuf {
+    path.strip_prefix(dir)
         .map(|suffix| Path::new("/").join(suffix))
-        .unwrap_or_else(|_| path.into());
+        .unwrap_or_else(|_| path.into())
+}
 
+/// Read as file.
+fn read(path: &Path) -> FileResult<Vec<u8>> {
+    let suffix = suffix(path, FILE_DIR);
     let f = |e| FileError::from_io(e, &suffix);
     if fs::metadata(path).map_err(f)?.is_dir() {
         Err(FileError::IsDirectory)
@@ -523,8 +643,8 @@ fn test_part(
         writeln!(output, "Syntax Tree:\n{:#?}\n", source.root()).unwrap();
     }
 
-    let (local_compare_ref, mut ref_errors) = parse_metadata(source);
-    let compare_ref = local_compare_ref.unwrap_or(compare_ref);
+    let metadata = parse_metadata(source);
+    let compare_ref = metadata.compare_ref.unwrap_or(compare_ref);
 
     ok &= test_spans(output, source.root());
     ok &= test_reparse(output, world.source(id).text(), i, rng);
@@ -538,43 +658,72 @@ fn test_part(
         writeln!(output, "Model:\n{:#?}\n", module.content()).unwrap();
     }
 
-    let (mut frames, errors) = match typst::compile(world) {
+    // Forget files written while printing the model.
+    world.take_writes();
+
+    let mut tracer = typst::eval::Tracer::default();
+    let (mut frames, mut errors) = match typst::compile(world, &mut tracer) {
         Ok(document) => (document.pages, vec![]),
         Err(errors) => (vec![], *errors),
     };
 
+    // Files are only written if compilation succeeded.
+    let mut writes = vec![];
+    let taken = world.take_writes();
+    if errors.is_empty() {
+        for (path, contents) in taken {
+            match contents {
+                Ok(data) => writes.push((
+                    path.to_string_lossy().replace('\\', "/"),
+                    String::from_utf8_lossy(&data).into_owned(),
+                )),
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+
     // Don't retain frames if we don't wanna compare with reference images.
     if !compare_ref {
         frames.clear();
     }
 
-    // Map errors to range and message format, discard traces and errors from
-    // other files.
-    let mut errors: Vec<_> = errors
-        .into_iter()
-        .filter(|error| error.span.source() == id)
-        .map(|error| (error.range(world), error.message.replace('\\', "/")))
-        .collect();
+    // Map errors and warnings to range and message format, discard traces and
+    // diagnostics from other files.
+    let diagnostics = |diagnostics: &[SourceError]| -> Vec<_> {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.span.source() == id)
+            .map(|diagnostic| {
+                (diagnostic.range(world), diagnostic.message.replace('\\', "/"))
+            })
+            .collect()
+    };
+
+    let source = world.source(id);
+    for (kind, found, annotated) in [
+        ("errors", diagnostics(errors.as_slice()), metadata.errors),
+        ("warnings", diagnostics(tracer.warnings()), metadata.warnings),
+    ] {
+        ok &= compare_diagnostics(output, source, i, line, kind, found, annotated);
+    }
 
-    errors.sort_by_key(|error| error.0.start);
-    ref_errors.sort_by_key(|error| error.0.start);
+    let mut ref_writes = metadata.writes;
+    writes.sort();
+    ref_writes.sort();
 
-    if errors != ref_errors {
-        writeln!(output, "  Subtest {i} does not match expected errors.").unwrap();
+    if writes != ref_writes {
+        writeln!(output, "  Subtest {i} does not match expected writes.").unwrap();
         ok = false;
 
-        let source = world.source(id);
-        for error in errors.iter() {
-            if !ref_errors.contains(error) {
-                write!(output, "    Not annotated | ").unwrap();
-                print_error(output, source, line, error);
+        for (path, data) in writes.iter() {
+            if !ref_writes.contains(&(path.clone(), data.clone())) {
+                writeln!(output, "    Not annotated | Written: {path} {data:?}").unwrap();
             }
         }
 
-        for error in ref_errors.iter() {
-            if !errors.contains(error) {
-                write!(output, "    Not emitted   | ").unwrap();
-                print_error(output, source, line, error);
+        for (path, data) in ref_writes.iter() {
+            if !writes.contains(&(path.clone(), data.clone())) {
+                writeln!(output, "    Not emitted   | Written: {path} {data:?}").unwrap();
             }
         }
     }
@@ -582,9 +731,58 @@ fn test_part(
     (ok, compare_ref, frames)
 }
 
-fn parse_metadata(source: &Source) -> (Option<bool>, Vec<(Range<usize>, String)>) {
+/// Check that the emitted errors or warnings match the annotated ones.
+fn compare_diagnostics(
+    output: &mut String,
+    source: &Source,
+    i: usize,
+    line: usize,
+    kind: &str,
+    mut found: Vec<(Range<usize>, String)>,
+    mut annotated: Vec<(Range<usize>, String)>,
+) -> bool {
+    found.sort_by_key(|diagnostic| diagnostic.0.start);
+    annotated.sort_by_key(|diagnostic| diagnostic.0.start);
+    if found == annotated {
+        return true;
+    }
+
+    writeln!(output, "  Subtest {i} does not match expected {kind}.").unwrap();
+    let label = if kind == "warnings" { "Warning" } else { "Error" };
+    for diagnostic in found.iter() {
+        if !annotated.contains(diagnostic) {
+            write!(output, "    Not annotated | ").unwrap();
+            print_error(output, source, line, label, diagnostic);
+        }
+    }
+
+    for diagnostic in annotated.iter() {
+        if !found.contains(diagnostic) {
+            write!(output, "    Not emitted   | ").unwrap();
+            print_error(output, source, line, label, diagnostic);
+        }
+    }
+
+    false
+}
+
+/// Expectations for a subtest, annotated in its comments.
+struct Metadata {
+    /// Whether to compare the output with the reference image.
+    compare_ref: Option<bool>,
+    /// The expected errors, with the ranges they should be annotated at.
+    errors: Vec<(Range<usize>, String)>,
+    /// The expected warnings, with the ranges they should be annotated at.
+    warnings: Vec<(Range<usize>, String)>,
+    /// The expected written files, by path in the destination directory.
+    writes: Vec<(String, String)>,
+}
+
+fn parse_metadata(source: &Source) -> Metadata {
     let mut compare_ref = None;
     let mut errors = vec![];
+    let mut warnings = vec![];
+    let mut writes = vec![];
 
     let lines: Vec<_> = source.text().lines().map(str::trim).collect();
     for (i, line) in lines.iter().enumerate() {
@@ -611,29 +809,73 @@ fn parse_metadata(source: &Source) -> (Option<bool>, Vec<(Range<usize>, String)>
             source.line_column_to_byte(line, column).unwrap()
         };
 
-        let Some(rest) = line.strip_prefix("// Error: ") else { continue; };
+        if let Some(rest) = line.strip_prefix("// Written: ") {
+            let (path, data) = rest.split_once(' ').unwrap_or((rest, "\"\""));
+            writes.push((path.to_string(), unescape(data.trim())));
+            continue;
+        }
+
+        let (rest, diagnostics) = if let Some(rest) = line.strip_prefix("// Error: ") {
+            (rest, &mut errors)
+        } else if let Some(rest) = line.strip_prefix("// Warning: ") {
+            (rest, &mut warnings)
+        } else {
+            continue;
+        };
+
         let mut s = Scanner::new(rest);
         let start = pos(&mut s);
         let end = if s.eat_if('-') { pos(&mut s) } else { start };
         let range = start..end;
 
-        errors.push((range, s.after().trim().to_string()));
+        diagnostics.push((range, s.after().trim().to_string()));
     }
 
-    (compare_ref, errors)
+    Metadata { compare_ref, errors, warnings, writes }
+}
+
+/// Parse a quoted string with backslash escapes, as printed by Rust's debug
+/// formatting of strings.
+fn unescape(quoted: &str) -> String {
+    let inner = quoted.strip_prefix('"').and_then(|s| s.strip_suffix('"'));
+    let mut s = Scanner::new(inner.unwrap_or(quoted));
+    let mut unescaped = String::new();
+    while let Some(c) = s.eat() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match s.eat() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('u') => {
+                s.eat_if('{');
+                let hex = s.eat_until('}');
+                s.eat_if('}');
+                let code = u32::from_str_radix(hex, 16).ok();
+                unescaped.extend(code.and_then(char::from_u32));
+            }
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
 }
 
 fn print_error(
     output: &mut String,
     source: &Source,
     line: usize,
+    label: &str,
     (range, message): &(Range<usize>, String),
 ) {
     let start_line = 1 + line + source.byte_to_line(range.start).unwrap();
     let start_col = 1 + source.byte_to_column(range.start).unwrap();
     let end_line = 1 + line + source.byte_to_line(range.end).unwrap();
     let end_col = 1 + source.byte_to_column(range.end).unwrap();
-    writeln!(output, "Error: {start_line}:{start_col}-{end_line}:{end_col}: {message}")
+    writeln!(output, "{label}: {start_line}:{start_col}-{end_line}:{end_col}: {message}")
         .unwrap();
 }
 
@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use typst::diag::{FileError, SourceResult};
+use typst::eval::Tracer;
+use typst::font::Font;
+use typst::syntax::Span;
+use typst::util::{Buffer, WriteMode};
+use typst::world::MemoryWorld;
+use typst::World;
+
+/// Create a world from sources and files, with `main.typ` as the main source.
+fn world(sources: &[(&str, &str)], files: &[(&str, &[u8])]) -> MemoryWorld {
+    let sources = sources
+        .iter()
+        .map(|&(path, text)| (PathBuf::from(path), text.to_string()))
+        .collect();
+    let files = files
+        .iter()
+        .map(|&(path, data)| (PathBuf::from(path), Buffer::from(data.to_vec())))
+        .collect();
+    let fonts = Font::iter(Buffer::from_static(include_bytes!(
+        "../../assets/fonts/LinLibertine_R.ttf"
+    )))
+    .collect();
+    MemoryWorld::new(typst_library::build(), "main.typ", sources, files, fonts).unwrap()
+}
+
+/// Compile the world and return everything written as strings.
+fn compile(world: &MemoryWorld) -> SourceResult<BTreeMap<PathBuf, String>> {
+    typst::compile(world, &mut Tracer::default())?;
+    Ok(world
+        .take_writes()
+        .into_iter()
+        .map(|(path, data)| (path, String::from_utf8(data).unwrap()))
+        .collect())
+}
+
+/// The writes expected from a compilation.
+fn writes(files: &[(&str, &str)]) -> BTreeMap<PathBuf, String> {
+    files
+        .iter()
+        .map(|&(path, data)| (PathBuf::from(path), data.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_world_main_is_required() {
+    let sources = HashMap::from([(PathBuf::from("lib.typ"), String::new())]);
+    let result = MemoryWorld::new(
+        typst_library::build(),
+        "main.typ",
+        sources,
+        HashMap::new(),
+        vec![],
+    );
+    assert_eq!(result.err().as_deref(), Some("main source main.typ does not exist"));
+}
+
+#[test]
+fn test_world_resolve_relative_and_absolute() {
+    let world = world(
+        &[
+            ("main.typ", "#include \"chapters/intro.typ\""),
+            (
+                "/chapters/intro.typ",
+                "#import \"/lib.typ\": name\n#write(\"out.txt\", name)",
+            ),
+            ("lib.typ", "#let name = read(\"data/name.txt\")"),
+        ],
+        &[("/data/name.txt", b"Typst")],
+    );
+
+    assert_eq!(world.path(world.main().id()), Path::new("main.typ"));
+    let intro = world
+        .resolve(Path::new("/root/chapters/../chapters/intro.typ"))
+        .unwrap();
+    assert_eq!(world.path(intro), Path::new("chapters/intro.typ"));
+    assert!(matches!(
+        world.resolve(Path::new("/chapters/intro.typ")),
+        Err(FileError::NotFound(_)),
+    ));
+
+    assert_eq!(compile(&world).unwrap(), writes(&[("out.txt", "Typst")]));
+}
+
+#[test]
+fn test_world_read_sources_as_files() {
+    let world = world(
+        &[
+            ("main.typ", "#write(\"copy.typ\", read(\"lib.typ\"))"),
+            ("lib.typ", "#let x = 1"),
+        ],
+        &[],
+    );
+
+    let data = world.read(Path::new("/root/lib.typ")).unwrap();
+    assert_eq!(data.as_slice(), b"#let x = 1");
+    assert_eq!(compile(&world).unwrap(), writes(&[("copy.typ", "#let x = 1")]));
+}
+
+#[test]
+fn test_world_write_outside_dest() {
+    let world = world(&[("main.typ", "")], &[]);
+    let write = |path: &str| {
+        world.write(
+            Path::new(path),
+            Some(0),
+            WriteMode::Overwrite,
+            vec![],
+            Span::detached(),
+        )
+    };
+
+    assert_eq!(
+        write("/root/main.typ"),
+        Err(FileError::OutsideDest(PathBuf::from("/root/main.typ"))),
+    );
+    assert_eq!(
+        write("/dest/../root/main.typ"),
+        Err(FileError::OutsideDest(PathBuf::from("/root/main.typ"))),
+    );
+    assert_eq!(write("/dest/sub/out.txt"), Ok(()));
+    let paths: Vec<_> = world.take_writes().into_keys().collect();
+    assert_eq!(paths, [Path::new("sub/out.txt")]);
+}
+
+#[test]
+fn test_world_write_chunk_order() {
+    let world = world(
+        &[(
+            "main.typ",
+            "#write(\"log.txt\", \"c\", at: 2)\n\
+             #write(\"log.txt\", \"a\", at: -1)\n\
+             #write(\"log.txt\", \"b\")\n\
+             #write(\"log.txt\", \"b\")",
+        )],
+        &[],
+    );
+
+    assert_eq!(compile(&world).unwrap(), writes(&[("log.txt", "abbc")]));
+}
+
+#[test]
+fn test_world_write_conflicts() {
+    let world = world(&[("main.typ", "")], &[]);
+    let write = |order, mode| {
+        world.write(Path::new("/dest/out.txt"), order, mode, vec![], Span::detached())
+    };
+
+    assert_eq!(write(Some(0), WriteMode::Append), Ok(()));
+    assert_eq!(write(Some(1), WriteMode::Overwrite), Err(FileError::ConflictingModes));
+    assert_eq!(write(None, WriteMode::Append), Err(FileError::WrittenTwice));
+}
+
+#[test]
+fn test_world_take_writes() {
+    let world = world(
+        &[(
+            "main.typ",
+            "#write(\"a.txt\", \"A\", mode: \"append\")\n\
+             #write_json(\"b.json\", (x: 1))\n\
+             #write_json(\"b.json\", (x: 2))",
+        )],
+        &[],
+    );
+
+    // The destination directory starts out empty, so appending is the same
+    // as overwriting.
+    let expected = writes(&[("a.txt", "A"), ("b.json", "{\"x\":2}")]);
+    assert_eq!(compile(&world).unwrap(), expected);
+    assert!(world.take_writes().is_empty());
+}
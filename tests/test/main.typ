@@ -1,4 +1,4 @@
-#import "template.typ": case
+#import "template.typ": case, write-positions
 #set page(numbering: "1 / 1")
 /* Calibrage */
 #place(top+left, dx : -60pt, dy : -60pt, square(size: 10pt, fill : black))
@@ -162,6 +162,7 @@ La fonction id_etudiant génère une grille de 8 colonnes et de 10 lignes */
 #let text = "/exemple"
 
 #forecast(json(text + ".json"))
+#write-positions()
 
 
 #locate((loc) => [
@@ -2,11 +2,22 @@
   return calc.round(size/1cm, digits: 2)
 }
 
+// The bounding boxes of all cases, by id.
+#let positions = state("positions", (:))
+
 #let case(id, size: 10pt, outset: 0pt) = {
   locate(loc => {
     let l = loc.position()
     let bb = (l.page,(tocm(l.x),tocm(l.x+size)),(tocm(l.y),tocm(l.y+size)))
-    write_json("/pos.json", id, bb)
+    positions.update(p => {
+      p.insert(id, bb)
+      p
+    })
     square(size: size, outset: outset)
   })
-}
\ No newline at end of file
+}
+
+// Write the bounding boxes of all cases. Must be placed once in the document.
+#let write-positions() = locate(loc => {
+  write_json("/pos.json", positions.final(loc))
+})
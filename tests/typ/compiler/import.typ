@@ -171,3 +171,30 @@ This is never reached.
 ---
 // Error: 14 expected comma
 #import "": a b
+
+---
+// Test importing a package, which imports a file relative to its entrypoint.
+#import "@local/mypkg:0.1.0": name, quad
+#test(name, "mypkg")
+#test(quad(3), 12)
+
+---
+// Test importing a package with a custom entrypoint.
+#import "@local/custom:0.1.0": name
+#test(name, "CUSTOM!")
+
+---
+// Error: 9-31 package not found (@local/missing:0.1.0)
+#import "@local/missing:0.1.0": *
+
+---
+// Error: 9-29 package not found (@local/mypkg:0.2.0)
+#import "@local/mypkg:0.2.0": *
+
+---
+// Error: 9-27 `0.1` is not a valid package version (expected major.minor.patch)
+#import "@local/mypkg:0.1": *
+
+---
+// Error: 9-23 package specification is missing version
+#import "@local/mypkg": *
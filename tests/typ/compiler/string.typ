@@ -96,14 +96,14 @@
 #test("Is there a".match("for this?"), none)
 #test(
   "The time of my life.".match(regex("[mit]+e")),
-  (start: 4, end: 8, text: "time", captures: ()),
+  (start: 4, end: 8, text: "time", captures: (), named: (:)),
 )
 
 // Test the `matches` method.
 #test("Hello there".matches("\d"), ())
 #test("Day by Day.".matches("Day"), (
-  (start: 0, end: 3, text: "Day", captures: ()),
-  (start: 7, end: 10, text: "Day", captures: ()),
+  (start: 0, end: 3, text: "Day", captures: (), named: (:)),
+  (start: 7, end: 10, text: "Day", captures: (), named: (:)),
 ))
 
 // Compute the sum of all timestamps in the text.
@@ -120,6 +120,18 @@
 #test(timesum("2:70"), "3:10")
 #test(timesum("1:20, 2:10, 0:40"), "4:10")
 
+// Test named capture groups.
+#let date = regex("(?<day>\d+)\.(?<month>\d+)\.(?<year>\d+)?")
+#test("On 14.07.".match(date).named, (day: "14", month: "07", year: none))
+#test(
+  "12.1.2023 or 1.2.2024".matches(date).map(m => m.named.year),
+  ("2023", "2024"),
+)
+#test(
+  "14.07.2023".replace(date, m => m.named.year + "-" + m.named.month),
+  "2023-07",
+)
+
 ---
 // Test the `replace` method with `Str` replacements.
 #test("ABC".replace("", "-"), "-A-B-C-")
@@ -210,6 +222,12 @@
 #test("abc".split("b"), ("a", "c"))
 #test("a123c".split(regex("\d")), ("a", "", "", "c"))
 #test("a123c".split(regex("\d+")), ("a", "c"))
+#test("a b  c ".split(), ("a", "b", "c"))
+#test("a b  c ".split(count: 1), ("a", "b  c "))
+#test("  a b".split(count: 0), ("a b",))
+#test("a,b,c".split(",", count: 1), ("a", "b,c"))
+#test("a,b,c".split(",", count: 5), ("a", "b", "c"))
+#test("a1b22c".split(regex("\d+"), count: 1), ("a", "b22c"))
 
 ---
 // Error: 2-2:1 unclosed string
@@ -18,11 +18,11 @@
 #test(calc.round(calc.pi, digits: 2), 3.14)
 
 ---
-// Error: 6-10 expected boolean, integer, float, or string, found length
+// Error: 6-10 expected boolean, integer, float, decimal, or string, found length
 #int(10pt)
 
 ---
-// Error: 8-13 expected boolean, integer, float, ratio, or string, found function
+// Error: 8-13 expected boolean, integer, float, decimal, ratio, or string, found function
 #float(float)
 
 ---
@@ -33,6 +33,46 @@
 // Error: 8-15 invalid float: 1.2.3
 #float("1.2.3")
 
+---
+// Test decimals.
+#test(decimal("0.1") + decimal("0.2"), decimal("0.3"))
+#test(decimal("1.50"), decimal("1.5"))
+#test(decimal(0.1), decimal("0.1"))
+#test(decimal(5), 5)
+#test(decimal("2.5") * 2, 5)
+#test(3 - decimal("0.25"), decimal("2.75"))
+#test(decimal(1) / 8, decimal("0.125"))
+#test(decimal(2) / 3, decimal("0.6666666666666666666666666667"))
+#test(-decimal("1.5"), decimal("-1.5"))
+#test(decimal("123456789012345678901234567890") + 1, decimal("123456789012345678901234567891"))
+#test(decimal("0.1") < decimal("0.2"), true)
+#test(decimal("2.5") > 2, true)
+#test(decimal("0.1") == 0.1, false)
+#test(calc.abs(decimal("-1.25")), decimal("1.25"))
+#test(calc.round(decimal("2.345"), digits: 2), decimal("2.35"))
+#test(calc.round(decimal("-2.5")), decimal(-3))
+#test(int(decimal("-7.9")), -7)
+#test(float(decimal("0.5")), 0.5)
+#test(str(decimal("-0.050")), "-0.05")
+#test(repr(decimal("1.25")), "decimal(\"1.25\")")
+#test(type(decimal(1)), "decimal")
+
+---
+// Error: 10-15 invalid decimal: 1e5
+#decimal("1e5")
+
+---
+// Error: 10-18 float is not representable as a decimal
+#decimal(calc.inf)
+
+---
+// Error: 3-23 cannot add decimal and float
+#(decimal("1.5") + 0.5)
+
+---
+// Error: 3-21 cannot divide by zero
+#(decimal("1.5") / 0)
+
 ---
 // Test the `abs` function.
 #test(calc.abs(-3), 3)
@@ -44,7 +84,7 @@
 #test(calc.abs(-25%), 25%)
 
 ---
-// Error: 11-22 expected integer, float, length, angle, ratio, or fraction, found string
+// Error: 11-22 expected integer, float, decimal, length, angle, ratio, or fraction, found string
 #calc.abs("no number")
 
 ---
@@ -200,6 +240,43 @@
 // Error: 16-19 cannot compare 1pt with 1em
 #calc.max(1em, 1pt)
 
+---
+// Test statistics.
+#test(calc.sum((1, 2, 3)), 6)
+#test(calc.sum((1, 2.5)), 3.5)
+#test(calc.sum((1pt, 2pt)), 3pt)
+#test(calc.sum((), default: 0), 0)
+#test(calc.product((1, 2, 3, 4)), 24)
+#test(calc.cumsum((1, 2, 3, 4)), (1, 3, 6, 10))
+#test(calc.cumsum(()), ())
+#test(calc.mean((1, 2, 3, 4)), 2.5)
+#test(calc.mean((1cm, 3cm)), 2cm)
+#test(calc.median((4, 1, 3)), 3)
+#test(calc.median((4, 1, 3, 2)), 2.5)
+#test(calc.median(("b", "a", "c")), "b")
+#test(calc.quantile((1, 2, 3, 4, 5), 0), 1)
+#test(calc.quantile((1, 2, 3, 4, 5), 1), 5)
+#test(calc.quantile((1, 2, 3, 4, 5), 0.75), 4)
+#test(calc.quantile((1, 2, 3, 4, 5, 6, 7, 8), 0.25), 2.75)
+#test(calc.variance((2, 4, 4, 4, 5, 5, 7, 9)), 4.0)
+#test(calc.variance((1, 2, 3, 4), sample: true), 5 / 3)
+
+---
+// Error: 11-15 cannot calculate mean of empty array
+#calc.mean(())
+
+---
+// Error: 13-23 cannot compare string and integer
+#calc.median((1, "a"))
+
+---
+// Error: 24-27 quantile must be between 0 and 1
+#calc.quantile((1, 2), 1.5)
+
+---
+// Error: 15-35 not enough values to calculate variance
+#calc.variance((1,), sample: true)
+
 ---
 // Test the `range` function.
 #test(range(4), (0, 1, 2, 3))
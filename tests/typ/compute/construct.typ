@@ -14,6 +14,39 @@
 #test(rgb("#133337").negate(), rgb(236, 204, 200))
 #test(white.lighten(100%), white)
 
+---
+// Test HSL and Oklab colors.
+#test(hsl(0deg, 100%, 50%), rgb(255, 0, 0))
+#test(hsl(120deg, 100%, 25%), rgb(0, 128, 0))
+#test(hsl(-120deg, 100%, 50%, 50%), rgb(0, 0, 255, 50%))
+#test(hsl(240deg, 100%, 50%).to-hsl(), (240deg, 100%, 50%))
+#test(oklab(100%, 0, 0), white)
+#test(oklab(0%, 0, 0), black)
+#test(oklab(70%, 0.1, -0.1), rgb(191, 129, 218))
+
+---
+// Test color mixing and conversion methods.
+#test(black.mix(white), rgb(99, 99, 99))
+#test(red.mix(blue), rgb(163, 112, 149))
+#test(red.mix(blue, 100%), blue)
+#test(luma(0).mix(luma(255)), luma(99))
+#test(cmyk(0%, 0%, 0%, 0%).mix(cmyk(100%, 0%, 0%, 0%)), cmyk(50%, 0%, 0%, 0%))
+#test(rgb(191, 64, 64).saturate(100%), rgb(255, 0, 0))
+#test(rgb(191, 64, 64).desaturate(100%), rgb(128, 128, 128))
+#test(rgb(255, 0, 0).rotate(120deg), rgb(0, 255, 0))
+#test(luma(50%).rotate(90deg), luma(50%))
+#test(red.to-rgba(), (255, 65, 54, 255))
+#test(rgb(255, 0, 0, 50%).to-hex(), "#ff000080")
+#test(rgb("#239dad").to-hex(), "#239dad")
+
+---
+// Error: 16-20 ratio must be between 0% and 100%
+#red.mix(blue, 150%)
+
+---
+// Error: 12-16 ratio must be between 0% and 100%
+#hsl(0deg, 120%, 50%)
+
 ---
 // Test gray color conversion.
 // Ref: true
@@ -71,7 +104,7 @@
 #test(str(10 / 3).len() > 10, true)
 
 ---
-// Error: 6-8 expected integer, float, label, or string, found content
+// Error: 6-8 expected integer, float, label, string, or bytes, found content
 #str([])
 
 ---
@@ -191,3 +224,135 @@
 ---
 // Error: 26-36 failed to format datetime in the requested format
 #datetime.today().display("[hour]")
+
+---
+// Test parsing of datetimes.
+#test(datetime.parse("2023-04-29"), datetime(year: 2023, month: 4, day: 29))
+#test(datetime.parse("14:26:50"), datetime(hour: 14, minute: 26, second: 50))
+#test(datetime.parse("29.04.2023", "[day].[month].[year]").month(), 4)
+#test(
+  datetime.parse("2023-04-29 14:26:50").display(),
+  "2023-04-29 14:26:50",
+)
+
+---
+// Error: 16-28 failed to parse "tomorrow" as a date or time
+#datetime.parse("tomorrow")
+
+---
+// Test datetime and duration arithmetic.
+#let d = datetime(year: 2023, month: 4, day: 29)
+#test((d + duration(days: 3)).display(), "2023-05-02")
+#test((d - duration(weeks: 1)).display(), "2023-04-22")
+#test(datetime(year: 2023, month: 5, day: 2) - d, duration(days: 3))
+#test(d < datetime(year: 2023, month: 5, day: 2), true)
+#let t = datetime(hour: 23, minute: 30, second: 0)
+#test((t + duration(hours: 1)).display(), "00:30:00")
+#test((t - datetime(hour: 22, minute: 0, second: 0)).minutes(), 90.0)
+#test(duration(hours: 1) * 2, duration(minutes: 120))
+#test(duration(days: 1) / duration(hours: 1), 24.0)
+#test(duration(minutes: 3) / 2, duration(seconds: 90))
+#test(-duration(days: 2) < duration(seconds: 0), true)
+#test(repr(duration(days: 8, seconds: 5)), "duration(weeks: 1, days: 1, seconds: 5)")
+
+---
+// Error: 3-40 cannot add a duration shorter than a day to a date
+#(datetime.today() + duration(hours: 1))
+
+---
+// Error: 3-61 cannot subtract time from date
+#(datetime.today() - datetime(hour: 1, minute: 0, second: 0))
+
+---
+// Error: 3-24 cannot divide by zero
+#(duration(days: 1) / 0)
+
+---
+// Test quantities and unit algebra.
+#let torque = quantity(3, "kN") * quantity(2, "m")
+#test(torque, quantity(6, "kN*m"))
+#test(torque.value(), 6.0)
+#test(torque.unit(), "kN*m")
+#test(torque.to("J"), quantity(6000, "J"))
+#test(quantity(1, "km") + quantity(500, "m"), quantity(1.5, "km"))
+#test(quantity(2, "h") - quantity(30, "min"), quantity(1.5, "h"))
+#test(quantity(10, "m") / quantity(2, "s"), quantity(5, "m/s"))
+#test(quantity(10, "m") / quantity(2, "m"), 5.0)
+#test(2 / quantity(4, "s"), quantity(0.5, "1/s"))
+#test(-quantity(2, "N") * 3, quantity(-6, "N"))
+#test(quantity(1, "h") > quantity(59, "min"), true)
+#test(repr(quantity(9.81, "m/s^2")), "quantity(9.81, \"m/s^2\")")
+#test(type(quantity(1, "m")), "quantity")
+
+---
+// Error: 16-23 unknown unit: xyz
+#quantity(1.5, "m*xyz")
+
+---
+// Error: 3-39 cannot convert from "s" to "km"
+#(quantity(1, "km") + quantity(1, "s"))
+
+---
+// Error: 22-27 cannot convert from "N" to "J*m"
+#quantity(1, "N").to("J*m")
+
+---
+// Error: 14-31 unit exponent is out of range
+#quantity(1, "1/m^-2147483648")
+
+---
+// Error: 14-30 unit exponent is out of range
+#quantity(1, "m^2147483647*m")
+
+---
+// Error: 3-49 unit exponent is out of range
+#(quantity(1, "m^2147483647") * quantity(1, "m"))
+
+---
+// Test the `bytes` function.
+#let data = bytes("Typst")
+#test(data.len(), 5)
+#test(data.at(0), 84)
+#test(data.at(-1), 116)
+#test(data.at(10, default: none), none)
+#test(data.slice(1, 3).to-array(), (121, 112))
+#test(data.slice(-2).to-array(), (115, 116))
+#test(bytes((84, 121, 112, 115, 116)), data)
+#test(str(data), "Typst")
+#test(repr(data), "bytes(5)")
+
+---
+// Error: 8-14 number must be between 0 and 255
+#bytes((256,))
+
+---
+// Error: 6-19 bytes are not valid utf-8
+#str(bytes((255,)))
+
+---
+// Error: 2-22 byte index out of bounds (index: 5, len: 5) and no default value was specified
+#bytes("Typst").at(5)
+
+---
+// Test hashing and encoding.
+#test(sha256("Hello"), "185f8db32271fe25f561a6fc938b2e264306ec304eda518007d1764826381969")
+#test(sha256(bytes(())), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+#test(md5("Hello"), "8b1a9953c4611296a827abf8c47804d7")
+#test(base64("Hello"), "SGVsbG8=")
+#test(base64(bytes((0, 255))), "AP8=")
+#test(str(base64.decode("SGVsbG8=")), "Hello")
+#test(hex(bytes((0, 15, 255))), "000fff")
+#test(hex.decode("000FfF").to-array(), (0, 15, 255))
+#test(str(hex.decode(hex("Typst"))), "Typst")
+
+---
+// Error: 15-21 invalid base64 string
+#base64.decode("!!")
+
+---
+// Error: 12-19 hex string must have an even number of digits
+#hex.decode("abc")
+
+---
+// Error: 12-18 invalid hex digit `z`
+#hex.decode("zz")
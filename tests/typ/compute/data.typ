@@ -14,6 +14,31 @@
 // Error: 18-28 file is not valid utf-8
 #let data = read("/bad.txt")
 
+---
+// Test reading files as bytes.
+#let data = read("/bad.txt", encoding: none)
+#test(type(data), "bytes")
+#test(read("/hello.txt", encoding: none), bytes("Hello, world!"))
+#test(str(read("/hello.txt", encoding: none).slice(0, 5)), "Hello")
+
+---
+// Error: 6-12 access to environment variable PATH is not allowed
+#env("PATH")
+
+---
+// Test running an external program.
+#test(exec("echo", ("hi",)), "hi\n")
+#test(exec("echo", ("a", "b c")).trim(), "a b c")
+#test(exec("echo"), "\n")
+
+---
+// Error: 7-11 running external programs like git is not allowed
+#exec("git", ("rev-parse", "HEAD"))
+
+---
+// Error: 8-27 network access is not allowed
+#fetch("https://typst.app")
+
 ---
 // Test reading CSV data.
 // Ref: true
@@ -30,6 +55,20 @@
 // Error: 6-16 failed to parse csv file: found 3 instead of 2 fields in line 3
 #csv("/bad.csv")
 
+---
+// Test CSV options.
+#let data = csv("/scores.csv", delimiter: ";", header: true, trim: true, infer: true)
+#test(data.len(), 3)
+#test(data.at(0), (name: "Alice", score: 12, ratio: 0.5))
+#test(data.at(1).ratio, 1000.0)
+#test(data.at(2), (name: "Eve", score: "", ratio: "n/a"))
+#test(csv("/scores.csv", delimiter: ";").at(1), ("Alice", " 12", " 0.5"))
+#test(csv("/monkeys.csv", infer: true).at(1), ("Capuchin", 12))
+
+---
+// Error: 6-16 failed to parse csv file: found 3 instead of 2 fields in line 3
+#csv("/bad.csv", header: true)
+
 ---
 // Test reading JSON data.
 #let data = json("/zoo.json")
@@ -41,6 +80,53 @@
 // Error: 7-18 failed to parse json file: syntax error in line 3
 #json("/bad.json")
 
+---
+// Integers that don't fit into 64 bits become floats.
+// Warning: 18-31 integer 18446744073709551615 is too large and was converted to a float
+#let data = json("/large.json")
+#test(type(data.id), "float")
+#test(data.name, "large")
+
+---
+// Test lazy loading.
+#let rows = csv.iter("/monkeys.csv", header: true, infer: true)
+#test(type(rows), "records")
+#test(rows.collect(), csv("/monkeys.csv", header: true, infer: true))
+#test(rows.skip(1).take(1).collect(), ((species: "Howler", count: 7),))
+#test(rows.filter(row => row.count > 10).take(5).collect().len(), 2)
+#let total = 0
+#for row in rows { total += row.count }
+#test(total, 42)
+#let animals = json.iter("/zoo.json")
+#test(animals.collect(), json("/zoo.json"))
+#test(animals.skip(1).collect().map(v => v.name), ("Fluffy", "Sleepy"))
+
+---
+// Error: 13-33 failed to parse csv file: found 3 instead of 2 fields in line 3
+#for row in csv.iter("/bad.csv") {}
+
+---
+// Error: 2-34 failed to parse json file: expected an array
+#json.iter("/bad.json").collect()
+
+---
+// Test reading through file handles.
+#let file = open("/hello.txt")
+#test(repr(file), "file(\"/hello.txt\")")
+#test(file.read(), "Hello, world!")
+#test(file.read(encoding: none), bytes("Hello, world!"))
+#test(file.lines(), ("Hello, world!",))
+#test(open("/zoo.json").json().at(0).name, "Debby")
+#test(file.close(), none)
+
+---
+// Error: 2-26 file is not valid utf-8
+#open("/bad.txt").lines()
+
+---
+// Error: 2-28 type file has no method `flush`
+#open("/hello.txt").flush()
+
 ---
 // Test reading TOML data.
 #let data = toml("/toml-types.toml")
@@ -111,3 +197,78 @@
 ---
 // Error: 6-16 failed to parse xml file: found closing tag 'data' instead of 'hello' in line 3
 #xml("/bad.xml")
+
+---
+// Test reading an Excel workbook.
+#test(xlsx("/sales.xlsx"), (("Region", "Total"), ("North", 100), ("South", 80)))
+
+#let sales = xlsx("/sales.xlsx", sheet: "Q2", range: "A1:C4", header: true)
+#test(sales.len(), 3)
+#test(sales.map(row => row.Region), ("North", "South", "West"))
+#test(sales.map(row => row.Total), (120, 90.5, 90))
+#test(sales.at(0).Updated, datetime(year: 2023, month: 4, day: 30))
+#test(sales.at(1).Updated, none)
+#test(xlsx("/sales.xlsx", sheet: "Q2", range: "b5"), ((30,),))
+
+---
+// Error: 29-33 worksheet `Q3` does not exist
+#xlsx("/sales.xlsx", sheet: "Q3")
+
+---
+// Error: 29-36 invalid cell range `C3:A1`
+#xlsx("/sales.xlsx", range: "C3:A1")
+
+---
+// Error: 7-18 file not found (searched at typ/compute/nope.xlsx)
+#xlsx("nope.xlsx")
+
+---
+// Test querying an SQLite database.
+#let rows = sql("/sales.sqlite", "SELECT name, total FROM sales WHERE total < 100")
+#test(rows, ((name: "South", total: 90), (name: "West", total: 90)))
+#let row = sql("/sales.sqlite", "SELECT * FROM sales LIMIT 1").first()
+#test(row, (name: "North", total: 120, share: 0.4, note: none))
+
+---
+// Error: 23-42 only read-only queries are allowed
+#sql("/sales.sqlite", "DELETE FROM sales")
+
+---
+// Error: 23-43 failed to query sqlite database: no such table: nope
+#sql("/sales.sqlite", "SELECT * FROM nope")
+
+---
+// Error: 23-60 failed to query sqlite database: too many attached databases - max 0
+#sql("/sales.sqlite", "ATTACH DATABASE 'other.db' AS other")
+
+---
+// Test reading ZIP archives.
+#let assets = zip("/assets.zip")
+#test(type(assets), "archive")
+#test(assets.entries(), ("hello.txt", "images/logo.svg"))
+#test(assets.read("hello.txt"), "Hello from the archive!\n")
+#test(type(assets.read("images/logo.svg", encoding: none)), "bytes")
+
+---
+// Error: 26-36 archive does not contain `nope.txt`
+#zip("/assets.zip").read("nope.txt")
+
+---
+// Error: 6-18 failed to read zip archive: invalid Zip archive: Could not find central directory end
+#zip("/hello.txt")
+
+---
+// Test converting Markdown into content.
+#let children = markdown("/notes.md").children
+#test(children.first().func(), heading)
+#test(children.first().body.text, "Notes")
+#test(children.filter(c => c.func() == list).first().children.len(), 2)
+#test(children.filter(c => c.func() == enum).first().children.len(), 2)
+#test(children.filter(c => c.func() == table).first().children.len(), 4)
+#let code = children.filter(c => c.func() == raw).first()
+#test(code.text, "let x = 1;")
+#test(code.lang, "rust")
+
+---
+// Error: 11-20 file not found (searched at typ/compute/nope.md)
+#markdown("nope.md")
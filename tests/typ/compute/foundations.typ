@@ -120,3 +120,63 @@ _No relative giraffe!_
 ---
 // Error: 7-12 expected semicolon or line break
 #eval("1 2")
+
+---
+// Test the system inputs, which are empty in the test runner.
+#test(type(sys.inputs), "dictionary")
+#test(sys.inputs.len(), 0)
+
+---
+// Test the `strfmt` function.
+#let date = datetime(year: 2023, month: 7, day: 14)
+#test(strfmt("{:.2f} EUR on {date}", 3.14159, date: date), "3.14 EUR on 2023-07-14")
+#test(strfmt("{1}{0}{}", "a", "b"), "baa")
+#test(strfmt("{a}-{b}", a: 1, b: none), "1-none")
+#test(strfmt("{{{}}}", "x"), "{x}")
+#test(strfmt("[{:>5}] [{:<5}] [{:^5}]", 1, 2, 3), "[    1] [2    ] [  3  ]")
+#test(strfmt("{:*^9}", "hi"), "***hi****")
+#test(strfmt("{:+08.3}", 2.5), "+002.500")
+#test(strfmt("{:05}", -42), "-0042")
+#test(strfmt("{0:x} {0:b} {0:o} {1:X}", 255, -255), "ff 11111111 377 -FF")
+#test(strfmt("{:e}", 1234.5), "1.2345e3")
+#test(strfmt("{:.2}", 3), "3.00")
+#test(strfmt("{:.2}", decimal("1.005")), "1.01")
+#test(strfmt("{:.2}", decimal("2")), "2.00")
+#test(strfmt("{:.3}", "abcdef"), "abc")
+#test(strfmt("{:?}", "hi"), "\"hi\"")
+
+---
+// Error: 9-12 unclosed placeholder in format string
+#strfmt("{")
+
+---
+// Error: 9-15 invalid format specifier `q`
+#strfmt("{:q}", 1)
+
+---
+// Error: 9-15 invalid format specifier `.`
+#strfmt("{:.}", 1)
+
+---
+// Error: 9-25 format width must be at most 65535
+#strfmt("{:99999999999}", 1)
+
+---
+// Error: 9-26 format precision must be at most 65535
+#strfmt("{:.99999999999}", 1)
+
+---
+// Error: 9-16 missing value for placeholder `{}`
+#strfmt("{} {}", 1)
+
+---
+// Error: 18-19 unused value in format string
+#strfmt("{}", 1, 2)
+
+---
+// Error: 17-20 cannot format float as integer
+#strfmt("{:x}", 1.5)
+
+---
+// Error: 15-18 cannot format content
+#strfmt("{}", [a])
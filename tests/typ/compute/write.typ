@@ -0,0 +1,131 @@
+// Test writing files.
+// Ref: false
+
+---
+// Chunks are ordered by their key. Data without a key is placed at zero.
+#write("order.txt", "c", at: 2)
+#write("order.txt", "a")
+#write("order.txt", "b", at: 1)
+#write("order.txt", "0", at: -1)
+// Written: /order.txt "0abc"
+
+---
+// Chunks with the same key keep the order of the calls, even if they are the
+// same.
+#for i in range(3) {
+  write("calls.txt", str(i), at: 1)
+  write("calls.txt", "-", at: 1)
+}
+#write("calls.txt", ">", at: 0)
+// Written: /calls.txt ">0-1-2-"
+
+---
+// Bytes are written as they are.
+#write("data.bin", bytes((72, 105, 10)))
+// Written: /data.bin "Hi\n"
+
+---
+// Writes through a file handle are ordered like other writes.
+#let handle = open("handle.txt")
+#handle.write("second\n", at: 2)
+#write("handle.txt", "first\n", at: 1)
+// Written: /handle.txt "first\nsecond\n"
+
+---
+// Nothing is written if compilation fails.
+#write("failed.txt", "data")
+// Error: 22-23 expected string or bytes, found integer
+#write("failed.txt", 1)
+
+---
+// An existing file is overwritten by default.
+#write("log.txt", "Replaced\n")
+// Written: /log.txt "Replaced\n"
+
+---
+// Data can be appended to an existing file.
+#write("log.txt", "Compiled again\n", mode: "append")
+// Written: /log.txt "Compiled\nCompiled again\n"
+
+---
+// Appending to a file that doesn't exist creates it.
+#write("created.txt", "Created\n", mode: "append")
+// Written: /created.txt "Created\n"
+
+---
+// Error: 8-17 file already exists (at /log.txt)
+#write("log.txt", "Compiled\n", mode: "error-if-exists")
+
+---
+#write("created.txt", "Created\n", mode: "error-if-exists")
+// Written: /created.txt "Created\n"
+
+---
+// All writes to a file must use the same mode.
+#write("modes.txt", "a", mode: "append")
+// Error: 8-19 file is written to with conflicting write modes
+#write("modes.txt", "b")
+
+---
+// Test writing JSON.
+#write_json("data.json", (
+  a: 1,
+  b: (true, none, "x"),
+  c: 2.5,
+  d: datetime(year: 2023, month: 6, day: 1),
+))
+#write_json("pretty.json", (a: (1, 2)), pretty: true)
+// Written: /data.json "{\"a\":1,\"b\":[true,null,\"x\"],\"c\":2.5,\"d\":\"2023-06-01\"}"
+// Written: /pretty.json "{\n  \"a\": [\n    1,\n    2\n  ]\n}"
+
+---
+// Writing structured data again replaces the earlier data.
+#write_json("replaced.json", (a: 1))
+#write_json("replaced.json", (a: 2))
+// Written: /replaced.json "{\"a\":2}"
+
+---
+// Structured data must be the file's only content.
+#write_json("mixed.json", (a: 1))
+// Error: 8-20 file can only be written to once
+#write("mixed.json", "more")
+
+---
+// Error: 23-37 cannot write content
+#write_json("x.json", (a: [Content]))
+
+---
+// Test writing YAML.
+#write_yaml("data.yaml", (name: "Typst", tags: ("a", "b")))
+// Written: /data.yaml "---\nname: Typst\ntags:\n  - a\n  - b"
+
+---
+// Test writing TOML.
+#write_toml("data.toml", (
+  date: datetime(year: 2023, month: 6, day: 1),
+  tags: ("a", "b"),
+  title: "Typst",
+))
+// Written: /data.toml "date = 2023-06-01\ntags = [\"a\", \"b\"]\ntitle = \"Typst\"\n"
+
+---
+// Error: 26-35 cannot write none
+#write_toml("none.toml", (a: none))
+
+---
+// Test writing CSV with a header from dictionary rows.
+#write_csv("dicts.csv", (
+  (name: "A", value: 1),
+  (value: 2.5, name: "B, C"),
+  (name: none),
+))
+// Written: /dicts.csv "name,value\nA,1\n\"B, C\",2.5\n,\n"
+
+---
+// Test writing CSV from array rows with a custom delimiter.
+#write_csv("arrays.csv", (("a", 1), ("b", true, 3)), delimiter: ";")
+// Written: /arrays.csv "a;1\nb;true;3\n"
+
+---
+// Error: 23-29 expected array or dictionary as row, found integer
+#write_csv("bad.csv", (1, 2))
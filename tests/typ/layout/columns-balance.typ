@@ -0,0 +1,39 @@
+// Test column balancing.
+
+---
+// Ref: false
+// Balanced columns share the content about equally.
+#let body = [#for i in range(12) [Line #i \ ]]
+#style(styles => {
+  let plain = measure(block(width: 120pt, columns(2, body)), styles)
+  let balanced = measure(block(width: 120pt, columns(2, balance: true, body)), styles)
+  test(balanced.height < plain.height * 0.6, true)
+  test(balanced.height >= plain.height / 2, true)
+})
+
+---
+// Ref: false
+// A single column can't be balanced.
+#let body = [#for i in range(4) [Line #i \ ]]
+#style(styles => {
+  let plain = measure(block(width: 120pt, columns(1, body)), styles)
+  let balanced = measure(block(width: 120pt, columns(1, balance: true, body)), styles)
+  test(balanced.height, plain.height)
+})
+
+---
+// Ref: false
+// The second column starts halfway through the lines.
+#set page(width: 120pt, height: 200pt, margin: 0pt)
+#let body(x) = for i in range(12) {
+  if i == 6 {
+    locate(loc => {
+      test(loc.position().x, x)
+      test(loc.position().y == 0pt, x != 0pt)
+    })
+  }
+  [Line #i \ ]
+}
+
+#columns(2, gutter: 0pt, balance: true, body(60pt))
+#columns(2, gutter: 0pt, body(0pt))
@@ -0,0 +1,38 @@
+// Test the direction of columns.
+
+---
+// Ref: false
+// Columns follow the text direction by default. The mirrored rules are checked
+// in the layout tests.
+#set page(width: 150pt, height: 60pt)
+#set text(lang: "he")
+#columns((1fr, 2fr), gutter: (4pt,), rule: 0.5pt)[
+  #for i in range(12) [שורה #i \ ]
+]
+
+---
+// Ref: false
+// Right-to-left text in left-to-right columns.
+#set page(width: 150pt, height: auto)
+#columns(2, dir: ltr)[
+  #set text(lang: "ar")
+  مرحبا بالعالم
+  #colbreak()
+  #text(dir: ltr)[Hello world]
+]
+
+---
+// Ref: false
+// Mixed directions in one document.
+#set page(width: 120pt, height: auto, margin: 0pt)
+#let probe(x) = locate(loc => test(loc.position().x, x))
+#columns(2, gutter: 10pt, dir: rtl, rule: 0.5pt)[
+  #probe(65pt)First #colbreak() #probe(0pt)Second
+]
+#columns(2, gutter: 10pt, dir: ltr, rule: 0.5pt)[
+  #probe(0pt)First #colbreak() #probe(65pt)Second
+]
+
+---
+// Error: 2-25 column direction must be horizontal
+#columns(2, dir: ttb)[A]
@@ -0,0 +1,17 @@
+// Test rules between columns.
+
+---
+// Ref: false
+// The rules' positions are checked in the layout tests.
+#set page(width: 150pt, height: 80pt)
+#columns(3, rule: 0.5pt + gray)[
+  #for i in range(20) [Line #i \ ]
+]
+
+---
+// Ref: false
+// Narrow gutters are widened to fit the rule.
+#set page(width: 120pt, height: auto, margin: 0pt)
+#columns(2, gutter: 0pt, rule: 2pt, rule-gutter: 4pt)[
+  A #colbreak() #locate(loc => test(loc.position().x, 65pt))B
+]
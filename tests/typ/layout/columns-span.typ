@@ -0,0 +1,77 @@
+// Test blocks that span all columns.
+
+---
+// Ref: false
+// The columns before the span are balanced and resume below it. With an
+// auto height, the columns after it are filled one after another.
+#set page(width: 120pt, height: auto, margin: 0pt)
+#let probe(x, below) = locate(loc => {
+  let pos = loc.position()
+  let span = query(<span>, loc).first().location().position()
+  test(pos.x, x)
+  test(pos.y > span.y, below)
+  test(pos.y == 0pt, not below)
+})
+
+#columns(2, gutter: 10pt)[
+  #for i in range(6) [#if i == 3 { probe(65pt, false) }Line #i \ ]
+  #colspan[#rect(width: 100%, height: 10pt) <span>]
+  #for i in range(6) [#probe(0pt, true)Line #i \ ]
+]
+
+---
+// Ref: false
+// A span at the start and one at the end.
+#set page(width: 120pt, height: 80pt, margin: 0pt)
+#columns(3)[
+  #colspan[= Heading]
+  #set text(8pt)
+  #for i in range(30) [Line #i \ ]
+  #colspan[#locate(loc => {
+    test(loc.position().x, 0pt)
+    test(loc.page(), counter(page).final(loc).first())
+    test(loc.page() > 1, true)
+  })The end.]
+]
+
+#locate(loc => {
+  let heading = query(heading, loc).first().location()
+  test(heading.position(), (page: 1, x: 0pt, y: 0pt))
+})
+
+---
+// Ref: false
+// Outside of columns, the span is a plain block.
+#style(styles => {
+  test(measure(colspan[Hello], styles), measure(block[Hello], styles))
+})
+#colspan[Hello]
+
+---
+// Ref: false
+// The columns before the span are balanced.
+#let body = columns(2)[
+  #for i in range(4) [Line #i \ ]
+  #colspan[Span]
+]
+#style(styles => {
+  let size = measure(block(width: 120pt, body), styles)
+  let plain = measure(block(width: 120pt, columns(2, balance: true)[
+    #for i in range(4) [Line #i \ ]
+  ]), styles)
+  test(size.height > plain.height, true)
+})
+
+---
+// Ref: false
+// A span that doesn't fit below the columns moves to the next page.
+#set page(width: 120pt, height: 80pt)
+#columns(2)[
+  #for i in range(8) [Line #i \ ]
+  #colspan[#rect(width: 100%, height: 40pt) <span>]
+]
+
+#locate(loc => {
+  let span = query(<span>, loc).first().location()
+  test(span.page(), 2)
+})
@@ -0,0 +1,69 @@
+// Test columns with individual widths and gutters.
+// Ref: false
+
+---
+#set page(width: 120pt, height: 80pt, margin: 0pt)
+#columns((1fr, 2fr), gutter: 6pt)[
+  #locate(loc => test(loc.position().x, 0pt))Notes
+  #colbreak()
+  #locate(loc => test(loc.position().x, 44pt))The main text in a wider column.
+]
+
+---
+// Blocks flow into the next column even if it is wider. A block that doesn't
+// fit below the others in its column moves to the next one as a whole.
+#set page(width: 120pt, height: 50pt, margin: 0pt)
+#let probe(page, x) = locate(loc => {
+  test(loc.page(), page)
+  test(loc.position().x, x)
+  test(loc.position().y, 0pt)
+})
+
+#columns((1fr, 2fr), gutter: 6pt)[
+  #block(height: 30pt, probe(1, 0pt))
+  #block(height: 30pt, probe(1, 44pt))
+  #block(height: 30pt, probe(2, 0pt))
+]
+
+---
+// Fixed and fractional columns with individual gutters across pages.
+#set page(width: 150pt, height: 60pt, margin: 0pt)
+#let probe(page, x) = locate(loc => {
+  test(loc.page(), page)
+  test(loc.position().x, x)
+})
+
+#columns((30pt, 1fr, auto), gutter: (4pt, 8pt))[
+  #for i in range(5) [
+    #probe(i + 1, 0pt)Left #i
+    #colbreak()
+    #probe(i + 1, 34pt)Middle #i
+    #colbreak()
+    #probe(i + 1, 96pt)Right #i
+    #colbreak()
+  ]
+]
+
+---
+// A number of columns resets individual widths.
+#set page(width: 120pt, height: 80pt, margin: 0pt)
+#set columns((1fr, 2fr), gutter: 6pt)
+#columns(2)[
+  First
+  #colbreak()
+  #locate(loc => test(loc.position().x, 63pt))Second
+]
+
+---
+// Equal fixed columns flow and can be balanced.
+#let body = [#for i in range(8) [Line #i \ ]]
+#style(styles => {
+  let plain = measure(block(width: 120pt, body), styles)
+  let balanced = measure(block(width: 120pt,
+    columns((50pt, 50pt), gutter: 20pt, balance: true, body)), styles)
+  test(balanced.height < plain.height, true)
+})
+
+---
+// Error: 10-12 must have at least one column
+#columns(())[]
@@ -0,0 +1,51 @@
+// Test keeping blocks and lines together across breaks.
+
+---
+// Ref: false
+// A block keeps with the paragraph after it.
+#set page(width: 100pt, height: 60pt, margin: 0pt)
+#v(40pt)
+#block(keep-with-next: true)[#locate(loc => test(loc.page(), 2))Title]
+#lorem(10)
+
+---
+// Ref: false
+// A block keeps with a shape after it.
+#set page(width: 100pt, height: 60pt, margin: 0pt)
+#v(30pt)
+#block(keep-with-next: true)[#locate(loc => test(loc.page(), 2))Title]
+#rect(height: 20pt)
+
+---
+// Ref: false
+// Single lines may stay behind.
+#set page(width: 100pt, height: 60pt, margin: 0pt)
+#set par(orphans: 1, widows: 1)
+#v(45pt)
+#locate(loc => test(loc.page(), 1))A \
+B \
+#locate(loc => test(loc.page(), 2))C
+
+---
+// Ref: false
+// More lines stay together.
+#set page(width: 100pt, height: 60pt, margin: 0pt)
+#set par(widows: 3)
+#v(30pt)
+#locate(loc => test(loc.page(), 1))A \
+B \
+#locate(loc => test(loc.page(), 2))C \
+D \
+E
+
+---
+// Ref: false
+// A kept block moves to the next column with the block after it.
+#set page(width: 120pt, height: 60pt, margin: 0pt)
+#set block(spacing: 0pt)
+#let probe(x) = locate(loc => test(loc.position().x, x))
+#columns(2, gutter: 10pt)[
+  #block(height: 30pt, probe(0pt))
+  #block(height: 10pt, keep-with-next: true, probe(65pt))
+  #block(height: 30pt, probe(65pt))
+]
@@ -0,0 +1,27 @@
+// Test page bleed and printer's marks.
+
+---
+// Ref: false
+#set page(width: 80pt, height: 60pt, bleed: 3mm, fill: aqua)
+#set page(background: rect(width: 100%, height: 100%, fill: teal))
+Printed edge to edge.
+
+---
+// Ref: false
+#set page(
+  width: 80pt,
+  height: 60pt,
+  bleed: 3mm,
+  marks: (crop: true, registration: true),
+)
+Ready for print.
+
+---
+// Ref: false
+#set page(width: 80pt, height: 60pt, marks: (crop: true))
+#set page(marks: none)
+No marks.
+
+---
+// Error: 18-41 unexpected key "cut", valid keys are "crop" and "registration"
+#set page(marks: (crop: true, cut: true))
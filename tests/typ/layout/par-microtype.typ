@@ -0,0 +1,23 @@
+// Test microtypography.
+
+---
+// Ref: false
+#set page(width: 180pt)
+#set par(justify: true, microtype: true)
+"Some texts are frustratingly challenging to break in a visually pleasing way."
+This very aesthetic example is one of them, (at least) in some fonts.
+
+---
+// Test that ragged lines may also hang into the margins.
+// Ref: false
+#set page(width: 100pt)
+#set par(microtype: true)
+#lorem(20)
+
+---
+// Test right-to-left text.
+// Ref: false
+#set page(width: 120pt)
+#set text(lang: "ar", font: ("Noto Sans Arabic", "Linux Libertine"))
+#set par(justify: true, microtype: true)
+"مرحبا بالعالم"، هذا نص عربي طويل بما يكفي لكسر السطر.
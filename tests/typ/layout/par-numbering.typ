@@ -0,0 +1,40 @@
+// Test line numbering of paragraphs.
+
+---
+// Ref: false
+#set page(width: 120pt, margin: (left: 30pt))
+#set par(numbering: "1")
+#show par.line: set text(gray)
+#lorem(20)
+
+#lorem(10)
+
+---
+// Test that line numbers continue across paragraphs.
+// Ref: false
+#set page(width: 120pt)
+#set par(numbering: "1")
+First paragraph.
+
+Second paragraph.
+
+Third paragraph.
+#locate(loc => test(counter(par.line).final(loc), (3,)))
+
+---
+// Test that line numbers can be restarted.
+// Ref: false
+#set page(width: 120pt)
+#set par(numbering: "1")
+First paragraph.
+
+#counter(par.line).update(0)
+Second paragraph.
+#locate(loc => test(counter(par.line).final(loc), (1,)))
+
+---
+// Test right-to-left line numbers.
+// Ref: false
+#set text(dir: rtl, lang: "ar")
+#set par(numbering: "(i)")
+#lorem(10)
@@ -0,0 +1,51 @@
+// Test floating placement.
+
+---
+// Ref: false
+// A float that doesn't fit moves to the next page.
+#set page(width: 100pt, height: 100pt, margin: 10pt)
+#v(50pt)
+#place(top, float: true, block(height: 50pt)[
+  #locate(loc => test(loc.page(), 2))
+])
+#locate(loc => test(loc.page(), 1))Text
+
+---
+// Ref: false
+// Floats go above the text of the page.
+#set page(width: 100pt, height: 100pt, margin: 0pt)
+#locate(loc => test(loc.position().y > 20pt, true))Text
+#place(top, float: true, rect(height: 20pt))
+
+---
+// Ref: false
+// Figures float with their caption.
+#set page(width: 120pt, height: 150pt)
+#figure(
+  placement: bottom,
+  caption: [A box],
+  rect(height: 30pt),
+) <box>
+#locate(loc => {
+  let figure = query(<box>, loc).first().location()
+  test(figure.position().y > loc.position().y, true)
+})@box shows a box.
+
+---
+// Ref: false
+// Floats in columns.
+#set page(width: 150pt, height: 100pt, margin: 0pt)
+#columns(2)[
+  #locate(loc => test(loc.position().y > 10pt, true))#lorem(10)
+  #place(top, float: true)[#rect(width: 100%, height: 10pt) <float>]
+  #lorem(20)
+]
+
+#locate(loc => {
+  let float = query(<float>, loc).first().location()
+  test(float.position(), (page: 1, x: 0pt, y: 0pt))
+})
+
+---
+// Error: 2-48 floating placement must be `top` or `bottom`
+#place(horizon, float: true, rect(height: 1pt))
@@ -47,3 +47,33 @@ nor #xetex!
 #box(scale(r, x: 50%, y: 200%, origin: left + top))
 #box(scale(r, x: 50%, origin: center))
 #box(scale(r, x: 50%, y: 200%, origin: right + bottom))
+
+---
+// Test skewing.
+// Ref: false
+#set page(width: 120pt)
+#skew(ax: -12deg)[Fake italics]
+#box(skew(ax: 30deg, origin: bottom + left, rect(width: 20pt, height: 20pt)))
+#box(skew(ay: 20deg, rect(width: 20pt, height: 10pt, fill: forest)))
+
+---
+// Test that reflowing transforms affect the layout.
+// Ref: false
+#let r = rect(width: 20pt, height: 10pt)
+#let within(length, target) = length > target - 0.01pt and length < target + 0.01pt
+#style(styles => {
+  let rotated = measure(rotate(90deg, reflow: true, r), styles)
+  test(within(rotated.width, 10pt), true)
+  test(within(rotated.height, 20pt), true)
+
+  let scaled = measure(scale(x: 50%, y: -200%, reflow: true, r), styles)
+  test(within(scaled.width, 10pt), true)
+  test(within(scaled.height, 20pt), true)
+
+  let skewed = measure(skew(ax: 45deg, reflow: true, r), styles)
+  test(within(skewed.width, 30pt), true)
+  test(within(skewed.height, 10pt), true)
+
+  let fixed = measure(rotate(90deg, r), styles)
+  test(fixed, (width: 20pt, height: 10pt))
+})
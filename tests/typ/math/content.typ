@@ -16,4 +16,5 @@ $ x := #table(columns: 2)[x][y]/mat(1, 2, 3)
 ---
 // Test font switch.
 #let here = text.with(font: "Noto Sans")
+// Warning: 8-9 unknown font family: noto sans (did you mean "PT Sans"?)
 $#here[f] := #here[Hi there]$.
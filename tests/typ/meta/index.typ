@@ -0,0 +1,26 @@
+// Test the back-of-book index.
+
+---
+// Ref: false
+#set page(height: 120pt)
+#set page(numbering: "i")
+
+Apples#index("Apple") and pears#index("pear").
+#pagebreak()
+More apples#index("Apple") and
+colors#index("Color", "Red")#index("Color", "Green").
+#pagebreak()
+Apples#index("Apple") again.
+#pagebreak()
+#index("Banana")#index("Color", "Red")
+
+#make-index()
+
+---
+// Ref: false
+#make-index(title: [Terms], letters: false)
+#index("zebra") #index("Aardvark") #index("")
+
+---
+// Error: 8-10 expected string, found integer
+#index(12)
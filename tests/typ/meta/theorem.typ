@@ -0,0 +1,75 @@
+// Test theorem environments.
+
+---
+// Ref: false
+#let check(label, number) = locate(loc => {
+  let it = query(label, loc).first()
+  test(it.counter.at(it.location()), number)
+})
+
+#set heading(numbering: "1.")
+#set theorem(level: 1)
+
+= Groups
+#definition[A _group_ is a set with an associative operation.] <def-group>
+
+#theorem(title: [Uniqueness])[
+  The identity of a group is unique.
+] <thm-unique>
+
+#proof[
+  If $e$ and $e'$ are identities, then $e = e e' = e'$.
+] <proof>
+
+#lemma[Inverses are unique.] <lemma>
+
+= Rings
+#theorem[Every ring is an abelian group under addition.] <thm-ring>
+
+By @def-group and @thm-unique, but also @thm-ring.
+
+#check(<def-group>, (1, 1))
+#check(<thm-unique>, (1, 1))
+#check(<lemma>, (1, 1))
+#check(<thm-ring>, (2, 1))
+#locate(loc => {
+  test(query(<def-group>, loc).first().supplement.text, "Definition")
+  test(query(<proof>, loc).first().numbering, none)
+})
+
+---
+// Ref: false
+// Shared counter and custom numbering.
+#let check(label, number) = locate(loc => {
+  let it = query(label, loc).first()
+  test(it.counter.at(it.location()), number)
+})
+
+#set theorem(group: "theorem", numbering: "I")
+#theorem[First.] <first>
+#lemma[Second.] <second>
+#corollary(supplement: [Folgerung])[Third.] <third>
+#theorem(kind: "conjecture", group: auto)[Counted on its own.] <conjecture>
+See @second.
+
+#check(<first>, (1,))
+#check(<second>, (2,))
+#check(<third>, (3,))
+#check(<conjecture>, (1,))
+#locate(loc => {
+  test(query(<third>, loc).first().supplement.text, "Folgerung")
+  test(query(<conjecture>, loc).first().supplement.text, "Conjecture")
+})
+
+---
+// Ref: false
+// Localized supplements and custom styling.
+#set text(lang: "de")
+#show theorem.where(kind: "proof"): it => block(it.body)
+#theorem[Ein Satz.] <satz>
+#proof[Trivial.]
+#locate(loc => test(query(<satz>, loc).first().supplement.text, "Satz"))
+
+---
+// Error: 10-12 expected content, found integer
+#theorem(12)
@@ -6,6 +6,7 @@
 
 #let try(top, bottom) = rect(inset: 0pt, fill: conifer)[
   #set text(font: "IBM Plex Mono", top-edge: top, bottom-edge: bottom)
+  // Warning: 3-7 unknown font family: ibm plex mono (did you mean "IBM Plex Sans"?)
   From #top to #bottom
 ]
 
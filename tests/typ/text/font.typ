@@ -58,3 +58,16 @@ Emoji: 🐪, 🌋, 🏞
 ---
 // Error: 11-31 unexpected argument: something
 #set text(something: "invalid")
+
+---
+// Unknown font families are reported once per file.
+// Ref: false
+// Warning: 24-29 unknown font family: robotto (did you mean "Roboto"?)
+#text(font: "Robotto")[Hello]
+#text(font: "Robotto")[World]
+
+---
+// Families without similarly named ones come without a suggestion.
+// Ref: false
+// Warning: 38-43 unknown font family: helvetica
+#text(font: ("Helvetica", "Roboto"))[Hello]
@@ -0,0 +1,17 @@
+// Test custom hyphenation patterns.
+
+---
+// Ref: false
+#set page(width: 60pt)
+#set text(hyphenate: true, hyphenation-patterns: "/hyph-liang.tex")
+Hyphenation of a table.
+
+---
+// Restore the built-in patterns.
+// Ref: false
+#set text(hyphenation-patterns: "/hyph-liang.tex")
+#set text(hyphenation-patterns: none)
+
+---
+// Error: 33-47 file not found (searched at /missing.tex)
+#set text(hyphenation-patterns: "/missing.tex")
@@ -0,0 +1,12 @@
+// Test typesetting of quantities.
+
+---
+// Ref: false
+#quantity(3.5, "kN*m") \
+#quantity(12345.678, "uOhm") \
+#quantity(6.022e23, "1/mol") \
+#quantity(90, "deg") \
+$F = #quantity(9.81, "kg*m/s^2")$
+
+#set text(lang: "de")
+#quantity(0.25, "L")
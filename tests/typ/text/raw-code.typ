@@ -69,3 +69,28 @@ end
       </body>
     </html>
     ```
+
+---
+// Test line numbering of raw blocks.
+// Ref: false
+#set raw(numbering: "1")
+#set page(width: 150pt)
+
+```rust
+/// A carefully designed state machine.
+#[derive(Debug)]
+enum State<'a> { A(u8), B(&'a str) }
+```
+
+```typ
+/* A comment that
+   spans lines */
+#let x = 1
+```
+
+```
+Plain text
+that wraps at the end of this line
+```
+
+Inline `raw` is not numbered.
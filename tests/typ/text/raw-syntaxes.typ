@@ -0,0 +1,44 @@
+// Test custom syntax definitions and themes for raw text.
+
+---
+// Ref: false
+#set raw(syntaxes: "/SExpressions.sublime-syntax")
+
+```sexp
+(defun factorial (x)
+  (if (zerop x)
+    ; with a comment
+    1
+    (* x (factorial (- x 1)))))
+```
+
+---
+// Ref: false
+#set raw(theme: "/halcyon.tmTheme")
+#show raw: it => block(fill: rgb("#1d2433"), inset: 8pt, it)
+
+```typ
+= Chapter 1
+#let hi = "Hello World"
+```
+
+```rust
+fn main() {
+    println!("Hello World!");
+}
+```
+
+---
+// Ref: false
+// A theme can be reset to the default one.
+#set raw(theme: "/halcyon.tmTheme")
+#set raw(theme: none)
+`#let x = 1`
+
+---
+// Error: 20-45 file not found (searched at /missing.sublime-syntax)
+#set raw(syntaxes: "/missing.sublime-syntax")
+
+---
+// Error: 17-35 file not found (searched at /missing.tmTheme)
+#set raw(theme: "/missing.tmTheme")
@@ -0,0 +1,26 @@
+// Test QR codes and barcodes.
+
+---
+// Ref: false
+// The modules are checked in the unit tests.
+#qrcode("https://typst.app", size: 2cm, ec: "H")
+#barcode("400638133393", format: "ean13")
+#barcode("4006381333931", format: "ean13", width: 4cm, height: 1cm)
+#barcode("123456", format: "code128")
+#barcode("Typst", fill: blue)
+
+---
+// Error: 2-43 ean-13 code has invalid check digit (expected 1)
+#barcode("4006381333932", format: "ean13")
+
+---
+// Error: 2-35 ean-13 code must consist of 12 or 13 digits
+#barcode("12345", format: "ean13")
+
+---
+// Error: 2-14 code 128 cannot encode `ä`
+#barcode("ä")
+
+---
+// Error: 34-37 expected "L", "M", "Q", or "H"
+#qrcode("https://typst.app", ec: "X")
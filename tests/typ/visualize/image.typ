@@ -60,3 +60,17 @@ A #box(image("/tiger.jpg", height: 1cm, width: 80%)) B
 ---
 // Error: 8-18 failed to parse svg: found closing tag 'g' instead of 'style' in line 4
 #image("/bad.svg")
+
+---
+// Ref: false
+// Test the natural size of images with a resolution.
+#style(styles => {
+  let coarse = measure(image("/rhino.png", dpi: 72), styles)
+  let fine = measure(image("/rhino.png", dpi: 144), styles)
+  test(coarse.width, 2 * fine.width)
+  test(coarse.height, 2 * fine.height)
+})
+
+---
+// Error: 27-30 dpi must be positive
+#image("/rhino.png", dpi: 0.0)
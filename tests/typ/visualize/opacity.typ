@@ -0,0 +1,33 @@
+// Test opacity of shapes, images and text.
+
+---
+// Ref: false
+#set page(width: 120pt)
+#stack(
+  dir: ltr,
+  spacing: -10pt,
+  rect(fill: red),
+  rect(fill: blue, opacity: 50%),
+  circle(fill: green, stroke: 4pt + black, opacity: 30%),
+)
+
+#rect(fill: rgb(0, 0, 255, 50%), opacity: 50%)[
+  #text(opacity: 40%)[Doubly faded]
+]
+
+---
+// Ref: false
+#line(length: 100%, stroke: 4pt + red, opacity: 25%)
+#polygon(fill: blue, (0pt, 0pt), (20pt, 20pt), (0pt, 20pt), opacity: 60%)
+#path(fill: green, stroke: black, closed: true, (0pt, 0pt), (10pt, 10pt), (0pt, 10pt), opacity: 0%)
+#image("/tiger.jpg", width: 50%, opacity: 40%)
+
+---
+// Out of range opacities are clamped.
+// Ref: false
+#square(fill: red, opacity: 150%)
+#square(fill: red, opacity: -20%)
+
+---
+// Error: 27-30 expected ratio, found float
+#rect(fill: red, opacity: 0.5)
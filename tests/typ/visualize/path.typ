@@ -50,3 +50,27 @@
 ---
 // Error: 7-31 point array must contain exactly two entries
 #path(((0%, 0%), (0%, 0%, 0%)))
+
+---
+// Ref: false
+#path(
+  fill: red,
+  stroke: 1pt,
+  path.move-to((0pt, 40pt)),
+  path.line-to((30pt, 0pt)),
+  path.cubic-to((50pt, -10pt), (70pt, 50pt), (90pt, 40pt)),
+  path.close(),
+  path.move-to((100%, 0pt)),
+  path.line-to((100%, 20pt)),
+)
+
+#test(repr(path.line-to((1pt, 50%))), "path.line-to((1pt, 50%))")
+#test(path.close(), path.close())
+
+---
+// Error: 2-44 path cannot mix vertices and commands
+#path((0pt, 0pt), path.line-to((5pt, 5pt)))
+
+---
+// Error: 2-44 path must start with a move-to command
+#path(path.line-to((5pt, 5pt)), (0pt, 0pt))
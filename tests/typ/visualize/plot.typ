@@ -0,0 +1,44 @@
+// Test plots.
+
+---
+// Ref: false
+#plot(
+  x-label: [Year],
+  y-label: [Visitors],
+  (label: [Zoo], data: ((2019, 12), (2020, 5), (2021, 9), (2022, 15))),
+  (label: [Museum], color: black, data: ((2019, 8), (2020, 3), (2021, 7))),
+)
+
+#plot.line((1, 4, 2, 5, 3), legend: none, y-ticks: (0, 2.5, 5))
+#plot.scatter(((0.1, 0.2), (0.15, 0.35), (0.3, 0.25)), x-ticks: 3)
+#plot.scatter((1, 10, 100, 1000), y-scale: "log", width: 6cm, height: 4cm)
+
+---
+// Ref: false
+#set plot(kind: "bar", legend: "top-left")
+#plot(
+  (label: [2022], data: (("Mon", 3), ("Tue", 5), ("Wed", -2))),
+  (label: [2023], data: (("Mon", 4), ("Wed", 1))),
+)
+
+#plot.bar((7, 7, 7))
+
+---
+// Error: 2-33 x values of a line plot must be numbers
+#plot.line((("a", 1), ("b", 2)))
+
+---
+// Error: 2-34 logarithmic scale requires positive values
+#plot(y-scale: "log", (1, -2, 3))
+
+---
+// Error: 7-15 expected number or pair of x and y values, found string
+#plot((1, "a"))
+
+---
+// Error: 7-30 unexpected key "size", valid keys are "data", "label", and "color"
+#plot((data: (1, 2), size: 3))
+
+---
+// Error: 2-30 plot is too small for its labels
+#plot(width: 10pt, (1, 2, 3))
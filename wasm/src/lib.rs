@@ -0,0 +1,150 @@
+//! WebAssembly bindings for Typst.
+//!
+//! The compiler runs entirely in memory: Sources, files and fonts are passed
+//! in from JavaScript and everything the document writes is handed back
+//! instead of touching a file system. The `sql`, `xlsx` and `zip` functions
+//! are not available, since the standard library is built without the
+//! features that provide them.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use js_sys::{Array, Date, Object, Reflect, Uint8Array};
+use typst::diag::SourceError;
+use typst::eval::{Datetime, Tracer};
+use typst::font::Font;
+use typst::util::Buffer;
+use typst::world::MemoryWorld;
+use wasm_bindgen::prelude::*;
+
+/// The path of the main source file.
+const MAIN: &str = "main.typ";
+
+/// Compile a document into a PDF file.
+///
+/// The `files` object maps paths relative to the root directory to further
+/// sources and files: Strings can be imported and read, `Uint8Array`s can only
+/// be read. The `options` object may contain `fonts`, an array of
+/// `Uint8Array`s with font data, and `today`, a `Date` that is reported as the
+/// current date.
+///
+/// Returns an object with the following fields:
+/// - `pdf`: The PDF file as an `Uint8Array` or `null` if compilation failed.
+/// - `writes`: An object mapping paths relative to the destination directory
+///   to the `Uint8Array`s written there.
+/// - `diagnostics`: An array of errors and warnings, each with a `severity`
+///   (`"error"` or `"warning"`), a `message` and, if it belongs to a source
+///   file, its `path` and the `start` and `end` byte offsets of the code it
+///   refers to.
+#[wasm_bindgen]
+pub fn compile(
+    source: String,
+    files: &Object,
+    options: &Object,
+) -> Result<Object, JsError> {
+    let mut sources = HashMap::new();
+    let mut buffers = HashMap::new();
+    for entry in Object::entries(files).iter() {
+        let entry = Array::from(&entry);
+        let path = entry
+            .get(0)
+            .as_string()
+            .ok_or_else(|| JsError::new("file paths must be strings"))?;
+
+        let value = entry.get(1);
+        if let Some(text) = value.as_string() {
+            sources.insert(PathBuf::from(path), text);
+        } else if let Some(data) = value.dyn_ref::<Uint8Array>() {
+            buffers.insert(PathBuf::from(path), Buffer::from(data.to_vec()));
+        } else {
+            return Err(JsError::new(&format!(
+                "file {path} must be a string or an Uint8Array"
+            )));
+        }
+    }
+    sources.insert(PathBuf::from(MAIN), source);
+
+    let mut fonts = vec![];
+    let data = get(options, "fonts")?;
+    if !data.is_undefined() {
+        for data in Array::from(&data).iter() {
+            let data = data
+                .dyn_into::<Uint8Array>()
+                .map_err(|_| JsError::new("fonts must be Uint8Arrays"))?;
+            fonts.extend(Font::iter(Buffer::from(data.to_vec())));
+        }
+    }
+
+    let mut world =
+        MemoryWorld::new(typst_library::build(), MAIN, sources, buffers, fonts)
+            .map_err(|err| JsError::new(&err))?;
+
+    if let Some(date) = get(options, "today")?.dyn_ref::<Date>() {
+        world.set_today(Datetime::from_ymd(
+            date.get_full_year() as i32,
+            date.get_month() as u8 + 1,
+            date.get_date() as u8,
+        ));
+    }
+
+    let result = Object::new();
+    let writes = Object::new();
+    let diagnostics = Array::new();
+    let mut tracer = Tracer::default();
+    match typst::compile(&world, &mut tracer) {
+        Ok(document) => {
+            let pdf = typst::export::pdf(&document);
+            set(&result, "pdf", Uint8Array::from(pdf.as_slice()))?;
+            for (path, data) in world.take_writes() {
+                let path = path.to_string_lossy();
+                set(&writes, &path, Uint8Array::from(data.as_slice()))?;
+            }
+        }
+        Err(errors) => {
+            set(&result, "pdf", JsValue::NULL)?;
+            for error in errors.iter() {
+                diagnostics.push(&diagnostic(&world, error, "error")?);
+            }
+        }
+    }
+
+    for warning in tracer.warnings() {
+        diagnostics.push(&diagnostic(&world, warning, "warning")?);
+    }
+
+    set(&result, "writes", writes)?;
+    set(&result, "diagnostics", diagnostics)?;
+    Ok(result)
+}
+
+/// Convert an error or a warning into a JavaScript object.
+fn diagnostic(
+    world: &MemoryWorld,
+    error: &SourceError,
+    severity: &str,
+) -> Result<Object, JsError> {
+    let object = Object::new();
+    set(&object, "severity", severity)?;
+    set(&object, "message", error.message.as_str())?;
+    if !error.span.is_detached() {
+        let range = error.range(world);
+        let path = world.path(error.span.source()).to_string_lossy();
+        set(&object, "path", path.as_ref())?;
+        set(&object, "start", range.start as u32)?;
+        set(&object, "end", range.end as u32)?;
+    }
+    Ok(object)
+}
+
+/// Get a property of a JavaScript object.
+fn get(object: &Object, key: &str) -> Result<JsValue, JsError> {
+    Reflect::get(object, &key.into())
+        .map_err(|_| JsError::new(&format!("failed to get {key}")))
+}
+
+/// Set a property of a JavaScript object.
+fn set(object: &Object, key: &str, value: impl Into<JsValue>) -> Result<(), JsError> {
+    Reflect::set(object, &key.into(), &value.into())
+        .map(|_| ())
+        .map_err(|_| JsError::new(&format!("failed to set {key}")))
+}